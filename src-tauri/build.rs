@@ -1,6 +1,7 @@
 fn main() {
     // Ensure Cargo recompiles when compile-time env vars change
-    println!("cargo:rerun-if-env-changed=API_PRIVATE_KEY");
+    println!("cargo:rerun-if-env-changed=API_MIRRORS");
+    println!("cargo:rerun-if-env-changed=API_PINNED_ROOTS");
     println!("cargo:rerun-if-env-changed=PLAYBOOK_PUBLIC_KEY");
     println!("cargo:rerun-if-env-changed=USE_PRODUCTION");
     println!("cargo:rerun-if-env-changed=USE_SANDBOX");
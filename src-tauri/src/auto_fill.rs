@@ -0,0 +1,211 @@
+use crate::field_matching::{self, FieldHints};
+use crate::models::{Broker, FormField, KnownField, PageStructure, Playbook, PlaybookStep};
+use crate::step_descriptions;
+
+/// `Playbook::status` of a playbook put together from the page because the broker has none.
+pub const AUTO_STATUS: &str = "auto";
+
+/// Input types that never take a profile value.
+const SKIPPED_TYPES: &[&str] = &["hidden", "password", "submit", "button", "reset", "image", "file", "checkbox", "radio"];
+
+fn is_fillable(field: &FormField) -> bool {
+    field.visible
+        && !field.honeypot
+        && matches!(field.tag.as_str(), "input" | "textarea" | "select")
+        && !field.field_type.as_deref().is_some_and(|t| SKIPPED_TYPES.contains(&t))
+}
+
+fn field_name(field: &FormField) -> String {
+    field
+        .label
+        .as_deref()
+        .or(field.placeholder.as_deref())
+        .or(field.name.as_deref())
+        .map(|l| format!("'{}'", l.split_whitespace().collect::<Vec<_>>().join(" ")))
+        .unwrap_or_else(|| "the highlighted field".to_string())
+}
+
+fn step(position: usize, action: &str, field: Option<&FormField>, profile_key: Option<String>, description: String) -> PlaybookStep {
+    PlaybookStep {
+        position: position as u32,
+        action: action.to_string(),
+        selector: field.map(|f| f.selector.clone()),
+        profile_key,
+        value: None,
+        description,
+        instructions: None,
+        wait_after_ms: 300,
+        optional: false,
+        fallback_selectors: vec![],
+        phase: None,
+    }
+}
+
+/// Steps for the opt-out form on the page, and the fills to check with the user first.
+pub struct Plan {
+    pub steps: Vec<PlaybookStep>,
+    /// Positions of fills whose field matched its profile key with too little confidence
+    /// to fill without asking
+    pub to_confirm: Vec<u32>,
+}
+
+/// Plan the opt-out form on the page: a fill for every field matched to the profile, a
+/// prompt for each other required field, then a hand-off for the user to check the form
+/// and submit it. No steps when the page has no form to fill.
+pub fn plan_steps(structure: &PageStructure, known_fields: &[KnownField]) -> Plan {
+    let mut plan = Plan { steps: Vec::new(), to_confirm: Vec::new() };
+    // The opt-out form is the one with the most to fill, not the newsletter box in the footer
+    let Some(form) = structure
+        .forms
+        .iter()
+        .max_by_key(|f| f.fields.iter().filter(|field| is_fillable(field)).count())
+        .filter(|f| f.fields.iter().any(is_fillable))
+    else {
+        return plan;
+    };
+
+    let steps = &mut plan.steps;
+    let mut left_over = Vec::new();
+    for field in form.fields.iter().filter(|f| is_fillable(f)) {
+        let matched = (field.tag != "select")
+            .then(|| field_matching::match_field(&FieldHints::from(field), known_fields))
+            .flatten();
+        let position = steps.len() + 1;
+        match matched {
+            Some(matched) => {
+                let phrase = step_descriptions::profile_field_phrase(&matched.profile_key);
+                let description = format!("Fill {} with {}", field_name(field), phrase);
+                if !matched.is_confident() {
+                    plan.to_confirm.push(position as u32);
+                }
+                steps.push(step(position, "fill", Some(field), Some(matched.profile_key), description));
+            }
+            None if field.required => {
+                let action = if field.tag == "select" { "select" } else { "fill" };
+                steps.push(step(position, action, Some(field), None, format!("Fill in {}", field_name(field))));
+            }
+            None => left_over.push(field_name(field)),
+        }
+    }
+    if structure.has_captcha {
+        steps.push(step(steps.len() + 1, "captcha", None, None, "Solve the CAPTCHA".to_string()));
+    }
+    let mut submit = step(
+        steps.len() + 1,
+        "user_prompt",
+        None,
+        None,
+        "Check the form and submit it".to_string(),
+    );
+    submit.instructions = Some(if left_over.is_empty() {
+        "This broker has no playbook yet, so the form was filled from its labels. Make sure every field is right, then submit it in the browser.".to_string()
+    } else {
+        format!(
+            "This broker has no playbook yet, so the form was filled from its labels. Fill in anything else it needs ({}), tick any consent boxes, then submit it in the browser.",
+            left_over.join(", ")
+        )
+    });
+    steps.push(submit);
+    plan
+}
+
+/// A throwaway playbook for `broker` from the steps `plan_steps` worked out. It stays on
+/// this machine: it's never saved, shared or reported on.
+pub fn playbook(broker: &Broker, steps: Vec<PlaybookStep>) -> Playbook {
+    Playbook {
+        id: format!("{}:{}", AUTO_STATUS, broker.id),
+        broker_id: broker.id.clone(),
+        broker_name: broker.name.clone(),
+        title: Some("Filled from the page".to_string()),
+        version: 0,
+        status: AUTO_STATUS.to_string(),
+        notes: None,
+        steps,
+        signature: None,
+        upvotes: 0,
+        downvotes: 0,
+        success_count: 0,
+        failure_count: 0,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        contributor: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FormStructure;
+
+    fn field(selector: &str, label: Option<&str>, name: Option<&str>, field_type: &str) -> FormField {
+        FormField {
+            selector: selector.to_string(),
+            tag: "input".to_string(),
+            field_type: Some(field_type.to_string()),
+            label: label.map(String::from),
+            placeholder: None,
+            name: name.map(String::from),
+            id: None,
+            autocomplete: None,
+            required: false,
+            options: None,
+            visible: true,
+            honeypot: false,
+        }
+    }
+
+    #[test]
+    fn hands_the_rest_of_the_form_to_the_user() {
+        let mut reason = field("#reason", Some("Reason for removal"), None, "text");
+        reason.required = true;
+        let structure = PageStructure {
+            url: "https://example.com/optout".to_string(),
+            title: "Opt out".to_string(),
+            forms: vec![
+                FormStructure {
+                    selector: "#newsletter".to_string(),
+                    action: None,
+                    method: None,
+                    fields: vec![field("#nl", Some("Email"), None, "email")],
+                },
+                FormStructure {
+                    selector: "#optout".to_string(),
+                    action: None,
+                    method: None,
+                    fields: vec![
+                        field("#email", Some("Email"), None, "email"),
+                        field("#f2", None, Some("fname"), "text"),
+                        // Offscreen, for bots to fill in
+                        FormField { honeypot: true, ..field("#confirm", Some("Confirm email"), None, "email") },
+                        field("#url", Some("Profile URL"), None, "url"),
+                        reason,
+                        field("#agree", Some("I agree"), None, "checkbox"),
+                    ],
+                },
+            ],
+            buttons: vec![],
+            text_blocks: vec![],
+            has_captcha: true,
+        };
+        let plan = plan_steps(&structure, &[]);
+        let summary: Vec<_> = plan
+            .steps
+            .iter()
+            .map(|s| (s.action.as_str(), s.selector.as_deref(), s.profile_key.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("fill", Some("#email"), Some("email")),
+                ("fill", Some("#f2"), Some("firstName")),
+                ("fill", Some("#reason"), None),
+                ("captcha", None, None),
+                ("user_prompt", None, None),
+            ]
+        );
+        // Only the field's name hints at a first name, so that fill is checked first
+        assert_eq!(plan.to_confirm, [2]);
+        let instructions = plan.steps[4].instructions.as_deref().unwrap();
+        assert!(instructions.contains("'Profile URL'") && !instructions.contains("Confirm email"));
+        assert!(plan_steps(&PageStructure { forms: vec![], ..structure }, &[]).steps.is_empty());
+    }
+}
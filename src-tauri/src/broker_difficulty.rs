@@ -0,0 +1,127 @@
+use crate::models::{BrokerOutcomeStats, BrokerOutcomeStore, LocalDifficulty};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::PathBuf;
+
+const FILENAME: &str = "broker_outcomes.json";
+/// Attempts needed before this device's own runs say more than the registry does.
+pub const MIN_ATTEMPTS: u32 = 3;
+/// A successful opt-out taking this long or longer counts fully towards difficulty.
+const SLOW_SECS: f32 = 10.0 * 60.0;
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<BrokerOutcomeStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(BrokerOutcomeStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &BrokerOutcomeStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// Count one attempt at a broker that got far enough to succeed or fail. Skipped,
+/// cancelled and not-found attempts say nothing about how hard the opt-out is.
+pub fn record(app: &tauri::AppHandle, broker_id: &str, succeeded: bool, duration_secs: u64, captchas: u32) -> Result<(), String> {
+    let mut store = load(app)?;
+    apply(&mut store, broker_id, succeeded, duration_secs, captchas, Utc::now());
+    save(app, &store)
+}
+
+fn apply(store: &mut BrokerOutcomeStore, broker_id: &str, succeeded: bool, duration_secs: u64, captchas: u32, now: DateTime<Utc>) {
+    let index = match store.brokers.iter().position(|b| b.broker_id == broker_id) {
+        Some(i) => i,
+        None => {
+            store.brokers.push(BrokerOutcomeStats {
+                broker_id: broker_id.to_string(),
+                attempts: 0,
+                failures: 0,
+                captchas: 0,
+                success_secs: 0,
+                last_attempt_at: now,
+            });
+            store.brokers.len() - 1
+        }
+    };
+    let entry = &mut store.brokers[index];
+    entry.attempts += 1;
+    entry.captchas += captchas;
+    if succeeded {
+        entry.success_secs += duration_secs;
+    } else {
+        entry.failures += 1;
+    }
+    entry.last_attempt_at = now;
+}
+
+/// A broker's difficulty from this device's runs of it: failures weigh most, then how
+/// long a successful opt-out takes and how often a CAPTCHA stops it. None until there
+/// have been `MIN_ATTEMPTS`.
+pub fn recalibrate(stats: &BrokerOutcomeStats) -> Option<LocalDifficulty> {
+    if stats.attempts < MIN_ATTEMPTS {
+        return None;
+    }
+    let failure_rate = stats.failures as f32 / stats.attempts as f32;
+    let successes = stats.attempts - stats.failures;
+    let average_secs = (successes > 0).then(|| stats.success_secs / successes as u64);
+    let captchas_per_attempt = stats.captchas as f32 / stats.attempts as f32;
+
+    let slowness = average_secs.map_or(1.0, |secs| (secs as f32 / SLOW_SECS).min(1.0));
+    let score = (50.0 * failure_rate + 25.0 * slowness + 25.0 * captchas_per_attempt.min(1.0)).round() as u32;
+    let difficulty = match score {
+        0..=24 => "easy",
+        25..=54 => "medium",
+        _ => "hard",
+    };
+    Some(LocalDifficulty {
+        broker_id: stats.broker_id.clone(),
+        difficulty: difficulty.to_string(),
+        score,
+        attempts: stats.attempts,
+        failure_rate,
+        average_secs,
+        captchas_per_attempt,
+    })
+}
+
+/// Recalibrated difficulty for every broker this device has run often enough.
+pub fn local_difficulties(app: &tauri::AppHandle) -> Result<Vec<LocalDifficulty>, String> {
+    Ok(load(app)?.brokers.iter().filter_map(recalibrate).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_brokers_from_their_outcomes() {
+        let mut store = BrokerOutcomeStore::default();
+        let now = Utc::now();
+        for _ in 0..3 {
+            apply(&mut store, "quick", true, 60, 0, now);
+        }
+        apply(&mut store, "stubborn", false, 900, 1, now);
+        apply(&mut store, "stubborn", false, 900, 1, now);
+        apply(&mut store, "stubborn", true, 700, 1, now);
+        apply(&mut store, "new", false, 30, 0, now);
+
+        let quick = recalibrate(&store.brokers[0]).unwrap();
+        assert_eq!((quick.difficulty.as_str(), quick.score, quick.average_secs), ("easy", 3, Some(60)));
+        let stubborn = recalibrate(&store.brokers[1]).unwrap();
+        assert_eq!((stubborn.difficulty.as_str(), stubborn.score), ("hard", 83));
+        assert_eq!(store.brokers[1].captchas, 3);
+        // Too few attempts to go on
+        assert!(recalibrate(&store.brokers[2]).is_none());
+    }
+}
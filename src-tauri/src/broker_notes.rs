@@ -0,0 +1,64 @@
+use crate::crypto;
+use crate::models::{BrokerNote, BrokerNoteStore};
+use crate::secrets::SecretsCache;
+use chrono::Utc;
+use std::fs;
+use std::path::PathBuf;
+
+/// Notes often mention old addresses or other PII, so they're encrypted like the profile.
+const FILENAME: &str = "broker_notes.enc";
+
+pub fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+fn encryption_key(app: &tauri::AppHandle) -> Result<Vec<u8>, String> {
+    use tauri::Manager;
+    app.state::<SecretsCache>().get_encryption_key()
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<BrokerNoteStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(BrokerNoteStore::default());
+    }
+    let encrypted = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let key = encryption_key(app)?;
+    let decrypted = crypto::decrypt(&encrypted, &key).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&decrypted).map_err(|e| e.to_string())
+}
+
+pub fn save(app: &tauri::AppHandle, store: &BrokerNoteStore) -> Result<(), String> {
+    let key = encryption_key(app)?;
+    let json = serde_json::to_string(store).map_err(|e| e.to_string())?;
+    let encrypted = crypto::encrypt(json.as_bytes(), &key).map_err(|e| e.to_string())?;
+    let path = store_path(app)?;
+    fs::write(&path, encrypted).map_err(|e| e.to_string())
+}
+
+/// Set the note for a broker. An empty note removes it.
+pub fn set(app: &tauri::AppHandle, broker_id: &str, note: &str) -> Result<(), String> {
+    let mut store = load(app)?;
+    store.notes.retain(|n| n.broker_id != broker_id);
+    let note = note.trim();
+    if !note.is_empty() {
+        store.notes.push(BrokerNote {
+            broker_id: broker_id.to_string(),
+            note: note.to_string(),
+            updated_at: Utc::now(),
+        });
+    }
+    save(app, &store)
+}
+
+pub fn get(app: &tauri::AppHandle, broker_id: &str) -> Result<Option<BrokerNote>, String> {
+    let store = load(app)?;
+    Ok(store.notes.into_iter().find(|n| n.broker_id == broker_id))
+}
+
+pub fn get_all(app: &tauri::AppHandle) -> Result<Vec<BrokerNote>, String> {
+    Ok(load(app)?.notes)
+}
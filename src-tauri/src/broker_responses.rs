@@ -0,0 +1,398 @@
+use crate::email_aliases;
+use crate::evidence;
+use crate::history;
+use crate::models::{
+    Broker, BrokerResponse, BrokerResponseKind, BrokerResponseStore, BrokerSubmissionStatus, EvidenceKind, FailureReason,
+    SubmissionRecord,
+};
+use chrono::{DateTime, Utc};
+use mail_parser::decoders::html::html_to_text;
+use mail_parser::{Address, Message, MessageParser, PartType};
+use std::fs;
+use std::path::PathBuf;
+
+const FILENAME: &str = "broker_responses.json";
+
+/// Phrases that mark each kind of reply, checked in this order: a refusal often also asks
+/// for ID, and a verification request often mentions the removal to come.
+const REFUSAL_PHRASES: &[&str] = &[
+    "unable to process your request",
+    "unable to honor",
+    "cannot process your request",
+    "could not process your request",
+    "could not verify your identity",
+    "unable to verify your identity",
+    "request has been denied",
+    "request was denied",
+    "we are declining",
+    "not required to honor",
+    "does not apply to",
+    "only available to residents",
+];
+const VERIFICATION_PHRASES: &[&str] = &[
+    "verify your identity",
+    "confirm your identity",
+    "verify your email",
+    "verify your request",
+    "confirm your request",
+    "confirm your opt-out",
+    "confirm your opt out",
+    "click the link below",
+    "government-issued",
+    "government issued",
+    "copy of your id",
+    "proof of identity",
+];
+const CONFIRMATION_PHRASES: &[&str] = &[
+    "has been removed",
+    "have been removed",
+    "has been deleted",
+    "have been deleted",
+    "has been suppressed",
+    "has been opted out",
+    "successfully opted out",
+    "opt-out is complete",
+    "opt-out has been completed",
+    "opt out has been completed",
+    "request has been completed",
+    "request has been processed",
+    "request is complete",
+    "we have processed your request",
+];
+
+/// The parts of an email a broker's reply is matched and classified by.
+#[derive(Debug)]
+pub struct Email {
+    pub message_id: Option<String>,
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub date: Option<DateTime<Utc>>,
+    /// Plain text of the body, or its HTML with the markup taken out
+    pub text: String,
+}
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<BrokerResponseStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(BrokerResponseStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &BrokerResponseStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// Text of each text part of a message, plain and HTML alike, in order, decoded from
+/// its transfer encoding and charset.
+fn text_parts<'a>(message: &'a Message) -> impl Iterator<Item = &'a str> {
+    message.parts.iter().filter_map(|part| match &part.body {
+        PartType::Text(text) | PartType::Html(text) => Some(text.as_ref()),
+        _ => None,
+    })
+}
+
+/// Readable text of a message: its first plain text part, or failing that its first
+/// HTML with the markup taken out.
+fn readable(message: &Message) -> String {
+    match message.text_bodies().next().map(|part| &part.body) {
+        Some(PartType::Text(text)) => text.trim().to_string(),
+        Some(PartType::Html(html)) => html_to_text(html).split_whitespace().collect::<Vec<_>>().join(" "),
+        _ => String::new(),
+    }
+}
+
+/// An address header the way it's usually written, e.g. `Spokeo <privacy@spokeo.com>`.
+fn address_field(address: Option<&Address>) -> String {
+    let Some(address) = address else { return String::new() };
+    address
+        .iter()
+        .map(|addr| match (addr.name(), addr.address()) {
+            (Some(name), Some(email)) => format!("{} <{}>", name, email),
+            (name, email) => email.or(name).unwrap_or_default().to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Every web link in an email, plain text and HTML parts alike, in the order they appear.
+pub fn links(raw: &str) -> Vec<String> {
+    let Some(message) = MessageParser::default().parse(raw) else { return Vec::new() };
+    let mut found: Vec<String> = Vec::new();
+    for text in text_parts(&message) {
+        let mut rest = text;
+        while let Some(start) = rest.find("http") {
+            let candidate = &rest[start..];
+            let end = candidate
+                .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | ')' | ']'))
+                .unwrap_or(candidate.len());
+            // A link at the end of a sentence doesn't take the full stop with it
+            let link = candidate[..end].trim_end_matches(['.', ',', ';']).replace("&amp;", "&");
+            if (link.starts_with("https://") || link.starts_with("http://")) && !found.contains(&link) {
+                found.push(link);
+            }
+            rest = &candidate[end.max(4)..];
+        }
+    }
+    found
+}
+
+/// Read an email saved as a .eml file. None when it has no headers to go on.
+pub fn parse(raw: &str) -> Option<Email> {
+    let message = MessageParser::default().parse(raw)?;
+    if message.from().is_none() && message.subject().is_none() {
+        return None;
+    }
+    Some(Email {
+        message_id: message.message_id().map(String::from),
+        from: address_field(message.from()),
+        to: address_field(message.to()),
+        subject: message.subject().unwrap_or_default().to_string(),
+        date: message.date().and_then(|d| DateTime::from_timestamp(d.to_timestamp(), 0)),
+        text: readable(&message),
+    })
+}
+
+/// What the broker's reply says, from its subject and text.
+pub fn classify(email: &Email) -> BrokerResponseKind {
+    let text = format!("{}\n{}", email.subject, email.text).to_lowercase();
+    let says = |phrases: &[&str]| phrases.iter().any(|p| text.contains(p));
+    if says(REFUSAL_PHRASES) {
+        BrokerResponseKind::Refusal
+    } else if says(VERIFICATION_PHRASES) {
+        BrokerResponseKind::VerificationRequest
+    } else if says(CONFIRMATION_PHRASES) {
+        BrokerResponseKind::Confirmation
+    } else {
+        BrokerResponseKind::Unrecognized
+    }
+}
+
+/// The domain an email address is at, from a header like `"Spokeo" <privacy@spokeo.com>`.
+fn address_domain(value: &str) -> Option<String> {
+    let address = match (value.rfind('<'), value.rfind('>')) {
+        (Some(open), Some(close)) if open < close => &value[open + 1..close],
+        _ => value.trim(),
+    };
+    Some(address.rsplit_once('@')?.1.trim().to_lowercase())
+}
+
+/// Public suffixes of two labels that brokers' sites sit under. Everyone under "co.uk"
+/// registers their own name, so "co.uk" alone says nothing about who sent an email.
+const TWO_LABEL_SUFFIXES: &[&str] = &[
+    "co.uk", "org.uk", "me.uk", "ltd.uk", "plc.uk", "net.uk", "com.au", "net.au", "org.au",
+    "co.nz", "net.nz", "org.nz", "co.za", "co.in", "co.jp", "com.br", "com.mx", "com.sg",
+];
+
+/// The registrable domain of a URL's host or a domain: the public suffix and the label
+/// before it ("optout.spokeo.com" is "spokeo.com", "mail.192.co.uk" is "192.co.uk").
+fn site(host: &str) -> String {
+    let host = host.to_lowercase();
+    let labels: Vec<&str> = host.split('.').filter(|l| !l.is_empty()).collect();
+    let suffix = labels[labels.len().saturating_sub(2)..].join(".");
+    let keep = if TWO_LABEL_SUFFIXES.contains(&suffix.as_str()) { 3 } else { 2 };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+pub fn url_site(url: &str) -> Option<String> {
+    let host = url.split("://").nth(1)?.split(['/', ':', '?', '#']).next()?;
+    Some(site(host))
+}
+
+/// The broker an email is from: the one given the address it was sent to, or else the
+/// one whose site the sender's address is on.
+fn match_broker<'a>(email: &Email, brokers: &'a [Broker], alias_broker: Option<&str>) -> Option<&'a Broker> {
+    if let Some(id) = alias_broker {
+        if let Some(broker) = brokers.iter().find(|b| b.id == id) {
+            return Some(broker);
+        }
+    }
+    let sender = site(&address_domain(&email.from)?);
+    brokers
+        .iter()
+        .find(|b| [&b.url, &b.opt_out_url].iter().any(|u| url_site(u).as_deref() == Some(sender.as_str())))
+}
+
+/// The submission a reply is about: the broker's latest one sent before the reply was.
+fn match_record<'a>(records: &'a [SubmissionRecord], broker_id: &str, received_at: Option<DateTime<Utc>>) -> Option<&'a SubmissionRecord> {
+    records
+        .iter()
+        .filter(|r| r.broker_id == broker_id && received_at.is_none_or(|at| r.submitted_at <= at))
+        .max_by_key(|r| r.submitted_at)
+}
+
+/// Status a submission moves to because of a reply, if it changes. A confirmation beats
+/// an earlier failure, but a later email never undoes a confirmation.
+pub fn next_status(current: &BrokerSubmissionStatus, kind: BrokerResponseKind) -> Option<BrokerSubmissionStatus> {
+    use BrokerSubmissionStatus::*;
+    match (kind, current) {
+        (BrokerResponseKind::Confirmation, Submitted | PendingVerification | Failed) => Some(Confirmed),
+        (BrokerResponseKind::VerificationRequest, Submitted) => Some(PendingVerification),
+        (BrokerResponseKind::Refusal, Submitted | PendingVerification) => Some(Failed),
+        _ => None,
+    }
+}
+
+/// Read a broker's email exported as a .eml file, classify it, and attach it to the
+/// submission it answers, moving that submission on when the email settles it.
+/// Importing the same email again returns what it did the first time.
+pub fn import(app: &tauri::AppHandle, brokers: &[Broker], file_name: &str, content: &str) -> Result<BrokerResponse, String> {
+    let email = parse(content).ok_or_else(|| format!("{} doesn't look like an email", file_name))?;
+    // Without a Message-ID, the same sender, subject and date mean the same email
+    let id = email
+        .message_id
+        .clone()
+        .unwrap_or_else(|| format!("{}|{}|{:?}", email.from, email.subject, email.date));
+    let mut store = load(app)?;
+    if let Some(existing) = store.responses.iter().find(|r| r.id == id) {
+        return Ok(existing.clone());
+    }
+
+    let kind = classify(&email);
+    let alias = email_aliases::find(app, &email.to).unwrap_or(None);
+    let broker = match_broker(&email, brokers, alias.as_ref().map(|a| a.broker_id.as_str()));
+    let records = history::load(app)?.records;
+    let record = broker.and_then(|b| match_record(&records, &b.id, email.date));
+
+    let mut new_status = None;
+    let mut evidence_id = None;
+    if let Some(record) = record {
+        evidence_id = Some(evidence::store(app, &record.id, EvidenceKind::Email, content.as_bytes())?.id);
+        new_status = next_status(&record.status, kind);
+        if let Some(status) = new_status.clone() {
+            let now = Utc::now();
+            history::update_records(app, std::slice::from_ref(&record.id), |r| {
+                history::set_status(r, status.clone(), now);
+                if status == BrokerSubmissionStatus::Failed {
+                    r.failure_reason = Some(FailureReason::RefusedByEmail);
+                }
+            })?;
+        }
+    }
+
+    let response = BrokerResponse {
+        id,
+        file_name: file_name.to_string(),
+        from: email.from,
+        subject: email.subject,
+        received_at: email.date,
+        kind,
+        broker_id: broker.map(|b| b.id.clone()),
+        broker_name: broker.map(|b| b.name.clone()),
+        record_id: record.map(|r| r.id.clone()),
+        new_status,
+        evidence_id,
+        imported_at: Utc::now(),
+    };
+    // Only emails that found their submission are kept, so an unmatched one can be
+    // imported again once the broker's submission is in history
+    if response.record_id.is_some() {
+        store.responses.push(response.clone());
+        save(app, &store)?;
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    #[test]
+    fn reads_multipart_emails() {
+        let raw = "Message-ID: <abc@mail.spokeo.com>\r\n\
+            From: =?UTF-8?B?U3Bva2Vv?= Privacy <privacy@mail.spokeo.com>\r\n\
+            To: jane+optoutta-spokeo@example.com\r\n\
+            Subject: =?utf-8?Q?Your_opt-out_request?=\r\n\
+            \x20=?utf-8?Q?_is_complete?=\r\n\
+            Date: Tue, 3 Mar 2026 10:00:00 +0000 (UTC)\r\n\
+            Content-Type: multipart/alternative; boundary=\"b1\"\r\n\
+            \r\n\
+            --b1\r\n\
+            Content-Type: text/html; charset=utf-8\r\n\
+            \r\n\
+            <style>p { color: red }</style><p>Your listing has been removed&nbsp;today.</p>\r\n\
+            --b1\r\n\
+            Content-Type: text/plain; charset=utf-8\r\n\
+            Content-Transfer-Encoding: quoted-printable\r\n\
+            \r\n\
+            Your listing has been remov=\r\n\
+            ed. Caf=C3=A9 regards.\r\n\
+            --b1--\r\n";
+        let email = parse(raw).unwrap();
+        assert_eq!(email.message_id.as_deref(), Some("abc@mail.spokeo.com"));
+        assert_eq!(email.from, "Spokeo Privacy <privacy@mail.spokeo.com>");
+        assert_eq!(email.subject, "Your opt-out request is complete");
+        assert_eq!(email.date.unwrap().to_rfc3339(), "2026-03-03T10:00:00+00:00");
+        assert_eq!(email.text, "Your listing has been removed. Café regards.");
+        assert_eq!(classify(&email), BrokerResponseKind::Confirmation);
+        assert_eq!(address_domain(&email.from).map(|d| site(&d)).as_deref(), Some("spokeo.com"));
+        assert!(parse("just some text").is_none());
+    }
+
+    #[test]
+    fn decodes_bodies_in_their_own_charset() {
+        let raw = "From: privacy@radaris.example\r\n\
+            Subject: =?iso-8859-1?Q?Demande_trait=E9e?=\r\n\
+            Content-Type: text/html; charset=iso-8859-1\r\n\
+            Content-Transfer-Encoding: quoted-printable\r\n\
+            \r\n\
+            <p>Removed&nbsp;&amp; gone. Caf=E9 <a href=3D\"https://radaris.example/done?a=3D1&amp;b=3D2\">here</a></p>\r\n";
+        let email = parse(raw).unwrap();
+        assert_eq!(email.subject, "Demande traitée");
+        assert_eq!(email.text, "Removed & gone. Café here");
+        assert_eq!(links(raw), ["https://radaris.example/done?a=1&b=2"]);
+    }
+
+    #[test]
+    fn tells_brokers_apart_under_the_same_country_suffix() {
+        let brokers = vec![
+            Broker { url: "https://www.192.co.uk".to_string(), ..test_fixtures::broker("192") },
+            Broker { opt_out_url: "https://optout.ukphonebook.co.uk/remove".to_string(), ..test_fixtures::broker("ukphonebook") },
+        ];
+        let email = Email {
+            message_id: None,
+            from: "UK Phonebook <privacy@mail.ukphonebook.co.uk>".to_string(),
+            to: String::new(),
+            subject: String::new(),
+            date: None,
+            text: String::new(),
+        };
+        assert_eq!(match_broker(&email, &brokers, None).map(|b| b.id.as_str()), Some("ukphonebook"));
+        assert_eq!(url_site("https://Mail.192.co.uk:443/x").as_deref(), Some("192.co.uk"));
+        assert_eq!(url_site("https://co.uk/").as_deref(), Some("co.uk"));
+    }
+
+    #[test]
+    fn sorts_replies_into_what_they_mean() {
+        let email = |subject: &str, text: &str| Email {
+            message_id: None,
+            from: String::new(),
+            to: String::new(),
+            subject: subject.to_string(),
+            date: None,
+            text: text.to_string(),
+        };
+        use BrokerResponseKind::*;
+        assert_eq!(classify(&email("Action required", "Please verify your identity by replying with a copy of your ID.")), VerificationRequest);
+        assert_eq!(classify(&email("Your request", "We could not verify your identity, so your request has been denied.")), Refusal);
+        assert_eq!(classify(&email("Weekly deals", "Save 20% this week")), Unrecognized);
+
+        use BrokerSubmissionStatus::*;
+        assert_eq!(next_status(&Submitted, VerificationRequest), Some(PendingVerification));
+        assert_eq!(next_status(&Failed, Confirmation), Some(Confirmed));
+        assert_eq!(next_status(&Confirmed, Refusal), None);
+        assert_eq!(next_status(&Submitted, Unrecognized), None);
+    }
+}
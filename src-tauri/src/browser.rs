@@ -1,46 +1,183 @@
-use crate::models::{FormAction, PageStructure, Profile};
-use chromiumoxide::browser::{Browser, BrowserConfig};
+use crate::browser_detection::{self, BrowserInstall, Packaging};
+use crate::browser_proxy;
+use crate::bundled_chromium;
+use crate::consent_banners;
+use crate::firefox;
+use crate::har;
+use crate::listing_match::{self, ListingMatch};
+use crate::models::{BrokerAutomation, BrowserEngine, BrowserMode, BrowserProxy, FormAction, PageStructure, Profile};
+use crate::profile_format;
+use crate::secrets::SecretsCache;
+use crate::settings;
+use chromiumoxide::browser::{Browser as CdpBrowser, BrowserConfig};
+use chromiumoxide::cdp::browser_protocol::emulation::{
+    ClearDeviceMetricsOverrideParams, SetDeviceMetricsOverrideParams, SetTouchEmulationEnabledParams, SetUserAgentOverrideParams,
+};
+use chromiumoxide::cdp::browser_protocol::fetch::{
+    self, AuthChallengeResponse, AuthChallengeResponseResponse, AuthChallengeSource, ContinueRequestParams,
+    ContinueWithAuthParams, EventAuthRequired, EventRequestPaused, RequestPattern,
+};
+use chromiumoxide::cdp::browser_protocol::input::InsertTextParams;
+use chromiumoxide::cdp::browser_protocol::network::{
+    self, EventLoadingFailed, EventLoadingFinished, EventRequestWillBeSent, EventResponseReceived, Headers, Response,
+    SetExtraHttpHeadersParams,
+};
+use chromiumoxide::cdp::browser_protocol::page::EventLifecycleEvent;
 use chromiumoxide::handler::Handler;
-use chromiumoxide::page::Page;
+use chromiumoxide::listeners::EventStream;
+use chromiumoxide::page::Page as CdpPage;
+use futures::StreamExt;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 
-/// Find the Chrome binary on this platform.
-pub fn find_chrome_binary() -> Option<PathBuf> {
-    let candidates = if cfg!(target_os = "macos") {
-        vec![
-            "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
-            "/Applications/Chromium.app/Contents/MacOS/Chromium",
-        ]
-    } else if cfg!(target_os = "windows") {
-        vec![
-            r"C:\Program Files\Google\Chrome\Application\chrome.exe",
-            r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
-        ]
-    } else {
-        vec![
-            "/usr/bin/google-chrome",
-            "/usr/bin/google-chrome-stable",
-            "/usr/bin/chromium",
-            "/usr/bin/chromium-browser",
-        ]
-    };
+/// The automation browser, with the proxy login its pages answer.
+pub struct Browser {
+    backend: Backend,
+    /// Username and password pages answer their proxy's login prompt with
+    proxy_login: Option<(String, String)>,
+    /// Pages already answering the proxy's login prompt. The docked browser reuses one
+    /// page for every broker, and it only needs to be set up once.
+    login_pages: std::sync::Mutex<Vec<String>>,
+}
 
-    candidates
-        .into_iter()
-        .map(PathBuf::from)
-        .find(|p| p.exists())
+/// Chrome, Chromium or Edge driven over the Chrome DevTools Protocol, or Firefox driven
+/// over WebDriver BiDi.
+enum Backend {
+    Chromium {
+        browser: Box<CdpBrowser>,
+        /// Drives the CDP connection until Chrome closes
+        handler: tokio::task::JoinHandle<()>,
+    },
+    Firefox(firefox::Session),
+}
+
+impl Browser {
+    fn chromium(browser: CdpBrowser, mut handler: Handler, proxy_login: Option<(String, String)>) -> Browser {
+        let handler = tokio::spawn(async move {
+            while handler.next().await.is_some() {}
+        });
+        Browser::new(Backend::Chromium { browser: Box::new(browser), handler }, proxy_login)
+    }
+
+    fn firefox(session: firefox::Session, proxy_login: Option<(String, String)>) -> Browser {
+        Browser::new(Backend::Firefox(session), proxy_login)
+    }
+
+    fn new(backend: Backend, proxy_login: Option<(String, String)>) -> Browser {
+        Browser { backend, proxy_login, login_pages: Default::default() }
+    }
+
+    /// Whether the connection to the browser is gone, e.g. because the user quit it.
+    pub fn is_closed(&self) -> bool {
+        match &self.backend {
+            Backend::Chromium { handler, .. } => handler.is_finished(),
+            Backend::Firefox(session) => session.is_closed(),
+        }
+    }
+
+    /// The pages open in the browser.
+    async fn pages(&self) -> Vec<Page> {
+        match &self.backend {
+            Backend::Chromium { browser, .. } => {
+                browser.pages().await.map(|p| p.into_iter().map(Page::Chromium).collect()).unwrap_or_default()
+            }
+            Backend::Firefox(session) => session.tabs().await.map(|t| t.into_iter().map(Page::Firefox).collect()).unwrap_or_default(),
+        }
+    }
+
+    async fn new_page(&self) -> Result<Page, String> {
+        match &self.backend {
+            Backend::Chromium { browser, .. } => browser.new_page("about:blank").await.map(Page::Chromium).map_err(|e| e.to_string()),
+            Backend::Firefox(session) => session.new_tab().await.map(Page::Firefox),
+        }
+        .map_err(|e| format!("Failed to open page: {}", e))
+    }
+
+    /// The browser's own user agent, whatever a page has been set to send.
+    async fn user_agent(&self) -> Result<String, String> {
+        match &self.backend {
+            Backend::Chromium { browser, .. } => browser.user_agent().await.map_err(|e| e.to_string()),
+            Backend::Firefox(session) => Ok(session.user_agent().to_string()),
+        }
+        .map_err(|e| format!("Failed to read the browser's user agent: {}", e))
+    }
+
+    /// Answer the proxy's login prompts on `page`, once per page, if the browser was
+    /// launched through a proxy that needs a login.
+    async fn answer_proxy_login(&self, page: &Page) -> Result<(), String> {
+        let Some(login) = self.proxy_login.clone() else { return Ok(()) };
+        {
+            let mut pages = self.login_pages.lock().unwrap();
+            let id = page.id();
+            if pages.contains(&id) {
+                return Ok(());
+            }
+            pages.push(id);
+        }
+        answer_proxy_login(page, login).await
+    }
+}
+
+/// A page (tab) in the automation browser.
+#[derive(Clone)]
+pub enum Page {
+    Chromium(CdpPage),
+    Firefox(firefox::Tab),
+}
+
+impl Page {
+    /// Evaluate a JavaScript expression in the page, awaiting it if it's a promise, and
+    /// read its result as `T`.
+    pub async fn evaluate<T: serde::de::DeserializeOwned>(&self, js: impl Into<String>) -> Result<T, String> {
+        match self {
+            Page::Chromium(page) => page
+                .evaluate(js.into())
+                .await
+                .map_err(|e| e.to_string())?
+                .into_value::<T>()
+                .map_err(|e| format!("Failed to convert JS result: {}", e)),
+            Page::Firefox(tab) => {
+                serde_json::from_value(tab.evaluate(&js.into()).await?).map_err(|e| format!("Failed to convert JS result: {}", e))
+            }
+        }
+    }
+
+    /// Evaluate a JavaScript expression in the page for what it does, ignoring its result.
+    pub async fn run(&self, js: impl Into<String>) -> Result<(), String> {
+        match self {
+            Page::Chromium(page) => page.evaluate(js.into()).await.map(|_| ()).map_err(|e| e.to_string()),
+            Page::Firefox(tab) => tab.evaluate(&js.into()).await.map(|_| ()),
+        }
+    }
+
+    pub async fn url(&self) -> Result<Option<String>, String> {
+        match self {
+            Page::Chromium(page) => page.url().await.map_err(|e| e.to_string()),
+            Page::Firefox(tab) => tab.evaluate("location.href").await.map(|v| v.as_str().map(String::from)),
+        }
+    }
+
+    fn id(&self) -> String {
+        match self {
+            Page::Chromium(page) => page.target_id().inner().clone(),
+            Page::Firefox(tab) => tab.id().to_string(),
+        }
+    }
 }
 
 /// Shut down any stale Chrome process left over from a previous opt-out run.
 /// Chrome's SingletonLock is a symlink whose target is "{hostname}-{pid}".
-fn cleanup_previous_chrome(data_dir: &Path) {
+fn cleanup_previous_chrome(data_dir: &Path, install: &BrowserInstall) {
     let lock_path = data_dir.join("SingletonLock");
 
+    // A Flatpak browser records its PID inside the sandbox's PID namespace, which would
+    // name some unrelated host process
+    let pid_is_ours = !matches!(install.packaging, Packaging::Flatpak { .. });
     if let Ok(target) = std::fs::read_link(&lock_path) {
         let target_str = target.to_string_lossy();
         if let Some(pid_str) = target_str.rsplit('-').next() {
-            if pid_str.parse::<u32>().is_ok() {
+            if pid_is_ours && pid_str.parse::<u32>().is_ok() {
                 // Kill the leftover automation Chrome (this is our process, not the user's browser)
                 let _ = std::process::Command::new("kill").arg(pid_str).output();
                 std::thread::sleep(std::time::Duration::from_secs(1));
@@ -53,43 +190,721 @@ fn cleanup_previous_chrome(data_dir: &Path) {
     let _ = std::fs::remove_file(data_dir.join("SingletonSocket"));
 }
 
-/// Launch a visible (headful) Chrome instance.
-pub async fn launch() -> Result<(Browser, Handler), String> {
-    let chrome_path = find_chrome_binary()
-        .ok_or_else(|| "Chrome not found. Please install Google Chrome.".to_string())?;
+/// Name of the automation profile for `purpose` ("" for runs). Firefox and Chrome keep
+/// separate profiles, which neither could read of the other's.
+fn profile_name(engine: BrowserEngine, purpose: &str) -> String {
+    let base = match engine {
+        BrowserEngine::Chromium => "opt-outta-chrome",
+        BrowserEngine::Gecko => "opt-outta-firefox",
+    };
+    if purpose.is_empty() {
+        base.to_string()
+    } else {
+        format!("{}-{}", base, purpose)
+    }
+}
 
-    // Use a dedicated data dir so we don't conflict with the user's Chrome
-    let data_dir = std::env::temp_dir().join("opt-outta-chrome");
-    cleanup_previous_chrome(&data_dir);
+/// Find the browser the user picked (or the first one installed), or with `chromium_only`
+/// the Chromium-based one to use, and get its profile directory for `purpose` ready,
+/// cleaning up after any previous run. Flatpak and Snap installs are launched through
+/// their wrapper scripts, which pass the browser's flags through, with the profile
+/// somewhere their sandbox can write.
+fn prepare_browser(app: &tauri::AppHandle, purpose: &str, chromium_only: bool) -> Result<(BrowserInstall, PathBuf), String> {
+    let preferred = settings::load(app).ok().and_then(|s| s.browser_path);
+    let downloaded = bundled_chromium::installed(app);
+    let install = if chromium_only {
+        browser_detection::find_chromium(preferred.as_deref(), downloaded)?
+    } else {
+        browser_detection::find(preferred.as_deref(), downloaded)?
+    };
+    let data_dir = install.profile_dir(&profile_name(install.engine, purpose));
+    if install.is_sandboxed() {
+        // The sandboxed app directory is missing if the browser has never been opened
+        std::fs::create_dir_all(&data_dir)
+            .map_err(|e| format!("Failed to create the browser profile directory: {}", e))?;
+    }
+    // Firefox stops its own leftovers when it launches
+    if install.engine == BrowserEngine::Chromium {
+        cleanup_previous_chrome(&data_dir, &install);
+    }
+    Ok((install, data_dir))
+}
+
+/// The username and password for `proxy`, if it needs a login. Chrome can't take a proxy
+/// login on the command line, so pages answer the proxy's prompt with it (see
+/// `answer_proxy_login`).
+pub fn proxy_login(app: &tauri::AppHandle, proxy: Option<&BrowserProxy>) -> Option<(String, String)> {
+    use tauri::Manager;
+    proxy
+        .and_then(|p| p.username.clone())
+        .filter(|u| !u.is_empty())
+        .map(|username| (username, app.state::<SecretsCache>().get_browser_proxy_password().unwrap_or_default()))
+}
+
+/// Launch a visible (headful) browser, through `proxy` when one is given.
+pub async fn launch(app: &tauri::AppHandle, proxy: Option<&BrowserProxy>) -> Result<Browser, String> {
+    // Use a dedicated data dir so we don't conflict with the user's own browser
+    let (install, data_dir) = prepare_browser(app, "", false)?;
+    if install.engine == BrowserEngine::Gecko {
+        return launch_firefox(app, &install, &data_dir, proxy, false).await;
+    }
+    let (browser, handler) = launch_chrome(&install, &data_dir, proxy).await?;
+    Ok(Browser::chromium(browser, handler, proxy_login(app, proxy)))
+}
 
-    let config = BrowserConfig::builder()
+/// Launch a visible Chrome, Chromium or Edge for work only the Chrome DevTools Protocol
+/// can do, such as recording a playbook, even when runs use Firefox.
+pub async fn launch_chromium(app: &tauri::AppHandle, proxy: Option<&BrowserProxy>) -> Result<(CdpBrowser, Handler), String> {
+    let (install, data_dir) = prepare_browser(app, "", true)?;
+    launch_chrome(&install, &data_dir, proxy).await
+}
+
+async fn launch_chrome(
+    install: &BrowserInstall,
+    data_dir: &Path,
+    proxy: Option<&BrowserProxy>,
+) -> Result<(CdpBrowser, Handler), String> {
+    let mut config = BrowserConfig::builder()
         .with_head()
-        .chrome_executable(chrome_path)
-        .user_data_dir(&data_dir)
+        .chrome_executable(&install.path)
+        .user_data_dir(data_dir)
         .arg("--disable-blink-features=AutomationControlled")
         .arg("--no-first-run")
         .arg("--no-default-browser-check")
         .arg("--disable-background-timer-throttling")
-        .viewport(None)
+        .viewport(None);
+    if let Some(proxy) = proxy {
+        config = config.arg(browser_proxy::server_arg(proxy));
+    }
+    let config = config
         .build()
         .map_err(|e| format!("Failed to build browser config: {}", e))?;
 
-    Browser::launch(config)
+    CdpBrowser::launch(config)
         .await
         .map_err(|e| format!("Failed to launch Chrome: {}", e))
 }
 
-/// Navigate to a URL and wait for load.
-pub async fn navigate(page: &Page, url: &str) -> Result<(), String> {
-    page.goto(url)
+/// Launch Firefox on the profile at `data_dir`, through `proxy` when one is given. The
+/// proxy and the GPC signal go in the profile's preferences, where Chrome gets them per
+/// launch and per page.
+async fn launch_firefox(
+    app: &tauri::AppHandle,
+    install: &BrowserInstall,
+    data_dir: &Path,
+    proxy: Option<&BrowserProxy>,
+    headless: bool,
+) -> Result<Browser, String> {
+    let gpc = settings::load(app).unwrap_or_default().send_gpc;
+    firefox::Session::launch(&install.path, data_dir, &firefox::user_prefs(gpc, proxy), headless)
+        .await
+        .map(|session| Browser::firefox(session, proxy_login(app, proxy)))
+}
+
+/// Label of the Tauri window that hosts the docked browser on Windows.
+pub const DOCKED_WINDOW: &str = "automation";
+/// Width of the docked browser window, in logical pixels.
+const DOCKED_WIDTH: f64 = 900.0;
+
+/// Launch the automation browser for the user's chosen mode.
+pub async fn launch_with(app: &tauri::AppHandle, mode: &BrowserMode, proxy: Option<&BrowserProxy>) -> Result<Browser, String> {
+    match mode {
+        BrowserMode::External => launch(app, proxy).await,
+        BrowserMode::Docked => launch_docked(app, proxy).await,
+    }
+}
+
+/// Position and size (logical pixels) for a window docked to the right of the app.
+fn docked_bounds(app: &tauri::AppHandle) -> Option<(f64, f64, f64, f64)> {
+    use tauri::Manager;
+    let main = app.get_webview_window("main")?;
+    let scale = main.scale_factor().ok()?;
+    let pos = main.outer_position().ok()?.to_logical::<f64>(scale);
+    let size = main.outer_size().ok()?.to_logical::<f64>(scale);
+    Some((pos.x + size.width, pos.y, DOCKED_WIDTH, size.height))
+}
+
+/// WebView2 speaks CDP, so on Windows the docked browser is a Tauri window driven
+/// over a local debugging port.
+#[cfg(target_os = "windows")]
+async fn launch_docked(app: &tauri::AppHandle, proxy: Option<&BrowserProxy>) -> Result<Browser, String> {
+    use tauri::Manager;
+    if let Some(existing) = app.get_webview_window(DOCKED_WINDOW) {
+        let _ = existing.close();
+    }
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .map(|a| a.port())
+        .map_err(|e| format!("No free port for the browser window: {}", e))?;
+
+    let mut builder = tauri::WebviewWindowBuilder::new(
+        app,
+        DOCKED_WINDOW,
+        tauri::WebviewUrl::External("about:blank".parse().unwrap()),
+    )
+    .title("Opt-Outta — Browser")
+    .data_directory(std::env::temp_dir().join("opt-outta-webview"))
+    .additional_browser_args(&format!(
+        "--remote-debugging-port={} --disable-blink-features=AutomationControlled {}",
+        port,
+        proxy.map(browser_proxy::server_arg).unwrap_or_default()
+    ));
+    if let Some((x, y, width, height)) = docked_bounds(app) {
+        builder = builder.position(x, y).inner_size(width, height);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("Failed to open the browser window: {}", e))?;
+
+    // WebView2 starts its debugging endpoint shortly after the window appears
+    let version_url = format!("http://127.0.0.1:{}/json/version", port);
+    for _ in 0..20 {
+        if let Ok(resp) = reqwest::get(&version_url).await {
+            if let Ok(info) = resp.json::<serde_json::Value>().await {
+                if let Some(ws) = info["webSocketDebuggerUrl"].as_str() {
+                    let (browser, handler) = CdpBrowser::connect(ws)
+                        .await
+                        .map_err(|e| format!("Failed to connect to the browser window: {}", e))?;
+                    return Ok(Browser::chromium(browser, handler, proxy_login(app, proxy)));
+                }
+            }
+        }
+        sleep(Duration::from_millis(250)).await;
+    }
+    Err("The browser window didn't start in time.".to_string())
+}
+
+/// Elsewhere the system webview has no CDP, so Chrome runs as a chromeless app
+/// window docked beside the app. Firefox has no app windows and opens as usual.
+#[cfg(not(target_os = "windows"))]
+async fn launch_docked(app: &tauri::AppHandle, proxy: Option<&BrowserProxy>) -> Result<Browser, String> {
+    let (install, data_dir) = prepare_browser(app, "", false)?;
+    if install.engine == BrowserEngine::Gecko {
+        return launch_firefox(app, &install, &data_dir, proxy, false).await;
+    }
+
+    let mut config = BrowserConfig::builder()
+        .with_head()
+        .chrome_executable(&install.path)
+        .user_data_dir(&data_dir)
+        .arg("--app=about:blank")
+        .arg("--disable-blink-features=AutomationControlled")
+        .arg("--no-first-run")
+        .arg("--no-default-browser-check")
+        .arg("--disable-background-timer-throttling")
+        .viewport(None);
+    if let Some((x, y, width, height)) = docked_bounds(app) {
+        config = config
+            .arg(format!("--window-position={},{}", x as i32, y as i32))
+            .window_size(width as u32, height as u32);
+    }
+    if let Some(proxy) = proxy {
+        config = config.arg(browser_proxy::server_arg(proxy));
+    }
+    let config = config
+        .build()
+        .map_err(|e| format!("Failed to build browser config: {}", e))?;
+
+    let (browser, handler) = CdpBrowser::launch(config)
+        .await
+        .map_err(|e| format!("Failed to launch Chrome: {}", e))?;
+    Ok(Browser::chromium(browser, handler, proxy_login(app, proxy)))
+}
+
+/// Close the automation browser, e.g. after a cancelled run. On Windows the docked
+/// browser is the app's own webview window, so that window is closed instead.
+#[cfg(target_os = "windows")]
+pub async fn close_browser(app: &tauri::AppHandle, browser: Browser, mode: &BrowserMode) {
+    use tauri::Manager;
+    if *mode == BrowserMode::Docked {
+        if let Some(window) = app.get_webview_window(DOCKED_WINDOW) {
+            let _ = window.close();
+        }
+        return;
+    }
+    shut_down(browser).await;
+}
+
+/// Close the automation browser, e.g. after a cancelled run.
+#[cfg(not(target_os = "windows"))]
+pub async fn close_browser(_app: &tauri::AppHandle, browser: Browser, _mode: &BrowserMode) {
+    shut_down(browser).await;
+}
+
+/// Ask the browser to exit and wait briefly for it, so its profile is unlocked for the next run.
+async fn shut_down(browser: Browser) {
+    match browser.backend {
+        Backend::Chromium { mut browser, handler } => {
+            let _ = browser.close().await;
+            let _ = tokio::time::timeout(Duration::from_secs(5), browser.wait()).await;
+            handler.abort();
+        }
+        Backend::Firefox(session) => session.close().await,
+    }
+}
+
+/// Exposes the Global Privacy Control signal to page scripts.
+const GPC_JS: &str =
+    "Object.defineProperty(Navigator.prototype, 'globalPrivacyControl', { get: () => true, configurable: true });";
+
+/// Send the Global Privacy Control signal from `page` (the `Sec-GPC: 1` request header and
+/// `navigator.globalPrivacyControl`). Some brokers must honor it as an opt-out request.
+pub async fn enable_gpc(page: &Page) -> Result<(), String> {
+    // Firefox sends it for the whole profile, set up at launch
+    let Page::Chromium(page) = page else { return Ok(()) };
+    page.execute(SetExtraHttpHeadersParams::new(Headers::new(serde_json::json!({ "Sec-GPC": "1" }))))
+        .await
+        .map_err(|e| format!("Failed to set the GPC header: {}", e))?;
+    page.evaluate_on_new_document(GPC_JS)
         .await
-        .map_err(|e| format!("Navigation failed: {}", e))?;
-    sleep(Duration::from_secs(2)).await;
+        .map_err(|e| format!("Failed to expose GPC to the page: {}", e))?;
+    Ok(())
+}
+
+enum FetchEvent {
+    Paused(Arc<EventRequestPaused>),
+    Challenged(Arc<EventAuthRequired>),
+}
+
+/// Answer the proxy's login prompts on `page` with `username` and `password`. Chrome
+/// only raises the prompt over CDP while it holds every request for the client, so the
+/// rest are let through untouched.
+pub async fn answer_proxy_login(page: &Page, (username, password): (String, String)) -> Result<(), String> {
+    let page = match page {
+        Page::Chromium(page) => page,
+        Page::Firefox(tab) => {
+            return tab
+                .answer_proxy_login(username, password)
+                .await
+                .map_err(|e| format!("Failed to set up the proxy login: {}", e));
+        }
+    };
+
+    let listen_err = |e: chromiumoxide::error::CdpError| format!("Failed to set up the proxy login: {}", e);
+    let events = futures::stream::select(
+        page.event_listener::<EventRequestPaused>().await.map_err(listen_err)?.map(FetchEvent::Paused),
+        page.event_listener::<EventAuthRequired>().await.map_err(listen_err)?.map(FetchEvent::Challenged),
+    );
+    page.execute(
+        fetch::EnableParams::builder()
+            .pattern(RequestPattern::builder().url_pattern("*").build())
+            .handle_auth_requests(true)
+            .build(),
+    )
+    .await
+    .map_err(listen_err)?;
+
+    let page = page.clone();
+    tokio::spawn(async move {
+        let mut events = events;
+        while let Some(event) = events.next().await {
+            match event {
+                FetchEvent::Paused(e) => {
+                    let _ = page.execute(ContinueRequestParams::new(e.request_id.clone())).await;
+                }
+                FetchEvent::Challenged(e) => {
+                    // Only the proxy gets the login; a site's own prompt is left to the user
+                    let response = if e.auth_challenge.source == Some(AuthChallengeSource::Proxy) {
+                        AuthChallengeResponse {
+                            response: AuthChallengeResponseResponse::ProvideCredentials,
+                            username: Some(username.clone()),
+                            password: Some(password.clone()),
+                        }
+                    } else {
+                        AuthChallengeResponse::new(AuthChallengeResponseResponse::Default)
+                    };
+                    let _ = page.execute(ContinueWithAuthParams::new(e.request_id.clone(), response)).await;
+                }
+            }
+        }
+    });
     Ok(())
 }
 
+/// Size of the phone screen shown to brokers whose forms only work on their mobile site.
+const MOBILE_WIDTH: i64 = 390;
+const MOBILE_HEIGHT: i64 = 844;
+
+/// Chrome's desktop user agent as Chrome on Android would send it: the platform swapped
+/// for the reduced one Android Chrome reports, and "Mobile" before "Safari".
+fn mobile_user_agent(desktop: &str) -> String {
+    let agent = match (desktop.find('('), desktop.find(')')) {
+        (Some(open), Some(close)) if open < close => {
+            format!("{}(Linux; Android 10; K){}", &desktop[..open], &desktop[close + 1..])
+        }
+        _ => desktop.to_string(),
+    };
+    agent.replacen(" Safari/", " Mobile Safari/", 1)
+}
+
+/// Make `page` look like a phone to the site, or with `mobile` false put back the desktop
+/// browser (the docked window's page is reused from one broker to the next). Firefox
+/// has no touch emulation, and only recent versions can change a page's user agent.
+async fn emulate_mobile(browser: &Browser, page: &Page, mobile: bool) -> Result<(), String> {
+    let desktop_agent = browser.user_agent().await?;
+    let result = match page {
+        Page::Chromium(page) => {
+            let result: Result<(), chromiumoxide::error::CdpError> = async {
+                if mobile {
+                    page.execute(SetDeviceMetricsOverrideParams::new(MOBILE_WIDTH, MOBILE_HEIGHT, 3.0, true)).await?;
+                    page.execute(SetUserAgentOverrideParams::new(mobile_user_agent(&desktop_agent))).await?;
+                } else {
+                    page.execute(ClearDeviceMetricsOverrideParams::default()).await?;
+                    page.execute(SetUserAgentOverrideParams::new(desktop_agent)).await?;
+                }
+                page.execute(SetTouchEmulationEnabledParams::new(mobile)).await?;
+                Ok(())
+            }
+            .await;
+            result.map_err(|e| e.to_string())
+        }
+        Page::Firefox(tab) if mobile => {
+            tab.set_viewport(Some((MOBILE_WIDTH, MOBILE_HEIGHT)), Some(3.0)).await?;
+            tab.set_user_agent(Some(&firefox::mobile_user_agent(&desktop_agent))).await
+        }
+        Page::Firefox(tab) => {
+            tab.set_viewport(None, None).await?;
+            // Nothing to undo on a Firefox too old to have changed it
+            let _ = tab.set_user_agent(None).await;
+            Ok(())
+        }
+    };
+    result.map_err(|e| format!("Failed to set up the mobile site: {}", e))
+}
+
+/// Open `url` for a broker. The external browser gets a new tab per page; the docked
+/// window reuses its one page so everything stays in the same place. With `gpc`, the
+/// page sends the Global Privacy Control signal from its first request. `automation`
+/// is the broker's site quirks, which decide the viewport and how long the page gets
+/// to load.
+pub async fn open_page(
+    browser: &Browser,
+    mode: &BrowserMode,
+    url: &str,
+    gpc: bool,
+    automation: &BrokerAutomation,
+) -> Result<Page, String> {
+    let page = blank_page(browser, mode, gpc, automation).await?;
+    navigate(&page, url, automation).await?;
+    Ok(page)
+}
+
+/// `open_page`, recording the page's network activity from its first request.
+pub async fn open_recorded_page(
+    browser: &Browser,
+    mode: &BrowserMode,
+    url: &str,
+    gpc: bool,
+    automation: &BrokerAutomation,
+) -> Result<(Page, NetworkRecorder), String> {
+    let page = blank_page(browser, mode, gpc, automation).await?;
+    let recorder = record_network(&page).await?;
+    navigate(&page, url, automation).await?;
+    Ok((page, recorder))
+}
+
+async fn blank_page(browser: &Browser, mode: &BrowserMode, gpc: bool, automation: &BrokerAutomation) -> Result<Page, String> {
+    let mut page = None;
+    if *mode == BrowserMode::Docked {
+        // The window's page shows up once the handler has seen its target
+        for _ in 0..10 {
+            page = browser.pages().await.into_iter().next();
+            if page.is_some() {
+                break;
+            }
+            sleep(Duration::from_millis(200)).await;
+        }
+    }
+    let reused = page.is_some();
+    // Open blank first so `navigate` can watch the real page load from the start
+    let page = match page {
+        Some(page) => page,
+        None => browser.new_page().await?,
+    };
+    browser.answer_proxy_login(&page).await?;
+    if gpc {
+        enable_gpc(&page).await?;
+    }
+    // A reused page may still be set up as a phone for the previous broker
+    if automation.mobile_viewport || reused {
+        emulate_mobile(browser, &page, automation.mobile_viewport).await?;
+    }
+    Ok(page)
+}
+
+/// A page's network activity, recorded until this is dropped.
+pub struct NetworkRecorder {
+    log: Arc<std::sync::Mutex<har::NetworkLog>>,
+    listener: tokio::task::JoinHandle<()>,
+}
+
+impl NetworkRecorder {
+    /// What was recorded so far as a HAR file, or None when there's nothing in it.
+    pub fn har(&self) -> Option<Vec<u8>> {
+        let log = self.log.lock().ok()?;
+        if log.is_empty() {
+            return None;
+        }
+        serde_json::to_vec_pretty(&log.to_har()).ok()
+    }
+}
+
+impl Drop for NetworkRecorder {
+    fn drop(&mut self) {
+        self.listener.abort();
+    }
+}
+
+enum NetworkEvent {
+    Sent(Arc<EventRequestWillBeSent>),
+    Received(Arc<EventResponseReceived>),
+    Finished(Arc<EventLoadingFinished>),
+    Failed(Arc<EventLoadingFailed>),
+}
+
+fn header_pairs(headers: &Headers) -> Vec<(String, String)> {
+    let Some(map) = headers.inner().as_object() else { return vec![] };
+    map.iter()
+        .map(|(name, value)| (name.clone(), value.as_str().map_or_else(|| value.to_string(), String::from)))
+        .collect()
+}
+
+fn received(response: &Response) -> har::ReceivedResponse {
+    har::ReceivedResponse {
+        status: response.status,
+        status_text: response.status_text.clone(),
+        headers: header_pairs(&response.headers),
+        mime_type: response.mime_type.clone(),
+    }
+}
+
+/// Start recording `page`'s requests and responses for a HAR log. Response bodies aren't
+/// kept; request bodies are, since a form post is usually what needs looking at (Firefox
+/// doesn't hand them over).
+pub async fn record_network(page: &Page) -> Result<NetworkRecorder, String> {
+    let log = Arc::new(std::sync::Mutex::new(har::NetworkLog::default()));
+    let task_log = log.clone();
+    let listener = match page {
+        Page::Chromium(page) => record_cdp_network(page, task_log).await?,
+        Page::Firefox(tab) => {
+            let mut events = tab
+                .events(firefox::NETWORK_EVENTS)
+                .await
+                .map_err(|e| format!("Failed to record network activity: {}", e))?;
+            tokio::spawn(async move {
+                while let Some((method, params)) = events.recv().await {
+                    let Ok(mut log) = task_log.lock() else { return };
+                    firefox::log_network_event(&mut log, &method, &params);
+                }
+            })
+        }
+    };
+    Ok(NetworkRecorder { log, listener })
+}
+
+async fn record_cdp_network(
+    page: &CdpPage,
+    task_log: Arc<std::sync::Mutex<har::NetworkLog>>,
+) -> Result<tokio::task::JoinHandle<()>, String> {
+    use base64::Engine;
+    let listen_err = |e: chromiumoxide::error::CdpError| format!("Failed to record network activity: {}", e);
+    page.execute(network::EnableParams::default()).await.map_err(listen_err)?;
+    let events = futures::stream::select_all([
+        page.event_listener::<EventRequestWillBeSent>().await.map_err(listen_err)?.map(NetworkEvent::Sent).boxed(),
+        page.event_listener::<EventResponseReceived>().await.map_err(listen_err)?.map(NetworkEvent::Received).boxed(),
+        page.event_listener::<EventLoadingFinished>().await.map_err(listen_err)?.map(NetworkEvent::Finished).boxed(),
+        page.event_listener::<EventLoadingFailed>().await.map_err(listen_err)?.map(NetworkEvent::Failed).boxed(),
+    ]);
+
+    Ok(tokio::spawn(async move {
+        let mut events = events;
+        while let Some(event) = events.next().await {
+            let Ok(mut log) = task_log.lock() else { return };
+            match event {
+                NetworkEvent::Sent(e) => {
+                    let body = e.request.post_data_entries.as_ref().map(|entries| {
+                        let bytes: Vec<u8> = entries
+                            .iter()
+                            .filter_map(|entry| entry.bytes.as_ref())
+                            .filter_map(|b| base64::engine::general_purpose::STANDARD.decode(AsRef::<str>::as_ref(b)).ok())
+                            .flatten()
+                            .collect();
+                        String::from_utf8_lossy(&bytes).into_owned()
+                    });
+                    let request = har::SentRequest {
+                        method: e.request.method.clone(),
+                        url: e.request.url.clone(),
+                        headers: header_pairs(&e.request.headers),
+                        body,
+                        resource_type: e.r#type.as_ref().map(|t| AsRef::<str>::as_ref(t).to_string()),
+                        wall_time: *e.wall_time.inner(),
+                        timestamp: *e.timestamp.inner(),
+                    };
+                    log.request_sent(e.request_id.inner(), request, e.redirect_response.as_ref().map(received));
+                }
+                NetworkEvent::Received(e) => log.response_received(e.request_id.inner(), received(&e.response)),
+                NetworkEvent::Finished(e) => log.finished(e.request_id.inner(), *e.timestamp.inner(), e.encoded_data_length),
+                NetworkEvent::Failed(e) => log.failed(e.request_id.inner(), *e.timestamp.inner(), e.error_text.clone()),
+            }
+        }
+    }))
+}
+
+/// Close a page opened by `open_page`, leaving the docked window's page in place.
+pub async fn close_page(page: Page, mode: &BrowserMode) {
+    if *mode == BrowserMode::External {
+        match page {
+            Page::Chromium(page) => {
+                let _ = page.close().await;
+            }
+            Page::Firefox(tab) => {
+                let _ = tab.close().await;
+            }
+        }
+    }
+}
+
+/// Start and close a throwaway headless browser, to catch a binary that's found but won't run.
+pub async fn check_launchable(app: &tauri::AppHandle) -> Result<(), String> {
+    let (install, data_dir) = prepare_browser(app, "preflight", false)?;
+    let browser = launch_headless(app, &install, &data_dir, true).await?;
+    shut_down(browser).await;
+    Ok(())
+}
+
+/// Start the browser at `install` without a window (or with one, when `headless` is
+/// false) and without any of the run's settings, for a quick look at a page.
+async fn launch_headless(
+    app: &tauri::AppHandle,
+    install: &BrowserInstall,
+    data_dir: &Path,
+    headless: bool,
+) -> Result<Browser, String> {
+    if install.engine == BrowserEngine::Gecko {
+        return launch_firefox(app, install, data_dir, None, headless).await;
+    }
+    let mut config = BrowserConfig::builder();
+    if !headless {
+        config = config.with_head();
+    }
+    let config = config
+        .chrome_executable(&install.path)
+        .user_data_dir(data_dir)
+        .arg("--disable-blink-features=AutomationControlled")
+        .arg("--no-first-run")
+        .arg("--no-default-browser-check")
+        .build()
+        .map_err(|e| format!("Failed to build browser config: {}", e))?;
+    let (browser, handler) = CdpBrowser::launch(config)
+        .await
+        .map_err(|e| format!("Failed to launch Chrome: {}", e))?;
+    Ok(Browser::chromium(browser, handler, None))
+}
+
+/// Load a URL in a throwaway browser and extract its page structure. The browser runs
+/// headless unless `automation` says the site turns headless browsers away.
+/// Uses its own profile directory so it never interferes with a run or recording.
+pub async fn snapshot_page_structure(
+    app: &tauri::AppHandle,
+    url: &str,
+    automation: &BrokerAutomation,
+) -> Result<PageStructure, String> {
+    let (install, data_dir) = prepare_browser(app, "snapshot", false)?;
+    let browser = launch_headless(app, &install, &data_dir, !automation.blocks_headless).await?;
+
+    let result = async {
+        let page = browser.new_page().await?;
+        if automation.mobile_viewport {
+            emulate_mobile(&browser, &page, true).await?;
+        }
+        navigate(&page, url, automation).await?;
+        extract_page_structure(&page).await
+    }
+    .await;
+
+    shut_down(browser).await;
+    result
+}
+
+/// Longest `navigate` and `reload` wait for a loaded page's network to go quiet. Pages
+/// with analytics or long polling may never get there.
+pub const SETTLE_CEILING: Duration = Duration::from_secs(6);
+/// `SETTLE_CEILING` for brokers whose pages are known to load slowly.
+pub const SLOW_SETTLE_CEILING: Duration = Duration::from_secs(20);
+
+/// How long a broker's pages get to settle after loading.
+pub fn settle_ceiling(automation: &BrokerAutomation) -> Duration {
+    if automation.slow_load {
+        SLOW_SETTLE_CEILING
+    } else {
+        SETTLE_CEILING
+    }
+}
+
+/// Navigate to a URL and wait for it to load and settle.
+pub async fn navigate(page: &Page, url: &str, automation: &BrokerAutomation) -> Result<(), String> {
+    match page {
+        Page::Chromium(page) => {
+            let lifecycle = page.event_listener::<EventLifecycleEvent>().await.ok();
+            page.goto(url)
+                .await
+                .map_err(|e| format!("Navigation failed: {}", e))?;
+            if let Some(events) = lifecycle {
+                wait_for_network_idle(page, events, settle_ceiling(automation)).await;
+            }
+        }
+        Page::Firefox(tab) => {
+            tab.navigate(url).await.map_err(|e| format!("Navigation failed: {}", e))?;
+            tab.settle(settle_ceiling(automation)).await;
+        }
+    }
+    Ok(())
+}
+
+/// Reload the page and wait for it to load and settle.
+pub async fn reload(page: &Page, automation: &BrokerAutomation) -> Result<(), String> {
+    match page {
+        Page::Chromium(page) => {
+            let lifecycle = page.event_listener::<EventLifecycleEvent>().await.ok();
+            page.reload()
+                .await
+                .map_err(|e| format!("Reload failed: {}", e))?;
+            if let Some(events) = lifecycle {
+                wait_for_network_idle(page, events, settle_ceiling(automation)).await;
+            }
+        }
+        Page::Firefox(tab) => {
+            tab.reload().await.map_err(|e| format!("Reload failed: {}", e))?;
+            tab.settle(settle_ceiling(automation)).await;
+        }
+    }
+    Ok(())
+}
+
+/// Wait for the main frame's new document to reach `networkAlmostIdle` (no more than two
+/// requests in flight for 500ms), up to `ceiling`. `events` must be subscribed
+/// before the navigation starts; lifecycle events from the previous document are skipped
+/// by waiting for the new document's `init` first.
+async fn wait_for_network_idle(page: &CdpPage, mut events: EventStream<EventLifecycleEvent>, ceiling: Duration) {
+    let main_frame = page.mainframe().await.ok().flatten();
+    let idle = async {
+        let mut committed = false;
+        while let Some(event) = events.next().await {
+            if main_frame.as_ref().is_some_and(|f| *f != event.frame_id) {
+                continue;
+            }
+            match event.name.as_str() {
+                "init" => committed = true,
+                "networkAlmostIdle" | "networkIdle" if committed => return,
+                _ => {}
+            }
+        }
+    };
+    let _ = tokio::time::timeout(ceiling, idle).await;
+}
+
 /// JavaScript that extracts page structure without reading any field values.
-#[allow(dead_code)]
 const EXTRACT_JS: &str = r#"
 (() => {
     function cssSelector(el) {
@@ -135,9 +950,11 @@ const EXTRACT_JS: &str = r#"
             placeholder: f.placeholder || null,
             name: f.name || null,
             id: f.id || null,
+            autocomplete: f.getAttribute('autocomplete') || null,
             required: f.required || false,
             options: f.tagName === 'SELECT' ? Array.from(f.options).map(o => o.textContent.trim()) : null,
-            visible: isVisible(f)
+            visible: isVisible(f),
+            honeypot: (HONEYPOT)(f)
         }));
         return {
             selector: cssSelector(form),
@@ -160,27 +977,8 @@ const EXTRACT_JS: &str = r#"
         .filter(t => t.length > 0 && t.length < 500);
 
     // Check for CAPTCHA presence, but also check if it's already solved
-    const captchaWidgetPresent = !!(
-        document.querySelector('[class*="captcha" i]') ||
-        document.querySelector('[id*="captcha" i]') ||
-        document.querySelector('iframe[src*="recaptcha"]') ||
-        document.querySelector('iframe[src*="hcaptcha"]') ||
-        document.querySelector('.g-recaptcha') ||
-        document.querySelector('.h-captcha')
-    );
-    let hasCaptcha = captchaWidgetPresent;
-    if (captchaWidgetPresent) {
-        // reCAPTCHA: solved when g-recaptcha-response textarea has a non-empty value
-        const recaptchaResponse = document.querySelector('textarea[name="g-recaptcha-response"]');
-        if (recaptchaResponse && recaptchaResponse.value && recaptchaResponse.value.length > 0) {
-            hasCaptcha = false;
-        }
-        // hCaptcha: solved when h-captcha-response textarea has a non-empty value
-        const hcaptchaResponse = document.querySelector('textarea[name="h-captcha-response"]');
-        if (hcaptchaResponse && hcaptchaResponse.value && hcaptchaResponse.value.length > 0) {
-            hasCaptcha = false;
-        }
-    }
+    const captcha = (CAPTCHA_STATE)();
+    const hasCaptcha = captcha.present && !captcha.solved;
 
     return {
         url: window.location.href,
@@ -193,22 +991,201 @@ const EXTRACT_JS: &str = r#"
 })()
 "#;
 
+/// JavaScript function reporting whether a text field is a honeypot: one named as a bot trap,
+/// or kept from people (transparent, hidden by an ancestor, tiny or pushed off the page)
+/// while staying in the form. Sites quietly drop requests that fill one in.
+const HONEYPOT_JS: &str = r#"
+(el) => {
+    if (!el || el.tagName === 'SELECT' || ['hidden', 'checkbox', 'radio', 'submit', 'button'].includes(el.type)) return false;
+    const named = /honey[_-]?pot|bot[_-]?field|leave[_-]?(this[_-]?)?blank|do[_-]?n[o']?t[_-]?fill/i;
+    if (named.test(el.name || '') || named.test(el.id || '')) return true;
+    for (let node = el; node && node.nodeType === 1; node = node.parentElement) {
+        const style = window.getComputedStyle(node);
+        if (style.display === 'none' || style.visibility === 'hidden' || parseFloat(style.opacity) === 0) return true;
+        if (node.getAttribute('aria-hidden') === 'true') return true;
+    }
+    const rect = el.getBoundingClientRect();
+    if (rect.width < 2 || rect.height < 2) return true;
+    const left = rect.left + window.scrollX;
+    const top = rect.top + window.scrollY;
+    return rect.right + window.scrollX <= 0 || rect.bottom + window.scrollY <= 0
+        || left >= document.documentElement.scrollWidth || top >= document.documentElement.scrollHeight;
+}
+"#;
+
+/// Whether the field `selector` matches is a honeypot (see `HONEYPOT_JS`). False when it
+/// can't be found; the step reports that itself.
+pub async fn is_honeypot(page: &Page, selector: &str) -> bool {
+    let js = format!(
+        "({})(document.querySelector({}))",
+        HONEYPOT_JS,
+        serde_json::to_string(selector).unwrap(),
+    );
+    page.evaluate::<bool>(js)
+        .await
+        .unwrap_or(false)
+}
+
+/// JavaScript function reporting whether the page shows a CAPTCHA, which provider it
+/// is, and whether the provider's solved-state signal (usually a response token) is set.
+const CAPTCHA_STATE_JS: &str = r#"
+() => {
+    const filled = (sel) => Array.from(document.querySelectorAll(sel)).some(el => (el.value || '').length > 0);
+    const providers = [
+        {
+            kind: 'recaptcha',
+            widget: '.g-recaptcha, iframe[src*="recaptcha"]',
+            solved: 'textarea[name="g-recaptcha-response"]'
+        },
+        {
+            kind: 'hcaptcha',
+            widget: '.h-captcha, iframe[src*="hcaptcha"]',
+            solved: 'textarea[name="h-captcha-response"]'
+        },
+        {
+            kind: 'turnstile',
+            widget: '.cf-turnstile, iframe[src*="challenges.cloudflare.com"]',
+            solved: 'input[name="cf-turnstile-response"]'
+        },
+        {
+            kind: 'funcaptcha',
+            widget: '#FunCaptcha, [id*="arkose" i], iframe[src*="arkoselabs"], iframe[src*="funcaptcha"]',
+            solved: 'input[name="fc-token"], #FunCaptcha-Token, input[name="verification-token"]'
+        },
+        {
+            // Image grids and distorted-text images answered in a plain input
+            kind: 'image',
+            widget: 'img[src*="captcha" i], img[alt*="captcha" i], canvas[id*="captcha" i]',
+            solved: 'input[name*="captcha" i]:not([type="hidden"]), input[id*="captcha" i]:not([type="hidden"])'
+        }
+    ];
+    for (const p of providers) {
+        if (document.querySelector(p.widget)) {
+            return { present: true, solved: filled(p.solved), kind: p.kind };
+        }
+    }
+    const generic = !!(
+        document.querySelector('[class*="captcha" i]') ||
+        document.querySelector('[id*="captcha" i]')
+    );
+    return { present: generic, solved: false, kind: null };
+}
+"#;
+
+/// What `CAPTCHA_STATE_JS` found on the page.
+#[derive(Debug, serde::Deserialize)]
+pub struct CaptchaState {
+    pub present: bool,
+    pub solved: bool,
+    pub kind: Option<String>,
+}
+
+/// Check the page for a CAPTCHA and whether it has been solved.
+pub async fn captcha_state(page: &Page) -> Result<CaptchaState, String> {
+    page.evaluate::<CaptchaState>(format!("({})()", CAPTCHA_STATE_JS))
+        .await
+        .map_err(|e| format!("Failed to check CAPTCHA: {}", e))
+}
+
+/// Resolve once a CAPTCHA on the page reports itself solved. Never resolves for
+/// CAPTCHAs without a solved-state signal; the user confirms those in the app.
+pub async fn wait_for_captcha_solved(page: &Page) {
+    loop {
+        sleep(Duration::from_secs(2)).await;
+        if let Ok(state) = captcha_state(page).await {
+            if state.present && state.solved {
+                return;
+            }
+        }
+    }
+}
+
+/// JavaScript that recognizes anti-bot interstitials: Cloudflare's "checking your
+/// browser" page, interactive challenges (Cloudflare, PerimeterX, DataDome) and
+/// outright block pages, including plain 403/429 and "access denied" responses.
+const CHALLENGE_JS: &str = r#"
+(() => {
+    const title = document.title.toLowerCase();
+    const text = (document.body ? document.body.innerText : '').slice(0, 5000).toLowerCase();
+    const has = (sel) => !!document.querySelector(sel);
+    const cloudflare = has('#challenge-form, #cf-challenge-running, #challenge-running, script[src*="/cdn-cgi/challenge-platform/"]')
+        || title.includes('just a moment') || text.includes('cloudflare ray id');
+
+    if (cloudflare && (title.includes('attention required') || text.includes('you have been blocked')
+        || text.includes('sorry, you have been blocked'))) {
+        return { kind: 'blocked', provider: 'Cloudflare' };
+    }
+    if (has('#px-captcha') || text.includes('press & hold')) {
+        return { kind: 'interactive', provider: 'PerimeterX' };
+    }
+    if (has('iframe[src*="captcha-delivery.com"]')) {
+        return { kind: 'interactive', provider: 'DataDome' };
+    }
+    if (cloudflare) {
+        // The checkbox widget needs a click; the plain interstitial clears itself
+        const interactive = has('iframe[src*="challenges.cloudflare.com"], .cf-turnstile, input[type="checkbox"]');
+        return { kind: interactive ? 'interactive' : 'checking', provider: 'Cloudflare' };
+    }
+    if ((title.includes('access denied') || text.includes('access denied'))
+        && (text.includes('reference #') || text.includes('errors.edgesuite.net'))) {
+        return { kind: 'blocked', provider: 'Akamai' };
+    }
+    // The broker's own server refusing the request
+    const nav = performance.getEntriesByType('navigation')[0];
+    const status = nav && nav.responseStatus;
+    if (status === 403 || status === 429 || status === 451) {
+        return { kind: 'blocked', provider: 'HTTP ' + status };
+    }
+    const denied = ['access denied', '403 forbidden', 'request blocked', 'your ip has been blocked', 'you have been blocked'];
+    if (text.length < 2000 && denied.some(p => title.includes(p) || text.includes(p))) {
+        return { kind: 'blocked', provider: 'access denied page' };
+    }
+    return null;
+})()
+"#;
+
+/// An anti-bot page standing between the automation and the broker's form.
+#[derive(Debug, serde::Deserialize)]
+pub struct Challenge {
+    /// `checking` clears by itself, `interactive` needs the user, `blocked` won't clear
+    pub kind: String,
+    /// Who is blocking: an anti-bot vendor, or the HTTP status / page for the broker's own
+    pub provider: String,
+}
+
+/// Check whether the page is an anti-bot challenge or block page.
+pub async fn detect_challenge(page: &Page) -> Option<Challenge> {
+    page.evaluate::<Option<Challenge>>(CHALLENGE_JS)
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Poll until the page is no longer a challenge, for up to `timeout`. Returns whether
+/// it cleared.
+pub async fn wait_for_challenge_to_clear(page: &Page, timeout: Duration) -> bool {
+    let start = std::time::Instant::now();
+    while start.elapsed() < timeout {
+        sleep(Duration::from_secs(2)).await;
+        if detect_challenge(page).await.is_none() {
+            return true;
+        }
+    }
+    false
+}
+
 /// Extract page structure (no PII — only labels, types, selectors).
-#[allow(dead_code)]
 pub async fn extract_page_structure(page: &Page) -> Result<PageStructure, String> {
-    let result = page
-        .evaluate(EXTRACT_JS)
+    let value = page
+        .evaluate::<serde_json::Value>(EXTRACT_JS.replace("CAPTCHA_STATE", CAPTCHA_STATE_JS).replace("HONEYPOT", HONEYPOT_JS))
         .await
         .map_err(|e| format!("Failed to extract page structure: {}", e))?;
 
-    let value = result.into_value::<serde_json::Value>()
-        .map_err(|e| format!("Failed to convert JS result: {}", e))?;
-
     serde_json::from_value(value).map_err(|e| format!("Failed to parse page structure: {}", e))
 }
 
 /// Resolve a profile key to the actual PII value (local only — never sent to API).
-fn resolve_profile_key(profile: &Profile, key: &str, transform: Option<&str>) -> Option<String> {
+pub fn resolve_profile_key(profile: &Profile, key: &str, transform: Option<&str>) -> Option<String> {
     if let Some(transform_str) = transform {
         if transform_str.starts_with("combine:") {
             let keys: Vec<&str> = transform_str
@@ -224,9 +1201,10 @@ fn resolve_profile_key(profile: &Profile, key: &str, transform: Option<&str>) ->
         }
     }
 
-    match key {
+    let value = match key {
         "firstName" => Some(profile.first_name.clone()),
         "lastName" => Some(profile.last_name.clone()),
+        "fullName" => Some(format!("{} {}", profile.first_name, profile.last_name).trim().to_string()),
         "email" => Some(profile.email.clone()),
         "phone" => Some(profile.phone.clone()),
         "address" => Some(profile.address.clone()),
@@ -234,12 +1212,136 @@ fn resolve_profile_key(profile: &Profile, key: &str, transform: Option<&str>) ->
         "state" => Some(profile.state.clone()),
         "zip" => Some(profile.zip.clone()),
         "dob" => Some(profile.dob.clone()),
-        _ => None,
+        // Only the user's location: runs resolve it per broker with `engine::apply_legal_basis`
+        "legalBasis" => crate::deadlines::legal_basis(profile, None).map(String::from),
+        _ => match profile_format::parse_indexed_key(key)? {
+            ("previousAddress", i, field) => {
+                let addr = profile.previous_addresses.get(i)?;
+                match field {
+                    "address" => Some(addr.address.clone()),
+                    "city" => Some(addr.city.clone()),
+                    "state" => Some(addr.state.clone()),
+                    "zip" => Some(addr.zip.clone()),
+                    _ => None,
+                }
+            }
+            ("alternateEmail", i, "") => profile.alternate_emails.get(i).cloned(),
+            ("alternatePhone", i, "") => profile.alternate_phones.get(i).cloned(),
+            _ => None,
+        },
+    }?;
+    Some(match transform {
+        Some(t) => profile_format::apply_transform(&value, t),
+        None => value,
+    })
+}
+
+/// Pause range between keys when typing a key at a time, in milliseconds.
+const KEY_DELAY_MS: (u64, u64) = (60, 180);
+
+/// Type `value` into the field a key at a time, pausing between keys like a person would,
+/// for forms that only pick up values as they're typed.
+async fn type_slowly(page: &Page, selector: &str, value: &str) -> Result<(), String> {
+    let sel_json = serde_json::to_string(selector).unwrap();
+    // Start from an empty field, as filling it in one go would
+    let js = format!(
+        r#"(() => {{
+            const el = document.querySelector({sel});
+            if (!el) throw new Error('Element not found: ' + {sel});
+            el.focus();
+            el.value = '';
+            el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+        }})()"#,
+        sel = sel_json,
+    );
+    page.run(js).await?;
+    let element = match page {
+        Page::Chromium(page) => Some(page.find_element(selector).await.map_err(|e| e.to_string())?),
+        Page::Firefox(_) => None,
+    };
+    for ch in value.chars() {
+        match (page, &element) {
+            (Page::Chromium(page), Some(element)) => {
+                // Characters without a key of their own (accents, other scripts) go in as text
+                if element.type_str(ch.to_string()).await.is_err() {
+                    page.execute(InsertTextParams::new(ch.to_string()))
+                        .await
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+            // Firefox types any character as a key, into the field focused above
+            (Page::Firefox(tab), _) => tab.type_text(&ch.to_string()).await?,
+            (Page::Chromium(_), None) => {}
+        }
+        let (min, max) = KEY_DELAY_MS;
+        sleep(Duration::from_millis(min + rand::random::<u64>() % (max - min))).await;
     }
+    let js = format!(
+        "document.querySelector({sel})?.dispatchEvent(new Event('change', {{ bubbles: true }}))",
+        sel = sel_json,
+    );
+    page.run(js).await?;
+    Ok(())
 }
 
-/// Execute a single form action on the page.
-pub async fn execute_action(page: &Page, action: &FormAction, profile: &Profile) -> Result<(), String> {
+/// Whether `selector` is a contenteditable box, such as a rich-text editor, which has no
+/// `value` for a fill to set.
+async fn is_content_editable(page: &Page, selector: &str) -> bool {
+    let js = format!(
+        "document.querySelector({sel})?.isContentEditable === true",
+        sel = serde_json::to_string(selector).unwrap(),
+    );
+    page.evaluate::<bool>(js)
+        .await
+        .unwrap_or(false)
+}
+
+/// Replace the text in a contenteditable box. It goes in through the browser's own text
+/// input, so editors that only follow typing (`beforeinput`, `input`) see the change.
+async fn fill_content_editable(page: &Page, selector: &str, value: &str) -> Result<(), String> {
+    let sel_json = serde_json::to_string(selector).unwrap();
+    // Select what's there so the new text replaces it, as typing over a selection would
+    let js = format!(
+        r#"(() => {{
+            const el = document.querySelector({sel});
+            if (!el) throw new Error('Element not found: ' + {sel});
+            el.focus();
+            const range = document.createRange();
+            range.selectNodeContents(el);
+            const selection = window.getSelection();
+            selection.removeAllRanges();
+            selection.addRange(range);
+            document.execCommand('delete');
+        }})()"#,
+        sel = sel_json,
+    );
+    page.run(js).await?;
+    if !value.is_empty() {
+        match page {
+            Page::Chromium(page) => {
+                page.execute(InsertTextParams::new(value.to_string()))
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            Page::Firefox(tab) => tab.type_text(value).await?,
+        }
+    }
+    let js = format!(
+        "document.querySelector({sel})?.dispatchEvent(new Event('change', {{ bubbles: true }}))",
+        sel = sel_json,
+    );
+    page.run(js).await?;
+    Ok(())
+}
+
+/// Execute a single form action on the page, working around the broker's site quirks
+/// in `automation`.
+pub async fn execute_action(
+    page: &Page,
+    action: &FormAction,
+    profile: &Profile,
+    automation: &BrokerAutomation,
+) -> Result<(), String> {
     // Human-like delay between actions
     let delay = Duration::from_millis(500 + (rand::random::<u64>() % 1000));
     sleep(delay).await;
@@ -252,6 +1354,16 @@ pub async fn execute_action(page: &Page, action: &FormAction, profile: &Profile)
             } else {
                 value.clone().unwrap_or_default()
             };
+            if is_content_editable(page, selector).await {
+                return fill_content_editable(page, selector, &value)
+                    .await
+                    .map_err(|e| format!("Fill failed for {}: {}", selector, e));
+            }
+            if automation.slow_typing {
+                return type_slowly(page, selector, &value)
+                    .await
+                    .map_err(|e| format!("Fill failed for {}: {}", selector, e));
+            }
             let sel_json = serde_json::to_string(selector).unwrap();
             let js = format!(
                 r#"(() => {{
@@ -266,25 +1378,106 @@ pub async fn execute_action(page: &Page, action: &FormAction, profile: &Profile)
                 sel = sel_json,
                 val = serde_json::to_string(&value).unwrap(),
             );
-            page.evaluate(js)
+            page.run(js)
                 .await
                 .map_err(|e| format!("Fill failed for {}: {}", selector, e))?;
         }
-        FormAction::Select { selector, value } => {
+        FormAction::Select { selector, value, profile_key } => {
+            let value = match profile_key {
+                Some(pk) => resolve_profile_key(profile, pk, None).ok_or_else(|| format!("Unknown profile key: {}", pk))?,
+                None => value.clone(),
+            };
+            // Values are matched against option labels too, so "CCPA" picks an option whose
+            // value is "ca_ccpa". Failing that, a label that matches once punctuation and case
+            // are set aside, or the only label containing the value.
             let js = format!(
                 r#"(() => {{
                     const el = document.querySelector({sel});
                     if (!el) throw new Error('Element not found');
-                    el.value = {val};
+                    const want = {val};
+                    if (!el.options) {{
+                        el.value = want;
+                        el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                        return;
+                    }}
+                    const options = Array.from(el.options);
+                    const norm = (s) => s.toLowerCase().replace(/[^\p{{L}}\p{{N}}]+/gu, ' ').trim();
+                    const loose = norm(want);
+                    const containing = loose ? options.filter((o) => norm(o.text).includes(loose)) : [];
+                    const option = options.find((o) => o.value === want)
+                        || options.find((o) => o.text.trim().toLowerCase() === want.trim().toLowerCase())
+                        || (loose && options.find((o) => norm(o.text) === loose || norm(o.value) === loose))
+                        || (containing.length === 1 ? containing[0] : null);
+                    if (!option) {{
+                        const labels = options.map((o) => o.text.trim()).filter(Boolean).slice(0, 10);
+                        throw new Error('No option matches ' + {shown} + ' (options: ' + labels.join(', ') + ')');
+                    }}
+                    el.value = option.value;
                     el.dispatchEvent(new Event('change', {{ bubbles: true }}));
                 }})()"#,
                 sel = serde_json::to_string(selector).unwrap(),
-                val = serde_json::to_string(value).unwrap(),
+                val = serde_json::to_string(&value).unwrap(),
+                // Errors end up in history, which names profile fields rather than holding their values
+                shown = serde_json::to_string(&match profile_key {
+                    Some(pk) => format!("your {}", pk),
+                    None => format!("\"{}\"", value),
+                })
+                .unwrap(),
             );
-            page.evaluate(js)
+            page.run(js)
                 .await
                 .map_err(|e| format!("Select failed for {}: {}", selector, e))?;
         }
+        FormAction::ChooseRadio { selector, value, profile_key, transform } => {
+            let value = match profile_key {
+                Some(pk) => resolve_profile_key(profile, pk, transform.as_deref())
+                    .ok_or_else(|| format!("Unknown profile key: {}", pk))?,
+                None => value.clone(),
+            };
+            // `selector` can match the inputs themselves or the element around the group.
+            // Labels match exactly, then once punctuation and case are set aside, then when
+            // only one label contains the value.
+            let js = format!(
+                r#"(() => {{
+                    const matched = Array.from(document.querySelectorAll({sel}));
+                    if (matched.length === 0) throw new Error('Element not found');
+                    const inputs = matched.flatMap((el) => el.matches('input[type=radio], input[type=checkbox]')
+                        ? [el]
+                        : Array.from(el.querySelectorAll('input[type=radio], input[type=checkbox]')));
+                    const labelOf = (input) => {{
+                        const label = (input.labels && input.labels[0]) || input.closest('label');
+                        return ((label && label.textContent) || input.getAttribute('aria-label') || input.value || '').trim();
+                    }};
+                    const norm = (s) => s.toLowerCase().replace(/[^\p{{L}}\p{{N}}]+/gu, ' ').trim();
+                    const want = {val};
+                    const loose = norm(want);
+                    const containing = loose ? inputs.filter((i) => norm(labelOf(i)).includes(loose)) : [];
+                    const input = inputs.find((i) => labelOf(i) === want.trim())
+                        || (loose && inputs.find((i) => norm(labelOf(i)) === loose || norm(i.value) === loose))
+                        || (containing.length === 1 ? containing[0] : null);
+                    if (!input) {{
+                        const labels = inputs.map(labelOf).filter(Boolean).slice(0, 10);
+                        throw new Error('No option matches ' + {shown} + ' (options: ' + labels.join(', ') + ')');
+                    }}
+                    // A click fires the events the page listens for, and would clear a ticked checkbox
+                    if (!input.checked) input.click();
+                    if (!input.checked) {{
+                        input.checked = true;
+                        input.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                    }}
+                }})()"#,
+                sel = serde_json::to_string(selector).unwrap(),
+                val = serde_json::to_string(&value).unwrap(),
+                shown = serde_json::to_string(&match profile_key {
+                    Some(pk) => format!("your {}", pk),
+                    None => format!("\"{}\"", value),
+                })
+                .unwrap(),
+            );
+            page.run(js)
+                .await
+                .map_err(|e| format!("Choosing an option failed for {}: {}", selector, e))?;
+        }
         FormAction::Check { selector, checked } => {
             let js = format!(
                 r#"(() => {{
@@ -296,7 +1489,7 @@ pub async fn execute_action(page: &Page, action: &FormAction, profile: &Profile)
                 sel = serde_json::to_string(selector).unwrap(),
                 checked = checked,
             );
-            page.evaluate(js)
+            page.run(js)
                 .await
                 .map_err(|e| format!("Check failed for {}: {}", selector, e))?;
         }
@@ -309,7 +1502,7 @@ pub async fn execute_action(page: &Page, action: &FormAction, profile: &Profile)
                 }})()"#,
                 sel = serde_json::to_string(selector).unwrap(),
             );
-            page.evaluate(js)
+            page.run(js)
                 .await
                 .map_err(|e| format!("Click failed for {}: {}", selector, e))?;
         }
@@ -322,7 +1515,7 @@ pub async fn execute_action(page: &Page, action: &FormAction, profile: &Profile)
             if !lower.starts_with("http://") && !lower.starts_with("https://") {
                 return Err(format!("Navigation blocked: only http/https URLs allowed, got: {}", url));
             }
-            navigate(page, url).await?;
+            navigate(page, url, automation).await?;
         }
         FormAction::WaitFor { selector, timeout_ms } => {
             let timeout = timeout_ms.unwrap_or(10000).min(30_000);
@@ -343,7 +1536,7 @@ pub async fn execute_action(page: &Page, action: &FormAction, profile: &Profile)
                 sel = sel_json,
                 timeout = timeout,
             );
-            page.evaluate(js)
+            page.run(js)
                 .await
                 .map_err(|e| format!("WaitFor failed for {}: {}", selector, e))?;
         }
@@ -358,29 +1551,48 @@ pub async fn execute_action(page: &Page, action: &FormAction, profile: &Profile)
                 }})()"#,
                 sel = sel_json,
             );
-            page.evaluate(js)
+            page.run(js)
                 .await
                 .map_err(|e| format!("ScrollTo failed for {}: {}", selector, e))?;
         }
         FormAction::FindAndClick { selector, profile_key } => {
+            match find_listing(page, selector, profile, profile_key).await?.0 {
+                ListingMatch::Confident(index) => click_listing(page, selector, index).await?,
+                ListingMatch::Ambiguous(_) => {
+                    return Err(format!("Several results in {} could be your listing", selector));
+                }
+                ListingMatch::NoMatch => {
+                    return Err(format!("No element matching profile value found in: {}", selector));
+                }
+            }
+        }
+        FormAction::ClickMatch { selector, profile_key, index } => {
             let value = resolve_profile_key(profile, profile_key, None)
                 .ok_or_else(|| format!("Unknown profile key: {}", profile_key))?;
-            let sel_json = serde_json::to_string(selector).unwrap();
             let js = format!(
                 r#"(() => {{
                     const sel = {sel};
-                    const containers = Array.from(document.querySelectorAll(sel));
                     const target = {val}.toLowerCase();
-                    const found = containers.find(el => el.textContent.toLowerCase().includes(target));
-                    if (!found) throw new Error('No element matching profile value found in: ' + sel);
+                    const matches = Array.from(document.querySelectorAll(sel))
+                        .filter(el => el.textContent.toLowerCase().includes(target));
+                    const found = matches[{index}];
+                    if (!found) throw new Error('Matching result ' + ({index} + 1) + ' not found in: ' + sel);
                     found.click();
                 }})()"#,
-                sel = sel_json,
+                sel = serde_json::to_string(selector).unwrap(),
                 val = serde_json::to_string(&value).unwrap(),
+                index = index,
             );
-            page.evaluate(js)
+            page.run(js)
                 .await
-                .map_err(|e| format!("FindAndClick failed for {}: {}", selector, e))?;
+                .map_err(|e| format!("ClickMatch failed for {}: {}", selector, e))?;
+        }
+        // The engine swaps in the saved login first, since it needs the app to decrypt it
+        FormAction::FillCredential { selector, .. } | FormAction::FillTotp { selector } => {
+            return Err(format!("No saved login to fill into {}", selector));
+        }
+        FormAction::FillSmsCode { selector } => {
+            return Err(format!("No texted code to fill into {}", selector));
         }
         // Captcha, UserPrompt, ManualFill, ManualSelect, Done, Error are handled by the engine, not here
         FormAction::Captcha { .. } | FormAction::UserPrompt { .. } | FormAction::ManualFill { .. } | FormAction::ManualSelect { .. } | FormAction::Done { .. } | FormAction::Error { .. } => {}
@@ -389,6 +1601,218 @@ pub async fn execute_action(page: &Page, action: &FormAction, profile: &Profile)
     Ok(())
 }
 
+/// Text of every element matching `selector`, whitespace-collapsed and capped in length.
+pub async fn listing_texts(page: &Page, selector: &str) -> Result<Vec<String>, String> {
+    let js = format!(
+        r#"(() => {{
+            return Array.from(document.querySelectorAll({sel}))
+                .map(el => (el.innerText || el.textContent || '').replace(/\s+/g, ' ').trim().slice(0, 500));
+        }})()"#,
+        sel = serde_json::to_string(selector).unwrap(),
+    );
+    page.evaluate::<Vec<String>>(js)
+        .await
+        .map_err(|e| format!("Failed to read results for {}: {}", selector, e))
+}
+
+/// Score each search result matching `selector` against the profile and decide which
+/// one is the user's listing. `profile_key` is the value every candidate must contain.
+/// Returns the decision along with each result's text.
+pub async fn find_listing(
+    page: &Page,
+    selector: &str,
+    profile: &Profile,
+    profile_key: &str,
+) -> Result<(ListingMatch, Vec<String>), String> {
+    let primary = resolve_profile_key(profile, profile_key, None)
+        .ok_or_else(|| format!("Unknown profile key: {}", profile_key))?;
+    let today = chrono::Utc::now().date_naive();
+    let texts = listing_texts(page, selector).await?;
+    let scores: Vec<f32> = texts
+        .iter()
+        .map(|text| listing_match::score_listing(text, profile, &primary, today))
+        .collect();
+    Ok((listing_match::pick_listing(&scores), texts))
+}
+
+/// Click the `index`-th element matching `selector`.
+pub async fn click_listing(page: &Page, selector: &str, index: usize) -> Result<(), String> {
+    let js = format!(
+        r#"(() => {{
+            const found = document.querySelectorAll({sel})[{index}];
+            if (!found) throw new Error('Result ' + ({index} + 1) + ' not found');
+            found.click();
+        }})()"#,
+        sel = serde_json::to_string(selector).unwrap(),
+        index = index,
+    );
+    page.run(js)
+        .await
+        .map_err(|e| format!("FindAndClick failed for {}: {}", selector, e))?;
+    Ok(())
+}
+
+/// Count elements matching `selector` whose text contains the profile value for `profile_key`.
+/// Used to check whether a listing still appears in search results without clicking it.
+pub async fn count_profile_matches(
+    page: &Page,
+    selector: &str,
+    profile: &Profile,
+    profile_key: &str,
+) -> Result<u32, String> {
+    let value = resolve_profile_key(profile, profile_key, None)
+        .ok_or_else(|| format!("Unknown profile key: {}", profile_key))?;
+    let js = format!(
+        r#"(() => {{
+            const target = {val}.toLowerCase();
+            return Array.from(document.querySelectorAll({sel}))
+                .filter(el => el.textContent.toLowerCase().includes(target))
+                .length;
+        }})()"#,
+        sel = serde_json::to_string(selector).unwrap(),
+        val = serde_json::to_string(&value).unwrap(),
+    );
+    page.evaluate::<u32>(js)
+        .await
+        .map_err(|e| format!("Search check failed for {}: {}", selector, e))
+}
+
+/// Close the cookie-consent banner if one is showing, so it doesn't cover the form or the
+/// user's view of it. Returns the consent platform the banner belonged to.
+pub async fn dismiss_consent_banner(page: &Page) -> Option<String> {
+    page.evaluate::<Option<String>>(consent_banners::dismiss_script())
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Whether an element matching `selector` is on the page and takes up space.
+pub async fn is_showing(page: &Page, selector: &str) -> bool {
+    let js = format!(
+        r#"(() => {{
+            const el = document.querySelector({sel});
+            return !!el && el.getClientRects().length > 0;
+        }})()"#,
+        sel = serde_json::to_string(selector).unwrap(),
+    );
+    page.evaluate::<bool>(js)
+        .await
+        .unwrap_or(false)
+}
+
+/// The first of `selector` and then `fallbacks` that matches an element on the page, so a step
+/// survives a broker redesign that broke its main locator. Returns `selector` when none match.
+pub async fn resolve_selector(page: &Page, selector: &str, fallbacks: &[String]) -> String {
+    let candidates: Vec<&str> = std::iter::once(selector).chain(fallbacks.iter().map(|s| s.as_str())).collect();
+    let js = format!(
+        r#"(() => {{
+            for (const sel of {sels}) {{
+                try {{
+                    if (document.querySelector(sel)) return sel;
+                }} catch (e) {{}}
+            }}
+            return null;
+        }})()"#,
+        sels = serde_json::to_string(&candidates).unwrap(),
+    );
+    page.evaluate::<Option<String>>(js)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| selector.to_string())
+}
+
+/// The page's URL and visible text (capped), for judging how a submission went.
+/// Stays on this machine.
+pub async fn page_url_and_text(page: &Page) -> Result<(String, String), String> {
+    let value = page
+        .evaluate::<serde_json::Value>("({ url: location.href, text: (document.body ? document.body.innerText : '').slice(0, 20000) })")
+        .await
+        .map_err(|e| format!("Failed to read page: {}", e))?;
+    Ok((
+        value["url"].as_str().unwrap_or_default().to_string(),
+        value["text"].as_str().unwrap_or_default().to_string(),
+    ))
+}
+
+/// Capture a full-page PNG screenshot.
+pub async fn capture_screenshot(page: &Page) -> Result<Vec<u8>, String> {
+    use chromiumoxide::page::ScreenshotParams;
+    match page {
+        Page::Chromium(page) => page
+            .screenshot(ScreenshotParams::builder().full_page(true).build())
+            .await
+            .map_err(|e| e.to_string()),
+        Page::Firefox(tab) => tab.screenshot().await,
+    }
+    .map_err(|e| format!("Failed to capture screenshot: {}", e))
+}
+
+/// Capture the page as an MHTML archive via CDP `Page.captureSnapshot`. Firefox can't
+/// save one, so its runs keep only the screenshot.
+pub async fn capture_mhtml(page: &Page) -> Result<Vec<u8>, String> {
+    use chromiumoxide::cdp::browser_protocol::page::{CaptureSnapshotFormat, CaptureSnapshotParams};
+    let Page::Chromium(page) = page else {
+        return Err("Firefox can't save pages as MHTML archives".to_string());
+    };
+    let params = CaptureSnapshotParams::builder()
+        .format(CaptureSnapshotFormat::Mhtml)
+        .build();
+    let snapshot = page
+        .execute(params)
+        .await
+        .map_err(|e| format!("Failed to capture MHTML snapshot: {}", e))?;
+    Ok(snapshot.result.data.into_bytes())
+}
+
+/// Bring the page's tab and window to the front, restoring the window if it was
+/// minimized, so the user doesn't have to hunt for the page that needs them.
+pub async fn bring_to_front(page: &Page) -> Result<(), String> {
+    use chromiumoxide::cdp::browser_protocol::browser::{
+        Bounds, GetWindowForTargetParams, SetWindowBoundsParams, WindowState,
+    };
+    let page = match page {
+        Page::Chromium(page) => page,
+        Page::Firefox(tab) => {
+            return tab.activate().await.map_err(|e| format!("Failed to bring the page to the front: {}", e));
+        }
+    };
+    if let Ok(window) = page.execute(GetWindowForTargetParams::default()).await {
+        let bounds = Bounds::builder().window_state(WindowState::Normal).build();
+        let _ = page
+            .execute(SetWindowBoundsParams::new(window.result.window_id, bounds))
+            .await;
+    }
+    page.bring_to_front()
+        .await
+        .map_err(|e| format!("Failed to bring the page to the front: {}", e))?;
+    Ok(())
+}
+
+/// Widgets of common CAPTCHA providers, with the type reported to the app.
+const CAPTCHA_SELECTORS: &[(&str, &str)] = &[
+    (".g-recaptcha", "recaptcha"),
+    ("iframe[src*='recaptcha']", "recaptcha"),
+    (".h-captcha", "hcaptcha"),
+    ("iframe[src*='hcaptcha.com']", "hcaptcha"),
+    (".cf-turnstile", "turnstile"),
+    ("iframe[src*='challenges.cloudflare.com']", "turnstile"),
+    ("#FunCaptcha, [id*='arkose' i]", "funcaptcha"),
+    ("iframe[src*='arkoselabs'], iframe[src*='funcaptcha']", "funcaptcha"),
+    ("#captcha, .captcha, img[src*='captcha' i]", "image"),
+];
+
+/// Find the page's CAPTCHA widget, scroll to it and highlight it. Returns the
+/// selector that matched (for `remove_highlight`) and the CAPTCHA type.
+pub async fn highlight_captcha(page: &Page) -> Option<(String, String)> {
+    for (selector, kind) in CAPTCHA_SELECTORS {
+        if highlight_element(page, selector).await.is_ok() {
+            return Some((selector.to_string(), kind.to_string()));
+        }
+    }
+    None
+}
+
 /// Scrolls to an element and adds a pulsing highlight border
 pub async fn highlight_element(page: &Page, selector: &str) -> Result<(), String> {
     let sel_json = serde_json::to_string(selector).unwrap();
@@ -411,7 +1835,7 @@ pub async fn highlight_element(page: &Page, selector: &str) -> Result<(), String
         }})()"#,
         sel = sel_json,
     );
-    page.evaluate(js)
+    page.run(js)
         .await
         .map_err(|e| format!("Failed to highlight element {}: {}", selector, e))?;
     Ok(())
@@ -437,7 +1861,7 @@ pub async fn remove_highlight(page: &Page, selector: &str) -> Result<(), String>
         }})()"#,
         sel = sel_json,
     );
-    page.evaluate(js)
+    page.run(js)
         .await
         .map_err(|e| format!("Failed to remove highlight {}: {}", selector, e))?;
     Ok(())
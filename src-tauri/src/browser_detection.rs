@@ -0,0 +1,257 @@
+use crate::models::BrowserEngine;
+use std::path::{Path, PathBuf};
+
+/// How a browser install is packaged. Flatpak and Snap builds run confined, so they need
+/// a profile directory inside their sandbox and can't be cleaned up by host PID.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Packaging {
+    Native,
+    /// Launched through the `flatpak run` wrapper Flatpak exports for the app
+    Flatpak { app_id: String },
+    /// Launched through the `/snap/bin` wrapper
+    Snap { name: String },
+}
+
+/// A browser found on this machine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrowserInstall {
+    /// What the user sees, e.g. "Google Chrome Beta" or "Chromium (Flatpak)"
+    pub name: String,
+    pub path: PathBuf,
+    pub packaging: Packaging,
+    pub engine: BrowserEngine,
+}
+
+impl BrowserInstall {
+    pub fn is_sandboxed(&self) -> bool {
+        self.packaging != Packaging::Native
+    }
+
+    /// Where this install keeps the automation profile called `name`. Confined browsers
+    /// can't see the host's temp dir, so they get a directory they're allowed to write.
+    pub fn profile_dir(&self, name: &str) -> PathBuf {
+        home_dir()
+            .and_then(|home| sandbox_profile_dir(&self.packaging, &home, name))
+            .unwrap_or_else(|| std::env::temp_dir().join(name))
+    }
+}
+
+/// Flatpak builds, in order of preference.
+const FLATPAK_APPS: &[(&str, &str)] = &[
+    ("Google Chrome (Flatpak)", "com.google.Chrome"),
+    ("Google Chrome Dev (Flatpak)", "com.google.ChromeDev"),
+    ("Chromium (Flatpak)", "org.chromium.Chromium"),
+    ("Ungoogled Chromium (Flatpak)", "io.github.ungoogled_software.ungoogled_chromium"),
+    ("Microsoft Edge (Flatpak)", "com.microsoft.Edge"),
+    ("Firefox (Flatpak)", "org.mozilla.firefox"),
+];
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").filter(|h| !h.is_empty()).map(PathBuf::from)
+}
+
+/// The packaging of the browser at `path`, from where Flatpak and Snap put their wrappers.
+pub fn packaging_for(path: &Path) -> Packaging {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let parent = path.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+    if parent.ends_with("flatpak/exports/bin") {
+        Packaging::Flatpak { app_id: name }
+    } else if parent == "/snap/bin" {
+        Packaging::Snap { name }
+    } else {
+        Packaging::Native
+    }
+}
+
+/// Profile directory for a confined browser: Flatpak apps may always write their own
+/// `~/.var/app/<id>` and snaps their `~/snap/<name>/common`, at the same path inside and out.
+pub fn sandbox_profile_dir(packaging: &Packaging, home: &Path, name: &str) -> Option<PathBuf> {
+    match packaging {
+        Packaging::Native => None,
+        Packaging::Flatpak { app_id } => Some(home.join(".var/app").join(app_id).join("data").join(name)),
+        Packaging::Snap { name: snap } => Some(home.join("snap").join(snap).join("common").join(name)),
+    }
+}
+
+/// The engine behind a browser, from its name. Everything found is Chromium-based except
+/// Firefox.
+pub fn engine_for(name: &str) -> BrowserEngine {
+    if name.starts_with("Firefox") {
+        BrowserEngine::Gecko
+    } else {
+        BrowserEngine::Chromium
+    }
+}
+
+/// Places a browser might be installed on this platform, in order of preference. Stable
+/// Chrome comes first, then its pre-release channels, then Chromium and Edge. Firefox
+/// comes last, since some sites behave differently for it than for the browsers most
+/// playbooks were recorded in.
+fn candidates() -> Vec<(String, PathBuf)> {
+    let named = |list: &[(&str, &str)]| -> Vec<(String, PathBuf)> {
+        list.iter().map(|(name, path)| (name.to_string(), PathBuf::from(path))).collect()
+    };
+    if cfg!(target_os = "macos") {
+        return named(&[
+            ("Google Chrome", "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome"),
+            ("Google Chrome Beta", "/Applications/Google Chrome Beta.app/Contents/MacOS/Google Chrome Beta"),
+            ("Google Chrome Dev", "/Applications/Google Chrome Dev.app/Contents/MacOS/Google Chrome Dev"),
+            ("Google Chrome Canary", "/Applications/Google Chrome Canary.app/Contents/MacOS/Google Chrome Canary"),
+            ("Chromium", "/Applications/Chromium.app/Contents/MacOS/Chromium"),
+            ("Microsoft Edge", "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge"),
+            ("Firefox", "/Applications/Firefox.app/Contents/MacOS/firefox"),
+        ]);
+    }
+    if cfg!(target_os = "windows") {
+        let mut paths = named(&[
+            ("Google Chrome", r"C:\Program Files\Google\Chrome\Application\chrome.exe"),
+            ("Google Chrome", r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe"),
+            ("Google Chrome Beta", r"C:\Program Files\Google\Chrome Beta\Application\chrome.exe"),
+            ("Google Chrome Dev", r"C:\Program Files\Google\Chrome Dev\Application\chrome.exe"),
+        ]);
+        // Canary and Chromium install per user
+        if let Some(local) = std::env::var_os("LOCALAPPDATA").map(PathBuf::from) {
+            paths.push(("Google Chrome Canary".to_string(), local.join(r"Google\Chrome SxS\Application\chrome.exe")));
+            paths.push(("Chromium".to_string(), local.join(r"Chromium\Application\chrome.exe")));
+        }
+        // Edge comes with Windows, so a machine without Chrome can still run opt-outs
+        paths.extend(named(&[
+            ("Microsoft Edge", r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe"),
+            ("Microsoft Edge", r"C:\Program Files\Microsoft\Edge\Application\msedge.exe"),
+            ("Firefox", r"C:\Program Files\Mozilla Firefox\firefox.exe"),
+            ("Firefox", r"C:\Program Files (x86)\Mozilla Firefox\firefox.exe"),
+        ]));
+        return paths;
+    }
+
+    let mut paths = named(&[
+        ("Google Chrome", "/usr/bin/google-chrome"),
+        ("Google Chrome", "/usr/bin/google-chrome-stable"),
+        ("Google Chrome Beta", "/usr/bin/google-chrome-beta"),
+        ("Google Chrome Dev", "/usr/bin/google-chrome-unstable"),
+        ("Google Chrome Canary", "/usr/bin/google-chrome-canary"),
+        ("Chromium", "/usr/bin/chromium"),
+        ("Chromium", "/usr/bin/chromium-browser"),
+        // Ubuntu's /usr/bin/chromium-browser is only a stub that installs the snap, so the
+        // snap wrapper itself is checked too
+        ("Chromium (Snap)", "/snap/bin/chromium"),
+        ("Microsoft Edge", "/usr/bin/microsoft-edge"),
+        ("Microsoft Edge", "/usr/bin/microsoft-edge-stable"),
+        ("Firefox", "/usr/bin/firefox"),
+        ("Firefox (Snap)", "/snap/bin/firefox"),
+    ]);
+    let mut export_dirs = vec![PathBuf::from("/var/lib/flatpak/exports/bin")];
+    if let Some(home) = home_dir() {
+        export_dirs.insert(0, home.join(".local/share/flatpak/exports/bin"));
+    }
+    for (name, app_id) in FLATPAK_APPS {
+        paths.extend(export_dirs.iter().map(|dir| (name.to_string(), dir.join(app_id))));
+    }
+    paths
+}
+
+/// Every browser installed, one per name (the same browser is often reachable from
+/// several paths), including Flatpak and Snap builds on Linux.
+pub fn find_all() -> Vec<BrowserInstall> {
+    let mut found: Vec<BrowserInstall> = Vec::new();
+    for (name, path) in candidates() {
+        if path.exists() && !found.iter().any(|f| f.name == name) {
+            found.push(BrowserInstall { engine: engine_for(&name), name, packaging: packaging_for(&path), path });
+        }
+    }
+    found
+}
+
+/// The browser to automate: the user's choice if it's still installed, otherwise the
+/// first Chromium-based one found, and Firefox only when there's nothing else.
+pub fn pick(mut installs: Vec<BrowserInstall>, preferred: Option<&str>) -> Option<BrowserInstall> {
+    if let Some(i) = preferred.and_then(|p| installs.iter().position(|i| i.path == Path::new(p))) {
+        return Some(installs.swap_remove(i));
+    }
+    // The sort is stable, so each engine's browsers keep their order of preference
+    installs.sort_by_key(|i| i.engine == BrowserEngine::Gecko);
+    installs.into_iter().next()
+}
+
+/// Find the browser to automate, preferring the one at `preferred`. `downloaded` is the
+/// Chromium the app downloaded, if any, used when nothing installed will do.
+pub fn find(preferred: Option<&str>, downloaded: Option<BrowserInstall>) -> Result<BrowserInstall, String> {
+    let mut installs = find_all();
+    installs.extend(downloaded);
+    pick(installs, preferred).ok_or_else(|| {
+        "No supported browser found. Install Google Chrome, Microsoft Edge or Firefox, or download Chromium in Settings."
+            .to_string()
+    })
+}
+
+/// `find`, but only Chromium-based browsers, for work that needs the Chrome DevTools
+/// Protocol such as recording a playbook.
+pub fn find_chromium(preferred: Option<&str>, downloaded: Option<BrowserInstall>) -> Result<BrowserInstall, String> {
+    let mut installs = find_all();
+    installs.extend(downloaded);
+    installs.retain(|i| i.engine == BrowserEngine::Chromium);
+    pick(installs, preferred).ok_or_else(|| {
+        "Recording playbooks needs Google Chrome, Chromium or Microsoft Edge. Install one, or download Chromium in Settings."
+            .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_flatpak_and_snap_wrappers() {
+        assert_eq!(
+            packaging_for(Path::new("/var/lib/flatpak/exports/bin/org.chromium.Chromium")),
+            Packaging::Flatpak { app_id: "org.chromium.Chromium".to_string() }
+        );
+        assert_eq!(
+            packaging_for(Path::new("/home/jane/.local/share/flatpak/exports/bin/com.google.Chrome")),
+            Packaging::Flatpak { app_id: "com.google.Chrome".to_string() }
+        );
+        assert_eq!(packaging_for(Path::new("/snap/bin/chromium")), Packaging::Snap { name: "chromium".to_string() });
+        assert_eq!(packaging_for(Path::new("/usr/bin/chromium")), Packaging::Native);
+    }
+
+    #[test]
+    fn keeps_confined_profiles_inside_the_sandbox() {
+        let home = Path::new("/home/jane");
+        let flatpak = Packaging::Flatpak { app_id: "org.chromium.Chromium".to_string() };
+        assert_eq!(
+            sandbox_profile_dir(&flatpak, home, "opt-outta-chrome"),
+            Some(PathBuf::from("/home/jane/.var/app/org.chromium.Chromium/data/opt-outta-chrome"))
+        );
+        let snap = Packaging::Snap { name: "chromium".to_string() };
+        assert_eq!(
+            sandbox_profile_dir(&snap, home, "opt-outta-chrome"),
+            Some(PathBuf::from("/home/jane/snap/chromium/common/opt-outta-chrome"))
+        );
+        assert_eq!(sandbox_profile_dir(&Packaging::Native, home, "x"), None);
+    }
+
+    #[test]
+    fn picks_the_preferred_browser_while_installed() {
+        let install = |name: &str, path: &str| BrowserInstall {
+            name: name.to_string(),
+            path: PathBuf::from(path),
+            packaging: Packaging::Native,
+            engine: engine_for(name),
+        };
+        let installs = vec![
+            install("Firefox", "/usr/bin/firefox"),
+            install("Google Chrome", "/usr/bin/google-chrome"),
+            install("Google Chrome Beta", "/usr/bin/google-chrome-beta"),
+            install("Microsoft Edge", "/usr/bin/microsoft-edge"),
+        ];
+        let pick_name = |preferred| pick(installs.clone(), preferred).map(|i| i.name);
+        assert_eq!(pick_name(Some("/usr/bin/google-chrome-beta")).as_deref(), Some("Google Chrome Beta"));
+        assert_eq!(pick_name(Some("/usr/bin/microsoft-edge")).as_deref(), Some("Microsoft Edge"));
+        assert_eq!(pick_name(Some("/usr/bin/chromium")).as_deref(), Some("Google Chrome"));
+        assert_eq!(pick_name(None).as_deref(), Some("Google Chrome"));
+        // Firefox is only picked when chosen, or when it's all there is
+        assert_eq!(pick_name(Some("/usr/bin/firefox")).as_deref(), Some("Firefox"));
+        assert_eq!(pick(installs[..1].to_vec(), None).map(|i| i.name).as_deref(), Some("Firefox"));
+        assert_eq!(pick(vec![], None), None);
+    }
+}
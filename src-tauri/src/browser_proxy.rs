@@ -0,0 +1,77 @@
+use crate::models::{AppSettings, BrowserProxy, ProxyKind, RunOptions};
+
+/// Check that a proxy is one Chrome can be pointed at.
+pub fn validate(proxy: &BrowserProxy) -> Result<(), String> {
+    let host = proxy.host.trim();
+    if host.is_empty() {
+        return Err("Enter the proxy's host name or IP address.".to_string());
+    }
+    if host.contains("://") || host.contains('/') || host.contains('@') || host.chars().any(char::is_whitespace) {
+        return Err("Enter just the proxy's host name or IP address, without a scheme, path or login.".to_string());
+    }
+    if proxy.port == 0 {
+        return Err("Enter the proxy's port.".to_string());
+    }
+    // Chrome has no way to log in to a SOCKS proxy
+    if proxy.kind == ProxyKind::Socks5 && proxy.username.as_deref().is_some_and(|u| !u.trim().is_empty()) {
+        return Err("Chrome can't log in to SOCKS5 proxies. Use an HTTP proxy, or a SOCKS5 one without a login.".to_string());
+    }
+    Ok(())
+}
+
+/// The `--proxy-server` flag that sends all of Chrome's traffic through `proxy`.
+pub fn server_arg(proxy: &BrowserProxy) -> String {
+    let scheme = match proxy.kind {
+        ProxyKind::Http => "http",
+        ProxyKind::Socks5 => "socks5",
+    };
+    let host = proxy.host.trim();
+    // IPv6 addresses need brackets before the port
+    let host = if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    };
+    format!("--proxy-server={}://{}:{}", scheme, host, proxy.port)
+}
+
+/// The proxy a run's browser uses: the one in settings, unless the run asks to connect
+/// directly.
+pub fn for_run(settings: &AppSettings, options: &RunOptions) -> Option<BrowserProxy> {
+    if options.direct_connection {
+        return None;
+    }
+    settings.browser_proxy.clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy(kind: ProxyKind, host: &str, username: Option<&str>) -> BrowserProxy {
+        BrowserProxy {
+            kind,
+            host: host.to_string(),
+            port: 8080,
+            username: username.map(String::from),
+        }
+    }
+
+    #[test]
+    fn builds_the_proxy_flag_for_chrome() {
+        assert_eq!(server_arg(&proxy(ProxyKind::Http, " proxy.corp.example ", None)), "--proxy-server=http://proxy.corp.example:8080");
+        assert_eq!(server_arg(&proxy(ProxyKind::Socks5, "::1", None)), "--proxy-server=socks5://[::1]:8080");
+        assert!(validate(&proxy(ProxyKind::Http, "proxy.corp.example", Some("jane"))).is_ok());
+        assert!(validate(&proxy(ProxyKind::Socks5, "127.0.0.1", Some("jane"))).is_err());
+        assert!(validate(&proxy(ProxyKind::Http, "http://proxy.corp.example", None)).is_err());
+        assert!(validate(&proxy(ProxyKind::Http, "", None)).is_err());
+    }
+
+    #[test]
+    fn runs_use_the_settings_proxy_unless_told_to_connect_directly() {
+        let settings = AppSettings { browser_proxy: Some(proxy(ProxyKind::Http, "proxy.corp.example", None)), ..Default::default() };
+        assert_eq!(for_run(&settings, &RunOptions::default()).map(|p| p.host).as_deref(), Some("proxy.corp.example"));
+        let direct = RunOptions { direct_connection: true, ..Default::default() };
+        assert!(for_run(&settings, &direct).is_none());
+    }
+}
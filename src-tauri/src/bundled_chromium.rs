@@ -0,0 +1,191 @@
+use crate::browser_detection::{BrowserInstall, Packaging};
+use crate::models::{BrowserEngine, BundledChromiumStatus};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Chrome for Testing build the app downloads when no browser is installed.
+const VERSION: &str = "131.0.6778.85";
+/// SHA-256 of each platform's download of `VERSION`, checked in beside it in `sha256sum`
+/// format. The release workflow downloads every platform's build and fails on a
+/// mismatch. A platform without a pin can't check the download, so it isn't offered.
+const CHECKSUMS: &str = include_str!("../chromium.sha256");
+const DOWNLOAD_BASE: &str = "https://storage.googleapis.com/chrome-for-testing-public";
+/// Directory in app data that holds the downloaded build.
+const DIR: &str = "chromium";
+
+/// Set while a download is in progress, so a second click doesn't start another.
+static DOWNLOADING: AtomicBool = AtomicBool::new(false);
+
+/// Chrome for Testing's name for this platform. None where it has no build.
+fn platform() -> Option<&'static str> {
+    if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some("linux64")
+    } else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        Some("mac-arm64")
+    } else if cfg!(all(target_os = "macos", target_arch = "x86_64")) {
+        Some("mac-x64")
+    } else if cfg!(all(target_os = "windows", target_arch = "x86_64")) {
+        Some("win64")
+    } else if cfg!(all(target_os = "windows", target_arch = "x86")) {
+        Some("win32")
+    } else {
+        None
+    }
+}
+
+/// The pinned checksum for `platform` among `pins`, lowercased. Lines that aren't a
+/// SHA-256 in hex followed by the platform's archive name are ignored.
+fn checksum_for(pins: &str, platform: &str) -> Option<String> {
+    let archive = format!("chrome-{}.zip", platform);
+    pins.lines().find_map(|line| {
+        let (hex, name) = line.trim().split_once(char::is_whitespace)?;
+        let hex = hex.to_lowercase();
+        (name.trim_start().trim_start_matches('*') == archive && hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .then_some(hex)
+    })
+}
+
+/// Where the browser is inside the unpacked download for `platform`.
+fn executable(platform: &str) -> PathBuf {
+    let root = PathBuf::from(format!("chrome-{}", platform));
+    if platform.starts_with("mac") {
+        root.join("Google Chrome for Testing.app/Contents/MacOS/Google Chrome for Testing")
+    } else if platform.starts_with("win") {
+        root.join("chrome.exe")
+    } else {
+        root.join("chrome")
+    }
+}
+
+fn base_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join(DIR);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// The downloaded Chromium, when it's been downloaded.
+pub fn installed(app: &tauri::AppHandle) -> Option<BrowserInstall> {
+    let path = base_dir(app).ok()?.join(VERSION).join(executable(platform()?));
+    path.exists().then(|| BrowserInstall {
+        name: "Chromium (downloaded)".to_string(),
+        path,
+        packaging: Packaging::Native,
+        engine: BrowserEngine::Chromium,
+    })
+}
+
+pub fn status(app: &tauri::AppHandle) -> BundledChromiumStatus {
+    BundledChromiumStatus {
+        version: VERSION.to_string(),
+        available: platform().and_then(|p| checksum_for(CHECKSUMS, p)).is_some(),
+        installed: installed(app).is_some(),
+        downloading: DOWNLOADING.load(Ordering::SeqCst),
+    }
+}
+
+/// Unpack the download into `dest`, which is replaced. The zip keeps the execute bits
+/// and the symlinks a macOS app bundle needs.
+fn unpack(archive: &Path, dest: &Path) -> Result<(), String> {
+    if dest.exists() {
+        fs::remove_dir_all(dest).map_err(|e| e.to_string())?;
+    }
+    let file = fs::File::open(archive).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("The Chromium download isn't a valid archive: {}", e))?;
+    zip.extract(dest).map_err(|e| format!("Failed to unpack Chromium: {}", e))
+}
+
+async fn fetch_and_install(app: &tauri::AppHandle, on_progress: impl Fn(u64, Option<u64>)) -> Result<PathBuf, String> {
+    let platform = platform().ok_or_else(|| "There's no Chromium download for this system.".to_string())?;
+    let expected = checksum_for(CHECKSUMS, platform)
+        .ok_or_else(|| "This build of the app has no Chromium download for your system.".to_string())?;
+    let dir = base_dir(app)?;
+    let archive = dir.join(format!("{}.zip.part", VERSION));
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let url = format!("{}/{}/{}/chrome-{}.zip", DOWNLOAD_BASE, VERSION, platform, platform);
+    let mut response = client.get(&url).send().await.map_err(|e| format!("Chromium download failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Chromium download failed ({})", response.status()));
+    }
+    let total = response.content_length();
+    let mut file = fs::File::create(&archive).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut downloaded = 0u64;
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Chromium download failed: {}", e))? {
+        hasher.update(&chunk);
+        file.write_all(&chunk).map_err(|e| e.to_string())?;
+        downloaded += chunk.len() as u64;
+        on_progress(downloaded, total);
+    }
+    drop(file);
+
+    if format!("{:x}", hasher.finalize()) != expected {
+        let _ = fs::remove_file(&archive);
+        return Err("The Chromium download didn't match its checksum, so it wasn't installed.".to_string());
+    }
+
+    // Unpack beside the final directory, so a failure part way never leaves a broken install
+    let staging = dir.join(format!("{}.partial", VERSION));
+    let install = dir.join(VERSION);
+    let (from, to) = (archive.clone(), staging.clone());
+    tauri::async_runtime::spawn_blocking(move || unpack(&from, &to))
+        .await
+        .map_err(|e| e.to_string())??;
+    let _ = fs::remove_file(&archive);
+    if install.exists() {
+        fs::remove_dir_all(&install).map_err(|e| e.to_string())?;
+    }
+    fs::rename(&staging, &install).map_err(|e| e.to_string())?;
+
+    // Builds pinned by earlier versions of the app aren't used any more
+    if let Ok(entries) = fs::read_dir(&dir) {
+        for entry in entries.flatten().filter(|e| e.file_name() != VERSION) {
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+
+    let path = install.join(executable(platform));
+    if !path.exists() {
+        return Err("The Chromium download didn't contain a browser.".to_string());
+    }
+    Ok(path)
+}
+
+/// Download the pinned Chromium build into app data, check it against its checksum and
+/// unpack it, reporting bytes downloaded (and the total, when known) as it goes.
+pub async fn download(app: &tauri::AppHandle, on_progress: impl Fn(u64, Option<u64>)) -> Result<PathBuf, String> {
+    if DOWNLOADING.swap(true, Ordering::SeqCst) {
+        return Err("Chromium is already downloading.".to_string());
+    }
+    let result = fetch_and_install(app, on_progress).await;
+    DOWNLOADING.store(false, Ordering::SeqCst);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_pinned_checksum_for_this_platform() {
+        let linux = "a".repeat(64);
+        let mac = "B".repeat(64);
+        let pins = format!(
+            "# pins\n{}  chrome-linux64.zip\n{} *chrome-mac-arm64.zip\nnot-a-hash  chrome-win64.zip\n",
+            linux, mac
+        );
+        assert_eq!(checksum_for(&pins, "linux64"), Some(linux));
+        assert_eq!(checksum_for(&pins, "mac-arm64"), Some("b".repeat(64)));
+        assert_eq!(checksum_for(&pins, "win64"), None);
+        assert_eq!(checksum_for(&pins, "mac-x64"), None);
+        assert_eq!(executable("win64"), PathBuf::from("chrome-win64").join("chrome.exe"));
+    }
+}
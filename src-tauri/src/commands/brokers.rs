@@ -1,10 +1,138 @@
-use crate::models::BrokerRegistry;
+use crate::broker_difficulty;
+use crate::commands::profile;
+use crate::credentials;
+use crate::deadlines;
+use crate::likelihood;
+use crate::models::{BrokerCategory, BrokerCredential, BrokerNetwork, BrokerRegistry, LikelihoodAssessment, RequestEmail, RequestType};
 use crate::playbook_api;
+use crate::region;
 use crate::registry_cache;
+use crate::request_letter;
 use tauri::Manager;
 
+/// Group brokers that share a `network` into `registry.networks`.
+fn group_networks(registry: &mut BrokerRegistry) {
+    let mut networks: Vec<BrokerNetwork> = Vec::new();
+    for broker in &registry.brokers {
+        let Some(network_id) = broker.network.as_deref() else {
+            continue;
+        };
+        let idx = match networks.iter().position(|n| n.id == network_id) {
+            Some(i) => i,
+            None => {
+                networks.push(BrokerNetwork {
+                    id: network_id.to_string(),
+                    parent_id: None,
+                    broker_ids: Vec::new(),
+                });
+                networks.len() - 1
+            }
+        };
+        networks[idx].broker_ids.push(broker.id.clone());
+        if broker.network_parent {
+            networks[idx].parent_id = Some(broker.id.clone());
+        }
+    }
+    registry.networks = networks;
+}
+
+/// Group brokers by `category` into `registry.categories`, sorted by category.
+fn group_categories(registry: &mut BrokerRegistry) {
+    let mut categories: Vec<BrokerCategory> = Vec::new();
+    for broker in &registry.brokers {
+        match categories.iter_mut().find(|c| c.id == broker.category) {
+            Some(category) => category.broker_ids.push(broker.id.clone()),
+            None => categories.push(BrokerCategory {
+                id: broker.category.clone(),
+                broker_ids: vec![broker.id.clone()],
+            }),
+        }
+    }
+    categories.sort_by(|a, b| a.id.cmp(&b.id));
+    registry.categories = categories;
+}
+
 #[tauri::command]
 pub fn get_brokers(app: tauri::AppHandle) -> Result<BrokerRegistry, String> {
+    let mut registry = load_registry(&app)?;
+    group_networks(&mut registry);
+    group_categories(&mut registry);
+    // Without a saved profile every broker stays visible
+    if let Ok(Some(prof)) = profile::get_profile(app.clone()) {
+        registry.out_of_region = region::out_of_region(&registry.brokers, &prof);
+    }
+    registry.local_difficulty = broker_difficulty::local_difficulties(&app).unwrap_or_default();
+    Ok(registry)
+}
+
+/// A deletion or access request email to the broker, filled in from the profile and citing
+/// the privacy law that applies.
+#[tauri::command]
+pub fn get_request_email(
+    app: tauri::AppHandle,
+    broker_id: String,
+    request_type: RequestType,
+) -> Result<RequestEmail, String> {
+    let registry = get_brokers(app.clone())?;
+    let broker = registry
+        .brokers
+        .iter()
+        .find(|b| b.id == broker_id)
+        .ok_or_else(|| format!("Unknown broker: {}", broker_id))?;
+    let prof = profile::get_profile(app)?
+        .ok_or_else(|| "No profile saved. Please set up your profile first.".to_string())?;
+    let statute = deadlines::statute_for_broker(broker, Some(&prof));
+    Ok(request_letter::request_email(&prof, broker, request_type, statute))
+}
+
+/// Estimate which brokers most likely list the user and suggest a first set to run.
+#[tauri::command]
+pub fn assess_broker_likelihood(app: tauri::AppHandle) -> Result<LikelihoodAssessment, String> {
+    let registry = get_brokers(app.clone())?;
+    let prof = profile::get_profile(app)?
+        .ok_or_else(|| "No profile saved. Please set up your profile first.".to_string())?;
+    Ok(likelihood::assess(&prof, &registry.brokers, chrono::Utc::now().date_naive()))
+}
+
+/// The user's saved login for a broker account, if any.
+#[tauri::command]
+pub fn get_broker_credential(app: tauri::AppHandle, broker_id: String) -> Result<Option<BrokerCredential>, String> {
+    credentials::get(&app, &broker_id)
+}
+
+/// Save the login for a broker account. Without a password a strong one is generated, for
+/// accounts the user is about to create.
+#[tauri::command]
+pub fn save_broker_credential(
+    app: tauri::AppHandle,
+    broker_id: String,
+    username: String,
+    password: Option<String>,
+) -> Result<BrokerCredential, String> {
+    let password = password
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| credentials::generate_password(credentials::PASSWORD_LEN));
+    credentials::set(&app, &broker_id, &username, &password)
+}
+
+/// Save the authenticator setup key (or `otpauth://` link) for a broker login, or clear it
+/// with `None`, so `fill_totp` steps can enter codes without the user.
+#[tauri::command]
+pub fn set_broker_totp(
+    app: tauri::AppHandle,
+    broker_id: String,
+    secret: Option<String>,
+) -> Result<BrokerCredential, String> {
+    credentials::set_totp(&app, &broker_id, secret.as_deref().filter(|s| !s.trim().is_empty()))
+}
+
+#[tauri::command]
+pub fn delete_broker_credential(app: tauri::AppHandle, broker_id: String) -> Result<(), String> {
+    credentials::delete(&app, &broker_id)
+}
+
+/// Load the newest available registry: the synced cache if newer, otherwise the bundled one.
+fn load_registry(app: &tauri::AppHandle) -> Result<BrokerRegistry, String> {
     // Load bundled registry
     let resource_path = app
         .path()
@@ -18,7 +146,7 @@ pub fn get_brokers(app: tauri::AppHandle) -> Result<BrokerRegistry, String> {
         serde_json::from_str(&data).map_err(|e| format!("Failed to parse broker registry: {}", e))?;
 
     // Prefer cached registry if its version is newer and non-empty
-    if let Ok(Some(cached)) = registry_cache::load(&app) {
+    if let Ok(Some(cached)) = registry_cache::load(app) {
         if cached.version > bundled.version && !cached.brokers.is_empty() {
             return Ok(cached);
         }
@@ -1,5 +1,21 @@
+use crate::broker_notes;
+use crate::broker_responses;
+use crate::commands::profile;
+use crate::complaint;
+use crate::deadlines;
+use crate::email_aliases;
+use crate::engine::EngineState;
+use crate::evidence;
 use crate::history;
-use crate::models::{BrokerSubmissionStatus, SubmissionRecord};
+use crate::models::{BrokerNote, BrokerResponse, BrokerSubmissionStatus, ComplaintPackage, DeadlineStatus, EmailAlias, EvidenceItem, EvidenceKind, HistoryRepairReport, RecheckSchedule, RelistingAlert, RemovalCheck, RequestDeadline, RequestType, SubmissionMethod, SubmissionRecord};
+use crate::recorder::RecorderState;
+use crate::relisting_alerts;
+use crate::run_windows;
+use crate::submission_receipts;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Utc};
+use std::fs;
+use tauri::State;
 
 #[tauri::command]
 pub fn get_submissions(app: tauri::AppHandle) -> Result<Vec<SubmissionRecord>, String> {
@@ -12,25 +28,353 @@ pub fn get_latest_submissions(app: tauri::AppHandle) -> Result<Vec<SubmissionRec
     history::get_latest_per_broker(&app)
 }
 
+/// Latest submissions whose relist check date has come, so the broker is worth searching again.
 #[tauri::command]
-pub fn get_relisting_alerts(app: tauri::AppHandle) -> Result<Vec<SubmissionRecord>, String> {
+pub fn get_due_rechecks(app: tauri::AppHandle) -> Result<Vec<SubmissionRecord>, String> {
     history::get_due_for_recheck(&app)
 }
 
+/// Brokers found listing the user again, newest first. Snoozed alerts are left out until
+/// they're due back; dismissed and resolved ones are gone.
+#[tauri::command]
+pub fn get_relisting_alerts(app: tauri::AppHandle) -> Result<Vec<RelistingAlert>, String> {
+    relisting_alerts::active(&app)
+}
+
+#[tauri::command]
+pub fn snooze_relisting_alert(app: tauri::AppHandle, id: String, days: u32) -> Result<(), String> {
+    relisting_alerts::snooze(&app, &id, days)
+}
+
+#[tauri::command]
+pub fn dismiss_relisting_alert(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    relisting_alerts::dismiss(&app, &id)
+}
+
+/// Statutory response deadlines for every open deletion request, soonest first.
+#[tauri::command]
+pub fn get_request_deadlines(app: tauri::AppHandle) -> Result<Vec<RequestDeadline>, String> {
+    let registry = crate::commands::brokers::get_brokers(app.clone())?;
+    let prof = profile::get_profile(app.clone())?;
+    let latest = history::get_latest_per_broker(&app)?;
+    Ok(deadlines::request_deadlines(&latest, &registry.brokers, prof.as_ref(), Utc::now()))
+}
+
+/// Open deletion requests past their statutory deadline, with escalation suggestions.
+#[tauri::command]
+pub fn get_overdue_requests(app: tauri::AppHandle) -> Result<Vec<RequestDeadline>, String> {
+    let mut deadlines = get_request_deadlines(app)?;
+    deadlines.retain(|d| d.status != DeadlineStatus::OnTrack);
+    Ok(deadlines)
+}
+
+/// Assemble a complaint for an overdue, refused or re-listed request in a new folder under
+/// Downloads: the complaint text plus decrypted copies of the request's evidence.
+/// Addressed to the state regulator for the applicable law, or the FTC with `to_ftc`.
+#[tauri::command]
+pub fn build_complaint_package(
+    app: tauri::AppHandle,
+    record_id: String,
+    to_ftc: Option<bool>,
+) -> Result<ComplaintPackage, String> {
+    use tauri::Manager;
+    let record = history::load(&app)?
+        .records
+        .into_iter()
+        .find(|r| r.id == record_id)
+        .ok_or("Submission record not found")?;
+    let registry = crate::commands::brokers::get_brokers(app.clone())?;
+    let broker = registry
+        .brokers
+        .iter()
+        .find(|b| b.id == record.broker_id)
+        .ok_or_else(|| format!("Unknown broker: {}", record.broker_id))?;
+    let prof = profile::get_profile(app.clone())?
+        .ok_or_else(|| "No profile saved. Please set up your profile first.".to_string())?;
+
+    let now = Utc::now();
+    let deadline = deadlines::request_deadline(&record, broker, Some(&prof), now);
+    if complaint::grounds(&record, deadline.as_ref()).is_none() {
+        return Err("Complaint packages are for requests that are overdue, refused or re-listed".to_string());
+    }
+    let statute = deadlines::statute_for_broker(broker, Some(&prof));
+    let recipient = complaint::recipient(statute, to_ftc.unwrap_or(false));
+
+    let dir = app
+        .path()
+        .download_dir()
+        .map_err(|e| e.to_string())?
+        .join(complaint::folder_name(broker, now));
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let mut items = evidence::list_for_record(&app, &record.id)?;
+    // Network logs are for debugging, not for a regulator
+    items.retain(|i| i.kind != EvidenceKind::Har);
+    items.sort_by_key(|i| i.captured_at);
+    let mut evidence_files = Vec::new();
+    for (n, item) in items.iter().enumerate() {
+        let (_, bytes) = evidence::read(&app, &item.id)?;
+        let name = complaint::evidence_file_name(n + 1, item);
+        fs::write(dir.join(&name), bytes).map_err(|e| e.to_string())?;
+        evidence_files.push(name);
+    }
+
+    let letter = complaint::letter(&prof, broker, &record, deadline.as_ref(), statute, &recipient, &evidence_files);
+    fs::write(dir.join("complaint.md"), &letter).map_err(|e| e.to_string())?;
+
+    Ok(ComplaintPackage {
+        folder: dir.display().to_string(),
+        recipient: recipient.name,
+        filing_url: recipient.filing_url,
+        statute: statute.map(|s| s.short_name.to_string()),
+        letter,
+        evidence_files,
+    })
+}
+
 #[tauri::command]
 pub fn update_submission_status(
     app: tauri::AppHandle,
     id: String,
     status: BrokerSubmissionStatus,
 ) -> Result<(), String> {
+    let mut record = history::load(&app)?
+        .records
+        .into_iter()
+        .find(|r| r.id == id)
+        .ok_or("Submission record not found")?;
+    history::set_status(&mut record, status, Utc::now());
+    history::upsert_record(&app, record)
+}
+
+/// Set the status of many submissions at once, e.g. confirming a run's pending requests
+/// after working through the verification emails. Returns how many were updated.
+#[tauri::command]
+pub fn bulk_update_submission_status(
+    app: tauri::AppHandle,
+    ids: Vec<String>,
+    status: BrokerSubmissionStatus,
+) -> Result<usize, String> {
+    let now = Utc::now();
+    history::update_records(&app, &ids, |record| history::set_status(record, status.clone(), now))
+}
+
+/// Move the relist check date of many submissions at once; `None` clears it.
+#[tauri::command]
+pub fn bulk_set_next_check_date(
+    app: tauri::AppHandle,
+    ids: Vec<String>,
+    next_check_date: Option<DateTime<Utc>>,
+) -> Result<usize, String> {
+    history::update_records(&app, &ids, |record| record.next_check_date = next_check_date)
+}
+
+/// Log an opt-out the user made outside the app (by hand, email, phone or mail).
+/// Relist tracking works the same as for automated submissions. `request_type`
+/// defaults to deletion; access requests are logged the same way.
+#[tauri::command]
+pub fn record_manual_submission(
+    app: tauri::AppHandle,
+    broker_id: String,
+    method: SubmissionMethod,
+    submitted_at: Option<DateTime<Utc>>,
+    status: Option<BrokerSubmissionStatus>,
+    request_type: Option<RequestType>,
+) -> Result<SubmissionRecord, String> {
+    if method == SubmissionMethod::Automated {
+        return Err("Manual submissions need a manual method".to_string());
+    }
+
+    let registry = crate::commands::brokers::get_brokers(app.clone())?;
+    let broker = registry
+        .brokers
+        .iter()
+        .find(|b| b.id == broker_id)
+        .ok_or_else(|| format!("Unknown broker: {}", broker_id))?;
+
+    let now = Utc::now();
+    let submitted_at = submitted_at.unwrap_or(now);
+    if submitted_at > now {
+        return Err("Submission date can't be in the future".to_string());
+    }
+
+    let status = status.unwrap_or(BrokerSubmissionStatus::Submitted);
+    let request_type = request_type.unwrap_or_default();
+    let record = SubmissionRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        broker_id: broker.id.clone(),
+        confirmed_at: (status == BrokerSubmissionStatus::Confirmed).then_some(now),
+        status,
+        submitted_at,
+        // Access requests don't remove anything, so there is no listing to recheck
+        next_check_date: match request_type {
+            RequestType::Deletion => history::next_check_date(broker, submitted_at),
+            RequestType::Access => None,
+        },
+        error_message: None,
+        run_id: history::MANUAL_RUN_ID.to_string(),
+        method,
+        last_removal_check: None,
+        covered_by: None,
+        searched_with: None,
+        failure_reason: None,
+        outcome_check: None,
+        gpc_sent: false,
+        request_type,
+        failed_step: None,
+    };
+    history::upsert_record(&app, record.clone())?;
+    Ok(record)
+}
+
+/// Collapse duplicate records and repair missing relist check dates.
+/// With `dry_run`, reports what would change without saving.
+#[tauri::command]
+pub fn repair_history(app: tauri::AppHandle, dry_run: Option<bool>) -> Result<HistoryRepairReport, String> {
+    let registry = crate::commands::brokers::get_brokers(app.clone())?;
     let mut h = history::load(&app)?;
-    if let Some(record) = h.records.iter_mut().find(|r| r.id == id) {
-        record.status = status;
-        if record.status == BrokerSubmissionStatus::Confirmed {
-            record.confirmed_at = Some(chrono::Utc::now());
-        }
-    } else {
-        return Err("Submission record not found".to_string());
-    }
-    history::save(&app, &h)
+    let report = history::repair(&mut h, &registry.brokers);
+    let changed = report.duplicates_removed > 0 || report.next_check_dates_repaired > 0;
+    if changed && !dry_run.unwrap_or(false) {
+        history::save(&app, &h)?;
+    }
+    Ok(report)
+}
+
+/// Re-run the search part of the broker's playbook to check a confirmed removal stuck.
+/// `playbook_selection` uses the same format as runs and defaults to "best".
+#[tauri::command]
+pub async fn verify_removal(
+    app: tauri::AppHandle,
+    engine_state: State<'_, EngineState>,
+    recorder_state: State<'_, RecorderState>,
+    id: String,
+    playbook_selection: Option<String>,
+) -> Result<RemovalCheck, String> {
+    // The automation browser is shared, so don't start while a run or recording is using it
+    if engine_state.0.lock().await.as_ref().is_some_and(|e| e.is_busy()) {
+        return Err("An opt-out run is in progress".to_string());
+    }
+    if recorder_state.0.lock().await.is_some() {
+        return Err("A recording session is active".to_string());
+    }
+    if run_windows::is_rechecking() {
+        return Err("A background removal check is running. Try again in a minute.".to_string());
+    }
+
+    let prof = profile::get_profile(app.clone())?
+        .ok_or_else(|| "No profile saved. Please set up your profile first.".to_string())?;
+    let selection = playbook_selection.unwrap_or_else(|| "best".to_string());
+    crate::removal_verification::verify_removal(&app, &id, &prof, &selection).await
+}
+
+/// Whether background rechecks may run now, and how many are waiting for a run window.
+#[tauri::command]
+pub fn get_recheck_schedule(app: tauri::AppHandle) -> Result<RecheckSchedule, String> {
+    run_windows::schedule(&app)
+}
+
+#[tauri::command]
+pub fn get_evidence(app: tauri::AppHandle, record_id: String) -> Result<Vec<EvidenceItem>, String> {
+    evidence::list_for_record(&app, &record_id)
+}
+
+/// Records that have a network log from a failed attempt.
+#[tauri::command]
+pub fn get_network_log_records(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    evidence::records_with(&app, &EvidenceKind::Har)
+}
+
+/// Decrypt a failed attempt's network log into the Downloads folder and return its path.
+#[tauri::command]
+pub fn save_network_log(app: tauri::AppHandle, record_id: String) -> Result<String, String> {
+    use tauri::Manager;
+    let item = evidence::list_for_record(&app, &record_id)?
+        .into_iter()
+        .filter(|i| i.kind == EvidenceKind::Har)
+        .max_by_key(|i| i.captured_at)
+        .ok_or("No network log was recorded for this attempt")?;
+    let (_, bytes) = evidence::read(&app, &item.id)?;
+    let path = app
+        .path()
+        .download_dir()
+        .map_err(|e| e.to_string())?
+        .join(format!("opt-outta-network-log-{}.har", item.captured_at.format("%Y-%m-%d-%H%M%S")));
+    fs::write(&path, bytes).map_err(|e| e.to_string())?;
+    Ok(path.display().to_string())
+}
+
+/// Records that have a submission receipt.
+#[tauri::command]
+pub fn get_receipt_records(app: tauri::AppHandle) -> Result<Vec<String>, String> {
+    Ok(submission_receipts::load(&app)?.receipts.into_iter().map(|r| r.record_id).collect())
+}
+
+/// Write a submission's receipt to a folder in Downloads and return the folder's path:
+/// the receipt itself, the statement it signs, and the time-stamp authority's reply, which
+/// `openssl ts -verify -data statement.txt -in timestamp.tsr` can check.
+#[tauri::command]
+pub fn save_submission_receipt(app: tauri::AppHandle, record_id: String) -> Result<String, String> {
+    use tauri::Manager;
+    let receipt = submission_receipts::get(&app, &record_id)?.ok_or("This submission has no receipt")?;
+    let dir = app
+        .path()
+        .download_dir()
+        .map_err(|e| e.to_string())?
+        .join(format!("opt-outta-receipt-{}-{}", receipt.broker_id, receipt.submitted_at.format("%Y-%m-%d-%H%M%S")));
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let json = serde_json::to_string_pretty(&receipt).map_err(|e| e.to_string())?;
+    fs::write(dir.join("receipt.json"), json).map_err(|e| e.to_string())?;
+    fs::write(dir.join("statement.txt"), &receipt.statement).map_err(|e| e.to_string())?;
+    if let Some(token) = &receipt.timestamp_token {
+        let reply = STANDARD.decode(token).map_err(|e| e.to_string())?;
+        fs::write(dir.join("timestamp.tsr"), reply).map_err(|e| e.to_string())?;
+    }
+    Ok(dir.display().to_string())
+}
+
+/// Decrypt an evidence file and return it base64-encoded.
+#[tauri::command]
+pub fn read_evidence(app: tauri::AppHandle, id: String) -> Result<String, String> {
+    let (_, bytes) = evidence::read(&app, &id)?;
+    Ok(STANDARD.encode(bytes))
+}
+
+#[tauri::command]
+pub fn get_broker_note(app: tauri::AppHandle, broker_id: String) -> Result<Option<BrokerNote>, String> {
+    broker_notes::get(&app, &broker_id)
+}
+
+#[tauri::command]
+pub fn get_broker_notes(app: tauri::AppHandle) -> Result<Vec<BrokerNote>, String> {
+    broker_notes::get_all(&app)
+}
+
+#[tauri::command]
+pub fn set_broker_note(app: tauri::AppHandle, broker_id: String, note: String) -> Result<(), String> {
+    broker_notes::set(&app, &broker_id, &note)
+}
+
+#[tauri::command]
+pub fn get_email_aliases(app: tauri::AppHandle) -> Result<Vec<EmailAlias>, String> {
+    Ok(email_aliases::load(&app)?.aliases)
+}
+
+/// Which broker was given the address in `text` (an address, or an email's headers).
+#[tauri::command]
+pub fn find_email_alias(app: tauri::AppHandle, text: String) -> Result<Option<EmailAlias>, String> {
+    email_aliases::find(&app, &text)
+}
+
+/// Read a broker's email saved as a .eml file, attach it to the submission it answers and
+/// update that submission's status when the email settles it.
+#[tauri::command]
+pub fn import_broker_email(app: tauri::AppHandle, file_name: String, content: String) -> Result<BrokerResponse, String> {
+    let registry = crate::commands::brokers::get_brokers(app.clone())?;
+    broker_responses::import(&app, &registry.brokers, &file_name, &content)
+}
+
+#[tauri::command]
+pub fn get_broker_responses(app: tauri::AppHandle) -> Result<Vec<BrokerResponse>, String> {
+    Ok(broker_responses::load(&app)?.responses)
 }
@@ -3,3 +3,4 @@ pub mod history;
 pub mod optout;
 pub mod playbooks;
 pub mod profile;
+pub mod settings;
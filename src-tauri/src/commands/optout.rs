@@ -1,12 +1,316 @@
 use crate::browser;
+use crate::browser_detection;
+use crate::bundled_chromium;
 use crate::commands::profile;
 use crate::engine::{self, EngineState, OptOutEngine};
-use crate::models::{Broker, RunStatus};
+use crate::evidence;
+use crate::history;
+use crate::hotkey;
+use crate::models::{
+    Broker, BrokerSubmissionStatus, BundledChromiumStatus, ChromeStatus, ChromiumDownloadProgress, DetectedBrowser, Playbook,
+    PreflightCheck, PreflightCheckKind, PreflightReport, PreflightStatus, RunOptions, RunPlan, RunPlanEntry, RunPlanIssue,
+    RunPlanIssueKind, RunPreset, RunStatus, SubmissionRecord,
+};
+use crate::playbook_api;
+use crate::profile_validation;
+use crate::relisting_alerts;
+use crate::run_plan;
+use crate::run_presets;
+use crate::settings;
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
 use tauri::State;
 
+/// The browsers installed and which one runs will use.
 #[tauri::command]
-pub fn check_chrome_installed() -> bool {
-    browser::find_chrome_binary().is_some()
+pub fn check_chrome_installed(app: tauri::AppHandle) -> ChromeStatus {
+    let preferred = settings::load(&app).ok().and_then(|s| s.browser_path);
+    let mut installs = browser_detection::find_all();
+    installs.extend(bundled_chromium::installed(&app));
+    let browsers = installs
+        .iter()
+        .map(|install| DetectedBrowser {
+            name: install.name.clone(),
+            path: install.path.to_string_lossy().to_string(),
+            sandboxed: install.is_sandboxed(),
+            engine: install.engine,
+        })
+        .collect();
+    let selected = browser_detection::pick(installs, preferred.as_deref()).map(|i| i.path.to_string_lossy().to_string());
+    ChromeStatus { installed: selected.is_some(), browsers, selected }
+}
+
+/// Whether the app can download Chromium for this system, and whether it has.
+#[tauri::command]
+pub fn get_bundled_chromium(app: tauri::AppHandle) -> BundledChromiumStatus {
+    bundled_chromium::status(&app)
+}
+
+/// Download Chromium for runs to use when no browser is installed, emitting
+/// `chromium-download` progress about every megabyte. Returns once it's ready.
+#[tauri::command]
+pub async fn download_chromium(app: tauri::AppHandle) -> Result<(), String> {
+    use tauri::Emitter;
+    let last = std::sync::atomic::AtomicU64::new(0);
+    bundled_chromium::download(&app, |downloaded, total| {
+        let previous = last.load(std::sync::atomic::Ordering::Relaxed);
+        if downloaded - previous >= 1 << 20 || Some(downloaded) == total {
+            last.store(downloaded, std::sync::atomic::Ordering::Relaxed);
+            let _ = app.emit("chromium-download", &ChromiumDownloadProgress { downloaded, total });
+        }
+    })
+    .await?;
+    Ok(())
+}
+
+/// Registry brokers matching the given IDs or in any of the given categories. Categories
+/// leave out brokers that don't serve the user's country; an explicit ID never does.
+fn selected_brokers(
+    app: &tauri::AppHandle,
+    broker_ids: &[String],
+    categories: &[String],
+) -> Result<Vec<Broker>, String> {
+    let registry = crate::commands::brokers::get_brokers(app.clone())?;
+    if let Some(unknown) = categories.iter().find(|c| !registry.categories.iter().any(|rc| &rc.id == *c)) {
+        return Err(format!("Unknown broker category: {}", unknown));
+    }
+    let selected: Vec<Broker> = registry
+        .brokers
+        .into_iter()
+        .filter(|b| {
+            broker_ids.contains(&b.id)
+                || (categories.contains(&b.category) && !registry.out_of_region.contains(&b.id))
+        })
+        .collect();
+    if selected.is_empty() {
+        return Err("No valid brokers selected".to_string());
+    }
+    Ok(selected)
+}
+
+/// Playbook selections with "best" filled in for brokers picked by category, so a
+/// whole category can run without choosing a playbook for each broker.
+fn with_category_defaults(
+    mut selections: HashMap<String, String>,
+    brokers: &[Broker],
+    categories: &[String],
+) -> HashMap<String, String> {
+    for broker in brokers.iter().filter(|b| categories.contains(&b.category)) {
+        selections.entry(broker.id.clone()).or_insert_with(|| "best".to_string());
+    }
+    selections
+}
+
+/// Brokers whose latest submission didn't fail, so dependencies on them are met.
+fn submitted_ids(latest: &[SubmissionRecord]) -> HashSet<String> {
+    latest
+        .iter()
+        .filter(|r| r.status != BrokerSubmissionStatus::Failed)
+        .map(|r| r.broker_id.clone())
+        .collect()
+}
+
+/// Resolve, verify and measure each broker's playbook without launching a browser,
+/// so problems show up before the run starts.
+#[tauri::command]
+pub async fn preview_run_plan(
+    app: tauri::AppHandle,
+    broker_ids: Vec<String>,
+    playbook_selections: Option<HashMap<String, String>>,
+    categories: Option<Vec<String>>,
+) -> Result<RunPlan, String> {
+    let categories = categories.unwrap_or_default();
+    let mut selected = selected_brokers(&app, &broker_ids, &categories)?;
+    let selections = with_category_defaults(playbook_selections.unwrap_or_default(), &selected, &categories);
+    let latest = history::get_latest_per_broker(&app)?;
+    run_plan::prioritize_by_exposure(&mut selected, &latest);
+    let (ordered, warnings) = run_plan::order_brokers(selected, &submitted_ids(&latest));
+    let prof = profile::get_profile(app.clone())?;
+    let now = Utc::now();
+
+    let mut entries = Vec::with_capacity(ordered.len());
+    let mut total_ms: u64 = 0;
+    for broker in &ordered {
+        let mut entry = RunPlanEntry {
+            broker_id: broker.id.clone(),
+            broker_name: broker.name.clone(),
+            playbook_id: None,
+            playbook_title: None,
+            captcha_steps: 0,
+            manual_steps: 0,
+            estimated_duration_ms: 0,
+            issues: Vec::new(),
+        };
+
+        let record = latest.iter().find(|r| r.broker_id == broker.id);
+        let recent = run_plan::recently_submitted(broker, record, now);
+        if recent {
+            let until = run_plan::cooldown_until(broker, record).unwrap_or(now);
+            entry.issues.push(RunPlanIssue {
+                kind: RunPlanIssueKind::RecentlySubmitted,
+                message: format!(
+                    "Submitted recently; skipped until {} unless forced",
+                    until.format("%Y-%m-%d")
+                ),
+            });
+        }
+
+        match selections.get(&broker.id) {
+            None => entry.issues.push(RunPlanIssue {
+                kind: RunPlanIssueKind::MissingSelection,
+                message: "No playbook selected".to_string(),
+            }),
+            Some(selection) => match engine::load_playbook(&app, broker, selection).await {
+                None => entry.issues.push(RunPlanIssue {
+                    kind: RunPlanIssueKind::MissingPlaybook,
+                    message: "No playbook available for this selection".to_string(),
+                }),
+                Some(pb) => {
+                    if let Err(e) = engine::check_playbook(&pb) {
+                        entry.issues.push(RunPlanIssue {
+                            kind: RunPlanIssueKind::RejectedPlaybook,
+                            message: e,
+                        });
+                    }
+                    if let Some(reason) = run_plan::stale_reason(&pb, now) {
+                        entry.issues.push(RunPlanIssue {
+                            kind: RunPlanIssueKind::StalePlaybook,
+                            message: reason,
+                        });
+                    }
+                    if let Some(message) = prof.as_ref().and_then(|p| run_plan::missing_fields_message(broker, p, &pb.steps)) {
+                        entry.issues.push(RunPlanIssue {
+                            kind: RunPlanIssueKind::MissingProfileFields,
+                            message,
+                        });
+                    }
+                    let stats = run_plan::step_stats(&pb.steps);
+                    entry.captcha_steps = stats.captcha_steps;
+                    entry.manual_steps = stats.manual_steps;
+                    entry.estimated_duration_ms = stats.estimated_duration_ms;
+                    entry.playbook_title = pb.title.clone();
+                    entry.playbook_id = Some(pb.id);
+                }
+            },
+        }
+
+        if !recent {
+            total_ms += entry.estimated_duration_ms;
+        }
+        entries.push(entry);
+    }
+
+    Ok(RunPlan {
+        entries,
+        warnings,
+        estimated_duration_ms: total_ms,
+    })
+}
+
+/// Evidence space to set aside per broker (a screenshot and an MHTML snapshot).
+const EVIDENCE_BYTES_PER_BROKER: u64 = 4 * 1024 * 1024;
+
+fn preflight_item(kind: PreflightCheckKind, result: Result<String, String>) -> PreflightCheck {
+    let (status, message) = match result {
+        Ok(message) => (PreflightStatus::Passed, message),
+        Err(message) => (PreflightStatus::Failed, message),
+    };
+    PreflightCheck {
+        kind,
+        status,
+        message,
+        details: Vec::new(),
+    }
+}
+
+fn profile_preflight(app: &tauri::AppHandle) -> PreflightCheck {
+    let prof = match profile::get_profile(app.clone()) {
+        Ok(Some(p)) => p,
+        Ok(None) => return preflight_item(PreflightCheckKind::Profile, Err("No profile saved".to_string())),
+        Err(e) => return preflight_item(PreflightCheckKind::Profile, Err(format!("Profile can't be read: {}", e))),
+    };
+    let errors = profile_validation::validate_profile(&prof);
+    if errors.is_empty() {
+        return preflight_item(PreflightCheckKind::Profile, Ok("Profile is complete".to_string()));
+    }
+    PreflightCheck {
+        kind: PreflightCheckKind::Profile,
+        status: PreflightStatus::Failed,
+        message: format!("{} profile field(s) need fixing", errors.len()),
+        details: errors.into_iter().map(|e| format!("{}: {}", e.field, e.message)).collect(),
+    }
+}
+
+fn playbooks_preflight(brokers: &[Broker], mut playbooks: HashMap<String, Result<Playbook, String>>) -> PreflightCheck {
+    // Brokers without any playbook still run, filled from their form's labels
+    let mut unusable = 0;
+    let details: Vec<String> = brokers
+        .iter()
+        .filter_map(|b| match playbooks.remove(&b.id) {
+            Some(Ok(_)) => None,
+            Some(Err(e)) if e != engine::NO_PLAYBOOK => {
+                unusable += 1;
+                Some(format!("{}: {}", b.name, e))
+            }
+            _ => Some(format!("{}: No playbook, so the form will be filled from its labels", b.name)),
+        })
+        .collect();
+    let (status, message) = if details.is_empty() {
+        (PreflightStatus::Passed, format!("Playbooks ready for all {} brokers", brokers.len()))
+    } else if unusable == brokers.len() {
+        (PreflightStatus::Failed, "None of the selected brokers has a usable playbook".to_string())
+    } else if unusable > 0 {
+        (
+            PreflightStatus::Warning,
+            format!("{} of {} brokers have no usable playbook and will fail", unusable, brokers.len()),
+        )
+    } else {
+        (
+            PreflightStatus::Warning,
+            format!("{} of {} brokers have no playbook and will need your help", details.len(), brokers.len()),
+        )
+    };
+    PreflightCheck {
+        kind: PreflightCheckKind::Playbooks,
+        status,
+        message,
+        details,
+    }
+}
+
+/// Check what a run needs before starting one (Chrome, profile, network, playbooks and
+/// disk space), so it doesn't fail minutes in for a predictable reason.
+#[tauri::command]
+pub async fn preflight_check(
+    app: tauri::AppHandle,
+    broker_ids: Vec<String>,
+    playbook_selections: Option<HashMap<String, String>>,
+    categories: Option<Vec<String>>,
+) -> Result<PreflightReport, String> {
+    let categories = categories.unwrap_or_default();
+    let selected = selected_brokers(&app, &broker_ids, &categories)?;
+    let selections = with_category_defaults(playbook_selections.unwrap_or_default(), &selected, &categories);
+
+    let (chrome, network, playbooks) = futures::join!(
+        browser::check_launchable(&app),
+        playbook_api::check_reachable(),
+        engine::prefetch_playbooks(&app, &selected, &selections, &HashSet::new()),
+    );
+    let evidence_bytes = EVIDENCE_BYTES_PER_BROKER * selected.len() as u64;
+
+    let checks = vec![
+        preflight_item(PreflightCheckKind::Chrome, chrome.map(|_| "Chrome starts".to_string())),
+        profile_preflight(&app),
+        preflight_item(PreflightCheckKind::Network, network.map(|_| "Playbook server reachable".to_string())),
+        playbooks_preflight(&selected, playbooks),
+        preflight_item(
+            PreflightCheckKind::DiskSpace,
+            evidence::check_free_space(&app, evidence_bytes)
+                .map(|_| format!("Room for {} MB of evidence", evidence_bytes / (1024 * 1024))),
+        ),
+    ];
+    let ready = checks.iter().all(|c| c.status != PreflightStatus::Failed);
+    Ok(PreflightReport { checks, ready })
 }
 
 #[tauri::command]
@@ -14,46 +318,123 @@ pub async fn start_opt_out_run(
     app: tauri::AppHandle,
     state: State<'_, EngineState>,
     broker_ids: Vec<String>,
-    playbook_selections: Option<std::collections::HashMap<String, String>>,
+    playbook_selections: Option<HashMap<String, String>>,
+    force: Option<bool>,
+    options: Option<RunOptions>,
+    categories: Option<Vec<String>>,
+    everywhere: Option<bool>,
 ) -> Result<String, String> {
     // Check if already running
     {
         let guard = state.0.lock().await;
         if let Some(ref engine) = *guard {
-            if engine.status == RunStatus::Running || engine.status == RunStatus::WaitingForUser {
+            if engine.is_busy() {
                 return Err("An opt-out run is already in progress".to_string());
             }
         }
     }
+    if crate::run_windows::is_rechecking() {
+        return Err("A background removal check is running. Try again in a minute.".to_string());
+    }
 
     // Load profile
     let prof = profile::get_profile(app.clone())?
         .ok_or_else(|| "No profile saved. Please set up your profile first.".to_string())?;
 
-    // Load brokers
-    let registry = crate::commands::brokers::get_brokers(app.clone())?;
-    let selected_brokers: Vec<Broker> = registry
-        .brokers
-        .into_iter()
-        .filter(|b| broker_ids.contains(&b.id))
-        .collect();
+    // Load brokers, expanding categories against the registry. "Everywhere" takes the whole registry
+    // and keeps the brokers with a usable playbook once cooldowns are known.
+    let everywhere = everywhere.unwrap_or(false);
+    let categories = categories.unwrap_or_default();
+    let mut selected_brokers = if everywhere {
+        let registry = crate::commands::brokers::get_brokers(app.clone())?;
+        registry
+            .brokers
+            .into_iter()
+            .filter(|b| !registry.out_of_region.contains(&b.id))
+            .collect()
+    } else {
+        selected_brokers(&app, &broker_ids, &categories)?
+    };
+    let options = options.unwrap_or_default();
 
-    if selected_brokers.is_empty() {
-        return Err("No valid brokers selected".to_string());
+    // Queue brokers confirmed to list the user first, optionally dropping ones with no hit
+    let latest = history::get_latest_per_broker(&app)?;
+    if options.skip_unlisted {
+        selected_brokers.retain(|b| {
+            run_plan::exposure(latest.iter().find(|r| r.broker_id == b.id)) != run_plan::Exposure::NotFound
+        });
+        if selected_brokers.is_empty() {
+            return Err("None of the selected brokers had a listing at their last removal check.".to_string());
+        }
     }
+    run_plan::prioritize_by_exposure(&mut selected_brokers, &latest);
 
-    // Require a playbook selection for every broker
-    let pb_selections = playbook_selections.unwrap_or_default();
-    let missing: Vec<&str> = selected_brokers
-        .iter()
-        .filter(|b| !pb_selections.contains_key(&b.id))
-        .map(|b| b.name.as_str())
-        .collect();
-    if !missing.is_empty() {
-        return Err(format!(
-            "No playbook selected for: {}. Select a playbook for each broker before running.",
-            missing.join(", ")
-        ));
+    // Skip brokers still inside their relist cooldown unless the user forces a re-submit
+    let now = Utc::now();
+    let skipped: HashSet<String> = if force.unwrap_or(false) {
+        HashSet::new()
+    } else {
+        selected_brokers
+            .iter()
+            .filter(|b| {
+                let record = latest.iter().find(|r| r.broker_id == b.id);
+                run_plan::recently_submitted(b, record, now)
+            })
+            .map(|b| b.id.clone())
+            .collect()
+    };
+    if skipped.len() == selected_brokers.len() {
+        return Err(
+            "All selected brokers were submitted recently. Run again with force to re-submit.".to_string(),
+        );
+    }
+
+    // Playbooks are fetched once here, checked, and handed to the run
+    let playbooks = if everywhere {
+        let selections: HashMap<String, String> = selected_brokers
+            .iter()
+            .map(|b| (b.id.clone(), "best".to_string()))
+            .collect();
+        // Drop brokers without an approved playbook, then run the hands-free ones first
+        let playbooks = engine::prefetch_playbooks(&app, &selected_brokers, &selections, &skipped).await;
+        let hands_free: HashSet<String> = playbooks
+            .iter()
+            .filter(|(_, pb)| pb.as_ref().is_ok_and(|pb| !run_plan::needs_user(&pb.steps)))
+            .map(|(id, _)| id.clone())
+            .collect();
+        // Brokers whose playbook needs a field the profile lacks would only fail part way
+        selected_brokers.retain(|b| {
+            !skipped.contains(&b.id)
+                && playbooks
+                    .get(&b.id)
+                    .is_some_and(|pb| pb.as_ref().is_ok_and(|pb| run_plan::missing_fields_message(b, &prof, &pb.steps).is_none()))
+        });
+        if selected_brokers.is_empty() {
+            return Err("No broker has a usable playbook that wasn't submitted recently.".to_string());
+        }
+        selected_brokers.sort_by_key(|b| !hands_free.contains(&b.id));
+        playbooks
+    } else {
+        let selections = with_category_defaults(playbook_selections.unwrap_or_default(), &selected_brokers, &categories);
+        // Fail now rather than mid-run when a playbook needs a field the profile lacks
+        let playbooks = engine::prefetch_playbooks(&app, &selected_brokers, &selections, &skipped).await;
+        let missing: Vec<String> = selected_brokers
+            .iter()
+            .filter_map(|b| match playbooks.get(&b.id) {
+                Some(Ok(pb)) => run_plan::missing_fields_message(b, &prof, &pb.steps),
+                _ => None,
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("{}. Add them to your profile, or leave those brokers out of the run.", missing.join(". ")));
+        }
+        playbooks
+    };
+
+    // Run network parents and dependencies first
+    let (selected_brokers, warnings) = run_plan::order_brokers(selected_brokers, &submitted_ids(&latest));
+    for warning in &warnings {
+        eprintln!("[opt-out] Warning: {}", warning);
     }
 
     let run_id = uuid::Uuid::new_v4().to_string();
@@ -66,33 +447,82 @@ pub async fn start_opt_out_run(
         *guard = Some(engine);
     }
 
+    hotkey::register_for_run(&app);
+
     // Spawn the run in background
     let run_id_clone = run_id.clone();
+    let own_run_id = run_id.clone();
     let state_clone = state.0.clone();
     tokio::spawn(async move {
         engine::run_opt_outs(
-            app,
+            app.clone(),
             run_id_clone,
             selected_brokers,
             prof,
-            pb_selections,
+            playbooks,
+            skipped,
+            options,
             user_action_channel,
             cancel_rx,
         )
         .await;
 
-        // Mark engine as completed
+        // Mark engine as completed, unless a later run has taken its place
         let mut guard = state_clone.lock().await;
-        if let Some(ref mut eng) = *guard {
-            if eng.status == RunStatus::Running {
-                eng.status = RunStatus::Completed;
-            }
+        if let Some(eng) = guard.as_mut().filter(|eng| eng.run_id == own_run_id) {
+            hotkey::unregister(&app);
+            eng.status = match eng.status {
+                RunStatus::Cancelling => RunStatus::Cancelled,
+                RunStatus::Running | RunStatus::WaitingForUser => RunStatus::Completed,
+                ref other => other.clone(),
+            };
         }
     });
 
     Ok(run_id)
 }
 
+/// Retry a failed broker from the step it failed at, as a new run of just that broker. The
+/// broker's page is reopened and only the earlier steps that bring it back are replayed, so
+/// CAPTCHAs and prompts the user already got through aren't asked again.
+#[tauri::command]
+pub async fn retry_broker_from_step(
+    app: tauri::AppHandle,
+    state: State<'_, EngineState>,
+    record_id: String,
+) -> Result<String, String> {
+    let record = history::load(&app)?
+        .records
+        .into_iter()
+        .find(|r| r.id == record_id)
+        .ok_or_else(|| "Submission not found".to_string())?;
+    let failed = match (&record.status, record.failed_step) {
+        (BrokerSubmissionStatus::Failed, Some(failed)) => failed,
+        _ => return Err("This submission didn't fail at a step it can resume from.".to_string()),
+    };
+    let options = RunOptions {
+        resume_from_step: Some(failed.position),
+        ..Default::default()
+    };
+    let selections = HashMap::from([(record.broker_id.clone(), failed.playbook)]);
+    start_opt_out_run(app, state, vec![record.broker_id], Some(selections), Some(true), Some(options), None, None).await
+}
+
+/// Opt out again from a broker a relisting alert is about, and close the alert once the
+/// run has started.
+#[tauri::command]
+pub async fn rerun_relisted_broker(
+    app: tauri::AppHandle,
+    state: State<'_, EngineState>,
+    alert_id: String,
+) -> Result<String, String> {
+    let alert = relisting_alerts::get(&app, &alert_id)?;
+    // The confirmed submission would otherwise keep the broker in its relist cooldown
+    let run_id = start_opt_out_run(app.clone(), state, vec![alert.broker_id], None, Some(true), None, None, None).await?;
+    relisting_alerts::resolve(&app, &alert_id, &run_id)?;
+    Ok(run_id)
+}
+
 #[tauri::command]
 pub async fn continue_opt_out(state: State<'_, EngineState>, response: Option<String>) -> Result<(), String> {
     let guard = state.0.lock().await;
@@ -104,11 +534,34 @@ pub async fn continue_opt_out(state: State<'_, EngineState>, response: Option<St
     }
 }
 
+/// How long a profile value copied for a manual step stays on the clipboard.
+const CLIPBOARD_CLEAR_SECS: u64 = 30;
+
+/// Copy a profile value for the user to paste into a field they're filling by hand, then
+/// clear it again so it doesn't linger in clipboard history. Anything the user copies in
+/// the meantime is left alone.
+#[tauri::command]
+pub fn copy_profile_value(app: tauri::AppHandle, profile_key: String) -> Result<(), String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+    let prof = profile::get_profile(app.clone())?.ok_or("No profile saved")?;
+    let value = browser::resolve_profile_key(&prof, &profile_key, None)
+        .filter(|v| !v.is_empty())
+        .ok_or("That detail isn't in your profile")?;
+    app.clipboard().write_text(value.clone()).map_err(|e| e.to_string())?;
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(CLIPBOARD_CLEAR_SECS)).await;
+        if app.clipboard().read_text().is_ok_and(|current| current == value) {
+            let _ = app.clipboard().clear();
+        }
+    });
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn cancel_opt_out(state: State<'_, EngineState>) -> Result<(), String> {
     let mut guard = state.0.lock().await;
     if let Some(ref mut engine) = *guard {
-        engine.cancel();
+        engine.cancel().await;
         Ok(())
     } else {
         Err("No active opt-out run".to_string())
@@ -123,3 +576,19 @@ pub async fn get_run_status(state: State<'_, EngineState>) -> Result<RunStatus,
         None => Ok(RunStatus::Idle),
     }
 }
+
+#[tauri::command]
+pub fn get_run_presets(app: tauri::AppHandle) -> Result<Vec<RunPreset>, String> {
+    Ok(run_presets::load(&app)?.presets)
+}
+
+/// Save a named run setup. A preset with the same name is replaced.
+#[tauri::command]
+pub fn save_run_preset(app: tauri::AppHandle, preset: RunPreset) -> Result<RunPreset, String> {
+    run_presets::upsert(&app, preset)
+}
+
+#[tauri::command]
+pub fn delete_run_preset(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    run_presets::delete(&app, &name)
+}
@@ -1,26 +1,66 @@
-use crate::models::{ChangelogEntry, LocalPlaybook, Playbook, PlaybookReportEntry, PlaybookSubmission, PlaybookSubmitResponse, PlaybookSummary, RecordedAction, TrackedSubmission};
+use crate::commands::profile;
+use crate::models::{ChangelogEntry, Contributor, LocalPlaybook, Playbook, PlaybookDraft, PlaybookReportEntry, PlaybookRevision, PlaybookSubmission, PlaybookSubmitResponse, PlaybookSubscription, PlaybookSummary, PlaybookUpdate, PlaybookVote, RecordedAction, RecordingSession, TrackedSubmission, VoteDirection};
+use crate::pii_scan;
 use crate::playbook_api;
+use crate::playbook_reliability;
+use crate::playbook_subscriptions;
 use crate::playbook_verification;
+use crate::playbook_votes;
 use crate::recorder::RecorderState;
+use crate::run_plan;
+use crate::step_descriptions;
 use crate::submission_tracker;
+use std::collections::HashMap;
 
 // --- Recording commands ---
 
 #[tauri::command]
 pub async fn start_recording(
+    app: tauri::AppHandle,
     state: tauri::State<'_, RecorderState>,
     broker_id: String,
     broker_name: String,
     opt_out_url: String,
+    start_url: Option<String>,
 ) -> Result<(), String> {
-    crate::recorder::start_recording(&state, broker_id, broker_name, opt_out_url).await
+    if crate::run_windows::is_rechecking() {
+        return Err("A background removal check is running. Try again in a minute.".to_string());
+    }
+    // Used to keep the user's details out of recorded labels and element text
+    let prof = profile::get_profile(app.clone()).unwrap_or(None);
+    // The registry's description of the form helps match recorded fields to profile values
+    let known_fields = crate::commands::brokers::get_brokers(app.clone())
+        .ok()
+        .and_then(|r| r.brokers.into_iter().find(|b| b.id == broker_id))
+        .map(|b| b.known_fields)
+        .unwrap_or_default();
+    crate::recorder::start_recording(app, &state, broker_id, broker_name, opt_out_url, start_url, prof, known_fields).await
 }
 
 #[tauri::command]
 pub async fn stop_recording(
     state: tauri::State<'_, RecorderState>,
-) -> Result<Vec<RecordedAction>, String> {
-    crate::recorder::stop_recording(&state).await
+) -> Result<PlaybookDraft, String> {
+    let actions = crate::recorder::stop_recording(&state).await?;
+    Ok(crate::playbook_draft::draft(&actions))
+}
+
+/// The recording session in progress, or None when nothing is being recorded.
+#[tauri::command]
+pub async fn get_recording_status(
+    state: tauri::State<'_, RecorderState>,
+) -> Result<Option<RecordingSession>, String> {
+    Ok(crate::recorder::status(&state).await)
+}
+
+/// End the recording session without keeping what it recorded, for when it's stuck.
+/// Does nothing if no session is active.
+#[tauri::command]
+pub async fn force_reset_recorder(
+    state: tauri::State<'_, RecorderState>,
+) -> Result<(), String> {
+    crate::recorder::force_reset(&state).await;
+    Ok(())
 }
 
 #[tauri::command]
@@ -46,9 +86,17 @@ pub async fn mark_user_prompt_step(
 
 // --- Playbook API commands ---
 
-#[tauri::command]
-pub async fn fetch_playbooks(broker_id: String) -> Result<Vec<PlaybookSummary>, String> {
-    let summaries = playbook_api::fetch_playbooks(&broker_id).await?;
+/// Fill in the captcha/manual/duration metadata from the summary's steps.
+fn annotate_summary(summary: &mut PlaybookSummary) {
+    let stats = run_plan::step_stats(&summary.steps);
+    summary.captcha_steps = stats.captcha_steps;
+    summary.manual_steps = stats.manual_steps;
+    summary.estimated_duration_ms = stats.estimated_duration_ms;
+}
+
+/// The summaries whose signature checks out, annotated and with this user's own results.
+fn verified_summaries(app: &tauri::AppHandle, summaries: Vec<PlaybookSummary>) -> Vec<PlaybookSummary> {
+    let reliability = playbook_reliability::load(app).unwrap_or_default();
 
     // Filter out playbooks that fail signature verification.
     // Build a temporary Playbook to reuse the verification function.
@@ -73,44 +121,103 @@ pub async fn fetch_playbooks(broker_id: String) -> Result<Vec<PlaybookSummary>,
                 success_count: s.success_count,
                 failure_count: s.failure_count,
                 created_at: s.created_at.clone(),
+                contributor: s.contributor.clone(),
             };
             playbook_verification::verify_playbook_signature(&pb).is_ok()
         })
+        .map(|mut s| {
+            annotate_summary(&mut s);
+            s.personal = reliability.playbooks.iter().find(|r| r.playbook_id == s.id).cloned();
+            s
+        })
         .collect();
 
-    Ok(verified)
+    verified
+}
+
+#[tauri::command]
+pub async fn fetch_playbooks(app: tauri::AppHandle, broker_id: String) -> Result<Vec<PlaybookSummary>, String> {
+    let summaries = playbook_api::fetch_playbooks(&broker_id).await?;
+    Ok(verified_summaries(&app, summaries))
+}
+
+/// Other playbooks by the same contributor, for their profile on a playbook's page.
+#[tauri::command]
+pub async fn fetch_contributor_playbooks(app: tauri::AppHandle, contributor_id: String) -> Result<Vec<PlaybookSummary>, String> {
+    let summaries = playbook_api::fetch_contributor_playbooks(&contributor_id).await?;
+    Ok(verified_summaries(&app, summaries))
+}
+
+#[tauri::command]
+pub async fn get_own_contributor() -> Result<Option<Contributor>, String> {
+    playbook_api::fetch_own_contributor().await
+}
+
+/// Claim a public handle for this device's playbooks. Handles that give away the user's
+/// name or other profile details are refused, since the whole point is to stay anonymous.
+#[tauri::command]
+pub async fn claim_contributor_handle(app: tauri::AppHandle, handle: String) -> Result<Contributor, String> {
+    let handle = playbook_api::validate_handle(&handle)?;
+    let prof = profile::get_profile(app).unwrap_or(None);
+    if pii_scan::redact(&handle, prof.as_ref()) != handle {
+        return Err("Pick a handle that doesn't include your name or other profile details.".to_string());
+    }
+    playbook_api::claim_handle(&handle).await
 }
 
 #[tauri::command]
 pub async fn fetch_playbook_detail(id: String) -> Result<Playbook, String> {
-    let playbook = playbook_api::fetch_playbook_detail(&id).await?;
+    let mut playbook = playbook_api::fetch_playbook_detail(&id).await?;
     playbook_verification::verify_playbook_signature(&playbook)?;
+    step_descriptions::fill_terse_descriptions(&mut playbook.steps);
     Ok(playbook)
 }
 
 #[tauri::command]
-pub async fn submit_playbook(submission: PlaybookSubmission) -> Result<PlaybookSubmitResponse, String> {
+pub async fn submit_playbook(app: tauri::AppHandle, submission: PlaybookSubmission) -> Result<PlaybookSubmitResponse, String> {
     crate::playbook_validation::validate_steps(&submission.steps)?;
+    // Refuse to publish anything that looks like the user's (or anyone's) personal details
+    let prof = profile::get_profile(app).unwrap_or(None);
+    let findings = pii_scan::scan_submission(&submission, prof.as_ref());
+    if !findings.is_empty() {
+        return Err(format!(
+            "This playbook may contain personal information in: {}. Remove it before sharing.",
+            findings.join(", ")
+        ));
+    }
     playbook_api::submit_playbook(&submission).await
 }
 
 #[tauri::command]
-pub async fn vote_on_playbook(id: String, vote: String) -> Result<(), String> {
-    playbook_api::vote_playbook(&id, &vote).await
+pub async fn vote_on_playbook(app: tauri::AppHandle, id: String, vote: Option<VoteDirection>) -> Result<(), String> {
+    playbook_votes::cast(&app, &id, vote).await
+}
+
+#[tauri::command]
+pub fn get_playbook_votes(app: tauri::AppHandle) -> Result<Vec<PlaybookVote>, String> {
+    Ok(playbook_votes::load(&app)?.votes)
+}
+
+#[tauri::command]
+pub fn import_playbook_votes(app: tauri::AppHandle, votes: HashMap<String, VoteDirection>) -> Result<(), String> {
+    playbook_votes::import(&app, votes)
 }
 
 #[tauri::command]
 pub async fn report_playbook_outcome(
+    app: tauri::AppHandle,
     id: String,
     outcome: String,
     failure_step: Option<u32>,
     error_message: Option<String>,
 ) -> Result<(), String> {
+    let prof = profile::get_profile(app).unwrap_or(None);
     let report = crate::models::PlaybookReport {
         device_id: playbook_api::get_device_id(),
         outcome,
         failure_step,
-        error_message,
+        error_message: error_message.map(|e| pii_scan::redact(&e, prof.as_ref())),
+        failure_reason: None,
         app_version: env!("CARGO_PKG_VERSION").to_string(),
     };
     playbook_api::report_outcome(&id, &report).await
@@ -196,13 +303,60 @@ pub async fn fetch_playbook_reports(id: String) -> Result<Vec<PlaybookReportEntr
     playbook_api::fetch_playbook_reports(&id).await
 }
 
+// --- Playbook history command ---
+
+#[tauri::command]
+pub async fn fetch_playbook_history(broker_id: String) -> Result<Vec<PlaybookRevision>, String> {
+    playbook_api::fetch_playbook_history(&broker_id).await
+}
+
+// --- Playbook subscription commands ---
+
+#[tauri::command]
+pub fn get_playbook_subscriptions(app: tauri::AppHandle) -> Result<Vec<PlaybookSubscription>, String> {
+    Ok(playbook_subscriptions::load(&app)?.subscriptions)
+}
+
+#[tauri::command]
+pub fn set_playbook_subscription(app: tauri::AppHandle, broker_id: String, subscribed: bool) -> Result<(), String> {
+    playbook_subscriptions::set(&app, &broker_id, subscribed)
+}
+
+#[tauri::command]
+pub async fn check_playbook_updates(app: tauri::AppHandle) -> Result<Vec<PlaybookUpdate>, String> {
+    playbook_subscriptions::check(&app).await
+}
+
 // --- Broker suggestion command ---
 
 #[tauri::command]
 pub async fn suggest_broker(
+    app: tauri::AppHandle,
     name: String,
     url: String,
     notes: String,
+    snapshot_url: Option<String>,
 ) -> Result<(), String> {
-    playbook_api::suggest_broker(&name, &url, &notes).await
+    let prof = profile::get_profile(app).unwrap_or(None);
+    let notes = pii_scan::redact(&notes, prof.as_ref());
+    // Optionally attach the structure of the opt-out page so maintainers don't have to visit it
+    let page_structure = match snapshot_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        Some(target) => {
+            crate::playbook_validation::validate_url(target, "Snapshot URL")?;
+            // A site the registry already knows may need a visible browser or its mobile layout
+            let host = |u: &str| reqwest::Url::parse(u).ok().and_then(|u| u.host_str().map(str::to_lowercase));
+            let automation = crate::commands::brokers::get_brokers(app.clone())
+                .ok()
+                .and_then(|r| {
+                    r.brokers
+                        .into_iter()
+                        .find(|b| host(&b.opt_out_url).is_some_and(|h| Some(h) == host(target)))
+                })
+                .map(|b| b.automation)
+                .unwrap_or_default();
+            Some(crate::browser::snapshot_page_structure(&app, target, &automation).await?)
+        }
+        None => None,
+    };
+    playbook_api::suggest_broker(&name, &url, &notes, page_structure.as_ref()).await
 }
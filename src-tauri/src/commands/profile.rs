@@ -1,5 +1,12 @@
+use crate::broker_notes;
+use crate::credentials;
 use crate::crypto;
-use crate::models::Profile;
+use crate::evidence;
+use crate::models::{Profile, ProfileFieldError, ProfileImport};
+use crate::profile_format;
+use crate::profile_import;
+use crate::profile_validation;
+use crate::recovery_phrase;
 use crate::secrets::SecretsCache;
 use std::fs;
 use tauri::Manager;
@@ -16,7 +23,25 @@ fn profile_path(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
 }
 
 #[tauri::command]
-pub fn save_profile(app: tauri::AppHandle, profile: Profile) -> Result<(), String> {
+pub fn validate_profile(mut profile: Profile) -> Vec<ProfileFieldError> {
+    profile_format::normalize_profile(&mut profile);
+    profile_validation::validate_profile(&profile)
+}
+
+/// Parse a vCard or CSV contact export for the user to review. Nothing is saved.
+#[tauri::command]
+pub fn import_profile(file_name: String, content: String) -> Result<ProfileImport, String> {
+    profile_import::import(&content, &file_name)
+}
+
+#[tauri::command]
+pub fn save_profile(app: tauri::AppHandle, mut profile: Profile) -> Result<(), String> {
+    profile_format::normalize_profile(&mut profile);
+    let errors = profile_validation::validate_profile(&profile);
+    if !errors.is_empty() {
+        let messages: Vec<String> = errors.iter().map(|e| e.message.clone()).collect();
+        return Err(format!("Profile is invalid: {}", messages.join("; ")));
+    }
     let secrets = app.state::<SecretsCache>();
     let key = secrets.get_encryption_key()?;
     let json = serde_json::to_string(&profile).map_err(|e| e.to_string())?;
@@ -47,3 +72,32 @@ pub fn delete_profile(app: tauri::AppHandle) -> Result<(), String> {
     }
     Ok(())
 }
+
+/// The encryption key as a recovery phrase, for restoring the profile, history and evidence
+/// after the OS keychain is lost.
+#[tauri::command]
+pub fn export_recovery_phrase(app: tauri::AppHandle) -> Result<String, String> {
+    let key = app.state::<SecretsCache>().get_encryption_key()?;
+    recovery_phrase::encode(&key)
+}
+
+/// Restore the encryption key from a recovery phrase. Something encrypted on this device
+/// has to open with it, so a phrase from another install can't lock this one's data away.
+#[tauri::command]
+pub fn import_recovery_phrase(app: tauri::AppHandle, phrase: String) -> Result<(), String> {
+    let key = recovery_phrase::decode(&phrase)?;
+    let mut stores = vec![
+        profile_path(&app)?,
+        broker_notes::store_path(&app)?,
+        credentials::store_path(&app)?,
+    ];
+    stores.extend(evidence::oldest_file(&app)?);
+    let mut encrypted = Vec::new();
+    for path in stores.iter().filter(|p| p.exists()) {
+        encrypted.push(fs::read_to_string(path).map_err(|e| e.to_string())?);
+    }
+    if !encrypted.is_empty() && !encrypted.iter().any(|e| crypto::decrypt(e, &key).is_ok()) {
+        return Err("This recovery phrase doesn't unlock the data saved on this device.".to_string());
+    }
+    app.state::<SecretsCache>().set_encryption_key(key)
+}
@@ -0,0 +1,168 @@
+use crate::browser_proxy;
+use crate::disposable_inbox;
+use crate::hotkey;
+use crate::models::{AppSettings, BrowserProxy};
+use crate::pacing;
+use crate::playbook_api;
+use crate::run_windows;
+use crate::secrets::SecretsCache;
+use crate::selector_repair;
+use crate::settings;
+use crate::sms_relay;
+use crate::submission_receipts;
+use tauri::Manager;
+
+#[tauri::command]
+pub fn get_settings(app: tauri::AppHandle) -> Result<AppSettings, String> {
+    settings::load(&app)
+}
+
+fn tidy_browser_proxy(proxy: BrowserProxy) -> BrowserProxy {
+    BrowserProxy {
+        host: proxy.host.trim().to_string(),
+        username: proxy.username.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()),
+        ..proxy
+    }
+}
+
+#[tauri::command]
+pub fn save_settings(app: tauri::AppHandle, settings: AppSettings) -> Result<(), String> {
+    let mut settings = settings;
+    settings.api_proxy = settings
+        .api_proxy
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    if let Some(ref proxy) = settings.api_proxy {
+        settings::validate_proxy_url(proxy)?;
+    }
+    settings.browser_proxy = settings.browser_proxy.map(tidy_browser_proxy);
+    if let Some(ref proxy) = settings.browser_proxy {
+        browser_proxy::validate(proxy)?;
+    }
+    settings.continue_hotkey = settings
+        .continue_hotkey
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty());
+    if let Some(ref accelerator) = settings.continue_hotkey {
+        hotkey::validate(accelerator)?;
+    }
+    settings.sms_relay_url = settings
+        .sms_relay_url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty());
+    if let Some(ref url) = settings.sms_relay_url {
+        sms_relay::validate_url(url)?;
+    }
+    settings.sms_relay_token = settings
+        .sms_relay_token
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    settings.disposable_inbox_url = settings
+        .disposable_inbox_url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty());
+    if let Some(ref url) = settings.disposable_inbox_url {
+        disposable_inbox::validate_url(url)?;
+    }
+    settings.disposable_inbox_token = settings
+        .disposable_inbox_token
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    settings.timestamp_authority_url = settings
+        .timestamp_authority_url
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty());
+    if let Some(ref url) = settings.timestamp_authority_url {
+        submission_receipts::validate_url(url)?;
+    }
+    settings.ai_api_base = settings
+        .ai_api_base
+        .map(|u| u.trim().trim_end_matches('/').to_string())
+        .filter(|u| !u.is_empty());
+    if let Some(ref url) = settings.ai_api_base {
+        selector_repair::validate_api_base(url)?;
+    }
+    settings.ai_model = settings
+        .ai_model
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty());
+    if settings.alert_repeat_minutes > settings::MAX_ALERT_REPEAT_MINUTES {
+        return Err(format!(
+            "Alerts can repeat at most every {} minutes.",
+            settings::MAX_ALERT_REPEAT_MINUTES
+        ));
+    }
+    if settings.pace_min_secs > settings.pace_max_secs {
+        return Err("The shortest pause between brokers can't be longer than the longest.".to_string());
+    }
+    if settings.pace_max_secs > pacing::MAX_PAUSE_SECS {
+        return Err(format!("Pauses between brokers can be at most {} seconds.", pacing::MAX_PAUSE_SECS));
+    }
+    if settings.domain_cooldown_secs > pacing::MAX_COOLDOWN_SECS {
+        return Err(format!("The site cooldown can be at most {} minutes.", pacing::MAX_COOLDOWN_SECS / 60));
+    }
+    run_windows::validate(&settings.run_windows)?;
+
+    settings::save(&app, &settings)?;
+    playbook_api::set_api_proxy(settings.api_proxy.clone());
+    Ok(())
+}
+
+/// Fetch the last day of messages from an SMS relay to check it works. Returns how many
+/// messages it sent back.
+#[tauri::command]
+pub async fn test_sms_relay(url: String, token: Option<String>) -> Result<usize, String> {
+    sms_relay::validate_url(&url)?;
+    let since = chrono::Utc::now() - chrono::Duration::days(1);
+    let messages = sms_relay::fetch_messages(&url, token.as_deref(), since).await?;
+    Ok(messages.len())
+}
+
+/// Ask a disposable-inbox provider for an address and read its messages, to check both
+/// work. Returns the address it gave out.
+#[tauri::command]
+pub async fn test_disposable_inbox(url: String, token: Option<String>) -> Result<String, String> {
+    disposable_inbox::validate_url(&url)?;
+    let address = disposable_inbox::create_address(&url, token.as_deref(), "optoutta-test").await?;
+    disposable_inbox::fetch_messages(&url, token.as_deref(), &address, chrono::Utc::now()).await?;
+    Ok(address)
+}
+
+/// Whether a selector-repair API key is stored. The key itself never goes back to the UI.
+#[tauri::command]
+pub fn has_ai_api_key(app: tauri::AppHandle) -> bool {
+    app.state::<SecretsCache>().get_ai_api_key().is_some()
+}
+
+/// Store the selector-repair API key in the OS keychain, or remove it when empty.
+#[tauri::command]
+pub fn set_ai_api_key(app: tauri::AppHandle, key: Option<String>) -> Result<(), String> {
+    let key = key.map(|k| k.trim().to_string()).filter(|k| !k.is_empty());
+    app.state::<SecretsCache>().set_ai_api_key(key)
+}
+
+/// Set the automation browser's proxy, or connect directly with `None`. `password` is
+/// kept in the OS keychain; leave it out to keep the one already saved. A proxy without
+/// a login has its saved password removed.
+#[tauri::command]
+pub fn set_browser_proxy(app: tauri::AppHandle, proxy: Option<BrowserProxy>, password: Option<String>) -> Result<(), String> {
+    let proxy = proxy.map(tidy_browser_proxy);
+    if let Some(ref proxy) = proxy {
+        browser_proxy::validate(proxy)?;
+    }
+    let secrets = app.state::<SecretsCache>();
+    if proxy.as_ref().is_none_or(|p| p.username.is_none()) {
+        secrets.set_browser_proxy_password(None)?;
+    } else if let Some(password) = password.filter(|p| !p.is_empty()) {
+        secrets.set_browser_proxy_password(Some(password))?;
+    }
+    let mut current = settings::load(&app)?;
+    current.browser_proxy = proxy;
+    settings::save(&app, &current)
+}
+
+/// Whether a browser proxy password is stored. The password never goes back to the UI.
+#[tauri::command]
+pub fn has_browser_proxy_password(app: tauri::AppHandle) -> bool {
+    app.state::<SecretsCache>().get_browser_proxy_password().is_some()
+}
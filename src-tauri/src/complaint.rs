@@ -0,0 +1,237 @@
+use crate::deadlines::Statute;
+use crate::models::{
+    Broker, BrokerSubmissionStatus, DeadlineStatus, EvidenceItem, EvidenceKind, FailureReason, Profile,
+    RequestDeadline, RequestType, SubmissionMethod, SubmissionRecord,
+};
+use chrono::{DateTime, Utc};
+
+/// Where a complaint is filed: a state regulator from the statute table, or the FTC.
+pub struct Recipient {
+    pub name: String,
+    pub filing_url: String,
+}
+
+pub const FTC_NAME: &str = "Federal Trade Commission";
+pub const FTC_FILING_URL: &str = "https://reportfraud.ftc.gov/";
+
+pub fn recipient(statute: Option<&Statute>, to_ftc: bool) -> Recipient {
+    match statute {
+        Some(s) if !to_ftc => Recipient {
+            name: s.regulator.to_string(),
+            filing_url: s.complaint_url.to_string(),
+        },
+        _ => Recipient {
+            name: FTC_NAME.to_string(),
+            filing_url: FTC_FILING_URL.to_string(),
+        },
+    }
+}
+
+/// Why the request warrants a complaint, or `None` if it doesn't (yet): the response
+/// deadline has passed, the broker blocked the request, or the listing came back.
+pub fn grounds(record: &SubmissionRecord, deadline: Option<&RequestDeadline>) -> Option<String> {
+    if let Some(d) = deadline.filter(|d| d.status != DeadlineStatus::OnTrack) {
+        return Some(format!(
+            "The broker has not completed the request, which was due by {} under {} ({} days overdue).",
+            format_date(d.due_at),
+            d.citation,
+            d.days_overdue
+        ));
+    }
+    match (&record.status, &record.failure_reason) {
+        (BrokerSubmissionStatus::Failed, Some(FailureReason::BlockedByBroker)) => {
+            Some("The broker's site refused the deletion request and blocked it from being submitted.".to_string())
+        }
+        (BrokerSubmissionStatus::Failed, Some(FailureReason::RefusedByEmail)) => {
+            Some("The broker replied by email refusing the deletion request.".to_string())
+        }
+        (BrokerSubmissionStatus::ReListed, _) => {
+            Some("The broker deleted the listing but it has since been published again.".to_string())
+        }
+        _ => None,
+    }
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.format("%B %-d, %Y").to_string()
+}
+
+fn request_noun(request_type: RequestType) -> &'static str {
+    match request_type {
+        RequestType::Deletion => "deletion",
+        RequestType::Access => "access",
+    }
+}
+
+fn method_phrase(method: &SubmissionMethod) -> &'static str {
+    match method {
+        SubmissionMethod::Automated | SubmissionMethod::ManualWeb => "through the broker's online opt-out form",
+        SubmissionMethod::ManualEmail => "by email",
+        SubmissionMethod::ManualPhone => "by phone",
+        SubmissionMethod::ManualMail => "by mail",
+    }
+}
+
+/// File name for a piece of evidence in the package, numbered in capture order.
+pub fn evidence_file_name(n: usize, item: &EvidenceItem) -> String {
+    let (label, ext) = match item.kind {
+        EvidenceKind::Screenshot => ("screenshot", "png"),
+        EvidenceKind::Mhtml => ("page-archive", "mhtml"),
+        EvidenceKind::Har => ("network-log", "har"),
+        EvidenceKind::Email => ("broker-email", "eml"),
+    };
+    format!("{:02}-{}-{}.{}", n, label, item.captured_at.format("%Y-%m-%d"), ext)
+}
+
+/// Folder name for a package, safe on every platform.
+pub fn folder_name(broker: &Broker, now: DateTime<Utc>) -> String {
+    let name: String = broker
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == ' ' || c == '-' { c } else { '_' })
+        .collect();
+    format!("Complaint - {} - {}", name.trim(), now.format("%Y-%m-%d"))
+}
+
+/// The complaint text: who is complaining, about whom, under which law, the
+/// timeline, and the evidence files that accompany it. Check `grounds` first.
+pub fn letter(
+    profile: &Profile,
+    broker: &Broker,
+    record: &SubmissionRecord,
+    deadline: Option<&RequestDeadline>,
+    statute: Option<&Statute>,
+    recipient: &Recipient,
+    evidence_files: &[String],
+) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("# Complaint against {}\n\n", broker.name));
+    out.push_str(&format!("To: {}\nFile at: {}\n\n", recipient.name, recipient.filing_url));
+
+    out.push_str("## Complainant\n\n");
+    out.push_str(&format!("{} {}\n", profile.first_name, profile.last_name));
+    let place = [profile.city.as_str(), profile.state.as_str()]
+        .into_iter()
+        .filter(|s| !s.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !place.is_empty() {
+        out.push_str(&format!("{}\n", place));
+    }
+    if !profile.email.trim().is_empty() {
+        out.push_str(&format!("{}\n", profile.email));
+    }
+
+    out.push_str("\n## Business\n\n");
+    out.push_str(&format!("{}\n{}\nOpt-out page: {}\n", broker.name, broker.url, broker.opt_out_url));
+
+    out.push_str("\n## Applicable law\n\n");
+    match statute {
+        Some(s) => out.push_str(&format!(
+            "{} ({}). A business must respond to a verified {} request within {} days, extendable once by {} days with notice to the consumer.\n",
+            s.short_name,
+            s.citation,
+            request_noun(record.request_type),
+            s.response_days,
+            s.extension_days
+        )),
+        None => out.push_str("No state privacy law with a deletion deadline was identified for this request.\n"),
+    }
+
+    out.push_str("\n## Timeline\n\n");
+    out.push_str(&format!(
+        "- {}: Requested {} my personal information {}.\n",
+        format_date(record.submitted_at),
+        match record.request_type {
+            RequestType::Deletion => "deletion of",
+            RequestType::Access => "access to",
+        },
+        method_phrase(&record.method)
+    ));
+    if let Some(d) = deadline {
+        out.push_str(&format!("- {}: Response deadline.\n", format_date(d.due_at)));
+        out.push_str(&format!("- {}: Latest deadline with an extension.\n", format_date(d.extended_due_at)));
+    }
+    if let Some(check) = &record.last_removal_check {
+        out.push_str(&format!(
+            "- {}: Checked the broker's site; the listing was {}.\n",
+            format_date(check.checked_at),
+            if check.still_listed { "still published" } else { "not found" }
+        ));
+    }
+
+    out.push_str("\n## Complaint\n\n");
+    out.push_str(&grounds(record, deadline).unwrap_or_default());
+    out.push_str(match record.request_type {
+        RequestType::Deletion => " I ask that the business be required to delete my personal information and stop selling it.\n",
+        RequestType::Access => " I ask that the business be required to disclose the personal information it holds about me.\n",
+    });
+
+    out.push_str("\n## Evidence\n\n");
+    if evidence_files.is_empty() {
+        out.push_str("No screenshots were captured for this request.\n");
+    }
+    for file in evidence_files {
+        out.push_str(&format!("- {}\n", file));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deadlines::statute_for;
+    use crate::test_fixtures;
+    use chrono::{Duration, TimeZone};
+
+    fn broker() -> Broker {
+        Broker {
+            name: "People/Finder".to_string(),
+            url: "https://peoplefinder.example".to_string(),
+            opt_out_url: "https://peoplefinder.example/optout".to_string(),
+            ..test_fixtures::broker("b")
+        }
+    }
+
+    fn record(status: BrokerSubmissionStatus, failure_reason: Option<FailureReason>) -> SubmissionRecord {
+        SubmissionRecord {
+            submitted_at: Utc.with_ymd_and_hms(2026, 1, 5, 12, 0, 0).unwrap(),
+            method: SubmissionMethod::ManualEmail,
+            failure_reason,
+            ..test_fixtures::record("b", status)
+        }
+    }
+
+    #[test]
+    fn only_overdue_or_refused_requests_have_grounds() {
+        assert!(grounds(&record(BrokerSubmissionStatus::Submitted, None), None).is_none());
+        assert!(grounds(&record(BrokerSubmissionStatus::Failed, Some(FailureReason::StepFailed)), None).is_none());
+        assert!(grounds(&record(BrokerSubmissionStatus::Failed, Some(FailureReason::BlockedByBroker)), None).is_some());
+        assert!(grounds(&record(BrokerSubmissionStatus::Failed, Some(FailureReason::RefusedByEmail)), None).is_some());
+        assert!(grounds(&record(BrokerSubmissionStatus::ReListed, None), None).is_some());
+    }
+
+    #[test]
+    fn letter_covers_statute_timeline_and_evidence() {
+        let profile = Profile {
+            last_name: "Lee".to_string(),
+            city: "Fresno".to_string(),
+            state: "CA".to_string(),
+            ..test_fixtures::profile()
+        };
+        let r = record(BrokerSubmissionStatus::Submitted, None);
+        let b = broker();
+        let statute = statute_for("CA");
+        let now = r.submitted_at + Duration::days(60);
+        let deadline = crate::deadlines::request_deadline(&r, &b, Some(&profile), now).unwrap();
+        let to = recipient(statute, false);
+        let text = letter(&profile, &b, &r, Some(&deadline), statute, &to, &["01-screenshot-2026-01-05.png".to_string()]);
+        assert!(text.contains("To: California Privacy Protection Agency"));
+        assert!(text.contains("- January 5, 2026: Requested deletion of my personal information by email."));
+        assert!(text.contains("- February 19, 2026: Response deadline."));
+        assert!(text.contains("15 days overdue"));
+        assert!(text.contains("- 01-screenshot-2026-01-05.png"));
+        assert_eq!(recipient(statute, true).name, FTC_NAME);
+        assert_eq!(folder_name(&b, now), "Complaint - People_Finder - 2026-03-06");
+    }
+}
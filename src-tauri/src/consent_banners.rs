@@ -0,0 +1,127 @@
+/// A cookie-consent platform and the buttons its banner closes with.
+struct ConsentPlatform {
+    name: &'static str,
+    /// Buttons that turn down tracking, tried first
+    reject: &'static [&'static str],
+    /// Buttons that accept, for banners with no way to say no up front
+    accept: &'static [&'static str],
+}
+
+/// The consent platforms brokers use most. Their buttons keep the same IDs and classes
+/// across sites, unlike anything a playbook records.
+const PLATFORMS: &[ConsentPlatform] = &[
+    ConsentPlatform {
+        name: "OneTrust",
+        reject: &["#onetrust-reject-all-handler"],
+        accept: &["#onetrust-accept-btn-handler"],
+    },
+    ConsentPlatform {
+        name: "Cookiebot",
+        reject: &["#CybotCookiebotDialogBodyButtonDecline"],
+        accept: &["#CybotCookiebotDialogBodyLevelButtonLevelOptinAllowAll", "#CybotCookiebotDialogBodyButtonAccept"],
+    },
+    ConsentPlatform {
+        name: "TrustArc",
+        reject: &["#truste-consent-required"],
+        accept: &["#truste-consent-button"],
+    },
+    ConsentPlatform {
+        name: "Didomi",
+        reject: &["#didomi-notice-disagree-button"],
+        accept: &["#didomi-notice-agree-button"],
+    },
+    ConsentPlatform {
+        name: "Osano",
+        reject: &[".osano-cm-denyAll", ".osano-cm-deny"],
+        accept: &[".osano-cm-accept-all", ".osano-cm-accept"],
+    },
+    ConsentPlatform {
+        name: "CookieYes",
+        reject: &[".cky-btn-reject"],
+        accept: &[".cky-btn-accept"],
+    },
+    ConsentPlatform {
+        name: "Complianz",
+        reject: &[".cmplz-btn.cmplz-deny"],
+        accept: &[".cmplz-btn.cmplz-accept"],
+    },
+    ConsentPlatform {
+        name: "Termly",
+        reject: &["[data-tid=\"banner-decline\"]"],
+        accept: &["[data-tid=\"banner-accept\"]"],
+    },
+    ConsentPlatform {
+        name: "iubenda",
+        reject: &[".iubenda-cs-reject-btn"],
+        accept: &[".iubenda-cs-accept-btn"],
+    },
+    ConsentPlatform {
+        name: "Quantcast",
+        reject: &[],
+        accept: &[".qc-cmp2-summary-buttons button[mode=\"primary\"]"],
+    },
+];
+
+/// JavaScript that clicks the first showing button of a known consent banner, turning
+/// tracking down where the banner allows it. Evaluates to the platform's name, or null
+/// when no banner is showing.
+pub fn dismiss_script() -> String {
+    let platforms: Vec<serde_json::Value> = PLATFORMS
+        .iter()
+        .map(|p| {
+            let buttons: Vec<&str> = p.reject.iter().chain(p.accept).copied().collect();
+            serde_json::json!({ "name": p.name, "buttons": buttons })
+        })
+        .collect();
+    format!(
+        r#"(() => {{
+            const showing = (el) => {{
+                const rect = el.getBoundingClientRect();
+                const style = window.getComputedStyle(el);
+                return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden';
+            }};
+            for (const platform of {platforms}) {{
+                for (const sel of platform.buttons) {{
+                    const button = Array.from(document.querySelectorAll(sel)).find(showing);
+                    if (button) {{
+                        button.click();
+                        return platform.name;
+                    }}
+                }}
+            }}
+            return null;
+        }})()"#,
+        platforms = serde_json::Value::Array(platforms),
+    )
+}
+
+/// Whether a playbook step's selector is one of the consent buttons `dismiss_script`
+/// already handles. Runs skip those steps when the banner has gone.
+pub fn is_banner_button(selector: &str) -> bool {
+    let selector = selector.trim();
+    PLATFORMS
+        .iter()
+        .flat_map(|p| p.reject.iter().chain(p.accept))
+        .any(|b| *b == selector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_consent_buttons() {
+        assert!(is_banner_button("#onetrust-accept-btn-handler"));
+        assert!(is_banner_button(" .cky-btn-reject "));
+        assert!(!is_banner_button("#submit-request"));
+    }
+
+    #[test]
+    fn tries_rejecting_before_accepting() {
+        let script = dismiss_script();
+        let reject = script.find("#onetrust-reject-all-handler").unwrap();
+        let accept = script.find("#onetrust-accept-btn-handler").unwrap();
+        assert!(reject < accept);
+        assert!(script.contains("Quantcast"));
+    }
+}
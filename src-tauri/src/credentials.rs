@@ -0,0 +1,176 @@
+use crate::crypto;
+use crate::models::{BrokerCredential, BrokerCredentialStore, CredentialField};
+use crate::secrets::SecretsCache;
+use crate::totp;
+use chrono::Utc;
+use rand::RngCore;
+use std::fs;
+use std::path::PathBuf;
+
+/// Broker account passwords, encrypted like the profile.
+const FILENAME: &str = "broker_credentials.enc";
+/// Long enough to be strong, short enough for brokers that cap password length.
+pub const PASSWORD_LEN: usize = 20;
+
+const LOWER: &[u8] = b"abcdefghijkmnopqrstuvwxyz";
+const UPPER: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"23456789";
+/// Symbols most sign-up forms accept
+const SYMBOLS: &[u8] = b"!#$%*+-=?@_";
+
+/// One character from `set`, without modulo bias.
+fn random_char(rng: &mut impl RngCore, set: &[u8]) -> char {
+    let limit = 256 - 256 % set.len();
+    loop {
+        let mut byte = [0u8; 1];
+        rng.fill_bytes(&mut byte);
+        if (byte[0] as usize) < limit {
+            return set[byte[0] as usize % set.len()] as char;
+        }
+    }
+}
+
+/// A random password of `len` characters (at least 4) with a lowercase letter, an uppercase
+/// letter, a digit and a symbol, since sign-up forms often demand one of each. Look-alike
+/// characters (l, I, O, 0, 1) are left out in case the user has to type it.
+pub fn generate_password(len: usize) -> String {
+    let len = len.max(4);
+    let mut rng = rand::thread_rng();
+    let all: Vec<u8> = [LOWER, UPPER, DIGITS, SYMBOLS].concat();
+    loop {
+        let password: String = (0..len).map(|_| random_char(&mut rng, &all)).collect();
+        let has = |set: &[u8]| password.bytes().any(|b| set.contains(&b));
+        if has(LOWER) && has(UPPER) && has(DIGITS) && has(SYMBOLS) {
+            return password;
+        }
+    }
+}
+
+impl BrokerCredential {
+    pub fn field(&self, field: CredentialField) -> &str {
+        match field {
+            CredentialField::Username => &self.username,
+            CredentialField::Password => &self.password,
+        }
+    }
+}
+
+pub fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+fn encryption_key(app: &tauri::AppHandle) -> Result<Vec<u8>, String> {
+    use tauri::Manager;
+    app.state::<SecretsCache>().get_encryption_key()
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<BrokerCredentialStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(BrokerCredentialStore::default());
+    }
+    let encrypted = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let key = encryption_key(app)?;
+    let decrypted = crypto::decrypt(&encrypted, &key).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&decrypted).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &BrokerCredentialStore) -> Result<(), String> {
+    let key = encryption_key(app)?;
+    let json = serde_json::to_string(store).map_err(|e| e.to_string())?;
+    let encrypted = crypto::encrypt(json.as_bytes(), &key).map_err(|e| e.to_string())?;
+    let path = store_path(app)?;
+    fs::write(&path, encrypted).map_err(|e| e.to_string())
+}
+
+pub fn get(app: &tauri::AppHandle, broker_id: &str) -> Result<Option<BrokerCredential>, String> {
+    let store = load(app)?;
+    Ok(store.credentials.into_iter().find(|c| c.broker_id == broker_id))
+}
+
+/// Save the login for a broker account, replacing any existing one.
+pub fn set(app: &tauri::AppHandle, broker_id: &str, username: &str, password: &str) -> Result<BrokerCredential, String> {
+    let username = username.trim();
+    if username.is_empty() {
+        return Err("Enter the username or email for this account".to_string());
+    }
+    if password.is_empty() {
+        return Err("Enter the password for this account".to_string());
+    }
+
+    let mut store = load(app)?;
+    let now = Utc::now();
+    let existing = store.credentials.iter().find(|c| c.broker_id == broker_id);
+    let created_at = existing.map(|c| c.created_at).unwrap_or(now);
+    let totp_secret = existing.and_then(|c| c.totp_secret.clone());
+    store.credentials.retain(|c| c.broker_id != broker_id);
+    let credential = BrokerCredential {
+        broker_id: broker_id.to_string(),
+        username: username.to_string(),
+        password: password.to_string(),
+        totp_secret,
+        created_at,
+        updated_at: now,
+    };
+    store.credentials.push(credential.clone());
+    save(app, &store)?;
+    Ok(credential)
+}
+
+/// The saved login for a broker, or a new one with a generated password for a playbook
+/// that registers an account. The username defaults to the user's email.
+pub fn get_or_create(app: &tauri::AppHandle, broker_id: &str, email: &str) -> Result<BrokerCredential, String> {
+    if let Some(existing) = get(app, broker_id)? {
+        return Ok(existing);
+    }
+    set(app, broker_id, email, &generate_password(PASSWORD_LEN))
+}
+
+/// Set or clear (`None`) the authenticator secret on a saved broker login.
+pub fn set_totp(app: &tauri::AppHandle, broker_id: &str, secret: Option<&str>) -> Result<BrokerCredential, String> {
+    let secret = secret.map(totp::normalize_secret).transpose()?;
+    let mut store = load(app)?;
+    let credential = store
+        .credentials
+        .iter_mut()
+        .find(|c| c.broker_id == broker_id)
+        .ok_or_else(|| "Save a login for this broker first".to_string())?;
+    credential.totp_secret = secret;
+    credential.updated_at = Utc::now();
+    let updated = credential.clone();
+    save(app, &store)?;
+    Ok(updated)
+}
+
+pub fn delete(app: &tauri::AppHandle, broker_id: &str) -> Result<(), String> {
+    let mut store = load(app)?;
+    store.credentials.retain(|c| c.broker_id != broker_id);
+    save(app, &store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_passwords_mix_character_classes() {
+        for _ in 0..50 {
+            let password = generate_password(PASSWORD_LEN);
+            assert_eq!(password.len(), PASSWORD_LEN);
+            assert!(password.bytes().any(|b| LOWER.contains(&b)));
+            assert!(password.bytes().any(|b| UPPER.contains(&b)));
+            assert!(password.bytes().any(|b| DIGITS.contains(&b)));
+            assert!(password.bytes().any(|b| SYMBOLS.contains(&b)));
+            assert!(!password.contains(['l', 'I', 'O', '0', '1']));
+        }
+        assert_ne!(generate_password(PASSWORD_LEN), generate_password(PASSWORD_LEN));
+    }
+
+    #[test]
+    fn short_lengths_still_fit_every_class() {
+        assert_eq!(generate_password(1).len(), 4);
+    }
+}
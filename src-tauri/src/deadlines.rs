@@ -0,0 +1,276 @@
+use crate::models::{Broker, BrokerSubmissionStatus, DeadlineStatus, Profile, RequestDeadline, SubmissionRecord};
+use crate::region;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+/// A state privacy law that gives consumers a deadline for deletion requests.
+pub struct Statute {
+    /// Two-letter state code
+    pub jurisdiction: &'static str,
+    pub state_name: &'static str,
+    pub short_name: &'static str,
+    pub citation: &'static str,
+    /// Days the business has to respond after receiving the request
+    pub response_days: i64,
+    /// Further days the business may take if it tells the consumer within the first period
+    pub extension_days: i64,
+    pub regulator: &'static str,
+    pub complaint_url: &'static str,
+}
+
+pub const STATUTES: &[Statute] = &[
+    Statute {
+        jurisdiction: "CA",
+        state_name: "California",
+        short_name: "CCPA",
+        citation: "Cal. Civ. Code § 1798.105, § 1798.130(a)(2)",
+        response_days: 45,
+        extension_days: 45,
+        regulator: "California Privacy Protection Agency",
+        complaint_url: "https://cppa.ca.gov/webapplications/complaint",
+    },
+    Statute {
+        jurisdiction: "CO",
+        state_name: "Colorado",
+        short_name: "CPA",
+        citation: "Colo. Rev. Stat. § 6-1-1306",
+        response_days: 45,
+        extension_days: 45,
+        regulator: "Colorado Attorney General",
+        complaint_url: "https://coag.gov/file-complaint/",
+    },
+    Statute {
+        jurisdiction: "CT",
+        state_name: "Connecticut",
+        short_name: "CTDPA",
+        citation: "Conn. Gen. Stat. § 42-518",
+        response_days: 45,
+        extension_days: 45,
+        regulator: "Connecticut Attorney General",
+        complaint_url: "https://portal.ct.gov/ag/consumer-issues/file-a-complaint",
+    },
+    Statute {
+        jurisdiction: "TX",
+        state_name: "Texas",
+        short_name: "TDPSA",
+        citation: "Tex. Bus. & Com. Code § 541.052",
+        response_days: 45,
+        extension_days: 45,
+        regulator: "Texas Attorney General",
+        complaint_url: "https://www.texasattorneygeneral.gov/consumer-protection/file-consumer-complaint",
+    },
+    Statute {
+        jurisdiction: "UT",
+        state_name: "Utah",
+        short_name: "UCPA",
+        citation: "Utah Code § 13-61-203",
+        response_days: 45,
+        extension_days: 45,
+        regulator: "Utah Division of Consumer Protection",
+        complaint_url: "https://consumerprotection.utah.gov/complaints/",
+    },
+    Statute {
+        jurisdiction: "VA",
+        state_name: "Virginia",
+        short_name: "VCDPA",
+        citation: "Va. Code § 59.1-577",
+        response_days: 45,
+        extension_days: 45,
+        regulator: "Virginia Attorney General",
+        complaint_url: "https://www.oag.state.va.us/consumer-protection/index.php/file-a-complaint",
+    },
+];
+
+/// The statute for a state, given as a code ("ca") or a name ("California").
+pub fn statute_for(state: &str) -> Option<&'static Statute> {
+    let state = state.trim();
+    STATUTES
+        .iter()
+        .find(|s| s.jurisdiction.eq_ignore_ascii_case(state) || s.state_name.eq_ignore_ascii_case(state))
+}
+
+/// The broker's own jurisdiction if it declares one, otherwise the user's state when
+/// they live in the US.
+pub fn statute_for_broker(broker: &Broker, profile: Option<&Profile>) -> Option<&'static Statute> {
+    match &broker.jurisdiction {
+        Some(j) => statute_for(j),
+        None => profile.filter(|p| region::is_us(p)).and_then(|p| statute_for(&p.state)),
+    }
+}
+
+/// Countries whose residents' requests fall under the GDPR: the EU plus the rest of the EEA.
+const GDPR_COUNTRIES: &[&str] = &[
+    "AT", "BE", "BG", "HR", "CY", "CZ", "DK", "EE", "FI", "FR", "DE", "GR", "HU", "IE", "IT", "LV", "LT", "LU", "MT",
+    "NL", "PL", "PT", "RO", "SK", "SI", "ES", "SE", "IS", "LI", "NO",
+];
+
+/// The law a request to `broker` is made under, as opt-out forms that ask name it ("CCPA",
+/// "GDPR", "VCDPA"). Residents outside the US go by their country; US residents by the
+/// broker's declared jurisdiction, then their own state. Without a broker only the user's
+/// location counts. `None` when no privacy law covers the user.
+pub fn legal_basis(profile: &Profile, broker: Option<&Broker>) -> Option<&'static str> {
+    let country = region::country(profile);
+    if country != region::DEFAULT_COUNTRY {
+        return match country.as_str() {
+            "GB" => Some("UK GDPR"),
+            "CA" => Some("PIPEDA"),
+            c if GDPR_COUNTRIES.contains(&c) => Some("GDPR"),
+            _ => None,
+        };
+    }
+    match broker {
+        Some(broker) => statute_for_broker(broker, Some(profile)),
+        None => statute_for(&profile.state),
+    }
+    .map(|s| s.short_name)
+}
+
+fn suggestions(status: DeadlineStatus, record: &SubmissionRecord, broker: &Broker, statute: &Statute) -> Vec<String> {
+    let mut out = Vec::new();
+    if status == DeadlineStatus::OnTrack {
+        return out;
+    }
+    if record.status == BrokerSubmissionStatus::PendingVerification {
+        out.push(format!(
+            "Complete {}'s verification step if you haven't — an unverified request can be denied",
+            broker.name
+        ));
+    }
+    match status {
+        DeadlineStatus::Overdue => {
+            out.push(format!(
+                "Ask {} for the status of your request and whether it extended the deadline under {}",
+                broker.name, statute.citation
+            ));
+        }
+        DeadlineStatus::ExtensionLapsed => {
+            out.push(format!(
+                "File a complaint with the {} ({})",
+                statute.regulator, statute.complaint_url
+            ));
+            out.push("Attach the evidence saved for this submission to the complaint".to_string());
+            out.push(format!("Re-submit the request to {} and note the date of your first one", broker.name));
+        }
+        DeadlineStatus::OnTrack => {}
+    }
+    out
+}
+
+/// The deadline for one submission, if it is still waiting on the broker and a
+/// statute applies. Requests covered by a network parent follow the parent's deadline.
+pub fn request_deadline(
+    record: &SubmissionRecord,
+    broker: &Broker,
+    profile: Option<&Profile>,
+    now: DateTime<Utc>,
+) -> Option<RequestDeadline> {
+    let open = matches!(
+        record.status,
+        BrokerSubmissionStatus::Submitted | BrokerSubmissionStatus::PendingVerification
+    );
+    if !open || record.covered_by.is_some() {
+        return None;
+    }
+    let statute = statute_for_broker(broker, profile)?;
+    let due_at = record.submitted_at + Duration::days(statute.response_days);
+    let extended_due_at = due_at + Duration::days(statute.extension_days);
+    let status = if now <= due_at {
+        DeadlineStatus::OnTrack
+    } else if now <= extended_due_at {
+        DeadlineStatus::Overdue
+    } else {
+        DeadlineStatus::ExtensionLapsed
+    };
+    Some(RequestDeadline {
+        record_id: record.id.clone(),
+        broker_id: broker.id.clone(),
+        broker_name: broker.name.clone(),
+        jurisdiction: statute.jurisdiction.to_string(),
+        statute: statute.short_name.to_string(),
+        citation: statute.citation.to_string(),
+        regulator: statute.regulator.to_string(),
+        complaint_url: statute.complaint_url.to_string(),
+        submitted_at: record.submitted_at,
+        due_at,
+        extended_due_at,
+        status,
+        days_overdue: (now - due_at).num_days().max(0),
+        suggestions: suggestions(status, record, broker, statute),
+    })
+}
+
+/// Deadlines for the latest record of each broker, most overdue first.
+pub fn request_deadlines(
+    latest: &[SubmissionRecord],
+    brokers: &[Broker],
+    profile: Option<&Profile>,
+    now: DateTime<Utc>,
+) -> Vec<RequestDeadline> {
+    let by_id: HashMap<&str, &Broker> = brokers.iter().map(|b| (b.id.as_str(), b)).collect();
+    let mut deadlines: Vec<RequestDeadline> = latest
+        .iter()
+        .filter_map(|r| request_deadline(r, by_id.get(r.broker_id.as_str())?, profile, now))
+        .collect();
+    deadlines.sort_by_key(|d| d.due_at);
+    deadlines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn broker(jurisdiction: Option<&str>) -> Broker {
+        Broker { name: "Broker".to_string(), jurisdiction: jurisdiction.map(|j| j.to_string()), ..test_fixtures::broker("b") }
+    }
+
+    fn profile(state: &str) -> Profile {
+        Profile { state: state.to_string(), ..test_fixtures::profile() }
+    }
+
+    fn record(status: BrokerSubmissionStatus, days_ago: i64) -> SubmissionRecord {
+        SubmissionRecord { submitted_at: Utc::now() - Duration::days(days_ago), ..test_fixtures::record("b", status) }
+    }
+
+    #[test]
+    fn picks_statute_from_broker_then_user_state() {
+        let now = Utc::now();
+        let r = record(BrokerSubmissionStatus::Submitted, 10);
+        let d = request_deadline(&r, &broker(None), Some(&profile("va")), now).unwrap();
+        assert_eq!(d.statute, "VCDPA");
+        let d = request_deadline(&r, &broker(Some("CA")), Some(&profile("Virginia")), now).unwrap();
+        assert_eq!(d.statute, "CCPA");
+        assert_eq!(d.status, DeadlineStatus::OnTrack);
+        assert!(d.suggestions.is_empty());
+        // No state privacy law for the user and none declared by the broker
+        assert!(request_deadline(&r, &broker(None), Some(&profile("IL")), now).is_none());
+    }
+
+    #[test]
+    fn names_the_law_a_request_is_under() {
+        assert_eq!(legal_basis(&profile("Virginia"), None), Some("VCDPA"));
+        assert_eq!(legal_basis(&profile("VA"), Some(&broker(Some("CA")))), Some("CCPA"));
+        assert_eq!(legal_basis(&profile("IL"), Some(&broker(None))), None);
+        let mut abroad = profile("");
+        abroad.country = "de".to_string();
+        assert_eq!(legal_basis(&abroad, Some(&broker(Some("CA")))), Some("GDPR"));
+        abroad.country = "GB".to_string();
+        assert_eq!(legal_basis(&abroad, None), Some("UK GDPR"));
+    }
+
+    #[test]
+    fn escalates_once_the_extension_lapses() {
+        let b = broker(Some("CA"));
+        let overdue = record(BrokerSubmissionStatus::Submitted, 50);
+        let now = Utc::now();
+        let d = request_deadline(&overdue, &b, None, now).unwrap();
+        assert_eq!(d.status, DeadlineStatus::Overdue);
+        assert_eq!(d.days_overdue, 5);
+        let d = request_deadline(&record(BrokerSubmissionStatus::PendingVerification, 100), &b, None, now).unwrap();
+        assert_eq!(d.status, DeadlineStatus::ExtensionLapsed);
+        assert!(d.suggestions[0].contains("verification"));
+        assert!(d.suggestions[1].contains("California Privacy Protection Agency"));
+        // Confirmed requests have nothing left to track
+        assert!(request_deadline(&record(BrokerSubmissionStatus::Confirmed, 100), &b, None, now).is_none());
+    }
+}
@@ -0,0 +1,158 @@
+use crate::broker_responses;
+use crate::models::Broker;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// How long to wait for a broker's confirmation email before leaving it to the user.
+/// Email is slower than texts, and some brokers queue their mail.
+pub const WAIT_SECS: u64 = 300;
+/// Gap between checks of the inbox.
+pub const POLL_SECS: u64 = 10;
+
+/// Words in a link that point at confirming a request, as opposed to a logo or a policy.
+const CONFIRM_WORDS: &[&str] = &["confirm", "verif", "validate", "activate", "opt-out", "optout", "opt_out", "remov", "suppress"];
+/// Links that never confirm anything, even when they mention it.
+const SKIP_WORDS: &[&str] = &["unsubscribe", "privacy-policy", "privacy_policy", "terms", "contact"];
+
+/// A message the inbox received, as the original email source.
+#[derive(Debug, Deserialize, Clone)]
+pub struct InboxMessage {
+    pub raw: String,
+    #[serde(default)]
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct NewAddress {
+    address: String,
+}
+
+/// Check that the inbox provider's URL is one the app can use.
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid inbox provider URL: {}", e))?;
+    match parsed.scheme() {
+        "https" => Ok(()),
+        "http" if matches!(parsed.host_str(), Some("localhost") | Some("127.0.0.1")) => Ok(()),
+        _ => Err("The inbox provider URL must use https:// (http:// is only allowed for localhost).".to_string()),
+    }
+}
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+fn endpoint(url: &str, path: &str) -> String {
+    format!("{}/{}", url.trim().trim_end_matches('/'), path)
+}
+
+/// A new address from the provider, labelled so the user can tell what it was for. The
+/// provider answers `POST {url}/addresses` (with a JSON `label`) with `{"address": "..."}`.
+pub async fn create_address(url: &str, token: Option<&str>, label: &str) -> Result<String, String> {
+    let mut request = client()?
+        .post(endpoint(url, "addresses"))
+        .json(&serde_json::json!({ "label": label }));
+    if let Some(token) = token.filter(|t| !t.is_empty()) {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.map_err(|e| format!("Inbox provider unreachable: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Inbox provider returned {}", response.status()));
+    }
+    let created = response
+        .json::<NewAddress>()
+        .await
+        .map_err(|e| format!("Inbox provider sent an unexpected response: {}", e))?;
+    match created.address.trim() {
+        address if address.contains('@') => Ok(address.to_string()),
+        _ => Err("Inbox provider didn't return an email address".to_string()),
+    }
+}
+
+/// Messages `address` has received since `since`. The provider answers
+/// `GET {url}/messages?address={address}&since={RFC 3339 time}` with a JSON array of
+/// messages, each with its `raw` source.
+pub async fn fetch_messages(url: &str, token: Option<&str>, address: &str, since: DateTime<Utc>) -> Result<Vec<InboxMessage>, String> {
+    let mut request = client()?
+        .get(endpoint(url, "messages"))
+        .query(&[("address", address.to_string()), ("since", since.to_rfc3339())]);
+    if let Some(token) = token.filter(|t| !t.is_empty()) {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.map_err(|e| format!("Inbox provider unreachable: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Inbox provider returned {}", response.status()));
+    }
+    response
+        .json::<Vec<InboxMessage>>()
+        .await
+        .map_err(|e| format!("Inbox provider sent an unexpected response: {}", e))
+}
+
+/// The link among `links` that confirms a request: one whose address talks about
+/// confirming, preferring those on one of `sites` over a mail service's tracking link.
+fn confirmation_link(links: &[String], sites: &[String]) -> Option<String> {
+    let candidates: Vec<&String> = links
+        .iter()
+        .filter(|link| {
+            let lower = link.to_lowercase();
+            CONFIRM_WORDS.iter().any(|w| lower.contains(w)) && !SKIP_WORDS.iter().any(|w| lower.contains(w))
+        })
+        .collect();
+    candidates
+        .iter()
+        .find(|link| broker_responses::url_site(link).is_some_and(|s| sites.contains(&s)))
+        .or(candidates.first())
+        .map(|link| link.to_string())
+}
+
+/// The confirmation link in the newest message received since `since` that has one.
+pub fn pick_link(messages: &[InboxMessage], broker: &Broker, since: DateTime<Utc>) -> Option<(InboxMessage, String)> {
+    let sites: Vec<String> = [&broker.url, &broker.opt_out_url]
+        .iter()
+        .filter_map(|u| broker_responses::url_site(u))
+        .collect();
+    let mut recent: Vec<&InboxMessage> = messages
+        .iter()
+        .filter(|m| m.received_at.is_none_or(|t| t >= since))
+        .collect();
+    recent.sort_by_key(|m| std::cmp::Reverse(m.received_at));
+    recent
+        .into_iter()
+        .find_map(|m| Some((m.clone(), confirmation_link(&broker_responses::links(&m.raw), &sites)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_confirmation_link_in_an_email() {
+        let raw = "From: Spokeo <privacy@spokeo.com>\r\n\
+            Subject: Confirm your opt-out\r\n\
+            Content-Type: text/html; charset=utf-8\r\n\
+            Content-Transfer-Encoding: quoted-printable\r\n\
+            \r\n\
+            <a href=3D\"https://www.spokeo.com/privacy-policy\">Policy</a>\r\n\
+            <a href=3D\"https://click.mailer.example/confirm?u=3D1\">Confirm</a>\r\n\
+            <a href=3D\"https://www.spokeo.com/optout/confirm?token=3Dabc&amp;id=3D7\">Con=\r\n\
+            firm removal</a> or visit https://www.spokeo.com/unsubscribe.\r\n";
+        let links = broker_responses::links(raw);
+        assert_eq!(links.len(), 4);
+        assert_eq!(links[3], "https://www.spokeo.com/unsubscribe");
+        let sites = vec!["spokeo.com".to_string()];
+        assert_eq!(
+            confirmation_link(&links, &sites).as_deref(),
+            Some("https://www.spokeo.com/optout/confirm?token=abc&id=7")
+        );
+        // A tracking link is the next best thing when the broker's own site isn't linked
+        assert_eq!(
+            confirmation_link(&links, &["radaris.com".to_string()]).as_deref(),
+            Some("https://click.mailer.example/confirm?u=1")
+        );
+        assert_eq!(confirmation_link(&links[..1], &sites), None);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::models::{OutcomeCheck, PageOutcome};
+
+/// Phrases brokers show once an opt-out request has gone through. Only past-tense
+/// wording: opt-out forms talk about emails and removals that will happen too.
+/// Also what the recorder looks for after a submission.
+pub const CONFIRMATION_PHRASES: &[&str] = &[
+    "request has been received",
+    "request has been submitted",
+    "request has been processed",
+    "request was received",
+    "request was submitted",
+    "we have received your request",
+    "we've received your request",
+    "we received your request",
+    "submitted successfully",
+    "successfully submitted",
+    "successfully removed",
+    "has been removed",
+    "have been removed",
+    "you have been opted out",
+    "opt-out request received",
+    "opt out request received",
+    "we have sent you an email",
+    "we've sent you an email",
+    "we sent you an email",
+    "email has been sent",
+    "thank you for submitting",
+];
+
+/// Phrases that mean the form was rejected rather than submitted.
+const ERROR_PHRASES: &[&str] = &[
+    "please try again",
+    "something went wrong",
+    "an error occurred",
+    "an error has occurred",
+    "could not be processed",
+    "unable to process",
+    "invalid captcha",
+    "captcha verification failed",
+    "please correct the",
+    "please fix the",
+];
+
+/// Phrases search pages show when nobody matched.
+const NO_RESULTS_PHRASES: &[&str] = &[
+    "no results found",
+    "no results were found",
+    "no records found",
+    "no records were found",
+    "no matches found",
+    "no matching records",
+    "no matching results",
+    "we couldn't find",
+    "we could not find",
+    "0 results",
+    "returned no results",
+    "did not match any",
+];
+
+/// URL path or query words that usually mark a success page.
+const SUCCESS_URL_WORDS: &[&str] = &[
+    "success", "thank-you", "thankyou", "thanks", "confirmation", "confirmed", "complete",
+    "completed", "submitted",
+];
+
+/// Judge the page left after a playbook's last step. A confirmation phrase wins over
+/// an error phrase (success pages often repeat generic help text), and the URL is
+/// only consulted when the text is inconclusive.
+pub fn assess(url: &str, text: &str) -> OutcomeCheck {
+    if let Some(phrase) = confirmation_phrase(text) {
+        return check(PageOutcome::Confirmed, phrase);
+    }
+    let text = text.to_lowercase();
+    if let Some(phrase) = ERROR_PHRASES.iter().find(|p| text.contains(*p)) {
+        return check(PageOutcome::ErrorShown, phrase);
+    }
+
+    // Only the path and query: the broker's domain could contain any of these words
+    let lower = url.to_lowercase();
+    let after_host = lower
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(&lower)
+        .split_once('/')
+        .map(|(_, path)| path)
+        .unwrap_or("");
+    let words: Vec<&str> = after_host
+        .split(|c: char| !(c.is_alphanumeric() || c == '-'))
+        .filter(|w| !w.is_empty())
+        .collect();
+    if let Some(word) = SUCCESS_URL_WORDS.iter().find(|w| words.contains(w)) {
+        return check(PageOutcome::Confirmed, word);
+    }
+
+    OutcomeCheck {
+        outcome: PageOutcome::Unknown,
+        matched: None,
+    }
+}
+
+/// The confirmation phrase in `text`, if any.
+pub fn confirmation_phrase(text: &str) -> Option<&'static str> {
+    let text = text.to_lowercase();
+    CONFIRMATION_PHRASES.iter().find(|p| text.contains(*p)).copied()
+}
+
+/// The phrase on a search page that says nobody matched, if there is one.
+pub fn no_results_phrase(text: &str) -> Option<&'static str> {
+    let text = text.to_lowercase();
+    NO_RESULTS_PHRASES.iter().find(|p| starts_a_word(&text, p)).copied()
+}
+
+/// Whether `phrase` appears in `text` starting at a word boundary, so "0 results"
+/// doesn't match "Showing 10 results".
+fn starts_a_word(text: &str, phrase: &str) -> bool {
+    text.match_indices(phrase)
+        .any(|(i, _)| !text[..i].chars().next_back().is_some_and(char::is_alphanumeric))
+}
+
+fn check(outcome: PageOutcome, matched: &str) -> OutcomeCheck {
+    OutcomeCheck {
+        outcome,
+        matched: Some(matched.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn confirms_on_phrase_or_success_url() {
+        let c = assess("https://broker.com/optout", "Thanks! Your request has been received.");
+        assert_eq!(c.outcome, PageOutcome::Confirmed);
+        assert_eq!(c.matched.as_deref(), Some("request has been received"));
+        assert_eq!(assess("https://broker.com/optout/thank-you", "").outcome, PageOutcome::Confirmed);
+    }
+
+    #[test]
+    fn flags_error_pages() {
+        let c = assess("https://broker.com/optout", "Something went wrong. Please try again.");
+        assert_eq!(c.outcome, PageOutcome::ErrorShown);
+    }
+
+    #[test]
+    fn prefers_confirmation_over_error_text() {
+        let text = "We have received your request. Having trouble? Please try again later.";
+        assert_eq!(assess("https://broker.com/", text).outcome, PageOutcome::Confirmed);
+    }
+
+    #[test]
+    fn form_intro_text_is_not_a_confirmation() {
+        // What opt-out forms say before anything is submitted
+        let form = "Opt out of PeopleFinder\n\
+                    Submit your opt-out request below. We will send a verification email; \
+                    check your email and click the link. Your listing will be removed within 72 hours. \
+                    Thank you for your request to be removed.\n\
+                    Email address\nSend code\nSubmit";
+        assert_eq!(confirmation_phrase(form), None);
+        let rejected = format!("{form}\nPlease correct the errors below.");
+        assert_eq!(assess("https://broker.com/optout", &rejected).outcome, PageOutcome::ErrorShown);
+
+        let done = "Thank you! Your opt-out request has been received. We've sent you an email.";
+        assert_eq!(confirmation_phrase(done), Some("request has been received"));
+    }
+
+    #[test]
+    fn spots_empty_search_results() {
+        assert_eq!(no_results_phrase("Sorry, we couldn't find anyone named Jane Doe."), Some("we couldn't find"));
+        assert_eq!(no_results_phrase("Showing 3 results for Jane Doe"), None);
+        assert_eq!(no_results_phrase("Showing 10 results for Jane Doe"), None);
+        assert_eq!(no_results_phrase("Search returned 0 results."), Some("0 results"));
+    }
+
+    #[test]
+    fn ignores_success_words_in_the_domain() {
+        assert_eq!(assess("https://success-people.com/search", "Results").outcome, PageOutcome::Unknown);
+    }
+}
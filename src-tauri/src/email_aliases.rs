@@ -0,0 +1,132 @@
+use crate::models::{Broker, EmailAlias, EmailAliasStore};
+use chrono::Utc;
+use std::fs;
+use std::path::PathBuf;
+
+const FILENAME: &str = "email_aliases.json";
+/// Tag before the broker's name in a plus address, marking it as one the app handed out.
+const TAG: &str = "optoutta";
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<EmailAliasStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(EmailAliasStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &EmailAliasStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// `email` with a tag naming the broker after a `+` (jane+optoutta-spokeo@example.com).
+/// Most providers deliver it to the user's inbox as usual. A tag already on `email` is
+/// replaced. None when `email` isn't an address.
+pub fn plus_address(email: &str, broker_id: &str) -> Option<String> {
+    let (local, domain) = email.trim().rsplit_once('@')?;
+    let base = local.split('+').next().unwrap_or(local);
+    if base.is_empty() || domain.is_empty() {
+        return None;
+    }
+    let slug: String = broker_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        return None;
+    }
+    Some(format!("{}+{}-{}@{}", base, TAG, slug, domain.to_lowercase()))
+}
+
+fn assign_in(store: &mut EmailAliasStore, address: &str, broker: &Broker, run_id: &str) {
+    match store.aliases.iter_mut().find(|a| a.address.eq_ignore_ascii_case(address)) {
+        Some(alias) => {
+            if !alias.run_ids.iter().any(|r| r == run_id) {
+                alias.run_ids.push(run_id.to_string());
+            }
+        }
+        None => store.aliases.push(EmailAlias {
+            address: address.to_string(),
+            broker_id: broker.id.clone(),
+            broker_name: broker.name.clone(),
+            first_used: Utc::now(),
+            run_ids: vec![run_id.to_string()],
+        }),
+    }
+}
+
+/// The address to give `broker` in place of `email` during `run_id`, recorded so mail to
+/// it can be traced back. None when `email` can't take a tag or the record couldn't be
+/// saved; an address nobody could trace isn't worth handing out.
+pub fn assign(app: &tauri::AppHandle, broker: &Broker, run_id: &str, email: &str) -> Option<String> {
+    let address = plus_address(email, &broker.id)?;
+    record(app, broker, run_id, &address).then_some(address)
+}
+
+/// Note that `address` was given to `broker` during `run_id`, such as one from the user's
+/// disposable inbox. False when the record couldn't be saved.
+pub fn record(app: &tauri::AppHandle, broker: &Broker, run_id: &str, address: &str) -> bool {
+    let recorded = load(app).and_then(|mut store| {
+        assign_in(&mut store, address, broker, run_id);
+        save(app, &store)
+    });
+    if let Err(e) = &recorded {
+        eprintln!("[email-aliases] Failed to record {}'s address: {}", broker.name, e);
+    }
+    recorded.is_ok()
+}
+
+/// The alias that appears in `text`, such as the To line or headers of an email.
+fn find_in<'a>(aliases: &'a [EmailAlias], text: &str) -> Option<&'a EmailAlias> {
+    let text = text.to_lowercase();
+    aliases.iter().find(|a| text.contains(&a.address.to_lowercase()))
+}
+
+/// Which broker was given the address an email was sent to. `text` can be the address
+/// or anything containing it.
+pub fn find(app: &tauri::AppHandle, text: &str) -> Result<Option<EmailAlias>, String> {
+    Ok(find_in(&load(app)?.aliases, text).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    #[test]
+    fn tags_the_address_with_the_broker() {
+        assert_eq!(plus_address("Jane@Example.com", "spokeo").as_deref(), Some("Jane+optoutta-spokeo@example.com"));
+        assert_eq!(
+            plus_address(" jane+news@example.com ", "Been_Verified").as_deref(),
+            Some("jane+optoutta-been-verified@example.com")
+        );
+        assert_eq!(plus_address("not an address", "spokeo"), None);
+        assert_eq!(plus_address("@example.com", "spokeo"), None);
+    }
+
+    #[test]
+    fn traces_mail_back_to_the_broker() {
+        let broker = |id: &str| Broker { name: id.to_uppercase(), ..test_fixtures::broker(id) };
+        let mut store = EmailAliasStore::default();
+        assign_in(&mut store, "jane+optoutta-spokeo@example.com", &broker("spokeo"), "run1");
+        assign_in(&mut store, "jane+optoutta-radaris@example.com", &broker("radaris"), "run1");
+        assign_in(&mut store, "jane+optoutta-spokeo@example.com", &broker("spokeo"), "run2");
+        assert_eq!(store.aliases.len(), 2);
+        assert_eq!(store.aliases[0].run_ids, ["run1", "run2"]);
+
+        let headers = "From: deals@spam.example\nTo: \"Jane\" <Jane+OptOutta-Radaris@example.com>";
+        assert_eq!(find_in(&store.aliases, headers).map(|a| a.broker_id.as_str()), Some("radaris"));
+        assert!(find_in(&store.aliases, "jane@example.com").is_none());
+    }
+}
@@ -1,17 +1,45 @@
-use crate::browser;
+use crate::auto_fill;
+use crate::broker_difficulty;
+use crate::broker_responses;
+use crate::browser::{self, Page};
+use crate::browser_proxy;
+use crate::consent_banners;
+use crate::credentials;
+use crate::deadlines;
+use crate::disposable_inbox;
+use crate::done_detection;
+use crate::email_aliases;
+use crate::evidence;
 use crate::history;
+use crate::listing_match::{self, ListingChoice, ListingMatch};
 use crate::local_playbooks;
 use crate::models::*;
+use crate::pacing::Pacer;
+use crate::pii_scan;
 use crate::playbook_api;
+use crate::playbook_reliability;
 use crate::playbook_validation;
 use crate::playbook_verification;
-use chrono::{Duration, Utc};
+use crate::profile_format;
+use crate::run_plan;
+use crate::run_summary;
+use crate::secrets::SecretsCache;
+use crate::selector_repair;
+use crate::settings;
+use crate::sms_relay;
+use crate::step_cursor::{self, CursorItem, StepCursor};
+use crate::step_descriptions;
+use crate::step_phases;
+use crate::submission_receipts;
+use crate::totp;
+use chrono::Utc;
+use futures::future::Either;
 use futures::StreamExt;
 use std::sync::Arc;
 use tokio::sync::{oneshot, Mutex};
 
 /// Convert a PlaybookStep (from API) to a FormAction (for browser::execute_action).
-fn playbook_step_to_form_action(step: &PlaybookStep) -> Option<FormAction> {
+pub fn playbook_step_to_form_action(step: &PlaybookStep) -> Option<FormAction> {
     match step.action.as_str() {
         "navigate" => Some(FormAction::Navigate {
             url: step.value.clone().unwrap_or_default(),
@@ -33,13 +61,11 @@ fn playbook_step_to_form_action(step: &PlaybookStep) -> Option<FormAction> {
         }
         "select" => {
             if step.value.is_some() || step.profile_key.is_some() {
-                // Auto-select: use profile_key or a static value
-                let value = step.value.clone().unwrap_or_else(|| {
-                    step.profile_key.clone().unwrap_or_default()
-                });
+                // Auto-select: a static value, or else the profile value
                 Some(FormAction::Select {
                     selector: step.selector.clone().unwrap_or_default(),
-                    value,
+                    value: step.value.clone().unwrap_or_default(),
+                    profile_key: step.profile_key.clone().filter(|_| step.value.is_none()),
                 })
             } else {
                 // Manual select: user picks the dropdown option
@@ -53,6 +79,21 @@ fn playbook_step_to_form_action(step: &PlaybookStep) -> Option<FormAction> {
             selector: step.selector.clone().unwrap_or_default(),
             checked: step.value.as_deref() != Some("false"),
         }),
+        "choose_radio" => {
+            if step.value.is_some() || step.profile_key.is_some() {
+                Some(FormAction::ChooseRadio {
+                    selector: step.selector.clone().unwrap_or_default(),
+                    value: step.value.clone().unwrap_or_default(),
+                    profile_key: step.profile_key.clone().filter(|_| step.value.is_none()),
+                    transform: None,
+                })
+            } else {
+                Some(FormAction::ManualSelect {
+                    selector: step.selector.clone().unwrap_or_default(),
+                    message: step.description.clone(),
+                })
+            }
+        }
         "click" => Some(FormAction::Click {
             selector: step.selector.clone().unwrap_or_default(),
         }),
@@ -66,6 +107,22 @@ fn playbook_step_to_form_action(step: &PlaybookStep) -> Option<FormAction> {
         "scroll_to" => Some(FormAction::ScrollTo {
             selector: step.selector.clone().unwrap_or_default(),
         }),
+        "fill_credential" => {
+            let field = match step.value.as_deref() {
+                Some("password") => CredentialField::Password,
+                _ => CredentialField::Username,
+            };
+            Some(FormAction::FillCredential {
+                selector: step.selector.clone().unwrap_or_default(),
+                field,
+            })
+        }
+        "fill_sms_code" => Some(FormAction::FillSmsCode {
+            selector: step.selector.clone().unwrap_or_default(),
+        }),
+        "fill_totp" => Some(FormAction::FillTotp {
+            selector: step.selector.clone().unwrap_or_default(),
+        }),
         "find_and_click" => Some(FormAction::FindAndClick {
             selector: step.selector.clone().unwrap_or_default(),
             profile_key: step.profile_key.clone().unwrap_or_default(),
@@ -80,10 +137,129 @@ fn playbook_step_to_form_action(step: &PlaybookStep) -> Option<FormAction> {
     }
 }
 
+/// Convert a step, swapping in the first fallback selector that matches when its own selector
+/// doesn't. Fallbacks sit outside the signed step fields, so only local playbooks use them.
+async fn step_to_form_action_on(page: &Page, step: &PlaybookStep, local: bool) -> Option<FormAction> {
+    let selector = match step.selector.as_deref() {
+        Some(s) if local && !step.fallback_selectors.is_empty() => s,
+        _ => return playbook_step_to_form_action(step),
+    };
+    let working = browser::resolve_selector(page, selector, &step.fallback_selectors).await;
+    if working == selector {
+        return playbook_step_to_form_action(step);
+    }
+    eprintln!("[opt-out] Step {}: {} not found, using fallback {}", step.position, selector, working);
+    let mut healed = step.clone();
+    healed.selector = Some(working);
+    playbook_step_to_form_action(&healed)
+}
+
+/// Turn a credential or authenticator-code fill into a plain fill with the user's login for
+/// the broker, creating one with a generated password the first time a playbook registers an
+/// account. If there's no usable login or secret, the user is asked to fill the field instead.
+pub fn apply_credentials(app: &tauri::AppHandle, action: FormAction, broker: &Broker, profile: &Profile) -> FormAction {
+    let (selector, value) = match action {
+        FormAction::FillCredential { selector, field } => {
+            let value = credentials::get_or_create(app, &broker.id, &profile.email)
+                .map(|c| c.field(field).to_string())
+                .map_err(|e| {
+                    let what = match field {
+                        CredentialField::Username => "username",
+                        CredentialField::Password => "password",
+                    };
+                    (e, format!("Enter your {} {} here", broker.name, what))
+                });
+            (selector, value)
+        }
+        FormAction::FillTotp { selector } => {
+            let value = credentials::get(app, &broker.id)
+                .and_then(|c| {
+                    let secret = c
+                        .and_then(|c| c.totp_secret)
+                        .ok_or_else(|| "No two-factor secret saved".to_string())?;
+                    totp::code(&secret, Utc::now().timestamp().max(0) as u64)
+                })
+                .map_err(|e| (e, format!("Enter the code from your authenticator app for {}", broker.name)));
+            (selector, value)
+        }
+        other => return other,
+    };
+    match value {
+        Ok(value) => FormAction::Fill {
+            selector,
+            profile_key: None,
+            value: Some(value),
+            transform: None,
+        },
+        Err((e, message)) => {
+            eprintln!("[opt-out] {}: Couldn't fill account login: {}", broker.name, e);
+            FormAction::ManualFill { selector, message }
+        }
+    }
+}
+
+/// Apply the broker's `field_formats` to a fill action so profile values match what its form expects.
+pub fn apply_field_format(action: FormAction, broker: &Broker) -> FormAction {
+    match action {
+        FormAction::Fill { selector, profile_key: Some(key), value, transform: None } => {
+            let transform = broker.field_formats.get(&key).cloned();
+            FormAction::Fill { selector, profile_key: Some(key), value, transform }
+        }
+        // Labels usually spell values out, like "California" for "CA"
+        FormAction::ChooseRadio { selector, value, profile_key: Some(key), transform: None } => {
+            let transform = broker.field_formats.get(&key).cloned();
+            FormAction::ChooseRadio { selector, value, profile_key: Some(key), transform }
+        }
+        other => other,
+    }
+}
+
+/// Resolve a `legalBasis` fill or select for `broker`, whose declared jurisdiction can
+/// decide the law where the profile alone can't. When no law covers the user, they're
+/// asked to answer the field themselves.
+pub fn apply_legal_basis(action: FormAction, broker: &Broker, profile: &Profile) -> FormAction {
+    let basis = deadlines::legal_basis(profile, Some(broker)).map(String::from);
+    match action {
+        FormAction::Fill { selector, profile_key: Some(key), transform, .. } if key == "legalBasis" => match basis {
+            Some(value) => FormAction::Fill {
+                selector,
+                profile_key: None,
+                value: Some(match transform {
+                    Some(t) => profile_format::apply_transform(&value, &t),
+                    None => value,
+                }),
+                transform: None,
+            },
+            None => FormAction::ManualFill { selector, message: "Fill in which privacy law your request is under".to_string() },
+        },
+        FormAction::Select { selector, profile_key: Some(key), .. } if key == "legalBasis" => match basis {
+            Some(value) => FormAction::Select { selector, value, profile_key: None },
+            None => FormAction::ManualSelect { selector, message: "Choose which privacy law your request is under".to_string() },
+        },
+        other => other,
+    }
+}
+
+/// Fill `alias` wherever the playbook fills the profile's email, so the broker is given an
+/// address of its own.
+pub fn apply_email_alias(action: FormAction, alias: Option<&str>) -> FormAction {
+    match (action, alias) {
+        (FormAction::Fill { selector, profile_key: Some(key), transform, .. }, Some(alias)) if key == "email" => FormAction::Fill {
+            selector,
+            profile_key: None,
+            value: Some(match transform {
+                Some(t) => profile_format::apply_transform(alias, &t),
+                None => alias.to_string(),
+            }),
+            transform: None,
+        },
+        (action, _) => action,
+    }
+}
+
 pub struct EngineState(pub Arc<Mutex<Option<OptOutEngine>>>);
 
 pub struct OptOutEngine {
-    #[allow(dead_code)]
     pub run_id: String,
     pub status: RunStatus,
     cancel_tx: Option<oneshot::Sender<()>>,
@@ -102,17 +278,30 @@ impl OptOutEngine {
         (engine, cancel_rx)
     }
 
-    pub fn cancel(&mut self) {
+    /// Stop the run. A prompt the run is waiting on is dropped so it stops waiting now
+    /// rather than after the user answers. The run stays busy until its task returns.
+    pub async fn cancel(&mut self) {
         if let Some(tx) = self.cancel_tx.take() {
             let _ = tx.send(());
         }
-        self.status = RunStatus::Failed;
+        self.user_action_tx.lock().await.take();
+        if self.is_busy() {
+            self.status = RunStatus::Cancelling;
+        }
+    }
+
+    /// Whether the run still has the automation browser, including while it winds down
+    /// after a cancel.
+    pub fn is_busy(&self) -> bool {
+        matches!(self.status, RunStatus::Running | RunStatus::WaitingForUser | RunStatus::Cancelling)
     }
 
-    pub async fn signal_user_action(&self, response: String) {
+    /// Answer the prompt the run is waiting on. Returns false if nothing was waiting.
+    pub async fn signal_user_action(&self, response: String) -> bool {
         let mut guard = self.user_action_tx.lock().await;
-        if let Some(tx) = guard.take() {
-            let _ = tx.send(response);
+        match guard.take() {
+            Some(tx) => tx.send(response).is_ok(),
+            None => false,
         }
     }
 
@@ -121,6 +310,32 @@ impl OptOutEngine {
     }
 }
 
+/// Ask the user's model for a selector to replace `step`'s missing one, if they've opted in.
+async fn propose_selector_repair(
+    app: &tauri::AppHandle,
+    app_settings: &AppSettings,
+    page: &Page,
+    step: &PlaybookStep,
+    profile: &Profile,
+) -> Option<String> {
+    use tauri::Manager;
+    let config = selector_repair::config(app_settings, app.state::<SecretsCache>().get_ai_api_key())?;
+    let structure = match browser::extract_page_structure(page).await {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("[opt-out] Selector repair: {}", e);
+            return None;
+        }
+    };
+    match selector_repair::suggest(&config, step, &structure, Some(profile)).await {
+        Ok(proposed) => proposed,
+        Err(e) => {
+            eprintln!("[opt-out] Selector repair: {}", e);
+            None
+        }
+    }
+}
+
 /// Convert raw browser/engine errors into human-readable messages.
 fn format_step_error(raw: &str, step_desc: &str) -> String {
     let lower = raw.to_lowercase();
@@ -137,18 +352,511 @@ fn format_step_error(raw: &str, step_desc: &str) -> String {
     }
 }
 
+/// How long a self-clearing "checking your browser" page gets before the user is asked.
+const CHALLENGE_WAIT_SECS: u64 = 20;
+/// How many times the user is asked to complete an interactive challenge.
+const CHALLENGE_PROMPTS: u32 = 3;
+
+/// Get past an anti-bot challenge on `page`, if there is one: wait for "checking your
+/// browser" pages to clear, ask the user to complete interactive checks, and reload
+/// once when blocked. Without this the playbook's steps fail one by one with
+/// "Element not found".
+async fn clear_challenge(
+    page: &Page,
+    broker: &Broker,
+    user_action_channel: &Arc<Mutex<Option<oneshot::Sender<String>>>>,
+    emit: impl Fn(&str, RunStatus, Option<UserActionRequired>),
+) -> Result<(), String> {
+    let mut prompts = 0;
+    let mut reloaded = false;
+    while let Some(challenge) = browser::detect_challenge(page).await {
+        match challenge.kind.as_str() {
+            "checking" => {
+                emit(&format!("Waiting for {}'s browser check...", challenge.provider), RunStatus::Running, None);
+                if browser::wait_for_challenge_to_clear(page, tokio::time::Duration::from_secs(CHALLENGE_WAIT_SECS)).await {
+                    continue;
+                }
+            }
+            "blocked" if !reloaded => {
+                reloaded = true;
+                emit(&format!("Blocked ({}) — retrying...", challenge.provider), RunStatus::Running, None);
+                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                let _ = browser::reload(page, &broker.automation).await;
+                continue;
+            }
+            "blocked" => {
+                return Err(format!(
+                    "{} blocked this browser ({}). Try again later or from a different network.",
+                    broker.name, challenge.provider
+                ));
+            }
+            _ => {}
+        }
+
+        // An interactive check, or a browser check that didn't clear by itself
+        if prompts == CHALLENGE_PROMPTS {
+            return Err(format!("{}'s {} check didn't clear.", broker.name, challenge.provider));
+        }
+        prompts += 1;
+        let _ = browser::bring_to_front(page).await;
+        let message = format!(
+            "{} wants to confirm you're human. Complete the check in the browser, then continue.",
+            challenge.provider
+        );
+        emit(
+            &message,
+            RunStatus::WaitingForUser,
+            Some(UserActionRequired::UserPrompt { message: message.clone(), description: None }),
+        );
+        let rx = wait_for_user(user_action_channel).await;
+        let cleared = browser::wait_for_challenge_to_clear(page, tokio::time::Duration::from_secs(3600));
+        futures::pin_mut!(cleared);
+        match futures::future::select(rx, cleared).await {
+            Either::Right(_) => {
+                user_action_channel.lock().await.take();
+                emit("Check passed — continuing", RunStatus::Running, None);
+            }
+            // The prompt was dropped by a cancel
+            Either::Left((Err(_), _)) => return Err("Run cancelled by user".to_string()),
+            Either::Left(_) => {}
+        }
+    }
+    Ok(())
+}
+
+/// Make the run wait on the user, for prompts that can also end without an answer.
+async fn wait_for_user(user_action_channel: &Arc<Mutex<Option<oneshot::Sender<String>>>>) -> oneshot::Receiver<String> {
+    let (tx, rx) = oneshot::channel::<String>();
+    *user_action_channel.lock().await = Some(tx);
+    rx
+}
+
+/// Wait for the user's answer to the prompt just shown, or `default` if the prompt is
+/// dropped by a cancel.
+async fn await_decision(user_action_channel: &Arc<Mutex<Option<oneshot::Sender<String>>>>, default: &str) -> String {
+    wait_for_user(user_action_channel).await.await.unwrap_or_else(|_| default.to_string())
+}
+
+/// How a step that involved the user ended.
+enum StepOutcome {
+    Done,
+    /// Move on without the step's wait or challenge check
+    Skipped,
+    /// Stop the playbook with this error
+    Failed(String),
+}
+
+/// The page a pass runs its steps on, and how to ask the user about them.
+struct StepContext<'a> {
+    app: &'a tauri::AppHandle,
+    page: &'a Page,
+    broker: &'a Broker,
+    profile: &'a Profile,
+    user_action_channel: &'a Arc<Mutex<Option<oneshot::Sender<String>>>>,
+    emit: &'a (dyn Fn(&str, RunStatus, Option<UserActionRequired>) + Sync),
+}
+
+impl StepContext<'_> {
+    /// Show why `step` failed and ask whether to retry, skip or abort it.
+    async fn ask_after_failure(&self, step: &PlaybookStep, friendly: &str) -> String {
+        (self.emit)(
+            friendly,
+            RunStatus::WaitingForUser,
+            Some(UserActionRequired::StepFailed {
+                message: friendly.to_string(),
+                step_description: step.description.clone(),
+                step_position: step.position,
+                broker_name: self.broker.name.clone(),
+            }),
+        );
+        await_decision(self.user_action_channel, "abort").await
+    }
+
+    /// When several results could be the user's, let them pick rather than guess.
+    async fn choose_listing(&self, indexes: &[usize], texts: &[String]) -> ListingChoice {
+        let candidates: Vec<ListingCandidate> = indexes
+            .iter()
+            .map(|&i| ListingCandidate {
+                index: i as u32,
+                text: listing_match::display_text(&texts[i]),
+            })
+            .collect();
+        let message = format!(
+            "{} results could be your listing on {}. Which one is you?",
+            candidates.len(),
+            self.broker.name
+        );
+        (self.emit)(
+            &message,
+            RunStatus::WaitingForUser,
+            Some(UserActionRequired::ChooseListing { message: message.clone(), candidates }),
+        );
+        // A closed channel is no answer, so nothing gets submitted
+        let decision = await_decision(self.user_action_channel, "").await;
+        listing_match::listing_choice(&decision, indexes)
+    }
+
+    /// Scroll to and highlight the field the user is asked to fill, asking them what to
+    /// do when it can't be found.
+    async fn highlight_field(&self, step: &PlaybookStep, selector: &str) -> StepOutcome {
+        let Err(e) = browser::highlight_element(self.page, selector).await else {
+            return StepOutcome::Done;
+        };
+        if step.optional {
+            return StepOutcome::Skipped;
+        }
+        let friendly = format_step_error(&e, &step.description);
+        loop {
+            match self.ask_after_failure(step, &friendly).await.as_str() {
+                "retry" => {
+                    if browser::highlight_element(self.page, selector).await.is_ok() {
+                        return StepOutcome::Done;
+                    }
+                }
+                "skip" => return StepOutcome::Skipped,
+                _ => return StepOutcome::Failed(friendly),
+            }
+        }
+    }
+
+    /// Wait for the user to solve the CAPTCHA, or for its provider to report it solved.
+    async fn solve_captcha(&self, message: Option<&str>) {
+        let msg = message.unwrap_or("Please solve the CAPTCHA.");
+        let _ = browser::bring_to_front(self.page).await;
+        let captcha = browser::highlight_captcha(self.page).await;
+        let captcha_type = match &captcha {
+            Some((_, kind)) => Some(kind.clone()),
+            None => browser::captcha_state(self.page).await.ok().and_then(|s| s.kind),
+        };
+        (self.emit)(
+            msg,
+            RunStatus::WaitingForUser,
+            Some(UserActionRequired::SolveCaptcha {
+                captcha_type,
+                message: msg.to_string(),
+            }),
+        );
+        let rx = wait_for_user(self.user_action_channel).await;
+        // Carry on by ourselves once the provider reports the CAPTCHA solved
+        let solved = browser::wait_for_captcha_solved(self.page);
+        futures::pin_mut!(solved);
+        if let Either::Right(_) = futures::future::select(rx, solved).await {
+            self.user_action_channel.lock().await.take();
+            (self.emit)("CAPTCHA solved — continuing", RunStatus::Running, None);
+        }
+        if let Some((selector, _)) = &captcha {
+            let _ = browser::remove_highlight(self.page, selector).await;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    }
+
+    async fn user_prompt(&self, step: &PlaybookStep, message: &str) {
+        let _ = browser::bring_to_front(self.page).await;
+        (self.emit)(
+            message,
+            RunStatus::WaitingForUser,
+            Some(UserActionRequired::UserPrompt {
+                message: message.to_string(),
+                description: step.instructions.clone(),
+            }),
+        );
+        await_decision(self.user_action_channel, "").await;
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    }
+
+    /// The user can type the value in the app (`value:{text}`) or fill the field in the browser.
+    async fn manual_fill(&self, step: &PlaybookStep, selector: &str, message: &str) -> StepOutcome {
+        let highlighted = self.highlight_field(step, selector).await;
+        if !matches!(highlighted, StepOutcome::Done) {
+            return highlighted;
+        }
+        let _ = browser::bring_to_front(self.page).await;
+        let mut fill_error: Option<String> = None;
+        loop {
+            (self.emit)(
+                message,
+                RunStatus::WaitingForUser,
+                Some(UserActionRequired::ManualFill {
+                    message: message.to_string(),
+                    description: step.instructions.clone(),
+                    error: fill_error.take(),
+                    profile_key: step.profile_key.clone(),
+                }),
+            );
+            let decision = await_decision(self.user_action_channel, "").await;
+            let Some(value) = decision.strip_prefix("value:") else {
+                break;
+            };
+            let fill = FormAction::Fill {
+                selector: selector.to_string(),
+                profile_key: None,
+                value: Some(value.to_string()),
+                transform: None,
+            };
+            match browser::execute_action(self.page, &fill, self.profile, &self.broker.automation).await {
+                Ok(_) => break,
+                Err(e) => fill_error = Some(format_step_error(&e, &step.description)),
+            }
+        }
+        // Remove the highlight after user confirms
+        let _ = browser::remove_highlight(self.page, selector).await;
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        StepOutcome::Done
+    }
+
+    async fn manual_select(&self, step: &PlaybookStep, selector: &str, message: &str) -> StepOutcome {
+        let highlighted = self.highlight_field(step, selector).await;
+        if !matches!(highlighted, StepOutcome::Done) {
+            return highlighted;
+        }
+        let _ = browser::bring_to_front(self.page).await;
+        (self.emit)(
+            message,
+            RunStatus::WaitingForUser,
+            Some(UserActionRequired::UserPrompt {
+                message: format!("Please fill out this field in the browser: {}", message),
+                description: None,
+            }),
+        );
+        await_decision(self.user_action_channel, "").await;
+        // Remove the highlight after user confirms
+        let _ = browser::remove_highlight(self.page, selector).await;
+        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        StepOutcome::Done
+    }
+
+    /// Run an automated step. When its element is gone the user's model may propose a
+    /// new selector, which is saved to `repaired` once the user approves it and it works.
+    async fn run_action(
+        &self,
+        app_settings: &AppSettings,
+        step: &PlaybookStep,
+        action: &FormAction,
+        pb: &Playbook,
+        mut repair_offered: bool,
+        repaired: &mut Option<LocalPlaybook>,
+    ) -> StepOutcome {
+        loop {
+            let Err(e) = browser::execute_action(self.page, action, self.profile, &self.broker.automation).await else {
+                return StepOutcome::Done;
+            };
+            if step.optional {
+                return StepOutcome::Done;
+            }
+            let missing = run_summary::classify_step_failure(&e, false, false) == FailureClass::Selector;
+            let proposed = match (repair_offered, missing, step.selector.as_deref()) {
+                (false, true, Some(_)) => {
+                    repair_offered = true;
+                    (self.emit)("Looking for the element's new selector...", RunStatus::Running, None);
+                    propose_selector_repair(self.app, app_settings, self.page, step, self.profile).await
+                }
+                _ => None,
+            };
+            if let Some(proposed) = proposed {
+                let message = format!("The element for \"{}\" wasn't found. Use this one instead?", step.description);
+                (self.emit)(
+                    &message,
+                    RunStatus::WaitingForUser,
+                    Some(UserActionRequired::ApproveSelectorRepair {
+                        message: message.clone(),
+                        step_description: step.description.clone(),
+                        step_position: step.position,
+                        failed_selector: step.selector.clone().unwrap_or_default(),
+                        proposed_selector: proposed.clone(),
+                    }),
+                );
+                let decision = await_decision(self.user_action_channel, "abort").await;
+                if decision == "abort" {
+                    return StepOutcome::Failed(format_step_error(&e, &step.description));
+                }
+                if let Some(fixed) = selector_repair::with_selector(action, &proposed).filter(|_| decision == "approve") {
+                    if browser::execute_action(self.page, &fixed, self.profile, &self.broker.automation).await.is_ok() {
+                        let target = repaired.get_or_insert_with(|| selector_repair::repair_target(self.app, pb));
+                        selector_repair::patch_step(&mut target.steps, step.position, &proposed);
+                        target.updated_at = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+                        match local_playbooks::upsert(self.app, target.clone()) {
+                            Ok(_) => (self.emit)(
+                                &format!("Saved the new selector to \"{}\"", target.title.as_deref().unwrap_or("local playbook")),
+                                RunStatus::Running,
+                                None,
+                            ),
+                            Err(e) => eprintln!("[opt-out] {}: Failed to save repaired playbook: {}", self.broker.name, e),
+                        }
+                        return StepOutcome::Done;
+                    }
+                }
+            }
+            let friendly = format_step_error(&e, &step.description);
+            match self.ask_after_failure(step, &friendly).await.as_str() {
+                "retry" => continue,
+                "skip" => return StepOutcome::Done,
+                _ => return StepOutcome::Failed(friendly),
+            }
+        }
+    }
+}
+
+/// Resolve a playbook selection ("best", "local:{id}" or a community playbook ID) for a broker.
+/// Signature verification and step validation are left to the caller.
+pub async fn load_playbook(app: &tauri::AppHandle, broker: &Broker, selection: &str) -> Option<Playbook> {
+    if let Some(local_id) = selection.strip_prefix("local:") {
+        // Local playbook selected — load from local storage
+        match local_playbooks::get_all(app) {
+            Ok(locals) => locals
+                .into_iter()
+                .find(|lp| lp.id == local_id)
+                .map(|lp| Playbook {
+                    id: lp.id,
+                    broker_id: lp.broker_id,
+                    broker_name: lp.broker_name,
+                    title: lp.title,
+                    version: 0,
+                    status: "local".to_string(),
+                    notes: lp.notes,
+                    steps: lp.steps,
+                    signature: None,
+                    upvotes: 0,
+                    downvotes: 0,
+                    success_count: 0,
+                    failure_count: 0,
+                    created_at: lp.created_at,
+                    contributor: None,
+                }),
+            Err(_) => None,
+        }
+    } else if selection == "best" {
+        match playbook_api::fetch_best_playbook(&broker.id).await {
+            Ok(pb) => pb,
+            Err(e) => {
+                eprintln!("[opt-out] {}: Failed to fetch best playbook: {}", broker.name, e);
+                None
+            }
+        }
+    } else {
+        // Specific community playbook ID selected
+        match playbook_api::fetch_playbook_detail(selection).await {
+            Ok(pb) => Some(pb),
+            Err(e) => {
+                eprintln!("[opt-out] {}: Failed to fetch playbook {}: {}", broker.name, selection, e);
+                None
+            }
+        }
+    }
+}
+
+/// Verify (community only) and validate a playbook before it is executed.
+pub fn check_playbook(pb: &Playbook) -> Result<(), String> {
+    if pb.status != "local" {
+        playbook_verification::verify_playbook_signature(pb)
+            .map_err(|e| format!("Playbook rejected: {}", e))?;
+    }
+    playbook_validation::validate_steps(&pb.steps).map_err(|e| format!("Playbook rejected: {}", e))
+}
+
+/// Why a broker has nothing to run; such brokers fall back to filling the form from the page.
+pub const NO_PLAYBOOK: &str = "No playbook available for this broker";
+
+/// How long to give a broker's opt-out page to open before taking Chrome for dead: time to
+/// load, plus however long the broker's pages get to settle.
+fn open_timeout(broker: &Broker) -> tokio::time::Duration {
+    tokio::time::Duration::from_secs(9) + browser::settle_ceiling(&broker.automation)
+}
+
+/// A one-off playbook for a broker with none, worked out from the form on its opt-out page,
+/// and the positions of fills to check with the user first. `None` when the page couldn't be
+/// read or has no form to fill.
+async fn auto_fill_playbook(
+    browser_instance: &browser::Browser,
+    browser_mode: &BrowserMode,
+    broker: &Broker,
+    send_gpc: bool,
+) -> Option<(Playbook, Vec<u32>)> {
+    let page = tokio::time::timeout(
+        open_timeout(broker),
+        browser::open_page(browser_instance, browser_mode, &broker.opt_out_url, send_gpc, &broker.automation),
+    )
+    .await
+    .ok()?
+    .ok()?;
+    let structure = browser::extract_page_structure(&page).await;
+    browser::close_page(page, browser_mode).await;
+    let structure = match structure {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("[opt-out] {}: Could not read the opt-out form: {}", broker.name, e);
+            return None;
+        }
+    };
+    let plan = auto_fill::plan_steps(&structure, &broker.known_fields);
+    if plan.steps.is_empty() || playbook_validation::validate_steps(&plan.steps).is_err() {
+        return None;
+    }
+    Some((auto_fill::playbook(broker, plan.steps), plan.to_confirm))
+}
+
+/// Playbooks fetched at once when prefetching a run.
+const PREFETCH_CONCURRENCY: usize = 6;
+
+/// Load and check the selected playbook of every broker that will run, a few at a time.
+/// Keyed by broker ID; the error is what the run reports for that broker.
+pub async fn prefetch_playbooks(
+    app: &tauri::AppHandle,
+    brokers: &[Broker],
+    playbook_selections: &std::collections::HashMap<String, String>,
+    recently_submitted: &std::collections::HashSet<String>,
+) -> std::collections::HashMap<String, Result<Playbook, String>> {
+    // Collected first: a stream over the filtered iterator makes the run's future non-Send
+    let loads: Vec<_> = brokers
+        .iter()
+        .filter(|b| !recently_submitted.contains(&b.id))
+        .map(|broker| async move {
+            let playbook = match playbook_selections.get(&broker.id) {
+                Some(selection) => load_playbook(app, broker, selection).await,
+                None => None,
+            };
+            let result = playbook
+                .ok_or_else(|| NO_PLAYBOOK.to_string())
+                // Verify signature on community playbooks and validate steps before executing
+                .and_then(|mut pb| {
+                    check_playbook(&pb)?;
+                    // Progress shows step descriptions, and some community playbooks ship terse ones
+                    step_descriptions::fill_terse_descriptions(&mut pb.steps);
+                    Ok(pb)
+                });
+            (broker.id.clone(), result)
+        })
+        .collect();
+    futures::stream::iter(loads)
+        .buffer_unordered(PREFETCH_CONCURRENCY)
+        .collect()
+        .await
+}
+
+fn set_phase(current: &std::sync::Mutex<Option<PhaseProgress>>, phase: Option<PhaseProgress>) {
+    if let Ok(mut current) = current.lock() {
+        *current = phase;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn run_opt_outs(
     app: tauri::AppHandle,
     run_id: String,
     brokers: Vec<Broker>,
     profile: Profile,
-    playbook_selections: std::collections::HashMap<String, String>,
+    mut playbooks: std::collections::HashMap<String, Result<Playbook, String>>,
+    recently_submitted: std::collections::HashSet<String>,
+    options: RunOptions,
     user_action_channel: Arc<Mutex<Option<oneshot::Sender<String>>>>,
     mut cancel_rx: oneshot::Receiver<()>,
 ) {
     use tauri::Emitter;
 
     let total = brokers.len();
+    let run_started = std::time::Instant::now();
+
+    // The playbook phase of the step being run, sent with every update until the next step
+    let current_phase: std::sync::Mutex<Option<PhaseProgress>> = std::sync::Mutex::new(None);
 
     // Launch browser
     let emit_progress = |broker: &Broker, step: &str, completed: usize, status: RunStatus, action: Option<UserActionRequired>, error: Option<String>| {
@@ -162,461 +870,901 @@ pub async fn run_opt_outs(
             brokers_total: total,
             action_required: action,
             error,
+            phase: current_phase.lock().map(|p| *p).unwrap_or(None),
         };
         let _ = app.emit("opt-out-progress", &progress);
     };
 
-    let (browser_instance, mut handler) = match browser::launch().await {
+    let app_settings = settings::load(&app).unwrap_or_default();
+    let browser_mode = options.browser_mode.clone().unwrap_or_else(|| app_settings.browser_mode.clone());
+    let send_gpc = app_settings.send_gpc;
+    let capture_har = app_settings.capture_har;
+    let proxy = browser_proxy::for_run(&app_settings, &options);
+    let mut pacer = Pacer::new(&app_settings);
+    let browser_instance = match browser::launch_with(&app, &browser_mode, proxy.as_ref()).await {
         Ok(b) => b,
         Err(e) => {
             if let Some(broker) = brokers.first() {
-                emit_progress(broker, "Failed to launch Chrome", 0, RunStatus::Failed, None, Some(e));
+                emit_progress(broker, "Failed to launch the browser", 0, RunStatus::Failed, None, Some(e));
             }
-            let _ = app.emit("opt-out-complete", serde_json::json!({
-                "run_id": run_id, "total": total, "succeeded": 0, "failed": total
-            }));
+            let _ = app.emit("opt-out-complete", &RunSummary {
+                run_id: run_id.clone(),
+                total,
+                succeeded: 0,
+                failed: total,
+                skipped: 0,
+                not_found: 0,
+                cancelled: 0,
+                duration_ms: run_started.elapsed().as_millis() as u64,
+                brokers: Vec::new(),
+            });
             return;
         }
     };
 
-    // Spawn handler in background — Handler implements Stream<Item = Result<()>>
-    let handler_handle = tokio::spawn(async move {
-        while let Some(_) = handler.next().await {}
-    });
-
     let mut succeeded = 0usize;
     let mut failed = 0usize;
+    let mut skipped = 0usize;
+    let mut not_found_count = 0usize;
+    let mut cancelled_count = 0usize;
+    // Brokers already covered by a network parent's opt-out earlier in this run
+    let mut covered: std::collections::HashSet<String> = std::collections::HashSet::new();
+    // The cancel signal can only be received once, so remember it
+    let mut cancelled = false;
+    let mut summaries: Vec<BrokerRunSummary> = Vec::with_capacity(total);
+
+    'brokers: for (idx, broker) in brokers.iter().enumerate() {
+        let broker_started = std::time::Instant::now();
+        set_phase(&current_phase, None);
+        // Texted codes older than this belong to some other broker
+        let texts_since = Utc::now();
 
-    for (idx, broker) in brokers.iter().enumerate() {
-        // Check for cancellation
-        if cancel_rx.try_recv().is_ok() {
-            emit_progress(broker, "Cancelled", idx, RunStatus::Failed, None, Some("Run cancelled by user".to_string()));
+        // Check for cancellation. Brokers not started yet are left out of the summary.
+        if cancelled || cancel_rx.try_recv().is_ok() {
+            cancelled = true;
             break;
         }
 
+        if recently_submitted.contains(&broker.id) {
+            skipped += 1;
+            emit_progress(broker, "Recently submitted — skipped", idx + 1, RunStatus::Running, None, None);
+            summaries.push(run_summary::broker_summary(broker, BrokerRunOutcome::Skipped, broker_started));
+            continue;
+        }
+
+        if covered.contains(&broker.id) {
+            succeeded += 1;
+            emit_progress(broker, "Covered by network opt-out", idx + 1, RunStatus::Running, None, None);
+            summaries.push(run_summary::broker_summary(broker, BrokerRunOutcome::Covered, broker_started));
+            continue;
+        }
+
         // Check if Chrome is still alive
-        if handler_handle.is_finished() {
-            let error_msg = "Chrome closed unexpectedly. Please try again.".to_string();
+        if browser_instance.is_closed() {
+            let error_msg = "The browser closed unexpectedly. Please try again.".to_string();
             eprintln!("[opt-out] {}", error_msg);
             emit_progress(broker, &error_msg, idx, RunStatus::Failed, None, Some(error_msg.clone()));
-            for remaining in &brokers[idx..] {
-                save_failed_record(&app, remaining, &run_id, "Chrome closed unexpectedly");
+            for remaining in brokers[idx..].iter().filter(|b| !recently_submitted.contains(&b.id)) {
+                save_failed_record(&app, remaining, &run_id, "The browser closed unexpectedly", None, None, None);
+                summaries.push(BrokerRunSummary {
+                    failure_class: Some(FailureClass::Other),
+                    error: Some("The browser closed unexpectedly".to_string()),
+                    ..run_summary::broker_summary(remaining, BrokerRunOutcome::Failed, broker_started)
+                });
+                failed += 1;
             }
-            failed += brokers.len() - idx;
             break;
         }
 
-        emit_progress(broker, "Navigating to opt-out page...", idx, RunStatus::Running, None, None);
-
-        // Open new page (with timeout — if Chrome died, this hangs forever)
-        let page = match tokio::time::timeout(
-            tokio::time::Duration::from_secs(15),
-            browser_instance.new_page(&broker.opt_out_url),
-        ).await {
-            Ok(Ok(p)) => p,
-            Ok(Err(e)) => {
-                let error_msg = format!("Failed to open page: {}", e);
-                eprintln!("[opt-out] {}: {}", broker.name, error_msg);
-                emit_progress(broker, &error_msg, idx, RunStatus::Running, None, Some(error_msg.clone()));
-                save_failed_record(&app, broker, &run_id, &error_msg);
-                failed += 1;
-                continue;
-            }
-            Err(_) => {
-                let error_msg = "Chrome not responding — it may have closed or crashed.".to_string();
-                eprintln!("[opt-out] {}: {}", broker.name, error_msg);
-                emit_progress(broker, &error_msg, idx, RunStatus::Failed, None, Some(error_msg.clone()));
-                for remaining in &brokers[idx..] {
-                    save_failed_record(&app, remaining, &run_id, "Chrome not responding");
-                }
-                failed += brokers.len() - idx;
+        // Space visits out so a run of related sites doesn't get this IP blocked part way
+        pacer.finish_visit(std::time::Instant::now());
+        let wait = pacer.wait_before(&broker.opt_out_url, std::time::Instant::now(), rand::random::<f64>());
+        if !wait.is_zero() {
+            let msg = format!("Waiting {}s before visiting this broker...", wait.as_secs());
+            emit_progress(broker, &msg, idx, RunStatus::Running, None, None);
+            let sleep = std::pin::pin!(tokio::time::sleep(wait));
+            if let Either::Left(_) = futures::future::select(&mut cancel_rx, sleep).await {
+                cancelled = true;
                 break;
             }
-        };
-
-        // Wait for page load
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-
-        let mut broker_success = false;
+        }
+        pacer.start_visit(&broker.opt_out_url);
+        // Timed from here, so pacing doesn't count towards how hard the broker is
+        let visit_started = std::time::Instant::now();
+        let mut captchas = 0u32;
 
         // --- Playbook path ---
-        let playbook = if let Some(selection) = playbook_selections.get(&broker.id) {
-            if selection.starts_with("local:") {
-                // Local playbook selected — load from local storage
-                let local_id = &selection[6..];
-                match local_playbooks::get_all(&app) {
-                    Ok(locals) => locals
-                        .into_iter()
-                        .find(|lp| lp.id == local_id)
-                        .map(|lp| Playbook {
-                            id: lp.id,
-                            broker_id: lp.broker_id,
-                            broker_name: lp.broker_name,
-                            title: lp.title,
-                            version: 0,
-                            status: "local".to_string(),
-                            notes: lp.notes,
-                            steps: lp.steps,
-                            signature: None,
-                            upvotes: 0,
-                            downvotes: 0,
-                            success_count: 0,
-                            failure_count: 0,
-                            created_at: lp.created_at,
-                        }),
-                    Err(_) => None,
-                }
-            } else if selection == "best" {
-                match playbook_api::fetch_best_playbook(&broker.id).await {
-                    Ok(pb) => pb,
-                    Err(e) => {
-                        eprintln!("[opt-out] {}: Failed to fetch best playbook: {}", broker.name, e);
-                        None
-                    }
-                }
-            } else {
-                // Specific community playbook ID selected
-                match playbook_api::fetch_playbook_detail(selection).await {
-                    Ok(pb) => Some(pb),
-                    Err(e) => {
-                        eprintln!("[opt-out] {}: Failed to fetch playbook {}: {}", broker.name, selection, e);
-                        None
+        // Run the checked playbook, or fill the form from its labels when the broker has none
+        let mut to_confirm: Vec<u32> = Vec::new();
+        let playbook = match playbooks.remove(&broker.id).unwrap_or_else(|| Err(NO_PLAYBOOK.to_string())) {
+            Err(e) if e == NO_PLAYBOOK => {
+                emit_progress(broker, "No playbook — reading the opt-out form...", idx, RunStatus::Running, None, None);
+                match auto_fill_playbook(&browser_instance, &browser_mode, broker, send_gpc).await {
+                    Some((pb, unsure)) => {
+                        to_confirm = unsure;
+                        Ok(pb)
                     }
+                    None => Err(e),
                 }
             }
-        } else {
-            None
+            other => other,
         };
-
-        // Require a playbook for every broker
         let pb = match playbook {
-            Some(pb) => pb,
-            None => {
-                let error_msg = "No playbook available for this broker".to_string();
+            Ok(pb) => pb,
+            Err(error_msg) => {
                 eprintln!("[opt-out] {}: {}", broker.name, error_msg);
                 emit_progress(broker, &error_msg, idx, RunStatus::Running, None, Some(error_msg.clone()));
-                save_failed_record(&app, broker, &run_id, &error_msg);
+                save_failed_record(&app, broker, &run_id, &error_msg, None, None, None);
+                summaries.push(BrokerRunSummary {
+                    failure_class: Some(FailureClass::NoPlaybook),
+                    error: Some(error_msg),
+                    ..run_summary::broker_summary(broker, BrokerRunOutcome::Failed, broker_started)
+                });
                 failed += 1;
                 continue;
             }
         };
 
-        // Verify signature on community playbooks before executing
-        if pb.status != "local" {
-            if let Err(e) = playbook_verification::verify_playbook_signature(&pb) {
-                let error_msg = format!("Playbook rejected: {}", e);
-                eprintln!("[opt-out] {}: {}", broker.name, error_msg);
+        let is_local = pb.status == "local";
+        let is_auto = pb.status == auto_fill::AUTO_STATUS;
+        let is_community = !is_local && !is_auto;
+        let label = if is_local {
+            "local playbook"
+        } else if is_auto {
+            "the form on the page (no playbook)"
+        } else {
+            &format!("community playbook v{}", pb.version)
+        };
+        emit_progress(broker, &format!("Using {}...", label), idx, RunStatus::Running, None, None);
+
+        // Give the broker its own address so confirmations, and any spam it causes, can be traced to it
+        let fills_email = pb.steps.iter().any(|s| s.action == "fill" && s.profile_key.as_deref() == Some("email"));
+        let inbox_url = app_settings.disposable_inbox_url.as_deref().filter(|_| fills_email);
+        let mut inbox_address = None;
+        if let Some(url) = inbox_url {
+            let token = app_settings.disposable_inbox_token.as_deref();
+            let label = format!("optoutta-{}", broker.id);
+            match disposable_inbox::create_address(url, token, &label).await {
+                Ok(address) if email_aliases::record(&app, broker, &run_id, &address) => inbox_address = Some(address),
+                Ok(_) => {}
+                Err(e) => eprintln!("[opt-out] {}: {}", broker.name, e),
+            }
+            // The user set up the inbox so brokers never see their real address
+            if inbox_address.is_none() {
+                let error_msg = "Couldn't get an address from your disposable inbox".to_string();
                 emit_progress(broker, &error_msg, idx, RunStatus::Running, None, Some(error_msg.clone()));
-                save_failed_record(&app, broker, &run_id, &error_msg);
+                save_failed_record(&app, broker, &run_id, &error_msg, None, None, None);
+                summaries.push(BrokerRunSummary {
+                    failure_class: Some(FailureClass::Other),
+                    error: Some(error_msg),
+                    ..run_summary::broker_summary(broker, BrokerRunOutcome::Failed, broker_started)
+                });
                 failed += 1;
                 continue;
             }
         }
+        let email_alias = if inbox_address.is_some() {
+            inbox_address.clone()
+        } else if app_settings.email_aliases && fills_email {
+            email_aliases::assign(&app, broker, &run_id, &profile.email)
+        } else {
+            None
+        };
 
-        // Validate playbook steps before executing
-        if let Err(validation_err) = playbook_validation::validate_steps(&pb.steps) {
-            let error_msg = format!("Playbook rejected: {}", validation_err);
-            eprintln!("[opt-out] {}: {}", broker.name, error_msg);
-            emit_progress(broker, &error_msg, idx, RunStatus::Running, None, Some(error_msg.clone()));
-            save_failed_record(&app, broker, &run_id, &error_msg);
-            failed += 1;
-            continue;
-        }
-
-        let is_local = pb.status == "local";
-        let label = if is_local { "local playbook" } else { &format!("community playbook v{}", pb.version) };
-        emit_progress(broker, &format!("Using {}...", label), idx, RunStatus::Running, None, None);
-
-        let mut playbook_failed = false;
-        let mut failure_step: Option<u32> = None;
-        let mut failure_error: Option<String> = None;
+        // Search-driven playbooks may run once per previous address; each pass is its own submission
+        let passes = run_plan::search_passes(&profile, &pb.steps, &options);
+        let mut broker_success = false;
+        let mut broker_held_back = false;
+        let mut broker_not_found = false;
+        // What the first pass that found no listing searched with
+        let mut not_found_searched_with: Option<String> = None;
+        let submit_position = run_plan::submit_step_position(&pb.steps);
+        let phases = step_phases::phase_starts(&pb.steps);
+        // Retrying from a failed step starts there, or at the loop it failed in
+        let resume_at = options.resume_from_step.map(|failed| run_plan::resume_position(&pb.steps, failed));
+        // How to load this playbook again, for retrying a failed step
+        let selection = if is_local { format!("local:{}", pb.id) } else { pb.id.clone() };
+        // Where approved selector repairs are saved, once there is one
+        let mut repaired: Option<LocalPlaybook> = None;
+        // Step, class and error of the latest failed pass
+        let mut last_failure: Option<(Option<u32>, FailureClass, String)> = None;
 
-        for step in &pb.steps {
-            if cancel_rx.try_recv().is_ok() {
+        for pass in &passes {
+            if cancelled {
                 break;
             }
+            let profile = &pass.profile;
+            let searched_with = pass.searched_with.as_deref();
+            if let Some(searched) = searched_with {
+                emit_progress(broker, &format!("Searching again — {}", searched), idx, RunStatus::Running, None, None);
+            }
+
+            emit_progress(broker, "Navigating to opt-out page...", idx, RunStatus::Running, None, None);
+
+            // Open new page (with timeout — if Chrome died, this hangs forever)
+            let opened = async {
+                if capture_har {
+                    let (page, recorder) = browser::open_recorded_page(
+                        &browser_instance,
+                        &browser_mode,
+                        &broker.opt_out_url,
+                        send_gpc,
+                        &broker.automation,
+                    )
+                    .await?;
+                    Ok::<_, String>((page, Some(recorder)))
+                } else {
+                    browser::open_page(&browser_instance, &browser_mode, &broker.opt_out_url, send_gpc, &broker.automation)
+                        .await
+                        .map(|p| (p, None))
+                }
+            };
+            let (page, network) = match tokio::time::timeout(open_timeout(broker), opened).await {
+                Ok(Ok(opened)) => opened,
+                Ok(Err(error_msg)) => {
+                    eprintln!("[opt-out] {}: {}", broker.name, error_msg);
+                    emit_progress(broker, &error_msg, idx, RunStatus::Running, None, Some(error_msg.clone()));
+                    save_failed_record(&app, broker, &run_id, &error_msg, searched_with, None, None);
+                    last_failure = Some((None, FailureClass::Network, error_msg));
+                    continue;
+                }
+                Err(_) => {
+                    let error_msg = "Chrome not responding — it may have closed or crashed.".to_string();
+                    eprintln!("[opt-out] {}: {}", broker.name, error_msg);
+                    emit_progress(broker, &error_msg, idx, RunStatus::Failed, None, Some(error_msg.clone()));
+                    for remaining in brokers[idx..].iter().filter(|b| !recently_submitted.contains(&b.id)) {
+                        save_failed_record(&app, remaining, &run_id, "Chrome not responding", None, None, None);
+                        summaries.push(BrokerRunSummary {
+                            playbook_id: (remaining.id == broker.id && !is_auto).then(|| pb.id.clone()),
+                            failure_class: Some(FailureClass::Other),
+                            error: Some("Chrome not responding".to_string()),
+                            ..run_summary::broker_summary(remaining, BrokerRunOutcome::Failed, broker_started)
+                        });
+                        failed += 1;
+                    }
+                    break 'brokers;
+                }
+            };
 
-            let form_action = match playbook_step_to_form_action(step) {
-                Some(a) => a,
-                None => continue,
+            let emit_step = |msg: &str, status: RunStatus, action: Option<UserActionRequired>| {
+                emit_progress(broker, msg, idx, status, action, None)
             };
+            let ctx = StepContext {
+                app: &app,
+                page: &page,
+                broker,
+                profile,
+                user_action_channel: &user_action_channel,
+                emit: &emit_step,
+            };
+            let mut playbook_failed = false;
+            let mut failure_step: Option<u32> = None;
+            let mut failure_error: Option<String> = None;
+            let mut failure_reason: Option<FailureReason> = None;
+            let mut failure_class: Option<FailureClass> = None;
+            // Set when the user declines to submit at the review pause
+            let mut held_back = false;
+            // Set when the search came back without the user
+            let mut not_found = false;
 
-            emit_progress(broker, &step.description, idx, RunStatus::Running, None, None);
+            if let Err(e) = clear_challenge(&page, broker, &user_action_channel, emit_step).await {
+                eprintln!("[opt-out] {}: {}", broker.name, e);
+                emit_progress(broker, &e, idx, RunStatus::Running, None, Some(e.clone()));
+                playbook_failed = true;
+                failure_error = Some(e);
+                failure_reason = Some(FailureReason::BlockedByBroker);
+                failure_class = Some(FailureClass::Blocked);
+            }
 
-            match &form_action {
-                FormAction::Captcha { message } => {
-                    let msg = message.as_deref().unwrap_or("Please solve the CAPTCHA.");
-                    emit_progress(
-                        broker, msg, idx, RunStatus::WaitingForUser,
-                        Some(UserActionRequired::SolveCaptcha {
-                            captcha_type: None,
-                            message: msg.to_string(),
-                        }),
-                        None,
-                    );
-                    let (tx, rx) = oneshot::channel::<String>();
-                    {
-                        let mut guard = user_action_channel.lock().await;
-                        *guard = Some(tx);
-                    }
-                    let _ = rx.await;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            // Steps inside a for_each_result loop run once per matching search result.
+            // There is nothing to run when the page never got past a block.
+            let steps: &[PlaybookStep] = if playbook_failed { &[] } else { &pb.steps };
+            let mut cursor = StepCursor::new(steps);
+            let mut results_url: Option<String> = None;
+
+            while let Some(item) = cursor.next() {
+                if cancel_rx.try_recv().is_ok() {
+                    cancelled = true;
+                    break;
                 }
-                FormAction::UserPrompt { message } => {
-                    emit_progress(
-                        broker, message, idx, RunStatus::WaitingForUser,
-                        Some(UserActionRequired::UserPrompt {
-                            message: message.clone(),
-                            description: step.instructions.clone(),
-                        }),
-                        None,
-                    );
-                    let (tx, rx) = oneshot::channel::<String>();
-                    {
-                        let mut guard = user_action_channel.lock().await;
-                        *guard = Some(tx);
+
+                // Loops before the resume point are skipped along with their bodies
+                if let CursorItem::Step(step) = &item {
+                    if step.action == step_cursor::FOR_EACH_ACTION && resume_at.is_some_and(|r| step.position < r) {
+                        continue;
                     }
-                    let _ = rx.await;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
                 }
-                FormAction::ManualFill { selector, message } => {
-                    // Scroll to and highlight the field in the browser
-                    let mut highlight_ok = false;
-                    match browser::highlight_element(&page, selector).await {
-                        Ok(_) => { highlight_ok = true; }
-                        Err(e) => {
-                            if step.optional {
-                                continue;
+
+                let (step, form_action) = match item {
+                    CursorItem::Step(step) if step.action == step_cursor::FOR_EACH_ACTION => {
+                        let selector = step.selector.as_deref().unwrap_or_default();
+                        let key = step.profile_key.as_deref().unwrap_or_default();
+                        let counted = browser::count_profile_matches(&page, selector, profile, key).await;
+                        let count = match &counted {
+                            Ok(n) => *n,
+                            Err(e) => {
+                                eprintln!("[opt-out] {}: {}", broker.name, e);
+                                0
                             }
-                            // Ask user to retry/skip/abort
-                            let friendly = format_step_error(&e, &step.description);
-                            loop {
-                                emit_progress(
-                                    broker, &friendly, idx, RunStatus::WaitingForUser,
-                                    Some(UserActionRequired::StepFailed {
-                                        message: friendly.clone(),
-                                        step_description: step.description.clone(),
-                                        step_position: step.position,
-                                        broker_name: broker.name.clone(),
-                                    }),
-                                    None,
-                                );
-                                let (tx, rx) = oneshot::channel::<String>();
-                                {
-                                    let mut guard = user_action_channel.lock().await;
-                                    *guard = Some(tx);
-                                }
-                                let decision = rx.await.unwrap_or_else(|_| "abort".to_string());
-                                match decision.as_str() {
-                                    "retry" => {
-                                        match browser::highlight_element(&page, selector).await {
-                                            Ok(_) => { highlight_ok = true; break; }
-                                            Err(_) => continue,
-                                        }
-                                    }
-                                    "skip" => break,
-                                    _ => {
-                                        playbook_failed = true;
-                                        failure_step = Some(step.position);
-                                        failure_error = Some(friendly.clone());
-                                        break;
-                                    }
+                        };
+                        results_url = page.url().await.ok().flatten();
+                        set_phase(&current_phase, step_phases::progress_at(&phases, step.position));
+                        emit_progress(
+                            broker,
+                            &format!("{} ({} matching listing{})", step.description, count, if count == 1 { "" } else { "s" }),
+                            idx, RunStatus::Running, None, None,
+                        );
+                        // A search that ran and matched nobody leaves nothing to remove
+                        if counted == Ok(0) {
+                            not_found = true;
+                            break;
+                        }
+                        cursor.enter_loop(count);
+                        continue;
+                    }
+                    CursorItem::Step(step) => match step_to_form_action_on(&page, step, pb.status == "local").await {
+                        Some(a) => {
+                            let a = apply_email_alias(apply_field_format(a, broker), email_alias.as_deref());
+                            let a = apply_legal_basis(a, broker, profile);
+                            (step, apply_credentials(&app, a, broker, profile))
+                        }
+                        None => continue,
+                    },
+                    CursorItem::LoopIteration { header, index } => {
+                        // Later results need the results page back before they can be opened
+                        if index > 0 {
+                            if let Some(url) = &results_url {
+                                if let Err(e) = browser::navigate(&page, url, &broker.automation).await {
+                                    eprintln!("[opt-out] {}: Failed to return to results: {}", broker.name, e);
                                 }
                             }
-                            if playbook_failed { break; }
-                            if !highlight_ok { continue; }
                         }
+                        let action = FormAction::ClickMatch {
+                            selector: header.selector.clone().unwrap_or_default(),
+                            profile_key: header.profile_key.clone().unwrap_or_default(),
+                            index,
+                        };
+                        (header, action)
                     }
-                    if !highlight_ok { continue; }
-                    emit_progress(
-                        broker, message, idx, RunStatus::WaitingForUser,
-                        Some(UserActionRequired::UserPrompt {
-                            message: format!("Please fill out this field in the browser: {}", message),
-                            description: None,
-                        }),
-                        None,
-                    );
-                    let (tx, rx) = oneshot::channel::<String>();
-                    {
-                        let mut guard = user_action_channel.lock().await;
-                        *guard = Some(tx);
+                };
+
+                // Before the resume point, only replay what brings the reopened page back
+                if resume_at.is_some_and(|r| step.position < r) && !run_plan::replays_on_resume(&form_action) {
+                    continue;
+                }
+
+                // Cookie banners can show up a moment after the page does, so look before every step
+                if let Some(platform) = browser::dismiss_consent_banner(&page).await {
+                    eprintln!("[opt-out] {}: Closed the {} cookie banner", broker.name, platform);
+                }
+                // A step that closes a cookie banner is done already, or had no banner to close
+                if let FormAction::Click { selector } = &form_action {
+                    if consent_banners::is_banner_button(selector) && !browser::is_showing(&page, selector).await {
+                        continue;
                     }
-                    let _ = rx.await;
-                    // Remove the highlight after user confirms
-                    let _ = browser::remove_highlight(&page, selector).await;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
                 }
-                FormAction::ManualSelect { selector, message } => {
-                    // Scroll to and highlight the dropdown in the browser
-                    let mut highlight_ok = false;
-                    match browser::highlight_element(&page, selector).await {
-                        Ok(_) => { highlight_ok = true; }
-                        Err(e) => {
-                            if step.optional {
-                                continue;
-                            }
-                            let friendly = format_step_error(&e, &step.description);
-                            loop {
-                                emit_progress(
-                                    broker, &friendly, idx, RunStatus::WaitingForUser,
-                                    Some(UserActionRequired::StepFailed {
-                                        message: friendly.clone(),
-                                        step_description: step.description.clone(),
-                                        step_position: step.position,
-                                        broker_name: broker.name.clone(),
-                                    }),
-                                    None,
-                                );
-                                let (tx, rx) = oneshot::channel::<String>();
-                                {
-                                    let mut guard = user_action_channel.lock().await;
-                                    *guard = Some(tx);
+
+                set_phase(&current_phase, step_phases::progress_at(&phases, step.position));
+                emit_progress(broker, &step.description, idx, RunStatus::Running, None, None);
+
+                // A texted code comes from the user's SMS relay when one is set up, otherwise from the user
+                let form_action = match form_action {
+                    FormAction::FillSmsCode { selector } => {
+                        let mut code = None;
+                        if let Some(url) = app_settings.sms_relay_url.as_deref() {
+                            emit_progress(
+                                broker, &format!("Waiting for the text from {}", broker.name),
+                                idx, RunStatus::Running, None, None,
+                            );
+                            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(sms_relay::WAIT_SECS);
+                            while code.is_none() && std::time::Instant::now() < deadline {
+                                if cancel_rx.try_recv().is_ok() {
+                                    cancelled = true;
+                                    break;
                                 }
-                                let decision = rx.await.unwrap_or_else(|_| "abort".to_string());
-                                match decision.as_str() {
-                                    "retry" => {
-                                        match browser::highlight_element(&page, selector).await {
-                                            Ok(_) => { highlight_ok = true; break; }
-                                            Err(_) => continue,
-                                        }
-                                    }
-                                    "skip" => break,
-                                    _ => {
-                                        playbook_failed = true;
-                                        failure_step = Some(step.position);
-                                        failure_error = Some(friendly.clone());
+                                match sms_relay::fetch_messages(url, app_settings.sms_relay_token.as_deref(), texts_since).await {
+                                    Ok(messages) => code = sms_relay::pick_code(&messages, &broker.name, texts_since),
+                                    Err(e) => {
+                                        eprintln!("[opt-out] {}: {}", broker.name, e);
                                         break;
                                     }
                                 }
+                                if code.is_none() {
+                                    tokio::time::sleep(tokio::time::Duration::from_secs(sms_relay::POLL_SECS)).await;
+                                }
+                            }
+                        }
+                        match code {
+                            Some(code) => FormAction::Fill { selector, profile_key: None, value: Some(code), transform: None },
+                            None => FormAction::ManualFill {
+                                selector,
+                                message: format!("Enter the code {} texted you", broker.name),
+                            },
+                        }
+                    }
+                    other => other,
+                };
+                if cancelled {
+                    break;
+                }
+
+                // When several results could be the user's, let them pick rather than guess
+                if let FormAction::FindAndClick { selector, profile_key } = &form_action {
+                    let found = browser::find_listing(&page, selector, profile, profile_key).await;
+                    // A page saying nobody matched, or results that all read like someone else's
+                    // listing, mean the user isn't listed. Anything else could be a stale
+                    // selector, so it fails the step as before.
+                    if let Ok((ListingMatch::NoMatch, texts)) = &found {
+                        let says_none = browser::page_url_and_text(&page)
+                            .await
+                            .is_ok_and(|(_, text)| done_detection::no_results_phrase(&text).is_some());
+                        if says_none || listing_match::looks_like_results(texts) {
+                            not_found = true;
+                            break;
+                        }
+                    }
+                    if let Ok((ListingMatch::Ambiguous(indexes), texts)) = found {
+                        match ctx.choose_listing(&indexes, &texts).await {
+                            ListingChoice::Open(index) => {
+                                if let Err(e) = browser::click_listing(&page, selector, index).await {
+                                    playbook_failed = true;
+                                    failure_step = Some(step.position);
+                                    failure_error = Some(format_step_error(&e, &step.description));
+                                    break;
+                                }
                             }
-                            if playbook_failed { break; }
-                            if !highlight_ok { continue; }
+                            // The user opened their listing in the browser themselves
+                            ListingChoice::OpenedByUser => {}
+                            ListingChoice::NotListed => {
+                                not_found = true;
+                                break;
+                            }
+                            ListingChoice::HeldBack => {
+                                held_back = true;
+                                break;
+                            }
+                        }
+                        if step.wait_after_ms > 0 {
+                            tokio::time::sleep(tokio::time::Duration::from_millis(step.wait_after_ms as u64)).await;
                         }
+                        continue;
                     }
-                    if !highlight_ok { continue; }
+                }
+
+                // Draft mode: show the filled form and wait for the go-ahead before submitting
+                if options.review_before_submit && Some(step.position) == submit_position {
+                    let _ = browser::bring_to_front(&page).await;
+                    let msg = "Check the filled-in form in the browser before it is submitted.";
                     emit_progress(
-                        broker, message, idx, RunStatus::WaitingForUser,
-                        Some(UserActionRequired::UserPrompt {
-                            message: format!("Please fill out this field in the browser: {}", message),
-                            description: None,
+                        broker, msg, idx, RunStatus::WaitingForUser,
+                        Some(UserActionRequired::ReviewBeforeSubmit {
+                            message: msg.to_string(),
+                            step_description: step.description.clone(),
                         }),
                         None,
                     );
-                    let (tx, rx) = oneshot::channel::<String>();
-                    {
-                        let mut guard = user_action_channel.lock().await;
-                        *guard = Some(tx);
+                    if await_decision(&user_action_channel, "skip").await != "continue" {
+                        held_back = true;
+                        break;
                     }
-                    let _ = rx.await;
-                    // Remove the highlight after user confirms
-                    let _ = browser::remove_highlight(&page, selector).await;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    emit_progress(broker, "Submitting...", idx, RunStatus::Running, None, None);
                 }
-                other => {
-                    loop {
-                        match browser::execute_action(&page, other, &profile).await {
-                            Ok(_) => break,
-                            Err(e) => {
-                                if step.optional {
-                                    break;
-                                }
-                                let friendly = format_step_error(&e, &step.description);
-                                emit_progress(
-                                    broker, &friendly, idx, RunStatus::WaitingForUser,
-                                    Some(UserActionRequired::StepFailed {
-                                        message: friendly.clone(),
-                                        step_description: step.description.clone(),
-                                        step_position: step.position,
-                                        broker_name: broker.name.clone(),
-                                    }),
-                                    None,
-                                );
-                                let (tx, rx) = oneshot::channel::<String>();
-                                {
-                                    let mut guard = user_action_channel.lock().await;
-                                    *guard = Some(tx);
-                                }
-                                let decision = rx.await.unwrap_or_else(|_| "abort".to_string());
-                                match decision.as_str() {
-                                    "retry" => continue,
-                                    "skip" => break,
-                                    _ => {
-                                        playbook_failed = true;
-                                        failure_step = Some(step.position);
-                                        failure_error = Some(friendly);
-                                        break;
-                                    }
-                                }
-                            }
+
+                // Auto-fill guessed this field's profile value, so check before typing it in
+                let form_action = match form_action {
+                    FormAction::Fill { selector, profile_key: Some(key), value, transform } if to_confirm.contains(&step.position) => {
+                        let _ = browser::bring_to_front(&page).await;
+                        let _ = browser::highlight_element(&page, &selector).await;
+                        let msg = format!(
+                            "Is the highlighted field asking for {}?",
+                            step_descriptions::profile_field_phrase(&key)
+                        );
+                        emit_progress(
+                            broker, &msg, idx, RunStatus::WaitingForUser,
+                            Some(UserActionRequired::ConfirmFieldMatch {
+                                message: msg.clone(),
+                                step_description: step.description.clone(),
+                                profile_key: key.clone(),
+                            }),
+                            None,
+                        );
+                        let confirmed = await_decision(&user_action_channel, "skip").await == "continue";
+                        let _ = browser::remove_highlight(&page, &selector).await;
+                        if confirmed {
+                            FormAction::Fill { selector, profile_key: Some(key), value, transform }
+                        } else {
+                            // Their call what goes in it
+                            FormAction::ManualFill { selector, message: "Fill in the highlighted field".to_string() }
+                        }
+                    }
+                    other => other,
+                };
+
+                // A field people can't see is there to catch bots, and filling it gets the request dropped
+                if let FormAction::Fill { selector, .. } = &form_action {
+                    if browser::is_honeypot(&page, selector).await {
+                        eprintln!("[opt-out] {}: Step {} fills a hidden trap field ({}), skipped", broker.name, step.position, selector);
+                        emit_progress(
+                            broker, &format!("Skipped a hidden field meant for bots: {}", step.description),
+                            idx, RunStatus::Running, None, None,
+                        );
+                        continue;
+                    }
+                }
+
+                // Approval mode: every fill waits for the user's go-ahead, unless it was just confirmed
+                if options.approve_each_fill && !to_confirm.contains(&step.position) {
+                    let fills = match &form_action {
+                        FormAction::Fill { selector, profile_key: Some(key), .. }
+                        | FormAction::Select { selector, profile_key: Some(key), .. }
+                        | FormAction::ChooseRadio { selector, profile_key: Some(key), .. } if step.profile_key.as_ref() == Some(key) => {
+                            Some((selector, step_descriptions::profile_field_phrase(key)))
+                        }
+                        FormAction::Fill { selector, .. } if step.action == "fill_sms_code" => {
+                            Some((selector, "the texted code".to_string()))
+                        }
+                        FormAction::Fill { selector, .. } | FormAction::Select { selector, .. } | FormAction::ChooseRadio { selector, .. } => {
+                            Some((selector, "a value set by the playbook".to_string()))
                         }
+                        _ => None,
+                    };
+                    if let Some((selector, fills_with)) = fills {
+                        let _ = browser::bring_to_front(&page).await;
+                        let _ = browser::highlight_element(&page, selector).await;
+                        let msg = format!("Fill the highlighted field with {}?", fills_with);
+                        emit_progress(
+                            broker, &msg, idx, RunStatus::WaitingForUser,
+                            Some(UserActionRequired::ApproveFill {
+                                message: msg.clone(),
+                                step_description: step.description.clone(),
+                                fills_with,
+                            }),
+                            None,
+                        );
+                        let approved = await_decision(&user_action_channel, "skip").await == "continue";
+                        let _ = browser::remove_highlight(&page, selector).await;
+                        if !approved {
+                            emit_progress(broker, "Skipped — left for you to fill", idx, RunStatus::Running, None, None);
+                            continue;
+                        }
+                    }
+                }
+
+                let outcome = match &form_action {
+                    FormAction::Captcha { message } => {
+                        captchas += 1;
+                        ctx.solve_captcha(message.as_deref()).await;
+                        StepOutcome::Done
+                    }
+                    FormAction::UserPrompt { message } => {
+                        ctx.user_prompt(step, message).await;
+                        StepOutcome::Done
+                    }
+                    FormAction::ManualFill { selector, message } => ctx.manual_fill(step, selector, message).await,
+                    FormAction::ManualSelect { selector, message } => ctx.manual_select(step, selector, message).await,
+                    // Auto-filled steps use selectors read from this very page
+                    other => ctx.run_action(&app_settings, step, other, &pb, is_auto, &mut repaired).await,
+                };
+                match outcome {
+                    StepOutcome::Done => {}
+                    StepOutcome::Skipped => continue,
+                    StepOutcome::Failed(error) => {
+                        playbook_failed = true;
+                        failure_step = Some(step.position);
+                        failure_error = Some(error);
+                        break;
                     }
-                    if playbook_failed { break; }
+                }
+
+                // Clicks and navigation can land on a challenge page
+                if matches!(
+                    form_action,
+                    FormAction::Navigate { .. } | FormAction::Click { .. } | FormAction::FindAndClick { .. } | FormAction::ClickMatch { .. }
+                ) {
+                    if let Err(e) = clear_challenge(&page, broker, &user_action_channel, emit_step).await {
+                        playbook_failed = true;
+                        failure_step = Some(step.position);
+                        failure_error = Some(e);
+                        failure_reason = Some(FailureReason::BlockedByBroker);
+                        failure_class = Some(FailureClass::Blocked);
+                        break;
+                    }
+                }
+
+                // Wait after step
+                if step.wait_after_ms > 0 {
+                    tokio::time::sleep(tokio::time::Duration::from_millis(step.wait_after_ms as u64)).await;
                 }
             }
 
-            // Wait after step
-            if step.wait_after_ms > 0 {
-                tokio::time::sleep(tokio::time::Duration::from_millis(step.wait_after_ms as u64)).await;
+            // A cancel that dropped a prompt ends the step loop like a skip or abort would
+            if !cancelled && cancel_rx.try_recv().is_ok() {
+                cancelled = true;
+            }
+            // Nothing was filed in this pass, so it leaves no record behind
+            if cancelled {
+                browser::close_page(page, &browser_mode).await;
+                break;
             }
-        }
 
-        // Report outcome to API for community playbooks (fire and forget)
-        if !is_local {
-            let playbook_id = pb.id.clone();
-            let outcome_str = if playbook_failed { "failure" } else { "success" }.to_string();
-            let report = PlaybookReport {
-                device_id: playbook_api::get_device_id(),
-                outcome: outcome_str,
-                failure_step,
-                error_message: failure_error.clone(),
-                app_version: env!("CARGO_PKG_VERSION").to_string(),
-            };
-            tokio::spawn(async move {
-                let _ = playbook_api::report_outcome(&playbook_id, &report).await;
-            });
-        }
+            // Nothing was filed, so there is no record or playbook outcome to report
+            if held_back {
+                emit_progress(broker, "Not submitted — held back at review", idx, RunStatus::Running, None, None);
+                broker_held_back = true;
+                browser::close_page(page, &browser_mode).await;
+                continue;
+            }
+
+            // Nothing to opt out of, which isn't a success or a failure of the playbook
+            if not_found {
+                emit_progress(broker, "No listing found", idx, RunStatus::Running, None, None);
+                if !broker_not_found {
+                    not_found_searched_with = searched_with.map(String::from);
+                }
+                broker_not_found = true;
+                browser::close_page(page, &browser_mode).await;
+                continue;
+            }
+
+            // Playbooks rarely assert their own success, so read the final page for a
+            // confirmation or an error message
+            let mut outcome_check: Option<OutcomeCheck> = None;
+            if !playbook_failed && !cancelled {
+                if let Ok((url, text)) = browser::page_url_and_text(&page).await {
+                    let check = done_detection::assess(&url, &text);
+                    if check.outcome == PageOutcome::ErrorShown {
+                        playbook_failed = true;
+                        failure_class = Some(FailureClass::Rejected);
+                        failure_error = Some(format!(
+                            "The page showed an error after the last step (\"{}\").",
+                            check.matched.as_deref().unwrap_or_default()
+                        ));
+                    }
+                    outcome_check = Some(check);
+                }
+            }
+
+            // A step that failed on a block page is down to the broker, not the playbook
+            if playbook_failed && failure_reason.is_none() {
+                let blocked = browser::detect_challenge(&page).await.is_some();
+                failure_reason = Some(if blocked { FailureReason::BlockedByBroker } else { FailureReason::StepFailed });
+                if failure_class.is_none() {
+                    let captcha_unsolved = browser::captcha_state(&page)
+                        .await
+                        .is_ok_and(|state| state.present && !state.solved);
+                    failure_class = Some(run_summary::classify_step_failure(
+                        failure_error.as_deref().unwrap_or_default(),
+                        blocked,
+                        captcha_unsolved,
+                    ));
+                }
+            }
+
+            // Report outcome to API for community playbooks (fire and forget)
+            if is_community {
+                let playbook_id = pb.id.clone();
+                let outcome_str = if playbook_failed { "failure" } else { "success" }.to_string();
+                let report = PlaybookReport {
+                    device_id: playbook_api::get_device_id(),
+                    outcome: outcome_str,
+                    failure_step,
+                    // Step errors can quote selectors or page text that name the user
+                    error_message: failure_error.as_deref().map(|e| pii_scan::redact(e, Some(profile))),
+                    failure_reason,
+                    app_version: env!("CARGO_PKG_VERSION").to_string(),
+                };
+                tokio::spawn(async move {
+                    let _ = playbook_api::report_outcome(&playbook_id, &report).await;
+                });
+            }
+
+            if !is_auto {
+                if let Err(e) = playbook_reliability::record(&app, &pb.id, !playbook_failed) {
+                    eprintln!("[opt-out] {}: Failed to record playbook reliability: {}", broker.name, e);
+                }
+            }
+
+            // Save record
+            if !playbook_failed {
+                let record_id = save_success_record(&app, broker, &run_id, searched_with, outcome_check, send_gpc);
+                capture_confirmation_evidence(&app, &page, &record_id).await;
+                broker_success = true;
+                // The confirmation email went to the disposable inbox, where the user won't see it
+                if let (Some(url), Some(address)) = (inbox_url, inbox_address.as_deref()) {
+                    if broker.requires_verification.is_some() && !cancelled {
+                        emit_progress(
+                            broker, &format!("Waiting for {}'s confirmation email", broker.name),
+                            idx, RunStatus::Running, None, None,
+                        );
+                        let token = app_settings.disposable_inbox_token.as_deref();
+                        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(disposable_inbox::WAIT_SECS);
+                        let mut found = None;
+                        while found.is_none() && std::time::Instant::now() < deadline {
+                            if cancel_rx.try_recv().is_ok() {
+                                cancelled = true;
+                                break;
+                            }
+                            match disposable_inbox::fetch_messages(url, token, address, texts_since).await {
+                                Ok(messages) => found = disposable_inbox::pick_link(&messages, broker, texts_since),
+                                Err(e) => {
+                                    eprintln!("[opt-out] {}: {}", broker.name, e);
+                                    break;
+                                }
+                            }
+                            if found.is_none() {
+                                tokio::time::sleep(tokio::time::Duration::from_secs(disposable_inbox::POLL_SECS)).await;
+                            }
+                        }
+                        match found {
+                            Some((message, link)) => {
+                                follow_confirmation_link(&app, &page, broker, &record_id, &message.raw, &link).await;
+                            }
+                            None => emit_progress(
+                                broker, &format!("No confirmation email from {} yet. Check your inbox provider.", broker.name),
+                                idx, RunStatus::Running, None, None,
+                            ),
+                        }
+                    }
+                }
+                // A signed, timestamped hash of the evidence, in case the broker later disputes the
+                // request. Made last so it covers the page the confirmation link opened too.
+                if let Err(e) = submission_receipts::create(&app, &record_id, app_settings.timestamp_authority_url.as_deref()).await {
+                    eprintln!("[opt-out] {}: Failed to make a receipt: {}", broker.name, e);
+                }
+            } else {
+                let err = failure_error.unwrap_or_else(|| "Playbook execution failed".to_string());
+                // An auto-filled form can't be loaded again to resume, so it starts over
+                let failed_at = failure_step
+                    .filter(|_| !is_auto)
+                    .map(|position| FailedStep { playbook: selection.clone(), position });
+                let record_id = save_failed_record(&app, broker, &run_id, &err, searched_with, failure_reason, failed_at);
+                if let Some(har) = network.as_ref().and_then(|n| n.har()) {
+                    if let Err(e) = evidence::store(&app, &record_id, EvidenceKind::Har, &har) {
+                        eprintln!("[opt-out] Failed to store network log: {}", e);
+                    }
+                }
+                last_failure = Some((failure_step, failure_class.unwrap_or(FailureClass::Other), err));
+            }
 
-        if !playbook_failed {
-            broker_success = true;
+            // Close the page
+            browser::close_page(page, &browser_mode).await;
         }
 
-        // Save record
-        if broker_success {
-            save_success_record(&app, broker, &run_id);
+        let outcome = if broker_success {
+            if broker.network_parent {
+                covered.extend(save_covered_records(&app, broker, &run_id));
+            }
             succeeded += 1;
             emit_progress(broker, "Opt-out submitted", idx + 1, RunStatus::Running, None, None);
+            BrokerRunOutcome::Succeeded
+        } else if cancelled {
+            cancelled_count += 1;
+            emit_progress(broker, "Cancelled", idx, RunStatus::Cancelled, None, None);
+            BrokerRunOutcome::Cancelled
+        } else if broker_not_found && last_failure.is_none() {
+            // Recorded once for the broker, so a pass that found nothing can't hide a submission
+            save_not_found_record(&app, broker, &run_id, not_found_searched_with.as_deref());
+            not_found_count += 1;
+            BrokerRunOutcome::NotFound
+        } else if broker_held_back && last_failure.is_none() {
+            skipped += 1;
+            BrokerRunOutcome::Skipped
         } else {
-            let err = failure_error.unwrap_or_else(|| "Playbook execution failed".to_string());
-            save_failed_record(&app, broker, &run_id, &err);
             failed += 1;
+            BrokerRunOutcome::Failed
+        };
+        if matches!(outcome, BrokerRunOutcome::Succeeded | BrokerRunOutcome::Failed) {
+            let secs = visit_started.elapsed().as_secs();
+            if let Err(e) = broker_difficulty::record(&app, &broker.id, broker_success, secs, captchas) {
+                eprintln!("[opt-out] {}: Failed to record broker outcome: {}", broker.name, e);
+            }
         }
-
-        // Close the page
-        let _ = page.close().await;
+        let (failure_step, failure_class, error) = match last_failure {
+            Some((step, class, error)) if outcome == BrokerRunOutcome::Failed => (step, Some(class), Some(error)),
+            _ => (None, None, None),
+        };
+        summaries.push(BrokerRunSummary {
+            playbook_id: (!is_auto).then(|| pb.id.clone()),
+            playbook_version: is_community.then_some(pb.version),
+            failure_step,
+            failure_phase: failure_step.and_then(|p| step_phases::progress_at(&phases, p)).map(|p| p.phase),
+            failure_class,
+            error,
+            ..run_summary::broker_summary(broker, outcome, broker_started)
+        });
     }
 
-    // Emit completion
-    let _ = app.emit("opt-out-complete", serde_json::json!({
-        "run_id": run_id,
-        "total": total,
-        "succeeded": succeeded,
-        "failed": failed
-    }));
+    // Emit completion, or a cancellation so the app doesn't report it as a finished run
+    let summary = RunSummary {
+        run_id: run_id.clone(),
+        total,
+        succeeded,
+        failed,
+        skipped,
+        not_found: not_found_count,
+        cancelled: cancelled_count,
+        duration_ms: run_started.elapsed().as_millis() as u64,
+        brokers: summaries,
+    };
+    let event = if cancelled { "opt-out-cancelled" } else { "opt-out-complete" };
+    let _ = app.emit(event, &summary);
 
+    if cancelled {
+        // The user asked to stop, so don't leave the automation browser behind
+        browser::close_browser(&app, browser_instance, &browser_mode).await;
+        return;
+    }
     // Leave Chrome open so the user can inspect pages, verify results, or debug.
     // The next run's cleanup_previous_chrome() will handle the stale process.
     std::mem::forget(browser_instance);
-    // Handler task will end naturally when Chrome is closed by the user.
-    drop(handler_handle);
 }
 
-fn save_success_record(app: &tauri::AppHandle, broker: &Broker, run_id: &str) {
+/// Keep a screenshot and an MHTML archive of the confirmation page. Screenshots miss
+/// confirmation text below the fold, so the archive is the more complete record.
+async fn capture_confirmation_evidence(app: &tauri::AppHandle, page: &Page, record_id: &str) {
+    match browser::capture_screenshot(page).await {
+        Ok(bytes) => {
+            if let Err(e) = evidence::store(app, record_id, EvidenceKind::Screenshot, &bytes) {
+                eprintln!("[opt-out] Failed to store screenshot: {}", e);
+            }
+        }
+        Err(e) => eprintln!("[opt-out] {}", e),
+    }
+    match browser::capture_mhtml(page).await {
+        Ok(bytes) => {
+            if let Err(e) = evidence::store(app, record_id, EvidenceKind::Mhtml, &bytes) {
+                eprintln!("[opt-out] Failed to store MHTML snapshot: {}", e);
+            }
+        }
+        Err(e) => eprintln!("[opt-out] {}", e),
+    }
+}
+
+/// Attach a broker's confirmation email to its submission and open the link in it. The
+/// request counts as verified unless the page it opens shows an error.
+async fn follow_confirmation_link(app: &tauri::AppHandle, page: &Page, broker: &Broker, record_id: &str, raw: &str, link: &str) {
+    if let Err(e) = broker_responses::import(app, std::slice::from_ref(broker), "disposable inbox", raw) {
+        eprintln!("[opt-out] {}: Failed to keep the confirmation email: {}", broker.name, e);
+    }
+    if let Err(e) = browser::navigate(page, link, &broker.automation).await {
+        eprintln!("[opt-out] {}: Failed to open the confirmation link: {}", broker.name, e);
+        return;
+    }
+    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    let rejected = browser::page_url_and_text(page)
+        .await
+        .is_ok_and(|(url, text)| done_detection::assess(&url, &text).outcome == PageOutcome::ErrorShown);
+    if rejected {
+        eprintln!("[opt-out] {}: The confirmation link opened an error page", broker.name);
+        return;
+    }
+    let now = Utc::now();
+    let verified = history::update_records(app, &[record_id.to_string()], |r| {
+        if r.status == BrokerSubmissionStatus::PendingVerification {
+            history::set_status(r, BrokerSubmissionStatus::Submitted, now);
+        }
+    });
+    if let Err(e) = verified {
+        eprintln!("[opt-out] {}: Failed to update the submission: {}", broker.name, e);
+    }
+    capture_confirmation_evidence(app, page, record_id).await;
+}
+
+/// Save a successful submission and return its record ID.
+fn save_success_record(
+    app: &tauri::AppHandle,
+    broker: &Broker,
+    run_id: &str,
+    searched_with: Option<&str>,
+    outcome_check: Option<OutcomeCheck>,
+    gpc_sent: bool,
+) -> String {
     let status = if broker.requires_verification.is_some() {
         BrokerSubmissionStatus::PendingVerification
     } else {
         BrokerSubmissionStatus::Submitted
     };
-    let next_check = broker.relist_days.map(|days| Utc::now() + Duration::days(days as i64));
+    let next_check = history::next_check_date(broker, Utc::now());
     let record = SubmissionRecord {
         id: uuid::Uuid::new_v4().to_string(),
         broker_id: broker.id.clone(),
@@ -626,11 +1774,101 @@ fn save_success_record(app: &tauri::AppHandle, broker: &Broker, run_id: &str) {
         next_check_date: next_check,
         error_message: None,
         run_id: run_id.to_string(),
+        method: SubmissionMethod::Automated,
+        last_removal_check: None,
+        covered_by: None,
+        searched_with: searched_with.map(String::from),
+        failure_reason: None,
+        outcome_check,
+        gpc_sent,
+        request_type: RequestType::Deletion,
+        failed_step: None,
+    };
+    let id = record.id.clone();
+    let _ = history::upsert_record(app, record);
+    id
+}
+
+/// Record a search that found no listing. It's checked again after the broker's relist
+/// period, since brokers add people as their data refreshes.
+fn save_not_found_record(app: &tauri::AppHandle, broker: &Broker, run_id: &str, searched_with: Option<&str>) {
+    let record = SubmissionRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        broker_id: broker.id.clone(),
+        status: BrokerSubmissionStatus::NotFound,
+        submitted_at: Utc::now(),
+        confirmed_at: None,
+        next_check_date: history::next_check_date(broker, Utc::now()),
+        error_message: None,
+        run_id: run_id.to_string(),
+        method: SubmissionMethod::Automated,
+        last_removal_check: None,
+        covered_by: None,
+        searched_with: searched_with.map(String::from),
+        failure_reason: None,
+        outcome_check: None,
+        gpc_sent: false,
+        request_type: RequestType::Deletion,
+        failed_step: None,
     };
     let _ = history::upsert_record(app, record);
 }
 
-fn save_failed_record(app: &tauri::AppHandle, broker: &Broker, run_id: &str, error: &str) {
+/// Record every other broker in the parent's network as submitted via the parent's
+/// opt-out. Returns the IDs of the covered brokers.
+fn save_covered_records(app: &tauri::AppHandle, parent: &Broker, run_id: &str) -> Vec<String> {
+    let Some(network) = parent.network.as_deref() else {
+        return Vec::new();
+    };
+    let registry = match crate::commands::brokers::get_brokers(app.clone()) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("[opt-out] Failed to load registry for network {}: {}", network, e);
+            return Vec::new();
+        }
+    };
+
+    let mut covered = Vec::new();
+    for sibling in registry
+        .brokers
+        .iter()
+        .filter(|b| b.id != parent.id && b.network.as_deref() == Some(network))
+    {
+        let record = SubmissionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            broker_id: sibling.id.clone(),
+            status: BrokerSubmissionStatus::Submitted,
+            submitted_at: Utc::now(),
+            confirmed_at: None,
+            next_check_date: history::next_check_date(sibling, Utc::now()),
+            error_message: None,
+            run_id: run_id.to_string(),
+            method: SubmissionMethod::Automated,
+            last_removal_check: None,
+            covered_by: Some(parent.id.clone()),
+            searched_with: None,
+            failure_reason: None,
+            outcome_check: None,
+            gpc_sent: false,
+            request_type: RequestType::Deletion,
+            failed_step: None,
+        };
+        let _ = history::upsert_record(app, record);
+        covered.push(sibling.id.clone());
+    }
+    covered
+}
+
+/// Save a failed attempt and return its record ID.
+fn save_failed_record(
+    app: &tauri::AppHandle,
+    broker: &Broker,
+    run_id: &str,
+    error: &str,
+    searched_with: Option<&str>,
+    failure_reason: Option<FailureReason>,
+    failed_step: Option<FailedStep>,
+) -> String {
     let record = SubmissionRecord {
         id: uuid::Uuid::new_v4().to_string(),
         broker_id: broker.id.clone(),
@@ -640,6 +1878,17 @@ fn save_failed_record(app: &tauri::AppHandle, broker: &Broker, run_id: &str, err
         next_check_date: None,
         error_message: Some(error.to_string()),
         run_id: run_id.to_string(),
+        method: SubmissionMethod::Automated,
+        last_removal_check: None,
+        covered_by: None,
+        searched_with: searched_with.map(String::from),
+        failure_reason,
+        outcome_check: None,
+        gpc_sent: false,
+        request_type: RequestType::Deletion,
+        failed_step,
     };
+    let id = record.id.clone();
     let _ = history::upsert_record(app, record);
+    id
 }
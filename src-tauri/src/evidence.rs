@@ -0,0 +1,135 @@
+use crate::crypto;
+use crate::models::{EvidenceIndex, EvidenceItem, EvidenceKind};
+use crate::secrets::SecretsCache;
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Evidence files can show the user's listing, so each one is encrypted like the profile.
+/// The index only holds metadata (record ID, kind, timestamp).
+const DIR_NAME: &str = "evidence";
+const INDEX_FILENAME: &str = "index.json";
+
+fn evidence_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join(DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn encryption_key(app: &tauri::AppHandle) -> Result<Vec<u8>, String> {
+    use tauri::Manager;
+    app.state::<SecretsCache>().get_encryption_key()
+}
+
+fn item_path(dir: &Path, item: &EvidenceItem) -> PathBuf {
+    let ext = match item.kind {
+        EvidenceKind::Screenshot => "png",
+        EvidenceKind::Mhtml => "mhtml",
+        EvidenceKind::Har => "har",
+        EvidenceKind::Email => "eml",
+    };
+    dir.join(format!("{}.{}.enc", item.id, ext))
+}
+
+fn load_index(app: &tauri::AppHandle) -> Result<EvidenceIndex, String> {
+    let path = evidence_dir(app)?.join(INDEX_FILENAME);
+    if !path.exists() {
+        return Ok(EvidenceIndex::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save_index(app: &tauri::AppHandle, index: &EvidenceIndex) -> Result<(), String> {
+    let path = evidence_dir(app)?.join(INDEX_FILENAME);
+    let data = serde_json::to_string_pretty(index).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// Check the evidence folder can take `bytes` more by writing a file that size and
+/// removing it again.
+pub fn check_free_space(app: &tauri::AppHandle, bytes: u64) -> Result<(), String> {
+    use std::io::Write;
+    let path = evidence_dir(app)?.join(".space-check");
+    let result = (|| {
+        let mut file = fs::File::create(&path)?;
+        let chunk = vec![0u8; 1024 * 1024];
+        let mut written = 0u64;
+        while written < bytes {
+            let n = chunk.len().min((bytes - written) as usize);
+            file.write_all(&chunk[..n])?;
+            written += n as u64;
+        }
+        file.sync_all()
+    })();
+    let _ = fs::remove_file(&path);
+    result.map_err(|e| format!("Not enough disk space for evidence: {}", e))
+}
+
+/// Encrypt and store a piece of evidence for a submission record.
+pub fn store(
+    app: &tauri::AppHandle,
+    record_id: &str,
+    kind: EvidenceKind,
+    bytes: &[u8],
+) -> Result<EvidenceItem, String> {
+    let item = EvidenceItem {
+        id: uuid::Uuid::new_v4().to_string(),
+        record_id: record_id.to_string(),
+        kind,
+        captured_at: Utc::now(),
+    };
+
+    let key = encryption_key(app)?;
+    let encrypted = crypto::encrypt(bytes, &key).map_err(|e| e.to_string())?;
+    let dir = evidence_dir(app)?;
+    fs::write(item_path(&dir, &item), encrypted).map_err(|e| e.to_string())?;
+
+    let mut index = load_index(app)?;
+    index.items.push(item.clone());
+    save_index(app, &index)?;
+    Ok(item)
+}
+
+pub fn list_for_record(app: &tauri::AppHandle, record_id: &str) -> Result<Vec<EvidenceItem>, String> {
+    let index = load_index(app)?;
+    Ok(index
+        .items
+        .into_iter()
+        .filter(|i| i.record_id == record_id)
+        .collect())
+}
+
+/// IDs of the records that have evidence of `kind`.
+pub fn records_with(app: &tauri::AppHandle, kind: &EvidenceKind) -> Result<Vec<String>, String> {
+    let mut ids: Vec<String> = load_index(app)?
+        .items
+        .into_iter()
+        .filter(|i| i.kind == *kind)
+        .map(|i| i.record_id)
+        .collect();
+    ids.dedup();
+    Ok(ids)
+}
+
+/// The oldest piece of evidence still on disk, for checking a key against.
+pub fn oldest_file(app: &tauri::AppHandle) -> Result<Option<PathBuf>, String> {
+    let dir = evidence_dir(app)?;
+    Ok(load_index(app)?.items.iter().map(|i| item_path(&dir, i)).find(|p| p.exists()))
+}
+
+/// Decrypt a stored piece of evidence.
+pub fn read(app: &tauri::AppHandle, id: &str) -> Result<(EvidenceItem, Vec<u8>), String> {
+    let index = load_index(app)?;
+    let item = index
+        .items
+        .into_iter()
+        .find(|i| i.id == id)
+        .ok_or("Evidence not found")?;
+    let dir = evidence_dir(app)?;
+    let encrypted = fs::read_to_string(item_path(&dir, &item)).map_err(|e| e.to_string())?;
+    let key = encryption_key(app)?;
+    let bytes = crypto::decrypt(&encrypted, &key).map_err(|e| e.to_string())?;
+    Ok((item, bytes))
+}
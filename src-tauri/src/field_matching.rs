@@ -0,0 +1,224 @@
+use crate::models::{FormField, KnownField};
+use serde::Deserialize;
+
+/// Matches at or above this are filled without asking; weaker ones are checked with the user.
+pub const CONFIDENT: f32 = 0.8;
+
+/// Matches below this aren't worth asking about.
+const MIN_CONFIDENCE: f32 = 0.4;
+
+/// What a form field says about itself. Sent by the recorder for each field the user fills,
+/// and read from the page structure for auto-fill.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct FieldHints {
+    pub label: Option<String>,
+    pub placeholder: Option<String>,
+    pub name: Option<String>,
+    pub id: Option<String>,
+    pub autocomplete: Option<String>,
+    #[serde(rename = "type")]
+    pub field_type: Option<String>,
+}
+
+impl From<&FormField> for FieldHints {
+    fn from(field: &FormField) -> Self {
+        Self {
+            label: field.label.clone(),
+            placeholder: field.placeholder.clone(),
+            name: field.name.clone(),
+            id: field.id.clone(),
+            autocomplete: field.autocomplete.clone(),
+            field_type: field.field_type.clone(),
+        }
+    }
+}
+
+/// A profile key for a field and how sure the match is, from 0 to 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldMatch {
+    pub profile_key: String,
+    pub confidence: f32,
+}
+
+impl FieldMatch {
+    pub fn is_confident(&self) -> bool {
+        self.confidence >= CONFIDENT
+    }
+}
+
+/// `autocomplete` tokens (from the HTML autofill spec) and the profile keys they name.
+const AUTOCOMPLETE_KEYS: &[(&str, &str)] = &[
+    ("given-name", "firstName"),
+    ("family-name", "lastName"),
+    ("name", "fullName"),
+    ("email", "email"),
+    ("tel", "phone"),
+    ("tel-national", "phone"),
+    ("postal-code", "zip"),
+    ("street-address", "address"),
+    ("address-line1", "address"),
+    ("address-level2", "city"),
+    ("address-level1", "state"),
+    ("bday", "dob"),
+];
+
+/// Words in a field's label, placeholder, name or ID that give away its profile key: ones
+/// that settle it, then ones that only suggest it. Checked in order, so "email address" is
+/// an email rather than an address and "first name" isn't taken for a full name.
+const KEY_HINTS: &[(&str, &[&str], &[&str])] = &[
+    ("dob", &["date of birth", "birth date", "birthdate", "birthday", "dob"], &["birth", "born"]),
+    ("email", &["email", "e mail"], &["mail"]),
+    ("zip", &["zip", "zipcode", "postal code", "postcode"], &["postal"]),
+    ("firstName", &["first name", "firstname", "fname", "given name"], &["first"]),
+    ("lastName", &["last name", "lastname", "lname", "surname", "family name"], &["last"]),
+    ("fullName", &["full name", "fullname", "your name"], &["name"]),
+    ("phone", &["phone", "telephone", "mobile"], &["cell", "tel"]),
+    ("city", &["city"], &["town"]),
+    ("state", &["state", "province"], &["region"]),
+    ("address", &["street", "address"], &["addr"]),
+];
+
+/// How much each kind of evidence counts for on its own.
+const AUTOCOMPLETE_SCORE: f32 = 0.95;
+const KNOWN_FIELD_SCORE: f32 = 0.9;
+const TYPE_SCORE: f32 = 0.85;
+/// (settling word, suggestive word) found in the label, name or ID, and placeholder
+const LABEL_SCORES: (f32, f32) = (0.8, 0.55);
+const NAME_SCORES: (f32, f32) = (0.7, 0.45);
+const PLACEHOLDER_SCORES: (f32, f32) = (0.6, 0.4);
+/// Added for every other piece of evidence that agrees
+const AGREEMENT_BONUS: f32 = 0.1;
+
+/// Lowercase words only, with camelCase and snake_case names split apart.
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut prev_lower = false;
+    for c in text.chars() {
+        if c.is_uppercase() && prev_lower {
+            out.push(' ');
+        }
+        prev_lower = c.is_lowercase();
+        out.push(if c.is_alphanumeric() { c.to_ascii_lowercase() } else { ' ' });
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    format!(" {} ", text).contains(&format!(" {} ", phrase))
+}
+
+/// The key `text` points to and whether it settles it.
+fn hinted_key(text: &str) -> Option<(&'static str, bool)> {
+    let text = normalize(text);
+    let find = |settles: bool| {
+        KEY_HINTS.iter().find_map(|(key, strong, weak)| {
+            let words = if settles { strong } else { weak };
+            words.iter().any(|w| contains_phrase(&text, w)).then_some((*key, settles))
+        })
+    };
+    find(true).or_else(|| find(false))
+}
+
+/// The profile key `field` most likely asks for. Confidence grows as the label, name,
+/// `autocomplete` and type agree, and drops when they point at different keys.
+pub fn match_field(field: &FieldHints, known_fields: &[KnownField]) -> Option<FieldMatch> {
+    let mut evidence: Vec<(String, f32)> = Vec::new();
+
+    if let Some(token) = field.autocomplete.as_deref().and_then(|a| a.split_whitespace().last()) {
+        if let Some((_, key)) = AUTOCOMPLETE_KEYS.iter().find(|(t, _)| token.eq_ignore_ascii_case(t)) {
+            evidence.push((key.to_string(), AUTOCOMPLETE_SCORE));
+        }
+    }
+    match field.field_type.as_deref() {
+        Some("email") => evidence.push(("email".to_string(), TYPE_SCORE)),
+        Some("tel") => evidence.push(("phone".to_string(), TYPE_SCORE)),
+        _ => {}
+    }
+
+    // The registry's own description of the form
+    let texts = [&field.label, &field.placeholder, &field.name, &field.id];
+    let known = known_fields.iter().find_map(|k| {
+        let label = normalize(&k.label);
+        let described = !label.is_empty()
+            && texts.iter().filter_map(|t| t.as_deref()).any(|t| contains_phrase(&normalize(t), &label));
+        if described { k.profile_key.clone() } else { None }
+    });
+    if let Some(key) = known {
+        evidence.push((key, KNOWN_FIELD_SCORE));
+    }
+
+    for (text, (settled, suggested)) in [
+        (&field.label, LABEL_SCORES),
+        (&field.name, NAME_SCORES),
+        (&field.id, NAME_SCORES),
+        (&field.placeholder, PLACEHOLDER_SCORES),
+    ] {
+        if let Some((key, settles)) = text.as_deref().and_then(hinted_key) {
+            evidence.push((key.to_string(), if settles { settled } else { suggested }));
+        }
+    }
+
+    // Best score per key, raised a little by each agreeing piece of evidence
+    let mut scores: Vec<(String, f32)> = Vec::new();
+    for (key, score) in evidence {
+        match scores.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, best)) => *best = best.max(score) + AGREEMENT_BONUS,
+            None => scores.push((key, score)),
+        }
+    }
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let mut scores = scores.into_iter();
+    let (profile_key, best) = scores.next()?;
+    let runner_up = scores.next().map_or(0.0, |(_, s)| s);
+    let confidence = (best.min(0.99) - runner_up / 2.0).max(0.0);
+    (confidence >= MIN_CONFIDENCE).then_some(FieldMatch { profile_key, confidence })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hints(label: Option<&str>, name: Option<&str>, field_type: &str) -> FieldHints {
+        FieldHints {
+            label: label.map(String::from),
+            name: name.map(String::from),
+            field_type: Some(field_type.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn key(field: FieldHints, known: &[KnownField]) -> Option<(String, bool)> {
+        match_field(&field, known).map(|m| {
+            let confident = m.is_confident();
+            (m.profile_key, confident)
+        })
+    }
+
+    #[test]
+    fn scores_agreeing_evidence_as_confident() {
+        let known = vec![KnownField {
+            label: "Listing Name".to_string(),
+            field_type: "text".to_string(),
+            profile_key: Some("fullName".to_string()),
+        }];
+        let confident = |k: &str| Some((k.to_string(), true));
+        assert_eq!(key(hints(Some("Email Address"), None, "text"), &[]), confident("email"));
+        assert_eq!(key(hints(Some("Phone"), Some("contact_phone"), "tel"), &[]), confident("phone"));
+        assert_eq!(key(hints(Some("Listing name"), Some("q"), "text"), &known), confident("fullName"));
+        let mut autocompleted = hints(None, Some("f7"), "text");
+        autocompleted.autocomplete = Some("section-1 given-name".to_string());
+        assert_eq!(key(autocompleted, &[]), confident("firstName"));
+    }
+
+    #[test]
+    fn leaves_vague_or_conflicting_fields_unsure() {
+        let unsure = |k: &str| Some((k.to_string(), false));
+        // Only the field's name says anything
+        assert_eq!(key(hints(None, Some("firstName"), "text"), &[]), unsure("firstName"));
+        // Label and name disagree
+        assert_eq!(key(hints(Some("Name"), Some("city"), "text"), &[]), unsure("city"));
+        assert_eq!(key(hints(Some("Last"), None, "text"), &[]), unsure("lastName"));
+        assert_eq!(key(hints(Some("Username"), None, "text"), &[]), None);
+        assert_eq!(key(hints(Some("Comments"), Some("msg"), "text"), &[]), None);
+    }
+}
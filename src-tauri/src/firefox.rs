@@ -0,0 +1,620 @@
+use crate::har;
+use crate::models::{BrowserProxy, ProxyKind};
+use async_tungstenite::tokio::ConnectStream;
+use async_tungstenite::tungstenite::Message;
+use async_tungstenite::WebSocketSender;
+use futures::StreamExt;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{sleep, Duration};
+
+/// How long Firefox gets to start its WebDriver BiDi server.
+const START_TIMEOUT: Duration = Duration::from_secs(30);
+/// File in the automation profile holding the PID of the Firefox last launched on it.
+const PID_FILE: &str = "opt-outta.pid";
+/// Events a page's network activity is recorded from.
+pub const NETWORK_EVENTS: &[&str] = &["network.beforeRequestSent", "network.responseCompleted", "network.fetchError"];
+
+type Replies = Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>;
+
+/// Someone waiting for `methods` events from the tab `context`.
+struct Listener {
+    context: String,
+    methods: Vec<String>,
+    events: mpsc::UnboundedSender<(String, Value)>,
+}
+
+/// A WebDriver BiDi connection. Commands are answered by ID, and events go to whoever is
+/// listening for them.
+#[derive(Clone)]
+struct Connection {
+    sink: Arc<tokio::sync::Mutex<WebSocketSender<ConnectStream>>>,
+    next_id: Arc<AtomicU64>,
+    replies: Arc<Replies>,
+    listeners: Arc<Mutex<Vec<Listener>>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Connection {
+    async fn open(url: &str) -> Result<Connection, String> {
+        let (socket, _) = async_tungstenite::tokio::connect_async(url)
+            .await
+            .map_err(|e| format!("Failed to connect to Firefox: {}", e))?;
+        let (sink, mut stream) = socket.split();
+        let connection = Connection {
+            sink: Arc::new(tokio::sync::Mutex::new(sink)),
+            next_id: Arc::new(AtomicU64::new(1)),
+            replies: Arc::default(),
+            listeners: Arc::default(),
+            closed: Arc::default(),
+        };
+        let reader = connection.clone();
+        tokio::spawn(async move {
+            while let Some(Ok(message)) = stream.next().await {
+                let Message::Text(text) = message else { continue };
+                if let Ok(message) = serde_json::from_str::<Value>(&text) {
+                    reader.dispatch(message);
+                }
+            }
+            // Firefox is gone, so nothing still waiting will hear back
+            reader.closed.store(true, Ordering::SeqCst);
+            reader.replies.lock().unwrap().clear();
+            reader.listeners.lock().unwrap().clear();
+        });
+        Ok(connection)
+    }
+
+    fn dispatch(&self, mut message: Value) {
+        if let Some(id) = message["id"].as_u64() {
+            if let Some(reply) = self.replies.lock().unwrap().remove(&id) {
+                let _ = reply.send(command_result(message));
+            }
+            return;
+        }
+        if message["type"] != "event" {
+            return;
+        }
+        let method = message["method"].as_str().unwrap_or_default().to_string();
+        let params = message["params"].take();
+        let context = params["context"].as_str().unwrap_or_default();
+        self.listeners.lock().unwrap().retain(|l| {
+            l.context != context || !l.methods.contains(&method) || l.events.send((method.clone(), params.clone())).is_ok()
+        });
+    }
+
+    async fn send(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (reply, replied) = oneshot::channel();
+        self.replies.lock().unwrap().insert(id, reply);
+        if self.closed.load(Ordering::SeqCst) {
+            self.replies.lock().unwrap().remove(&id);
+            return Err("Firefox has closed".to_string());
+        }
+        let message = json!({ "id": id, "method": method, "params": params }).to_string();
+        if let Err(e) = self.sink.lock().await.send(Message::text(message)).await {
+            self.replies.lock().unwrap().remove(&id);
+            return Err(format!("Lost the connection to Firefox: {}", e));
+        }
+        replied.await.map_err(|_| "Firefox has closed".to_string())?
+    }
+
+    fn listen(&self, context: &str, methods: &[&str]) -> mpsc::UnboundedReceiver<(String, Value)> {
+        let (events, received) = mpsc::unbounded_channel();
+        self.listeners.lock().unwrap().push(Listener {
+            context: context.to_string(),
+            methods: methods.iter().map(|m| m.to_string()).collect(),
+            events,
+        });
+        received
+    }
+}
+
+/// A command's result, or its error as Firefox describes it.
+fn command_result(mut message: Value) -> Result<Value, String> {
+    if message["type"] == "error" {
+        return Err(format!(
+            "{} ({})",
+            message["message"].as_str().unwrap_or_default(),
+            message["error"].as_str().unwrap_or("unknown error"),
+        ));
+    }
+    Ok(message["result"].take())
+}
+
+/// The address Firefox prints once its WebDriver BiDi server is up.
+fn listening_url(line: &str) -> Option<&str> {
+    line.trim().strip_prefix("WebDriver BiDi listening on ").map(str::trim)
+}
+
+/// Firefox's desktop user agent as Firefox for Android would send it.
+pub fn mobile_user_agent(desktop: &str) -> String {
+    let version = desktop
+        .split("Firefox/")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap_or_default();
+    if version.is_empty() {
+        return desktop.to_string();
+    }
+    format!("Mozilla/5.0 (Android 10; Mobile; rv:{v}) Gecko/{v} Firefox/{v}", v = version)
+}
+
+/// The automation profile's `user.js`, written before every launch: no first-run pages,
+/// default-browser prompt or crash recovery, the Global Privacy Control signal when `gpc`,
+/// and `proxy` if there is one. Preferences stick in the profile once set, so the ones
+/// for a proxy are always written, if only to clear the last run's.
+pub fn user_prefs(gpc: bool, proxy: Option<&BrowserProxy>) -> String {
+    let mut prefs: Vec<(&str, Value)> = vec![
+        ("browser.shell.checkDefaultBrowser", json!(false)),
+        ("browser.startup.homepage_override.mstone", json!("ignore")),
+        ("browser.startup.page", json!(0)),
+        ("browser.aboutwelcome.enabled", json!(false)),
+        ("startup.homepage_welcome_url", json!("about:blank")),
+        ("datareporting.policy.dataSubmissionPolicyBypassNotification", json!(true)),
+        // A run's browser is often left open and closed with the next run
+        ("browser.sessionstore.resume_from_crash", json!(false)),
+        ("toolkit.startup.max_resumed_crashes", json!(-1)),
+        ("privacy.globalprivacycontrol.enabled", json!(gpc)),
+        ("privacy.globalprivacycontrol.functionality.enabled", json!(gpc)),
+    ];
+    let host = proxy.map(|p| p.host.trim().trim_start_matches('[').trim_end_matches(']').to_string());
+    let (http, socks) = match proxy.map(|p| p.kind) {
+        Some(ProxyKind::Http) => (host.clone(), None),
+        Some(ProxyKind::Socks5) => (None, host.clone()),
+        None => (None, None),
+    };
+    let port = proxy.map_or(0, |p| p.port);
+    // 1 is the manual configuration below, 5 the system's proxy settings
+    prefs.push(("network.proxy.type", json!(if proxy.is_some() { 1 } else { 5 })));
+    for pref in ["network.proxy.http", "network.proxy.ssl"] {
+        prefs.push((pref, json!(http.clone().unwrap_or_default())));
+    }
+    for pref in ["network.proxy.http_port", "network.proxy.ssl_port"] {
+        prefs.push((pref, json!(if http.is_some() { port } else { 0 })));
+    }
+    prefs.push(("network.proxy.socks", json!(socks.clone().unwrap_or_default())));
+    prefs.push(("network.proxy.socks_port", json!(if socks.is_some() { port } else { 0 })));
+    prefs.push(("network.proxy.socks_version", json!(5)));
+    prefs.push(("network.proxy.socks_remote_dns", json!(socks.is_some())));
+
+    prefs
+        .iter()
+        .map(|(name, value)| format!("user_pref({}, {});\n", json!(name), value))
+        .collect()
+}
+
+/// Stop the Firefox a previous run left open on `profile`, as a second one can't share it.
+async fn stop_previous(profile: &Path) {
+    let pid_path = profile.join(PID_FILE);
+    let pid = std::fs::read_to_string(&pid_path).ok().and_then(|s| s.trim().parse::<u32>().ok());
+    if let Some(pid) = pid {
+        // Only if it's still that Firefox and not whatever has taken its PID since. When
+        // the system can't say, the process is left alone.
+        let ours = command_line(pid).is_some_and(|cmdline| cmdline.contains(&*profile.to_string_lossy()));
+        if ours {
+            kill(pid);
+            sleep(Duration::from_secs(1)).await;
+        }
+    }
+    let _ = std::fs::remove_file(pid_path);
+}
+
+/// The command line of the process `pid`, if it's running and the system will say.
+#[cfg(target_os = "linux")]
+fn command_line(pid: u32) -> Option<String> {
+    let cmdline = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    Some(String::from_utf8_lossy(&cmdline).into_owned())
+}
+
+#[cfg(target_os = "windows")]
+fn command_line(pid: u32) -> Option<String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let query = format!("(Get-CimInstance Win32_Process -Filter 'ProcessId={}').CommandLine", pid);
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &query])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+    let cmdline = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !cmdline.is_empty()).then_some(cmdline)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn command_line(pid: u32) -> Option<String> {
+    let output = Command::new("ps").args(["-ww", "-o", "command=", "-p", &pid.to_string()]).output().ok()?;
+    let cmdline = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !cmdline.is_empty()).then_some(cmdline)
+}
+
+#[cfg(target_os = "windows")]
+fn kill(pid: u32) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let _ = Command::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output();
+}
+
+#[cfg(not(target_os = "windows"))]
+fn kill(pid: u32) {
+    let _ = Command::new("kill").arg(pid.to_string()).output();
+}
+
+/// Firefox, launched on an automation profile and driven over WebDriver BiDi.
+pub struct Session {
+    connection: Connection,
+    process: Child,
+    user_agent: String,
+}
+
+impl Session {
+    /// Launch the Firefox at `path` on the profile directory `profile` with the
+    /// preferences `prefs` (see `user_prefs`), and start a session on it.
+    pub async fn launch(path: &Path, profile: &Path, prefs: &str, headless: bool) -> Result<Session, String> {
+        std::fs::create_dir_all(profile).map_err(|e| format!("Failed to create the Firefox profile: {}", e))?;
+        stop_previous(profile).await;
+        std::fs::write(profile.join("user.js"), prefs).map_err(|e| format!("Failed to set up the Firefox profile: {}", e))?;
+
+        let mut command = Command::new(path);
+        command
+            .arg("--profile")
+            .arg(profile)
+            .arg("--no-remote")
+            .args(["--remote-debugging-port", "0"]);
+        if headless {
+            command.arg("--headless");
+        }
+        let mut process = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to launch Firefox: {}", e))?;
+        let _ = std::fs::write(profile.join(PID_FILE), process.id().to_string());
+
+        // Firefox prints where its server listens on stderr, which is read to the end
+        // so Firefox never blocks writing to it
+        let (found, listening) = oneshot::channel();
+        if let Some(stderr) = process.stderr.take() {
+            std::thread::spawn(move || {
+                let mut found = Some(found);
+                for line in std::io::BufReader::new(stderr).lines().map_while(Result::ok) {
+                    if let Some(url) = listening_url(&line) {
+                        if let Some(found) = found.take() {
+                            let _ = found.send(url.to_string());
+                        }
+                    }
+                }
+            });
+        }
+
+        let started = async {
+            let url = match tokio::time::timeout(START_TIMEOUT, listening).await {
+                Ok(Ok(url)) => url,
+                _ => return Err("Firefox didn't start its automation server. Firefox 115 or newer is needed.".to_string()),
+            };
+            let connection = Connection::open(&format!("{}/session", url)).await?;
+            // Leave dialogs such as "leave this page?" for the user to answer
+            let session = connection
+                .send("session.new", json!({ "capabilities": { "alwaysMatch": { "unhandledPromptBehavior": { "default": "ignore" } } } }))
+                .await
+                .map_err(|e| format!("Failed to start a Firefox session: {}", e))?;
+            let user_agent = session["capabilities"]["userAgent"].as_str().unwrap_or_default().to_string();
+            Ok((connection, user_agent))
+        }
+        .await;
+        match started {
+            Ok((connection, user_agent)) => Ok(Session { connection, process, user_agent }),
+            Err(e) => {
+                let _ = process.kill();
+                Err(e)
+            }
+        }
+    }
+
+    /// Whether the connection to Firefox is gone, e.g. because the user quit it.
+    pub fn is_closed(&self) -> bool {
+        self.connection.closed.load(Ordering::SeqCst)
+    }
+
+    /// Firefox's own user agent, whatever a tab is set to send.
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    fn tab(&self, context: &str) -> Tab {
+        Tab { connection: self.connection.clone(), context: context.to_string() }
+    }
+
+    /// The tabs open in Firefox.
+    pub async fn tabs(&self) -> Result<Vec<Tab>, String> {
+        let tree = self.connection.send("browsingContext.getTree", json!({ "maxDepth": 0 })).await?;
+        Ok(tree["contexts"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|c| c["context"].as_str())
+            .map(|context| self.tab(context))
+            .collect())
+    }
+
+    /// Open a blank tab.
+    pub async fn new_tab(&self) -> Result<Tab, String> {
+        let created = self.connection.send("browsingContext.create", json!({ "type": "tab" })).await?;
+        created["context"]
+            .as_str()
+            .map(|context| self.tab(context))
+            .ok_or_else(|| "Firefox didn't say which tab it opened".to_string())
+    }
+
+    /// Ask Firefox to quit and wait briefly for it, killing it if it doesn't, so its
+    /// profile is free for the next launch.
+    pub async fn close(mut self) {
+        let _ = tokio::time::timeout(Duration::from_secs(5), self.connection.send("browser.close", json!({}))).await;
+        for _ in 0..25 {
+            if !matches!(self.process.try_wait(), Ok(None)) {
+                return;
+            }
+            sleep(Duration::from_millis(200)).await;
+        }
+        let _ = self.process.kill();
+    }
+}
+
+/// A tab in a Firefox session.
+#[derive(Clone)]
+pub struct Tab {
+    connection: Connection,
+    context: String,
+}
+
+impl Tab {
+    /// The tab's browsing context ID.
+    pub fn id(&self) -> &str {
+        &self.context
+    }
+
+    async fn send(&self, method: &str, mut params: Value) -> Result<Value, String> {
+        params["context"] = json!(self.context);
+        self.connection.send(method, params).await
+    }
+
+    /// Evaluate a JavaScript expression in the page, awaiting it if it's a promise, and
+    /// return its result as JSON (null for `undefined`). A thrown error comes back as
+    /// its message.
+    pub async fn evaluate(&self, js: &str) -> Result<Value, String> {
+        // The result is passed back as a JSON string, which is simpler to read than
+        // BiDi's typed remote values
+        let expression = format!("(async () => JSON.stringify(await ({}\n)))()", js);
+        let evaluated = self
+            .connection
+            .send(
+                "script.evaluate",
+                json!({
+                    "expression": expression,
+                    "target": { "context": self.context },
+                    "awaitPromise": true,
+                    "resultOwnership": "none",
+                }),
+            )
+            .await?;
+        evaluation_result(evaluated)
+    }
+
+    /// Load `url` and wait for the page's load event.
+    pub async fn navigate(&self, url: &str) -> Result<(), String> {
+        self.send("browsingContext.navigate", json!({ "url": url, "wait": "complete" })).await.map(|_| ())
+    }
+
+    /// Reload the page and wait for its load event.
+    pub async fn reload(&self) -> Result<(), String> {
+        self.send("browsingContext.reload", json!({ "wait": "complete" })).await.map(|_| ())
+    }
+
+    /// Wait until no resource has finished loading for half a second, up to `ceiling`.
+    /// BiDi has no network-idle event like Chrome's lifecycle events.
+    pub async fn settle(&self, ceiling: Duration) {
+        let quiet = async {
+            let mut last = None;
+            loop {
+                let loaded = self.evaluate("performance.getEntriesByType('resource').length").await.ok();
+                if loaded.is_some() && loaded == last {
+                    return;
+                }
+                last = loaded;
+                sleep(Duration::from_millis(500)).await;
+            }
+        };
+        let _ = tokio::time::timeout(ceiling, quiet).await;
+    }
+
+    pub async fn close(&self) -> Result<(), String> {
+        self.send("browsingContext.close", json!({})).await.map(|_| ())
+    }
+
+    /// Bring the tab to the front of its window.
+    pub async fn activate(&self) -> Result<(), String> {
+        self.send("browsingContext.activate", json!({})).await.map(|_| ())
+    }
+
+    /// A PNG of the whole page, not just the part in view.
+    pub async fn screenshot(&self) -> Result<Vec<u8>, String> {
+        use base64::Engine;
+        let shot = self.send("browsingContext.captureScreenshot", json!({ "origin": "document" })).await?;
+        base64::engine::general_purpose::STANDARD
+            .decode(shot["data"].as_str().unwrap_or_default())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Show the page at `size` (CSS pixels) and `scale` device pixels per CSS pixel, or
+    /// with None at the window's own.
+    pub async fn set_viewport(&self, size: Option<(i64, i64)>, scale: Option<f64>) -> Result<(), String> {
+        let viewport = size.map(|(width, height)| json!({ "width": width, "height": height }));
+        self.send("browsingContext.setViewport", json!({ "viewport": viewport, "devicePixelRatio": scale }))
+            .await
+            .map(|_| ())
+    }
+
+    /// Send `agent` as the tab's user agent, or with None Firefox's own again.
+    pub async fn set_user_agent(&self, agent: Option<&str>) -> Result<(), String> {
+        self.connection
+            .send("emulation.setUserAgentOverride", json!({ "userAgent": agent, "contexts": [self.context] }))
+            .await
+            .map(|_| ())
+    }
+
+    /// Press and release a key for each character of `text`, into whatever has focus.
+    pub async fn type_text(&self, text: &str) -> Result<(), String> {
+        let keys: Vec<Value> = text
+            .chars()
+            // WebDriver's code for the Enter key
+            .map(|c| if c == '\n' { '\u{E007}' } else { c })
+            .flat_map(|c| [json!({ "type": "keyDown", "value": c }), json!({ "type": "keyUp", "value": c })])
+            .collect();
+        self.send("input.performActions", json!({ "actions": [{ "type": "key", "id": "keyboard", "actions": keys }] }))
+            .await
+            .map(|_| ())
+    }
+
+    /// `methods` events from this tab, from now until the receiver is dropped.
+    pub async fn events(&self, methods: &[&str]) -> Result<mpsc::UnboundedReceiver<(String, Value)>, String> {
+        let events = self.connection.listen(&self.context, methods);
+        self.connection
+            .send("session.subscribe", json!({ "events": methods, "contexts": [self.context] }))
+            .await?;
+        Ok(events)
+    }
+
+    /// Answer the proxy's login prompts in this tab with `username` and `password`. BiDi
+    /// doesn't say who is asking, so a 407 response marks the proxy; a site's own prompt
+    /// is left to the user.
+    pub async fn answer_proxy_login(&self, username: String, password: String) -> Result<(), String> {
+        let mut challenges = self.events(&["network.authRequired"]).await?;
+        self.connection
+            .send("network.addIntercept", json!({ "phases": ["authRequired"], "contexts": [self.context] }))
+            .await?;
+        let connection = self.connection.clone();
+        tokio::spawn(async move {
+            while let Some((_, challenge)) = challenges.recv().await {
+                let request = challenge["request"]["request"].clone();
+                let answer = if challenge["response"]["status"] == 407 {
+                    json!({
+                        "request": request,
+                        "action": "provideCredentials",
+                        "credentials": { "type": "password", "username": username, "password": password },
+                    })
+                } else {
+                    json!({ "request": request, "action": "default" })
+                };
+                let _ = connection.send("network.continueWithAuth", answer).await;
+            }
+        });
+        Ok(())
+    }
+}
+
+/// The value of a `script.evaluate` of the JSON-encoding expression `Tab::evaluate` builds.
+fn evaluation_result(evaluated: Value) -> Result<Value, String> {
+    if evaluated["type"] == "exception" {
+        return Err(evaluated["exceptionDetails"]["text"].as_str().unwrap_or("The script failed").to_string());
+    }
+    match evaluated["result"]["value"].as_str() {
+        Some(json) => serde_json::from_str(json).map_err(|e| format!("Failed to read the script's result: {}", e)),
+        None => Ok(Value::Null),
+    }
+}
+
+fn header_pairs(headers: &Value) -> Vec<(String, String)> {
+    headers
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|h| {
+            let name = h["name"].as_str().unwrap_or_default().to_string();
+            (name, h["value"]["value"].as_str().unwrap_or_default().to_string())
+        })
+        .collect()
+}
+
+/// Add one of the `NETWORK_EVENTS` to a page's HAR log. BiDi times events in
+/// milliseconds since the epoch and doesn't hand over request bodies.
+pub fn log_network_event(log: &mut har::NetworkLog, method: &str, params: &Value) {
+    let id = params["request"]["request"].as_str().unwrap_or_default();
+    let seconds = params["timestamp"].as_f64().unwrap_or_default() / 1000.0;
+    match method {
+        "network.beforeRequestSent" => {
+            let request = &params["request"];
+            let sent = har::SentRequest {
+                method: request["method"].as_str().unwrap_or_default().to_string(),
+                url: request["url"].as_str().unwrap_or_default().to_string(),
+                headers: header_pairs(&request["headers"]),
+                body: None,
+                resource_type: request["destination"].as_str().filter(|d| !d.is_empty()).map(String::from),
+                wall_time: seconds,
+                timestamp: seconds,
+            };
+            log.request_sent(id, sent, None);
+        }
+        "network.responseCompleted" => {
+            let response = &params["response"];
+            log.response_received(
+                id,
+                har::ReceivedResponse {
+                    status: response["status"].as_i64().unwrap_or_default(),
+                    status_text: response["statusText"].as_str().unwrap_or_default().to_string(),
+                    headers: header_pairs(&response["headers"]),
+                    mime_type: response["mimeType"].as_str().unwrap_or_default().to_string(),
+                },
+            );
+            log.finished(id, seconds, response["bytesReceived"].as_f64().unwrap_or_default());
+        }
+        "network.fetchError" => log.failed(id, seconds, params["errorText"].as_str().unwrap_or_default().to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_what_firefox_reports() {
+        assert_eq!(
+            listening_url("WebDriver BiDi listening on ws://127.0.0.1:41077\n"),
+            Some("ws://127.0.0.1:41077")
+        );
+        assert_eq!(listening_url("console.error: something else"), None);
+
+        let returned = json!({ "type": "success", "result": { "type": "string", "value": "{\"n\":2}" } });
+        assert_eq!(evaluation_result(returned), Ok(json!({ "n": 2 })));
+        assert_eq!(evaluation_result(json!({ "type": "success", "result": { "type": "undefined" } })), Ok(Value::Null));
+        let thrown = json!({ "type": "exception", "exceptionDetails": { "text": "Error: Element not found" } });
+        assert_eq!(evaluation_result(thrown), Err("Error: Element not found".to_string()));
+
+        assert_eq!(
+            mobile_user_agent("Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0"),
+            "Mozilla/5.0 (Android 10; Mobile; rv:128.0) Gecko/128.0 Firefox/128.0"
+        );
+    }
+
+    #[test]
+    fn points_the_profile_at_the_proxy() {
+        let proxy = BrowserProxy { kind: ProxyKind::Socks5, host: "::1".to_string(), port: 1080, username: None };
+        let prefs = user_prefs(true, Some(&proxy));
+        assert!(prefs.contains("user_pref(\"network.proxy.type\", 1);"));
+        assert!(prefs.contains("user_pref(\"network.proxy.socks\", \"::1\");"));
+        assert!(prefs.contains("user_pref(\"network.proxy.socks_port\", 1080);"));
+        assert!(prefs.contains("user_pref(\"network.proxy.http\", \"\");"));
+        assert!(prefs.contains("user_pref(\"privacy.globalprivacycontrol.enabled\", true);"));
+        // Without a proxy the last run's is cleared
+        assert!(user_prefs(false, None).contains("user_pref(\"network.proxy.type\", 5);"));
+    }
+}
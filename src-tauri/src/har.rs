@@ -0,0 +1,263 @@
+use chrono::{TimeZone, Utc};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Requests kept per broker; a page that keeps polling stops being logged here.
+const MAX_ENTRIES: usize = 2000;
+/// Longest request body kept, which is plenty for a form post.
+const MAX_BODY_LEN: usize = 64 * 1024;
+/// Headers that carry the browser's session. Their values are left out so a log shared
+/// while asking for help can't be used to sign in as the user.
+const SESSION_HEADERS: &[&str] = &["cookie", "set-cookie", "authorization", "proxy-authorization"];
+
+/// A request as the browser sent it.
+pub struct SentRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub resource_type: Option<String>,
+    /// Seconds since the Unix epoch
+    pub wall_time: f64,
+    /// Browser clock in seconds, which the other events are timed by
+    pub timestamp: f64,
+}
+
+pub struct ReceivedResponse {
+    pub status: i64,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub mime_type: String,
+}
+
+struct Entry {
+    request: SentRequest,
+    response: Option<ReceivedResponse>,
+    ended_at: Option<f64>,
+    size: Option<f64>,
+    error: Option<String>,
+}
+
+/// A page's network activity, built up from the browser's events and written out as a
+/// HAR 1.2 log that browser dev tools and HAR viewers can open.
+#[derive(Default)]
+pub struct NetworkLog {
+    entries: Vec<Entry>,
+    /// Browser request ID to the entry it's filling in. A redirect reuses the ID, so it
+    /// moves on to the redirected request's entry.
+    open: HashMap<String, usize>,
+}
+
+fn without_session(headers: Vec<(String, String)>) -> Vec<(String, String)> {
+    headers
+        .into_iter()
+        .map(|(name, value)| {
+            if SESSION_HEADERS.contains(&name.to_lowercase().as_str()) {
+                (name, "(removed)".to_string())
+            } else {
+                (name, value)
+            }
+        })
+        .collect()
+}
+
+fn header_list(headers: &[(String, String)]) -> Vec<Value> {
+    headers.iter().map(|(name, value)| json!({ "name": name, "value": value })).collect()
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+}
+
+impl NetworkLog {
+    /// `redirected_by` is the response that sent the browser here, for a request that
+    /// follows a redirect; it completes the previous request under the same ID.
+    pub fn request_sent(&mut self, id: &str, mut request: SentRequest, redirected_by: Option<ReceivedResponse>) {
+        if let (Some(redirect), Some(&i)) = (redirected_by, self.open.get(id)) {
+            let entry = &mut self.entries[i];
+            entry.response = Some(ReceivedResponse { headers: without_session(redirect.headers), ..redirect });
+            entry.ended_at = Some(request.timestamp);
+        }
+        self.open.remove(id);
+        if self.entries.len() >= MAX_ENTRIES {
+            return;
+        }
+        request.headers = without_session(request.headers);
+        if let Some(body) = request.body.as_mut().filter(|b| b.len() > MAX_BODY_LEN) {
+            let mut cut = MAX_BODY_LEN;
+            while !body.is_char_boundary(cut) {
+                cut -= 1;
+            }
+            body.truncate(cut);
+        }
+        self.open.insert(id.to_string(), self.entries.len());
+        self.entries.push(Entry { request, response: None, ended_at: None, size: None, error: None });
+    }
+
+    pub fn response_received(&mut self, id: &str, response: ReceivedResponse) {
+        if let Some(&i) = self.open.get(id) {
+            self.entries[i].response = Some(ReceivedResponse { headers: without_session(response.headers), ..response });
+        }
+    }
+
+    pub fn finished(&mut self, id: &str, timestamp: f64, size: f64) {
+        if let Some(i) = self.open.remove(id) {
+            self.entries[i].ended_at = Some(timestamp);
+            self.entries[i].size = Some(size);
+        }
+    }
+
+    pub fn failed(&mut self, id: &str, timestamp: f64, error: String) {
+        if let Some(i) = self.open.remove(id) {
+            self.entries[i].ended_at = Some(timestamp);
+            self.entries[i].error = Some(error);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The log as HAR. Requests still in flight are included without a response, and
+    /// failed ones carry the browser's error in `_error` like Chrome's own exports.
+    pub fn to_har(&self) -> Value {
+        let entries: Vec<Value> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let request = &entry.request;
+                let started = Utc
+                    .timestamp_millis_opt((request.wall_time * 1000.0) as i64)
+                    .single()
+                    .unwrap_or_default();
+                let time = entry.ended_at.map_or(0.0, |end| ((end - request.timestamp) * 1000.0).max(0.0));
+                let mut har_request = json!({
+                    "method": request.method,
+                    "url": request.url,
+                    "httpVersion": "",
+                    "headers": header_list(&request.headers),
+                    "queryString": [],
+                    "cookies": [],
+                    "headersSize": -1,
+                    "bodySize": request.body.as_ref().map_or(0, |b| b.len()),
+                });
+                if let Some(body) = &request.body {
+                    har_request["postData"] = json!({
+                        "mimeType": header(&request.headers, "content-type").unwrap_or_default(),
+                        "text": body,
+                    });
+                }
+                let response = entry.response.as_ref();
+                let mut har_entry = json!({
+                    "startedDateTime": started.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                    "time": time,
+                    "request": har_request,
+                    "response": {
+                        "status": response.map_or(0, |r| r.status),
+                        "statusText": response.map_or("", |r| r.status_text.as_str()),
+                        "httpVersion": "",
+                        "headers": response.map_or(vec![], |r| header_list(&r.headers)),
+                        "cookies": [],
+                        "content": {
+                            "size": entry.size.unwrap_or(0.0),
+                            "mimeType": response.map_or("", |r| r.mime_type.as_str()),
+                        },
+                        "redirectURL": response.and_then(|r| header(&r.headers, "location")).unwrap_or_default(),
+                        "headersSize": -1,
+                        "bodySize": entry.size.unwrap_or(-1.0),
+                    },
+                    "cache": {},
+                    "timings": { "send": 0, "wait": time, "receive": 0 },
+                });
+                if let Some(kind) = &request.resource_type {
+                    har_entry["_resourceType"] = json!(kind);
+                }
+                if let Some(error) = &entry.error {
+                    har_entry["_error"] = json!(error);
+                }
+                har_entry
+            })
+            .collect();
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": { "name": "Opt-Outta", "version": env!("CARGO_PKG_VERSION") },
+                "pages": [],
+                "entries": entries,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent(method: &str, url: &str, timestamp: f64) -> SentRequest {
+        SentRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: vec![("Cookie".to_string(), "session=abc".to_string())],
+            body: None,
+            resource_type: Some("Document".to_string()),
+            wall_time: 1_700_000_000.0 + timestamp,
+            timestamp,
+        }
+    }
+
+    fn response(status: i64, headers: &[(&str, &str)]) -> ReceivedResponse {
+        ReceivedResponse {
+            status,
+            status_text: String::new(),
+            headers: headers.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect(),
+            mime_type: "text/html".to_string(),
+        }
+    }
+
+    #[test]
+    fn follows_redirects_and_keeps_form_posts() {
+        let mut log = NetworkLog::default();
+        let mut post = sent("POST", "https://broker.example/optout", 10.0);
+        post.headers.push(("Content-Type".to_string(), "application/x-www-form-urlencoded".to_string()));
+        post.body = Some("email=jane%40example.com".to_string());
+        log.request_sent("1", post, None);
+        log.request_sent(
+            "1",
+            sent("GET", "https://broker.example/thanks", 10.25),
+            Some(response(302, &[("Location", "/thanks"), ("Set-Cookie", "id=1")])),
+        );
+        log.response_received("1", response(200, &[]));
+        log.finished("1", 10.5, 512.0);
+
+        let har = log.to_har();
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["request"]["postData"]["text"], "email=jane%40example.com");
+        assert_eq!(entries[0]["response"]["status"], 302);
+        assert_eq!(entries[0]["response"]["redirectURL"], "/thanks");
+        assert_eq!(entries[0]["response"]["headers"][1]["value"], "(removed)");
+        assert_eq!(entries[0]["request"]["headers"][0]["value"], "(removed)");
+        assert_eq!(entries[0]["time"], 250.0);
+        assert_eq!(entries[1]["response"]["status"], 200);
+        assert_eq!(entries[1]["response"]["content"]["size"], 512.0);
+    }
+
+    #[test]
+    fn records_failures_and_requests_still_in_flight() {
+        let mut log = NetworkLog::default();
+        assert!(log.is_empty());
+        log.request_sent("7", sent("POST", "https://broker.example/api/remove", 3.0), None);
+        log.failed("7", 3.1, "net::ERR_BLOCKED_BY_CLIENT".to_string());
+        log.request_sent("8", sent("GET", "https://broker.example/poll", 4.0), None);
+        // Events for requests it never saw start are ignored
+        log.finished("9", 5.0, 10.0);
+
+        let har = log.to_har();
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["_error"], "net::ERR_BLOCKED_BY_CLIENT");
+        assert_eq!(entries[0]["response"]["status"], 0);
+        assert_eq!(entries[1]["time"], 0.0);
+        assert!(entries[1].get("_error").is_none());
+    }
+}
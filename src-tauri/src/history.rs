@@ -1,40 +1,253 @@
-use crate::models::{SubmissionHistory, SubmissionRecord};
-use chrono::Utc;
+use crate::models::{Broker, BrokerSubmissionStatus, HistoryRepairReport, RequestType, SubmissionHistory, SubmissionRecord};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 const HISTORY_FILENAME: &str = "submissions.json";
+/// Upserts since the last compaction, one JSON record per line, replayed over the snapshot on load.
+const JOURNAL_FILENAME: &str = "submissions.journal";
+/// Journal entries after which the snapshot is rewritten and the journal emptied.
+const COMPACT_AFTER: usize = 200;
 
-pub fn history_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+/// Run ID used for submissions the user logged by hand.
+pub const MANUAL_RUN_ID: &str = "manual";
+
+/// The loaded history, shared by every caller so writes are serialized and reads skip the disk.
+static INDEX: Mutex<Option<HistoryIndex>> = Mutex::new(None);
+
+/// History plus lookups kept up to date as records are upserted.
+#[derive(Default)]
+struct HistoryIndex {
+    history: SubmissionHistory,
+    /// Record ID -> position in `history.records`
+    by_id: HashMap<String, usize>,
+    /// Broker ID -> position of its most recent record
+    latest: HashMap<String, usize>,
+    journal_entries: usize,
+}
+
+impl HistoryIndex {
+    fn new(history: SubmissionHistory) -> Self {
+        let mut index = HistoryIndex::default();
+        for record in history.records {
+            index.upsert(record);
+        }
+        index
+    }
+
+    fn upsert(&mut self, record: SubmissionRecord) {
+        let i = match self.by_id.get(&record.id) {
+            Some(&i) => {
+                self.history.records[i] = record;
+                i
+            }
+            None => {
+                self.by_id.insert(record.id.clone(), self.history.records.len());
+                self.history.records.push(record);
+                self.history.records.len() - 1
+            }
+        };
+        let broker_id = self.history.records[i].broker_id.clone();
+        let submitted_at = self.history.records[i].submitted_at;
+        match self.latest.get(&broker_id).copied() {
+            // The latest record changed; if its date moved back another one may now be newer
+            Some(j) if j == i => self.reindex_latest(&broker_id),
+            Some(j) if self.history.records[j].submitted_at >= submitted_at => {}
+            _ => {
+                self.latest.insert(broker_id, i);
+            }
+        }
+    }
+
+    fn reindex_latest(&mut self, broker_id: &str) {
+        let newest = self
+            .history
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.broker_id == broker_id)
+            .max_by_key(|(i, r)| (r.submitted_at, std::cmp::Reverse(*i)))
+            .map(|(i, _)| i);
+        if let Some(i) = newest {
+            self.latest.insert(broker_id.to_string(), i);
+        }
+    }
+}
+
+/// Apply journal lines on top of `history`. A line that doesn't parse (a write cut short
+/// by a crash) is skipped. Returns the number of entries applied.
+fn replay(index: &mut HistoryIndex, journal: &str) -> usize {
+    let mut applied = 0;
+    for line in journal.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str::<SubmissionRecord>(line) {
+            Ok(record) => {
+                index.upsert(record);
+                applied += 1;
+            }
+            Err(e) => eprintln!("[history] Skipping unreadable journal entry: {}", e),
+        }
+    }
+    applied
+}
+
+/// When to check a broker for relisting after a submission, from its `relist_days`.
+pub fn next_check_date(broker: &Broker, submitted_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    broker.relist_days.map(|days| submitted_at + Duration::days(days as i64))
+}
+
+fn data_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     use tauri::Manager;
     let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
-    Ok(dir.join(HISTORY_FILENAME))
+    Ok(dir)
 }
 
-pub fn load(app: &tauri::AppHandle) -> Result<SubmissionHistory, String> {
+pub fn history_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(data_dir(app)?.join(HISTORY_FILENAME))
+}
+
+fn journal_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(data_dir(app)?.join(JOURNAL_FILENAME))
+}
+
+fn read_from_disk(app: &tauri::AppHandle) -> Result<HistoryIndex, String> {
     let path = history_path(app)?;
-    if !path.exists() {
-        return Ok(SubmissionHistory::default());
+    let snapshot = if path.exists() {
+        let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&data).map_err(|e| e.to_string())?
+    } else {
+        SubmissionHistory::default()
+    };
+    let mut index = HistoryIndex::new(snapshot);
+    let journal = journal_path(app)?;
+    if journal.exists() {
+        let data = fs::read_to_string(&journal).map_err(|e| e.to_string())?;
+        index.journal_entries = replay(&mut index, &data);
     }
-    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&data).map_err(|e| e.to_string())
+    Ok(index)
 }
 
-pub fn save(app: &tauri::AppHandle, history: &SubmissionHistory) -> Result<(), String> {
+/// Run `f` against the loaded history, reading it from disk on first use.
+fn with_index<T>(
+    app: &tauri::AppHandle,
+    f: impl FnOnce(&mut HistoryIndex) -> Result<T, String>,
+) -> Result<T, String> {
+    let mut guard = INDEX.lock().map_err(|e| e.to_string())?;
+    if guard.is_none() {
+        *guard = Some(read_from_disk(app)?);
+    }
+    f(guard.as_mut().expect("history index loaded above"))
+}
+
+/// Write the full history as the new snapshot and empty the journal. The snapshot is
+/// written to a temp file and renamed, so a crash leaves either the old or new one.
+fn compact(app: &tauri::AppHandle, index: &mut HistoryIndex) -> Result<(), String> {
     let path = history_path(app)?;
-    let data = serde_json::to_string_pretty(history).map_err(|e| e.to_string())?;
-    fs::write(&path, data).map_err(|e| e.to_string())
+    let tmp = path.with_extension("json.tmp");
+    let data = serde_json::to_string_pretty(&index.history).map_err(|e| e.to_string())?;
+    let mut file = fs::File::create(&tmp).map_err(|e| e.to_string())?;
+    file.write_all(data.as_bytes()).map_err(|e| e.to_string())?;
+    // Renaming a file whose data hasn't reached the disk can leave an empty snapshot after a crash
+    file.sync_all().map_err(|e| e.to_string())?;
+    drop(file);
+    fs::rename(&tmp, &path).map_err(|e| e.to_string())?;
+    // Replaying stale entries over the new snapshot is harmless, so this can come last
+    fs::write(journal_path(app)?, "").map_err(|e| e.to_string())?;
+    index.journal_entries = 0;
+    Ok(())
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<SubmissionHistory, String> {
+    with_index(app, |index| Ok(index.history.clone()))
+}
+
+/// Replace the whole history (used by repairs). Prefer `upsert_record` for single changes;
+/// it can't overwrite records written since this history was loaded.
+pub fn save(app: &tauri::AppHandle, history: &SubmissionHistory) -> Result<(), String> {
+    with_index(app, |index| {
+        *index = HistoryIndex::new(history.clone());
+        compact(app, index)
+    })
+}
+
+/// Append `lines` to the journal at `path`. A last line cut short by a crash is ended
+/// first, so the new entries don't get glued onto it and skipped with it on replay.
+fn append_lines(path: &Path, lines: &str) -> std::io::Result<()> {
+    let mut journal = fs::OpenOptions::new().create(true).read(true).append(true).open(path)?;
+    let mut data = String::new();
+    if journal.metadata()?.len() > 0 {
+        let mut last = [0u8; 1];
+        journal.seek(SeekFrom::End(-1))?;
+        journal.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            data.push('\n');
+        }
+    }
+    data.push_str(lines);
+    journal.write_all(data.as_bytes())?;
+    journal.sync_data()
+}
+
+/// Journal `records` with a single write and apply them to the index.
+fn journal_records(app: &tauri::AppHandle, index: &mut HistoryIndex, records: Vec<SubmissionRecord>) -> Result<(), String> {
+    let mut lines = String::new();
+    for record in &records {
+        lines.push_str(&serde_json::to_string(record).map_err(|e| e.to_string())?);
+        lines.push('\n');
+    }
+    append_lines(&journal_path(app)?, &lines).map_err(|e| e.to_string())?;
+
+    index.journal_entries += records.len();
+    for record in records {
+        index.upsert(record);
+    }
+    if index.journal_entries >= COMPACT_AFTER {
+        compact(app, index)?;
+    }
+    Ok(())
 }
 
+/// Add or replace a record by ID. Appends one line to the journal instead of rewriting
+/// the history file.
 pub fn upsert_record(app: &tauri::AppHandle, record: SubmissionRecord) -> Result<(), String> {
-    let mut history = load(app)?;
-    if let Some(existing) = history.records.iter_mut().find(|r| r.id == record.id) {
-        *existing = record;
-    } else {
-        history.records.push(record);
+    with_index(app, |index| journal_records(app, index, vec![record]))
+}
+
+/// Apply `f` to each record in `ids` and journal the changes together. IDs without a
+/// record are skipped. Returns how many records were changed.
+pub fn update_records(
+    app: &tauri::AppHandle,
+    ids: &[String],
+    mut f: impl FnMut(&mut SubmissionRecord),
+) -> Result<usize, String> {
+    with_index(app, |index| {
+        let mut seen = std::collections::HashSet::new();
+        let changed: Vec<SubmissionRecord> = ids
+            .iter()
+            .filter(|id| seen.insert(id.as_str()))
+            .filter_map(|id| index.by_id.get(id).map(|&i| index.history.records[i].clone()))
+            .map(|mut record| {
+                f(&mut record);
+                record
+            })
+            .collect();
+        let count = changed.len();
+        if count > 0 {
+            journal_records(app, index, changed)?;
+        }
+        Ok(count)
+    })
+}
+
+/// Set a record's status, stamping `confirmed_at` when it first becomes confirmed.
+pub fn set_status(record: &mut SubmissionRecord, status: BrokerSubmissionStatus, now: DateTime<Utc>) {
+    if status == BrokerSubmissionStatus::Confirmed && record.status != BrokerSubmissionStatus::Confirmed {
+        record.confirmed_at = Some(now);
     }
-    save(app, &history)
+    record.status = status;
 }
 
 #[allow(dead_code)]
@@ -48,15 +261,13 @@ pub fn get_by_broker(app: &tauri::AppHandle, broker_id: &str) -> Result<Vec<Subm
 }
 
 pub fn get_latest_per_broker(app: &tauri::AppHandle) -> Result<Vec<SubmissionRecord>, String> {
-    let history = load(app)?;
-    let mut latest: std::collections::HashMap<String, SubmissionRecord> = std::collections::HashMap::new();
-    for record in history.records {
-        let entry = latest.entry(record.broker_id.clone()).or_insert_with(|| record.clone());
-        if record.submitted_at > entry.submitted_at {
-            *entry = record;
-        }
-    }
-    Ok(latest.into_values().collect())
+    with_index(app, |index| {
+        Ok(index
+            .latest
+            .values()
+            .map(|&i| index.history.records[i].clone())
+            .collect())
+    })
 }
 
 pub fn get_due_for_recheck(app: &tauri::AppHandle) -> Result<Vec<SubmissionRecord>, String> {
@@ -71,3 +282,188 @@ pub fn get_due_for_recheck(app: &tauri::AppHandle) -> Result<Vec<SubmissionRecor
         })
         .collect())
 }
+
+/// How much a record says about a broker's state; the higher one wins when collapsing duplicates.
+fn status_rank(status: &BrokerSubmissionStatus) -> u8 {
+    match status {
+        BrokerSubmissionStatus::Confirmed => 3,
+        BrokerSubmissionStatus::ReListed => 2,
+        BrokerSubmissionStatus::Submitted | BrokerSubmissionStatus::PendingVerification | BrokerSubmissionStatus::NotFound => 1,
+        BrokerSubmissionStatus::Failed => 0,
+    }
+}
+
+/// Collapse duplicate records per broker/run (and search pass) and fill in missing relist check dates.
+/// Manual submissions all share one run ID, so they're never collapsed.
+pub fn repair(history: &mut SubmissionHistory, brokers: &[Broker]) -> HistoryRepairReport {
+    let mut report = HistoryRepairReport::default();
+
+    // Keep the most informative (then most recent) record for each broker/run/search pass
+    let mut best: HashMap<(String, String, Option<String>), usize> = HashMap::new();
+    for (i, record) in history.records.iter().enumerate() {
+        if record.run_id == MANUAL_RUN_ID {
+            continue;
+        }
+        let key = (record.broker_id.clone(), record.run_id.clone(), record.searched_with.clone());
+        match best.get(&key) {
+            Some(&j) => {
+                let current = &history.records[j];
+                let better = (status_rank(&record.status), record.submitted_at)
+                    > (status_rank(&current.status), current.submitted_at);
+                if better {
+                    best.insert(key, i);
+                }
+            }
+            None => {
+                best.insert(key, i);
+            }
+        }
+    }
+
+    let mut kept = Vec::with_capacity(history.records.len());
+    for (i, record) in history.records.drain(..).enumerate() {
+        let key = (record.broker_id.clone(), record.run_id.clone(), record.searched_with.clone());
+        if record.run_id != MANUAL_RUN_ID && best.get(&key) != Some(&i) {
+            report.duplicates_removed += 1;
+            report.details.push(format!(
+                "Removed duplicate {:?} record for {} in run {}",
+                record.status, record.broker_id, record.run_id
+            ));
+            continue;
+        }
+        kept.push(record);
+    }
+    history.records = kept;
+
+    for record in history.records.iter_mut() {
+        let Some(broker) = brokers.iter().find(|b| b.id == record.broker_id) else {
+            report.unknown_broker_records += 1;
+            continue;
+        };
+        // Access requests never get a relist check
+        if record.next_check_date.is_none()
+            && record.status != BrokerSubmissionStatus::Failed
+            && record.request_type == RequestType::Deletion
+        {
+            if let Some(date) = next_check_date(broker, record.submitted_at) {
+                record.next_check_date = Some(date);
+                report.next_check_dates_repaired += 1;
+                report.details.push(format!(
+                    "Set relist check for {} to {}",
+                    record.broker_id,
+                    date.format("%Y-%m-%d")
+                ));
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn broker(id: &str, relist_days: Option<u32>) -> Broker {
+        Broker { relist_days, ..test_fixtures::broker(id) }
+    }
+
+    fn record(broker_id: &str, run_id: &str, status: BrokerSubmissionStatus, minutes: i64) -> SubmissionRecord {
+        SubmissionRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            submitted_at: Utc::now() + Duration::minutes(minutes),
+            run_id: run_id.to_string(),
+            ..test_fixtures::record(broker_id, status)
+        }
+    }
+
+    #[test]
+    fn collapses_duplicates_keeping_best_status() {
+        let mut history = SubmissionHistory {
+            records: vec![
+                record("spokeo", "run-1", BrokerSubmissionStatus::Failed, 2),
+                record("spokeo", "run-1", BrokerSubmissionStatus::Submitted, 0),
+                record("spokeo", "run-2", BrokerSubmissionStatus::Failed, 5),
+            ],
+        };
+        let report = repair(&mut history, &[broker("spokeo", None)]);
+        assert_eq!(report.duplicates_removed, 1);
+        assert_eq!(history.records.len(), 2);
+        assert_eq!(history.records[0].status, BrokerSubmissionStatus::Submitted);
+    }
+
+    #[test]
+    fn repairs_missing_next_check_date() {
+        let mut history = SubmissionHistory {
+            records: vec![
+                record("spokeo", "run-1", BrokerSubmissionStatus::Submitted, 0),
+                record("whitepages", "run-1", BrokerSubmissionStatus::Failed, 0),
+                record("gone", "run-1", BrokerSubmissionStatus::Submitted, 0),
+            ],
+        };
+        let report = repair(&mut history, &[broker("spokeo", Some(90)), broker("whitepages", Some(90))]);
+        assert_eq!(report.next_check_dates_repaired, 1);
+        assert_eq!(report.unknown_broker_records, 1);
+        assert!(history.records[0].next_check_date.is_some());
+        assert!(history.records[1].next_check_date.is_none());
+    }
+
+    #[test]
+    fn confirming_again_keeps_the_first_confirmation_date() {
+        let mut r = record("spokeo", "run-1", BrokerSubmissionStatus::PendingVerification, 0);
+        let first = Utc::now();
+        set_status(&mut r, BrokerSubmissionStatus::Confirmed, first);
+        set_status(&mut r, BrokerSubmissionStatus::Confirmed, first + Duration::days(2));
+        assert_eq!(r.confirmed_at, Some(first));
+        set_status(&mut r, BrokerSubmissionStatus::ReListed, first + Duration::days(40));
+        assert_eq!(r.status, BrokerSubmissionStatus::ReListed);
+    }
+
+    #[test]
+    fn journal_replay_upserts_and_skips_torn_entry() {
+        let original = record("spokeo", "run-1", BrokerSubmissionStatus::Submitted, 0);
+        let mut index = HistoryIndex::new(SubmissionHistory { records: vec![original.clone()] });
+
+        let mut updated = original.clone();
+        updated.status = BrokerSubmissionStatus::Confirmed;
+        let added = record("whitepages", "run-1", BrokerSubmissionStatus::Submitted, 0);
+        let journal = format!(
+            "{}\n{}\n{{\"id\":\"cut-off",
+            serde_json::to_string(&updated).unwrap(),
+            serde_json::to_string(&added).unwrap()
+        );
+
+        assert_eq!(replay(&mut index, &journal), 2);
+        assert_eq!(index.history.records.len(), 2);
+        assert_eq!(index.history.records[0].status, BrokerSubmissionStatus::Confirmed);
+    }
+
+    #[test]
+    fn appends_after_a_torn_journal_line() {
+        let path = std::env::temp_dir().join(format!("journal-{}.tmp", uuid::Uuid::new_v4()));
+        let before = record("spokeo", "run-1", BrokerSubmissionStatus::Submitted, 0);
+        let after = record("whitepages", "run-1", BrokerSubmissionStatus::Submitted, 0);
+        fs::write(&path, format!("{}\n{{\"id\":\"cut-off", serde_json::to_string(&before).unwrap())).unwrap();
+
+        append_lines(&path, &format!("{}\n", serde_json::to_string(&after).unwrap())).unwrap();
+        let journal = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut index = HistoryIndex::default();
+        assert_eq!(replay(&mut index, &journal), 2);
+        assert!(index.by_id.contains_key(&before.id) && index.by_id.contains_key(&after.id));
+    }
+
+    #[test]
+    fn latest_index_follows_submission_dates() {
+        let older = record("spokeo", "run-1", BrokerSubmissionStatus::Failed, 0);
+        let mut newer = record("spokeo", "run-2", BrokerSubmissionStatus::Submitted, 5);
+        let mut index = HistoryIndex::new(SubmissionHistory { records: vec![older.clone(), newer.clone()] });
+        assert_eq!(index.history.records[index.latest["spokeo"]].id, newer.id);
+
+        newer.submitted_at = older.submitted_at - Duration::minutes(10);
+        index.upsert(newer);
+        assert_eq!(index.history.records[index.latest["spokeo"]].id, older.id);
+    }
+}
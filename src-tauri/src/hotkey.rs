@@ -0,0 +1,46 @@
+use crate::engine::EngineState;
+use crate::settings;
+use tauri::{Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// Check that an accelerator string (e.g. `CommandOrControl+Shift+Enter`) is one the
+/// OS can register.
+pub fn validate(accelerator: &str) -> Result<(), String> {
+    accelerator
+        .parse::<Shortcut>()
+        .map(|_| ())
+        .map_err(|e| format!("Invalid shortcut \"{}\": {}", accelerator, e))
+}
+
+/// Register the user's continue hotkey for the length of a run. The key is only held
+/// while a run is active so it doesn't shadow other apps the rest of the time.
+pub fn register_for_run(app: &tauri::AppHandle) {
+    let Some(accelerator) = settings::load(app).ok().and_then(|s| s.continue_hotkey) else {
+        return;
+    };
+    let result = app.global_shortcut().on_shortcut(accelerator.as_str(), |app, _shortcut, event| {
+        if event.state != ShortcutState::Pressed {
+            return;
+        }
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            let state = app.state::<EngineState>();
+            let guard = state.0.lock().await;
+            if let Some(engine) = guard.as_ref() {
+                // Only answers a pending prompt; a no-op while the run is busy
+                if engine.signal_user_action("continue".to_string()).await {
+                    let _ = app.emit("user-action-resolved", ());
+                }
+            }
+        });
+    });
+    if let Err(e) = result {
+        eprintln!("[hotkey] Failed to register {}: {}", accelerator, e);
+    }
+}
+
+pub fn unregister(app: &tauri::AppHandle) {
+    if let Err(e) = app.global_shortcut().unregister_all() {
+        eprintln!("[hotkey] Failed to unregister: {}", e);
+    }
+}
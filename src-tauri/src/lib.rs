@@ -1,19 +1,68 @@
+mod auto_fill;
+mod broker_difficulty;
+mod broker_notes;
+mod broker_responses;
 mod browser;
+mod browser_detection;
+mod browser_proxy;
+mod bundled_chromium;
 mod commands;
+mod complaint;
+mod consent_banners;
+mod credentials;
 mod crypto;
+mod deadlines;
+mod disposable_inbox;
+mod done_detection;
+mod email_aliases;
 mod engine;
+mod evidence;
+mod field_matching;
+mod firefox;
+mod har;
 mod history;
+mod hotkey;
+mod likelihood;
+mod listing_match;
 mod local_playbooks;
 mod models;
+mod pacing;
+mod pii_scan;
+mod playbook_draft;
 mod playbook_validation;
 mod playbook_verification;
 mod playbook_api;
+mod playbook_reliability;
+mod playbook_subscriptions;
+mod playbook_votes;
+mod profile_format;
+mod profile_import;
+mod profile_validation;
 mod recorder;
+mod recovery_phrase;
+mod region;
 mod registry_cache;
+mod relisting_alerts;
+mod removal_verification;
+mod request_letter;
+mod run_plan;
+mod run_presets;
+mod run_summary;
+mod run_windows;
 mod secrets;
+mod selector_repair;
+mod settings;
+mod sms_relay;
+mod step_cursor;
+mod step_descriptions;
+mod step_phases;
+mod submission_receipts;
 mod submission_tracker;
+#[cfg(test)]
+mod test_fixtures;
+mod totp;
 
-use commands::{brokers, history as history_cmd, optout, playbooks, profile};
+use commands::{brokers, history as history_cmd, optout, playbooks, profile, settings as settings_cmd};
 use engine::EngineState;
 use recorder::RecorderState;
 use secrets::SecretsCache;
@@ -27,6 +76,8 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .manage(EngineState(Arc::new(Mutex::new(None))))
         .manage(RecorderState::new())
         .setup(|app| {
@@ -34,7 +85,42 @@ pub fn run() {
             if let Err(e) = secrets.load() {
                 eprintln!("Warning: Failed to load secrets from keychain: {}", e);
             }
+            match secrets.get_device_key() {
+                Ok(seed) => {
+                    if let Err(e) = playbook_api::init_device_key(&seed) {
+                        eprintln!("Warning: Failed to initialize device key: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Warning: No device key available: {}", e),
+            }
+            let needs_registration = !secrets.is_device_registered();
             app.manage(secrets);
+
+            match settings::load(app.handle()) {
+                Ok(s) => playbook_api::set_api_proxy(s.api_proxy),
+                Err(e) => eprintln!("Warning: Failed to load settings: {}", e),
+            }
+
+            // Watch subscribed brokers for new or better playbooks
+            tauri::async_runtime::spawn(playbook_subscriptions::run_background_checks(app.handle().clone()));
+
+            // Recheck confirmed removals as they come due, inside the user's run windows
+            tauri::async_runtime::spawn(run_windows::run_background_rechecks(app.handle().clone()));
+
+            // Register this device's public key with the API on first run
+            if needs_registration {
+                let handle = app.handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    match playbook_api::register_device().await {
+                        Ok(()) => {
+                            if let Err(e) = handle.state::<SecretsCache>().mark_device_registered() {
+                                eprintln!("Warning: Failed to save device registration: {}", e);
+                            }
+                        }
+                        Err(e) => eprintln!("Warning: Device registration failed: {}", e),
+                    }
+                });
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -42,30 +128,82 @@ pub fn run() {
             profile::save_profile,
             profile::get_profile,
             profile::delete_profile,
+            profile::validate_profile,
+            profile::import_profile,
+            profile::export_recovery_phrase,
+            profile::import_recovery_phrase,
             // Brokers
             brokers::get_brokers,
             brokers::sync_registry,
+            brokers::get_request_email,
+            brokers::assess_broker_likelihood,
+            brokers::get_broker_credential,
+            brokers::save_broker_credential,
+            brokers::set_broker_totp,
+            brokers::delete_broker_credential,
             // Opt-out
             optout::check_chrome_installed,
+            optout::get_bundled_chromium,
+            optout::download_chromium,
+            optout::preview_run_plan,
+            optout::preflight_check,
             optout::start_opt_out_run,
+            optout::retry_broker_from_step,
+            optout::rerun_relisted_broker,
             optout::continue_opt_out,
+            optout::copy_profile_value,
             optout::cancel_opt_out,
             optout::get_run_status,
+            optout::get_run_presets,
+            optout::save_run_preset,
+            optout::delete_run_preset,
             // History
             history_cmd::get_submissions,
             history_cmd::get_latest_submissions,
+            history_cmd::get_due_rechecks,
             history_cmd::get_relisting_alerts,
+            history_cmd::snooze_relisting_alert,
+            history_cmd::dismiss_relisting_alert,
+            history_cmd::get_request_deadlines,
+            history_cmd::get_overdue_requests,
+            history_cmd::build_complaint_package,
             history_cmd::update_submission_status,
+            history_cmd::bulk_update_submission_status,
+            history_cmd::bulk_set_next_check_date,
+            history_cmd::record_manual_submission,
+            history_cmd::repair_history,
+            history_cmd::verify_removal,
+            history_cmd::get_recheck_schedule,
+            history_cmd::get_evidence,
+            history_cmd::read_evidence,
+            history_cmd::get_network_log_records,
+            history_cmd::save_network_log,
+            history_cmd::get_receipt_records,
+            history_cmd::save_submission_receipt,
+            history_cmd::get_broker_note,
+            history_cmd::get_broker_notes,
+            history_cmd::set_broker_note,
+            history_cmd::get_email_aliases,
+            history_cmd::find_email_alias,
+            history_cmd::import_broker_email,
+            history_cmd::get_broker_responses,
             // Playbooks
             playbooks::start_recording,
             playbooks::stop_recording,
+            playbooks::force_reset_recorder,
+            playbooks::get_recording_status,
             playbooks::get_recorded_actions,
             playbooks::mark_captcha_step,
             playbooks::mark_user_prompt_step,
             playbooks::fetch_playbooks,
             playbooks::fetch_playbook_detail,
+            playbooks::fetch_contributor_playbooks,
+            playbooks::get_own_contributor,
+            playbooks::claim_contributor_handle,
             playbooks::submit_playbook,
             playbooks::vote_on_playbook,
+            playbooks::get_playbook_votes,
+            playbooks::import_playbook_votes,
             playbooks::report_playbook_outcome,
             // Local playbooks
             playbooks::save_local_playbook,
@@ -79,8 +217,22 @@ pub fn run() {
             playbooks::fetch_changelog,
             // Playbook reports
             playbooks::fetch_playbook_reports,
+            playbooks::fetch_playbook_history,
+            // Playbook subscriptions
+            playbooks::get_playbook_subscriptions,
+            playbooks::set_playbook_subscription,
+            playbooks::check_playbook_updates,
             // Broker suggestions
             playbooks::suggest_broker,
+            // Settings
+            settings_cmd::get_settings,
+            settings_cmd::save_settings,
+            settings_cmd::test_sms_relay,
+            settings_cmd::test_disposable_inbox,
+            settings_cmd::has_ai_api_key,
+            settings_cmd::set_ai_api_key,
+            settings_cmd::set_browser_proxy,
+            settings_cmd::has_browser_proxy_password,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,236 @@
+use crate::models::{Broker, BrokerLikelihood, Likelihood, LikelihoodAssessment, Profile};
+use crate::region;
+use chrono::{Datelike, NaiveDate};
+
+/// Most brokers suggested for a first run, so it stays short enough to finish.
+const MAX_RECOMMENDED: usize = 12;
+/// Scores at or above these are "high" and "medium" likelihood.
+const HIGH_SCORE: i32 = 60;
+const MEDIUM_SCORE: i32 = 40;
+
+/// How often a category's brokers list a typical US adult, before adjustments.
+fn base_score(category: &str) -> (i32, &'static str) {
+    match category {
+        "people-search" => (60, "People-search sites list most US adults from public records"),
+        "background-check" => (55, "Background-check sites compile court, property and address records"),
+        "data-aggregator" => (45, "Data aggregators buy consumer data in bulk"),
+        "identity" => (40, "Identity services match records across many sources"),
+        "marketing" => (35, "Marketing lists mostly hold people who shopped or subscribed somewhere"),
+        _ => (30, "Little is known about this broker's coverage"),
+    }
+}
+
+/// Whether the category builds profiles from public records, which grow with age and moves.
+fn uses_public_records(category: &str) -> bool {
+    matches!(category, "people-search" | "background-check")
+}
+
+fn age_on(dob: &str, today: NaiveDate) -> Option<i32> {
+    let dob = NaiveDate::parse_from_str(dob.trim(), "%Y-%m-%d").ok()?;
+    let mut age = today.year() - dob.year();
+    if (today.month(), today.day()) < (dob.month(), dob.day()) {
+        age -= 1;
+    }
+    (age >= 0).then_some(age)
+}
+
+fn age_bracket(age: i32) -> &'static str {
+    match age {
+        ..=24 => "18-24",
+        25..=34 => "25-34",
+        35..=49 => "35-49",
+        50..=64 => "50-64",
+        _ => "65+",
+    }
+}
+
+/// States the user has lived in, current first, as uppercase codes.
+fn user_states(profile: &Profile) -> Vec<String> {
+    let mut states: Vec<String> = Vec::new();
+    for state in std::iter::once(&profile.state).chain(profile.previous_addresses.iter().map(|a| &a.state)) {
+        let state = state.trim().to_ascii_uppercase();
+        if !state.is_empty() && !states.contains(&state) {
+            states.push(state);
+        }
+    }
+    states
+}
+
+fn score_broker(broker: &Broker, profile: &Profile, age: Option<i32>, states: &[String]) -> (i32, Vec<String>) {
+    let (mut score, reason) = base_score(&broker.category);
+    let mut reasons = vec![reason.to_string()];
+
+    if uses_public_records(&broker.category) {
+        match age {
+            Some(a) if a < 25 => {
+                score -= 20;
+                reasons.push("Younger adults have fewer public records".to_string());
+            }
+            Some(a) if a < 35 => score -= 5,
+            Some(_) => {
+                score += 10;
+                reasons.push("Older adults have longer public-record histories".to_string());
+            }
+            None => {}
+        }
+        let moves = profile.previous_addresses.len().min(3) as i32;
+        if moves > 0 {
+            score += moves * 5;
+            reasons.push("Past addresses add property and address records".to_string());
+        }
+    }
+
+    if !broker.coverage_states.is_empty() {
+        match states.iter().find(|s| broker.coverage_states.iter().any(|c| c.eq_ignore_ascii_case(s))) {
+            Some(state) => {
+                score += 15;
+                reasons.push(format!("Concentrates on {}, where you have lived", state));
+            }
+            None => {
+                score -= 40;
+                reasons.push("Mostly covers states you haven't lived in".to_string());
+            }
+        }
+    }
+
+    (score.clamp(0, 100), reasons)
+}
+
+/// Estimate which brokers most likely list the user from their age, states and the
+/// registry's categories, and pick a short first run of the likeliest ones. A broker
+/// whose network parent is in the registry is left to the parent's opt-out, and one
+/// that doesn't serve the user's country is never suggested.
+pub fn assess(profile: &Profile, brokers: &[Broker], today: NaiveDate) -> LikelihoodAssessment {
+    let age = age_on(&profile.dob, today);
+    let states = user_states(profile);
+    let country = region::country(profile);
+
+    let mut results: Vec<(BrokerLikelihood, &Broker)> = brokers
+        .iter()
+        .map(|broker| {
+            let (score, mut reasons) = if region::serves(broker, &country) {
+                score_broker(broker, profile, age, &states)
+            } else {
+                (0, vec![format!("Doesn't take requests from residents of {}", country)])
+            };
+            let parent = broker.network.as_deref().and_then(|network| {
+                brokers
+                    .iter()
+                    .find(|b| b.network_parent && b.id != broker.id && b.network.as_deref() == Some(network))
+            });
+            if let Some(parent) = parent {
+                reasons.push(format!("Covered by opting out of {}", parent.name));
+            }
+            let likelihood = if score >= HIGH_SCORE {
+                Likelihood::High
+            } else if score >= MEDIUM_SCORE {
+                Likelihood::Medium
+            } else {
+                Likelihood::Low
+            };
+            let entry = BrokerLikelihood {
+                broker_id: broker.id.clone(),
+                broker_name: broker.name.clone(),
+                likelihood,
+                score: score as u8,
+                reasons,
+                // Provisional; trimmed to MAX_RECOMMENDED below
+                recommended: likelihood == Likelihood::High && parent.is_none(),
+            };
+            (entry, broker)
+        })
+        .collect();
+
+    // Likeliest first; among equals, easier opt-outs first
+    let difficulty_rank = |b: &Broker| match b.difficulty.as_str() {
+        "easy" => 0,
+        "medium" => 1,
+        _ => 2,
+    };
+    results.sort_by_key(|(entry, broker)| (std::cmp::Reverse(entry.score), difficulty_rank(broker)));
+
+    let mut recommended_ids = Vec::new();
+    for (entry, _) in results.iter_mut() {
+        if entry.recommended && recommended_ids.len() < MAX_RECOMMENDED {
+            recommended_ids.push(entry.broker_id.clone());
+        } else {
+            entry.recommended = false;
+        }
+    }
+
+    LikelihoodAssessment {
+        age_bracket: age.map(|a| age_bracket(a).to_string()),
+        brokers: results.into_iter().map(|(entry, _)| entry).collect(),
+        recommended_ids,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PreviousAddress;
+    use crate::test_fixtures;
+
+    fn broker(id: &str, category: &str, difficulty: &str) -> Broker {
+        Broker { category: category.to_string(), difficulty: difficulty.to_string(), ..test_fixtures::broker(id) }
+    }
+
+    fn profile(dob: &str) -> Profile {
+        Profile { state: "ca".to_string(), dob: dob.to_string(), ..test_fixtures::profile() }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn ranks_people_search_first_and_recommends_easy_ones() {
+        let brokers = vec![
+            broker("ads", "marketing", "easy"),
+            broker("hard", "people-search", "hard"),
+            broker("easy", "people-search", "easy"),
+        ];
+        let result = assess(&profile("1970-07-01"), &brokers, today());
+        assert_eq!(result.age_bracket.as_deref(), Some("50-64"));
+        let ids: Vec<&str> = result.brokers.iter().map(|b| b.broker_id.as_str()).collect();
+        assert_eq!(ids, vec!["easy", "hard", "ads"]);
+        assert_eq!(result.recommended_ids, vec!["easy", "hard"]);
+        assert_eq!(result.brokers[2].likelihood, Likelihood::Low);
+    }
+
+    #[test]
+    fn adjusts_for_age_states_and_networks() {
+        let mut regional = broker("regional", "people-search", "easy");
+        regional.coverage_states = vec!["TX".to_string()];
+        let mut parent = broker("parent", "people-search", "easy");
+        parent.network = Some("net".to_string());
+        parent.network_parent = true;
+        let mut child = broker("child", "people-search", "easy");
+        child.network = Some("net".to_string());
+
+        let young = assess(&profile("2004-01-01"), &[broker("p", "people-search", "easy")], today());
+        assert_eq!(young.brokers[0].likelihood, Likelihood::Medium);
+
+        let mut p = profile("1980-01-01");
+        let result = assess(&p, &[regional.clone(), parent.clone(), child.clone()], today());
+        assert_eq!(result.recommended_ids, vec!["parent"]);
+        let regional_result = result.brokers.iter().find(|b| b.broker_id == "regional").unwrap();
+        assert_eq!(regional_result.likelihood, Likelihood::Low);
+
+        // Having lived in the broker's state makes it likely again
+        p.previous_addresses.push(PreviousAddress {
+            address: String::new(),
+            city: String::new(),
+            state: "TX".to_string(),
+            zip: String::new(),
+        });
+        let result = assess(&p, &[regional], today());
+        assert_eq!(result.brokers[0].likelihood, Likelihood::High);
+
+        // US-only brokers are never suggested to someone abroad
+        p.country = "DE".to_string();
+        let result = assess(&p, &[broker("p", "people-search", "easy")], today());
+        assert_eq!(result.brokers[0].score, 0);
+        assert!(result.recommended_ids.is_empty());
+    }
+}
@@ -0,0 +1,329 @@
+use crate::models::Profile;
+use crate::profile_format;
+use chrono::{Datelike, NaiveDate};
+
+/// Minimum score for a search result to count as the user's listing.
+pub const MATCH_THRESHOLD: f32 = 0.6;
+/// How far the best result must lead the runner-up to be picked without asking.
+const AMBIGUITY_MARGIN: f32 = 0.15;
+
+const NAME_WEIGHT: f32 = 0.4;
+const CITY_WEIGHT: f32 = 0.25;
+const STATE_WEIGHT: f32 = 0.1;
+const AGE_WEIGHT: f32 = 0.25;
+/// Listings showing an age this far from the user's are treated as someone else.
+const AGE_TOLERANCE: u32 = 1;
+
+/// Which search result a `find_and_click` step should open.
+#[derive(Debug, PartialEq)]
+pub enum ListingMatch {
+    /// One result is clearly the user's
+    Confident(usize),
+    /// Several results could be the user's, best first
+    Ambiguous(Vec<usize>),
+    NoMatch,
+}
+
+/// Score a search result's text against the profile, from 0 to 1.
+///
+/// The step's own profile value (`primary`) must appear or the result scores 0.
+/// Beyond that the score is the share of available signals that agree: full name,
+/// city, state, and age (from the date of birth). A listed age that doesn't match
+/// counts against the result.
+pub fn score_listing(text: &str, profile: &Profile, primary: &str, today: NaiveDate) -> f32 {
+    let text = text.to_lowercase();
+    let primary = primary.trim().to_lowercase();
+    if primary.is_empty() || !text.contains(&primary) {
+        return 0.0;
+    }
+
+    let mut earned = 0.0;
+    let mut possible = 0.0;
+
+    let first = profile.first_name.trim().to_lowercase();
+    let last = profile.last_name.trim().to_lowercase();
+    if !first.is_empty() || !last.is_empty() {
+        possible += NAME_WEIGHT;
+        let has_first = !first.is_empty() && text.contains(&first);
+        let has_last = !last.is_empty() && text.contains(&last);
+        earned += match (has_first, has_last) {
+            (true, true) => NAME_WEIGHT,
+            (false, true) => NAME_WEIGHT / 2.0,
+            _ => 0.0,
+        };
+    }
+
+    let city = profile.city.trim().to_lowercase();
+    if !city.is_empty() {
+        possible += CITY_WEIGHT;
+        if text.contains(&city) {
+            earned += CITY_WEIGHT;
+        }
+    }
+
+    if let Some(code) = profile_format::state_code(&profile.state) {
+        possible += STATE_WEIGHT;
+        let name = profile_format::STATES
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, n)| n.to_lowercase())
+            .unwrap_or_default();
+        let code = code.to_lowercase();
+        if words(&text).any(|w| w == code) || text.contains(&name) {
+            earned += STATE_WEIGHT;
+        }
+    }
+
+    if let Some(age) = age_on(&profile.dob, today) {
+        possible += AGE_WEIGHT;
+        let listed = listed_ages(&text);
+        if listed.iter().any(|a| a.abs_diff(age) <= AGE_TOLERANCE) {
+            earned += AGE_WEIGHT;
+        } else if !listed.is_empty() {
+            earned -= AGE_WEIGHT;
+        }
+    }
+
+    if possible == 0.0 {
+        return 1.0;
+    }
+    (earned / possible).max(0.0)
+}
+
+/// Decide which result to open from each result's score.
+pub fn pick_listing(scores: &[f32]) -> ListingMatch {
+    let mut ranked: Vec<usize> = (0..scores.len()).filter(|&i| scores[i] > 0.0).collect();
+    ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+    let Some(&best) = ranked.first() else {
+        return ListingMatch::NoMatch;
+    };
+    if scores[best] < MATCH_THRESHOLD {
+        return ListingMatch::Ambiguous(ranked);
+    }
+    match ranked.get(1) {
+        Some(&second) if scores[best] - scores[second] < AMBIGUITY_MARGIN => {
+            ranked.retain(|&i| scores[best] - scores[i] < AMBIGUITY_MARGIN);
+            ListingMatch::Ambiguous(ranked)
+        }
+        _ => ListingMatch::Confident(best),
+    }
+}
+
+/// What the user answered when asked which of several results is theirs.
+#[derive(Debug, PartialEq)]
+pub enum ListingChoice {
+    /// Open this result
+    Open(usize),
+    /// The user opened their listing in the browser themselves
+    OpenedByUser,
+    /// None of the results is the user's
+    NotListed,
+    /// No usable answer, so nothing is submitted
+    HeldBack,
+}
+
+/// Read the user's answer to a listing prompt: `listing:N` for one of `candidates`,
+/// `continue` once they've opened it themselves, or `skip` for none of them.
+pub fn listing_choice(decision: &str, candidates: &[usize]) -> ListingChoice {
+    if let Some(index) = decision.strip_prefix("listing:") {
+        return match index.parse::<usize>() {
+            Ok(i) if candidates.contains(&i) => ListingChoice::Open(i),
+            _ => ListingChoice::HeldBack,
+        };
+    }
+    match decision {
+        "continue" => ListingChoice::OpenedByUser,
+        "skip" => ListingChoice::NotListed,
+        _ => ListingChoice::HeldBack,
+    }
+}
+
+/// Whether the texts found for a result selector read like people-search results: most
+/// of them show an age or a "City, ST" place. Anything else (menus, ads, an empty list)
+/// suggests the selector no longer points at the results.
+pub fn looks_like_results(texts: &[String]) -> bool {
+    let rows: Vec<&String> = texts.iter().filter(|t| !t.trim().is_empty()).collect();
+    let listings = rows
+        .iter()
+        .filter(|t| !listed_ages(&t.to_lowercase()).is_empty() || shows_place(t))
+        .count();
+    !rows.is_empty() && listings * 2 > rows.len()
+}
+
+/// Whether `text` names a US state right after a comma, as in "Springfield, IL".
+fn shows_place(text: &str) -> bool {
+    text.split(',').skip(1).any(|after| {
+        let after = after.trim_start();
+        let code = after.split(|c: char| !c.is_alphanumeric()).next().unwrap_or_default();
+        profile_format::STATES.iter().any(|(c, name)| {
+            *c == code || after.to_lowercase().starts_with(&name.to_lowercase())
+        })
+    })
+}
+
+const STREET_SUFFIXES: &[&str] = &[
+    "st", "street", "ave", "avenue", "rd", "road", "dr", "drive", "ln", "lane", "blvd",
+    "boulevard", "ct", "court", "way", "pl", "place", "cir", "circle", "pkwy", "hwy",
+];
+
+/// Result text safe to show in the app: emails, phone numbers and street addresses are
+/// replaced with placeholders, leaving names, ages and cities to tell listings apart.
+pub fn display_text(text: &str) -> String {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let digit_count = |t: &str| t.chars().filter(|c| c.is_ascii_digit()).count();
+    let bare = |t: &str| t.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase();
+    let is_number = |t: &str| {
+        let t = bare(t);
+        !t.is_empty() && t.chars().all(|c| c.is_ascii_digit())
+    };
+
+    let mut out: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        if token.contains('@') && token.contains('.') {
+            out.push("[email]");
+            i += 1;
+            continue;
+        }
+        // "(555) 123-4567" splits into two tokens
+        let area_code = token.starts_with('(') && digit_count(token) == 3;
+        if area_code && tokens.get(i + 1).is_some_and(|t| digit_count(t) >= 7) {
+            out.push("[phone]");
+            i += 2;
+            continue;
+        }
+        if digit_count(token) >= 7 {
+            out.push("[phone]");
+            i += 1;
+            continue;
+        }
+        // A house number followed within a few words by a street suffix
+        if is_number(token) {
+            let suffix = (i + 1..tokens.len().min(i + 6))
+                .take_while(|&j| !is_number(tokens[j]))
+                .find(|&j| STREET_SUFFIXES.contains(&bare(tokens[j]).as_str()));
+            if let Some(end) = suffix {
+                out.push("[address]");
+                i = end + 1;
+                continue;
+            }
+        }
+        out.push(token);
+        i += 1;
+    }
+    out.join(" ")
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty())
+}
+
+/// Ages shown in a result, e.g. "Age 45", "age: 45", "45 years old", "45 yrs".
+fn listed_ages(text: &str) -> Vec<u32> {
+    let tokens: Vec<&str> = words(text).collect();
+    let mut ages = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let next = tokens.get(i + 1).copied();
+        let age = match (*token, next) {
+            ("age", Some(n)) => n.parse().ok(),
+            (n, Some("years" | "yrs" | "yo")) => n.parse().ok(),
+            _ => None,
+        };
+        if let Some(age) = age.filter(|a: &u32| (1..=120).contains(a)) {
+            ages.push(age);
+        }
+    }
+    ages
+}
+
+fn age_on(dob: &str, today: NaiveDate) -> Option<u32> {
+    let dob = NaiveDate::parse_from_str(dob.trim(), "%Y-%m-%d").ok()?;
+    let mut age = today.year() - dob.year();
+    if (today.month(), today.day()) < (dob.month(), dob.day()) {
+        age -= 1;
+    }
+    u32::try_from(age).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn profile() -> Profile {
+        Profile { last_name: "Smith".to_string(), dob: "1980-06-15".to_string(), ..test_fixtures::profile() }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()
+    }
+
+    fn score(text: &str) -> f32 {
+        score_listing(text, &profile(), "Smith", today())
+    }
+
+    #[test]
+    fn scores_full_match_highest() {
+        assert_eq!(score("Jane Smith, Age 44 - Springfield, IL"), 1.0);
+        assert!(score("Jane Smith - Springfield, IL") < 1.0);
+        assert_eq!(score("John Doe - Springfield, IL"), 0.0);
+    }
+
+    #[test]
+    fn penalizes_wrong_age() {
+        let right = score("Jane Smith, 44 years old, Chicago, IL");
+        let wrong = score("Jane Smith, 71 years old, Chicago, IL");
+        assert!(right > wrong);
+        assert!(wrong < MATCH_THRESHOLD);
+    }
+
+    #[test]
+    fn picks_clear_winner() {
+        let scores = [
+            score("John Smith, Age 62 - Peoria, IL"),
+            score("Jane Smith, Age 44 - Springfield, IL"),
+            score("Jane Smith, Age 23 - Austin, TX"),
+        ];
+        assert_eq!(pick_listing(&scores), ListingMatch::Confident(1));
+    }
+
+    #[test]
+    fn flags_close_candidates_as_ambiguous() {
+        let scores = [
+            score("Jane Smith - Springfield, IL"),
+            score("Jane A Smith - Springfield, IL"),
+        ];
+        assert_eq!(pick_listing(&scores), ListingMatch::Ambiguous(vec![0, 1]));
+        assert_eq!(pick_listing(&[0.0, 0.0]), ListingMatch::NoMatch);
+    }
+
+    #[test]
+    fn tells_result_rows_from_other_page_text() {
+        let rows = |texts: &[&str]| texts.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert!(looks_like_results(&rows(&["John Doe, Age 52 - Peoria, IL", "Mary Doe - Austin, Texas"])));
+        assert!(!looks_like_results(&rows(&["Home", "About us", "Contact"])));
+        assert!(!looks_like_results(&rows(&["Sign in, or create an account", ""])));
+        assert!(!looks_like_results(&[]));
+    }
+
+    #[test]
+    fn reads_the_answer_to_a_listing_prompt() {
+        let candidates = [2, 5];
+        assert_eq!(listing_choice("listing:5", &candidates), ListingChoice::Open(5));
+        assert_eq!(listing_choice("continue", &candidates), ListingChoice::OpenedByUser);
+        // "None of these" must never carry on to the opt-out form
+        assert_eq!(listing_choice("skip", &candidates), ListingChoice::NotListed);
+        assert_eq!(listing_choice("listing:3", &candidates), ListingChoice::HeldBack);
+        assert_eq!(listing_choice("", &candidates), ListingChoice::HeldBack);
+    }
+
+    #[test]
+    fn redacts_contact_details_for_display() {
+        assert_eq!(
+            display_text("Jane Smith, Age 44 123 N Main St. Springfield (555) 123-4567 jane@example.com"),
+            "Jane Smith, Age 44 [address] Springfield [phone] [email]"
+        );
+        assert_eq!(display_text("Jane Smith 555.123.4567"), "Jane Smith [phone]");
+    }
+}
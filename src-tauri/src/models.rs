@@ -20,6 +20,10 @@ pub struct Profile {
     pub alternate_phones: Vec<String>,
     #[serde(rename = "previousAddresses")]
     pub previous_addresses: Vec<PreviousAddress>,
+    /// Two-letter country of residence. Empty on profiles saved before it existed, which
+    /// are treated as US.
+    #[serde(default)]
+    pub country: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -30,6 +34,28 @@ pub struct PreviousAddress {
     pub zip: String,
 }
 
+/// A value from an imported contact file that couldn't be matched to a profile field
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportedField {
+    /// The column header or vCard property it came from
+    pub label: String,
+    pub value: String,
+}
+
+/// Result of importing a vCard or CSV file, for the user to review before saving
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfileImport {
+    pub profile: Profile,
+    pub unmatched: Vec<ImportedField>,
+}
+
+/// A validation problem with one profile field (e.g. `zip`, `alternateEmails[1]`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProfileFieldError {
+    pub field: String,
+    pub message: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct KnownField {
     pub label: String,
@@ -52,12 +78,85 @@ pub struct Broker {
     pub relist_days: Option<u32>,
     pub difficulty: String,
     pub last_verified: String,
+    /// Parent network (e.g. "peopleconnect") for brokers run by the same company
+    #[serde(default)]
+    pub network: Option<String>,
+    /// Opting out of this broker also covers the rest of its network
+    #[serde(default)]
+    pub network_parent: bool,
+    /// Brokers whose opt-out must happen first (e.g. a sister site where the listing is found)
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How this broker's form expects profile values, keyed by profile key
+    /// (e.g. `{"phone": "phone:digits", "dob": "date:%m/%d/%Y"}`). See `profile_format::apply_transform`.
+    #[serde(default)]
+    pub field_formats: std::collections::HashMap<String, String>,
+    /// State whose privacy law the broker applies to every deletion request (e.g. "CA" for
+    /// brokers that honor CCPA requests nationwide). Otherwise the user's state decides.
+    #[serde(default)]
+    pub jurisdiction: Option<String>,
+    /// States whose records the broker mostly covers. Empty means nationwide.
+    #[serde(default)]
+    pub coverage_states: Vec<String>,
+    /// Countries whose residents the broker takes requests from ("*" for anywhere).
+    /// Empty means US residents only.
+    #[serde(default)]
+    pub countries: Vec<String>,
+    /// Site quirks the browser has to work around for this broker
+    #[serde(default)]
+    pub automation: BrokerAutomation,
+}
+
+/// How a broker's site needs to be driven. Everything is off by default; a flag is only
+/// set once a site has been seen to need it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct BrokerAutomation {
+    /// The form ignores values set all at once, so they're typed a key at a time
+    #[serde(default)]
+    pub slow_typing: bool,
+    /// The opt-out form only works (or only exists) on the mobile site
+    #[serde(default)]
+    pub mobile_viewport: bool,
+    /// The site turns away headless browsers, so it's only ever opened in a visible one
+    #[serde(default)]
+    pub blocks_headless: bool,
+    /// Pages take a long time to load, so they're given longer to settle
+    #[serde(default)]
+    pub slow_load: bool,
+}
+
+/// Brokers that share a parent company. Computed from `Broker::network` in `get_brokers`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrokerNetwork {
+    pub id: String,
+    /// The broker whose opt-out covers the whole network, if any
+    pub parent_id: Option<String>,
+    pub broker_ids: Vec<String>,
+}
+
+/// Brokers sharing a `category` (e.g. "people-search"). Computed in `get_brokers`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrokerCategory {
+    pub id: String,
+    pub broker_ids: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct BrokerRegistry {
     pub version: String,
     pub brokers: Vec<Broker>,
+    #[serde(default)]
+    pub networks: Vec<BrokerNetwork>,
+    #[serde(default)]
+    pub categories: Vec<BrokerCategory>,
+    /// Brokers that don't take requests from residents of the user's country. Computed
+    /// from `Broker::countries` and the profile in `get_brokers`.
+    #[serde(default)]
+    pub out_of_region: Vec<String>,
+    /// Difficulty recalibrated from this device's own runs, for brokers run often enough.
+    /// Computed in `get_brokers`.
+    #[serde(default)]
+    pub local_difficulty: Vec<LocalDifficulty>,
 }
 
 // --- Phase 2: Opt-out automation types ---
@@ -74,9 +173,14 @@ pub struct FormField {
     pub placeholder: Option<String>,
     pub name: Option<String>,
     pub id: Option<String>,
+    #[serde(default)]
+    pub autocomplete: Option<String>,
     pub required: bool,
     pub options: Option<Vec<String>>,
     pub visible: bool,
+    /// Looks like a trap for bots: hidden from people, or named as one (see `browser::is_honeypot`)
+    #[serde(default)]
+    pub honeypot: bool,
 }
 
 /// A form structure extracted from a page
@@ -127,12 +231,26 @@ pub enum FormAction {
     Select {
         selector: String,
         value: String,
+        /// Profile value to choose instead of the fixed `value`
+        #[serde(default)]
+        profile_key: Option<String>,
     },
     #[serde(rename = "check")]
     Check {
         selector: String,
         checked: bool,
     },
+    /// Pick the radio button or checkbox, among those `selector` matches or contains,
+    /// whose label matches `value` or the profile value
+    #[serde(rename = "choose_radio")]
+    ChooseRadio {
+        selector: String,
+        value: String,
+        #[serde(default)]
+        profile_key: Option<String>,
+        #[serde(default)]
+        transform: Option<String>,
+    },
     #[serde(rename = "click")]
     Click {
         selector: String,
@@ -158,11 +276,34 @@ pub enum FormAction {
     ScrollTo {
         selector: String,
     },
+    /// Fill the username or password of the user's account with the broker
+    #[serde(rename = "fill_credential")]
+    FillCredential {
+        selector: String,
+        field: CredentialField,
+    },
+    /// Fill the current authenticator code for the user's account with the broker
+    #[serde(rename = "fill_totp")]
+    FillTotp {
+        selector: String,
+    },
+    /// Fill the code the broker texted to the user, read from their SMS relay if they have one
+    #[serde(rename = "fill_sms_code")]
+    FillSmsCode {
+        selector: String,
+    },
     #[serde(rename = "find_and_click")]
     FindAndClick {
         selector: String,
         profile_key: String,
     },
+    /// Click the `index`-th element matching `selector` whose text contains the profile value
+    #[serde(rename = "click_match")]
+    ClickMatch {
+        selector: String,
+        profile_key: String,
+        index: u32,
+    },
     #[serde(rename = "done")]
     Done {
         message: Option<String>,
@@ -197,6 +338,10 @@ pub enum RunStatus {
     Paused,
     Completed,
     Failed,
+    /// Stop requested; the run is still closing its pages and counts as in progress
+    Cancelling,
+    /// Stopped by the user; brokers in flight were left unsubmitted rather than failed
+    Cancelled,
 }
 
 /// What the user needs to do
@@ -213,6 +358,17 @@ pub enum UserActionRequired {
     ManualStep { message: String },
     #[serde(rename = "user_prompt")]
     UserPrompt { message: String, description: Option<String> },
+    /// A field only the user can fill. They type the value in the app (`value:{text}`)
+    /// for the engine to enter, or fill it in the browser and `continue`. `error`
+    /// explains why the last typed value couldn't be entered.
+    #[serde(rename = "manual_fill")]
+    ManualFill {
+        message: String,
+        description: Option<String>,
+        error: Option<String>,
+        /// Profile value the field probably wants, offered for copying
+        profile_key: Option<String>,
+    },
     #[serde(rename = "step_failed")]
     StepFailed {
         message: String,
@@ -220,6 +376,56 @@ pub enum UserActionRequired {
         step_position: u32,
         broker_name: String,
     },
+    /// A step's element is gone and the user's model proposed another one on the page.
+    /// `approve` retries the step with it and saves it to a local playbook; anything
+    /// else falls back to the usual `step_failed` prompt.
+    #[serde(rename = "approve_selector_repair")]
+    ApproveSelectorRepair {
+        message: String,
+        step_description: String,
+        step_position: u32,
+        failed_selector: String,
+        proposed_selector: String,
+    },
+    /// Several search results could be the user's; they pick one (`listing:{index}`),
+    /// open it themselves (`continue`), or `skip` the step.
+    #[serde(rename = "choose_listing")]
+    ChooseListing {
+        message: String,
+        candidates: Vec<ListingCandidate>,
+    },
+    /// Draft mode: the form is filled and the submit step is next. `continue` submits it,
+    /// `skip` leaves this broker without submitting.
+    #[serde(rename = "review_before_submit")]
+    ReviewBeforeSubmit {
+        message: String,
+        step_description: String,
+    },
+    /// Approval mode: the highlighted field is about to be filled with `fills_with` (a
+    /// description like "your email", never the value). `continue` fills it, `skip` leaves
+    /// it empty and moves on.
+    #[serde(rename = "approve_fill")]
+    ApproveFill {
+        message: String,
+        step_description: String,
+        fills_with: String,
+    },
+    /// Auto-fill isn't sure the highlighted field wants `profile_key`. `continue` fills it
+    /// in; anything else leaves the field for the user to fill.
+    #[serde(rename = "confirm_field_match")]
+    ConfirmFieldMatch {
+        message: String,
+        step_description: String,
+        profile_key: String,
+    },
+}
+
+/// A search result offered in a `ChooseListing` prompt, with contact details redacted
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ListingCandidate {
+    /// Position among the elements matching the step's selector
+    pub index: u32,
+    pub text: String,
 }
 
 /// Status of an individual broker submission
@@ -231,6 +437,40 @@ pub enum BrokerSubmissionStatus {
     Confirmed,
     Failed,
     ReListed,
+    /// The broker's search came back without the user, so there was nothing to opt out of
+    NotFound,
+}
+
+/// How an opt-out was submitted
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionMethod {
+    /// Submitted by an opt-out run in the app
+    #[default]
+    Automated,
+    /// Logged by the user after submitting the broker's web form by hand
+    ManualWeb,
+    ManualEmail,
+    ManualPhone,
+    ManualMail,
+}
+
+/// What a privacy request asks the broker for
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestType {
+    /// Delete the user's personal information (an opt-out)
+    #[default]
+    Deletion,
+    /// Disclose what personal information the broker holds (a data subject access request)
+    Access,
+}
+
+/// A ready-to-send privacy request email, built by `request_letter::request_email`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RequestEmail {
+    pub subject: String,
+    pub body: String,
 }
 
 /// A single opt-out submission record
@@ -244,6 +484,293 @@ pub struct SubmissionRecord {
     pub next_check_date: Option<DateTime<Utc>>,
     pub error_message: Option<String>,
     pub run_id: String,
+    #[serde(default)]
+    pub method: SubmissionMethod,
+    #[serde(default)]
+    pub last_removal_check: Option<RemovalCheck>,
+    /// Set when this broker was covered by its network parent's opt-out (parent broker ID)
+    #[serde(default)]
+    pub covered_by: Option<String>,
+    /// What this pass searched with when a broker is run once per identifier
+    /// (e.g. "Previous address: Peoria, IL"). `None` for the primary profile.
+    #[serde(default)]
+    pub searched_with: Option<String>,
+    /// Why a failed submission failed, when the engine could tell
+    #[serde(default)]
+    pub failure_reason: Option<FailureReason>,
+    /// What the page showed after the last step of an automated submission
+    #[serde(default)]
+    pub outcome_check: Option<OutcomeCheck>,
+    /// Whether the automation browser sent a Global Privacy Control signal with the submission
+    #[serde(default)]
+    pub gpc_sent: bool,
+    #[serde(default)]
+    pub request_type: RequestType,
+    /// Where a failed automated submission stopped, for `retry_broker_from_step`
+    #[serde(default)]
+    pub failed_step: Option<FailedStep>,
+}
+
+/// The playbook step an automated submission failed at
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FailedStep {
+    /// Playbook selection that ran ("local:{id}" or a community playbook ID)
+    pub playbook: String,
+    pub position: u32,
+}
+
+/// Where an open deletion request stands against its statutory deadline
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadlineStatus {
+    /// The broker still has time to respond
+    OnTrack,
+    /// Past the response deadline, but the broker may have taken the extension the law allows
+    Overdue,
+    /// Past the deadline including any extension
+    ExtensionLapsed,
+}
+
+/// The response deadline a privacy law sets for an open deletion request.
+/// Computed from history in `deadlines::request_deadlines`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RequestDeadline {
+    pub record_id: String,
+    pub broker_id: String,
+    pub broker_name: String,
+    /// State code of the law that applies (e.g. "CA")
+    pub jurisdiction: String,
+    /// Short name of the law (e.g. "CCPA")
+    pub statute: String,
+    pub citation: String,
+    pub regulator: String,
+    pub complaint_url: String,
+    pub submitted_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    /// The deadline if the broker takes the extension the law allows
+    pub extended_due_at: DateTime<Utc>,
+    pub status: DeadlineStatus,
+    /// Days past `due_at`, 0 while on track
+    pub days_overdue: i64,
+    /// What the user can do next, most useful first
+    pub suggestions: Vec<String>,
+}
+
+/// A complaint assembled for an overdue or refused request by `build_complaint_package`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComplaintPackage {
+    /// Folder holding `complaint.md` and the evidence files
+    pub folder: String,
+    pub recipient: String,
+    pub filing_url: String,
+    /// Short name of the law the complaint cites, if one applies
+    pub statute: Option<String>,
+    pub letter: String,
+    pub evidence_files: Vec<String>,
+}
+
+/// How the page after a playbook's last step reads
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PageOutcome {
+    /// A confirmation message or success URL
+    Confirmed,
+    /// An error message: the form was likely rejected
+    ErrorShown,
+    /// Nothing recognizable either way
+    Unknown,
+}
+
+/// Best-effort check of a submission's final page against generic confirmation signals
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OutcomeCheck {
+    pub outcome: PageOutcome,
+    /// The phrase or URL word that decided the outcome
+    pub matched: Option<String>,
+}
+
+/// Why an automated submission failed
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureReason {
+    /// The broker refused the browser: a 403, "access denied" or anti-bot block page
+    BlockedByBroker,
+    /// A playbook step failed on a page that loaded normally
+    StepFailed,
+    /// The broker wrote back turning the request down
+    RefusedByEmail,
+}
+
+/// Result of re-running a broker's search to check that a removal stuck
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RemovalCheck {
+    pub checked_at: DateTime<Utc>,
+    pub still_listed: bool,
+    /// Number of search results matching the profile
+    pub matches: u32,
+    /// Screenshot of the search results in the evidence vault
+    pub evidence_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelistingAlertStatus {
+    Open,
+    /// Hidden until `snoozed_until`, then open again
+    Snoozed,
+    Dismissed,
+    /// The opt-out was run again
+    Resolved,
+}
+
+/// A broker found listing the user again after a confirmed removal
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelistingAlert {
+    pub id: String,
+    pub broker_id: String,
+    pub broker_name: String,
+    /// The confirmed submission the listing came back after
+    pub record_id: String,
+    pub detected_at: DateTime<Utc>,
+    /// Search results matching the profile when it was detected
+    pub matches: u32,
+    /// Screenshot of the search results in the evidence vault
+    pub evidence_id: Option<String>,
+    pub suggested_action: String,
+    pub status: RelistingAlertStatus,
+    #[serde(default)]
+    pub snoozed_until: Option<DateTime<Utc>>,
+    /// Run started from the alert to opt out again
+    #[serde(default)]
+    pub rerun_id: Option<String>,
+}
+
+/// Storage wrapper for relisting alerts
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RelistingAlertStore {
+    pub alerts: Vec<RelistingAlert>,
+}
+
+/// An email address given to one broker in place of the user's own, so mail sent to it
+/// can be traced back to that broker.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EmailAlias {
+    pub address: String,
+    pub broker_id: String,
+    pub broker_name: String,
+    pub first_used: DateTime<Utc>,
+    /// Runs that filled it into the broker's form, oldest first
+    pub run_ids: Vec<String>,
+}
+
+/// What a broker's email about a request says
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrokerResponseKind {
+    /// The data was removed or the request was completed
+    Confirmation,
+    /// The broker wants a link clicked or ID sent before it acts
+    VerificationRequest,
+    Refusal,
+    Unrecognized,
+}
+
+/// A broker's email imported by `import_broker_email`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrokerResponse {
+    /// Message-ID of the email, so importing it again changes nothing
+    pub id: String,
+    pub file_name: String,
+    pub from: String,
+    pub subject: String,
+    pub received_at: Option<DateTime<Utc>>,
+    pub kind: BrokerResponseKind,
+    /// Broker and submission it was matched to, if any
+    pub broker_id: Option<String>,
+    pub broker_name: Option<String>,
+    pub record_id: Option<String>,
+    /// Status the submission was moved to because of it
+    pub new_status: Option<BrokerSubmissionStatus>,
+    /// The .eml in the evidence vault
+    pub evidence_id: Option<String>,
+    pub imported_at: DateTime<Utc>,
+}
+
+/// Storage wrapper for imported broker emails
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BrokerResponseStore {
+    pub responses: Vec<BrokerResponse>,
+}
+
+/// Storage wrapper for email aliases
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EmailAliasStore {
+    pub aliases: Vec<EmailAlias>,
+}
+
+/// Kind of file stored in the evidence vault
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceKind {
+    Screenshot,
+    /// Single-file web archive of the whole page, including text below the fold
+    Mhtml,
+    /// Network activity (HAR) of a failed attempt, kept for debugging
+    Har,
+    /// An email the broker sent about the request, as the .eml file it was imported from
+    Email,
+}
+
+/// Metadata for an encrypted evidence file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvidenceItem {
+    pub id: String,
+    /// Submission record the evidence belongs to
+    pub record_id: String,
+    pub kind: EvidenceKind,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// Storage wrapper for the evidence index
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EvidenceIndex {
+    pub items: Vec<EvidenceItem>,
+}
+
+/// Hash of one evidence file, as it was when a receipt was made
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReceiptEvidence {
+    pub evidence_id: String,
+    pub kind: EvidenceKind,
+    /// SHA-256 of the decrypted file, in hex
+    pub sha256: String,
+}
+
+/// Proof of when a submission was made, for a broker that disputes it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmissionReceipt {
+    pub record_id: String,
+    pub broker_id: String,
+    pub submitted_at: DateTime<Utc>,
+    pub evidence: Vec<ReceiptEvidence>,
+    /// The text that was signed and timestamped, listing the evidence hashes
+    pub statement: String,
+    /// SHA-256 of `statement`, in hex
+    pub digest: String,
+    /// Ed25519 signature of `statement` by this device's registered key, base64
+    pub signature: Option<String>,
+    pub public_key: Option<String>,
+    /// RFC 3161 time-stamp authority that stamped `digest`
+    pub timestamp_authority: Option<String>,
+    /// The authority's TimeStampResp (DER), base64
+    pub timestamp_token: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Storage wrapper for submission receipts
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReceiptStore {
+    pub receipts: Vec<SubmissionReceipt>,
 }
 
 /// Full submission history
@@ -252,6 +779,146 @@ pub struct SubmissionHistory {
     pub records: Vec<SubmissionRecord>,
 }
 
+/// What `repair_history` changed (or would change, in a dry run)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HistoryRepairReport {
+    pub duplicates_removed: usize,
+    pub next_check_dates_repaired: usize,
+    /// Records whose broker is no longer in the registry (left untouched)
+    pub unknown_broker_records: usize,
+    /// One human-readable line per change
+    pub details: Vec<String>,
+}
+
+/// A user's own note about a broker (e.g. "wants my old Ohio address")
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrokerNote {
+    pub broker_id: String,
+    pub note: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Storage wrapper for broker notes
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BrokerNoteStore {
+    pub notes: Vec<BrokerNote>,
+}
+
+/// Login for an account the user holds with a broker, for brokers that only take removal
+/// requests from account holders
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrokerCredential {
+    pub broker_id: String,
+    pub username: String,
+    pub password: String,
+    /// Base32 secret for brokers that require an authenticator app on the account
+    #[serde(default)]
+    pub totp_secret: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Which part of a broker credential a `fill_credential` step enters
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialField {
+    Username,
+    Password,
+}
+
+/// Storage wrapper for broker credentials
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BrokerCredentialStore {
+    pub credentials: Vec<BrokerCredential>,
+}
+
+/// Optional behaviour for an opt-out run
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RunOptions {
+    /// Repeat search-driven playbooks once per previous address in the profile
+    #[serde(default)]
+    pub search_previous_addresses: bool,
+    /// Repeat playbooks that use the email or phone once per alternate email/phone
+    #[serde(default)]
+    pub repeat_alternate_contacts: bool,
+    /// Leave out brokers whose last removal check found no listing
+    #[serde(default)]
+    pub skip_unlisted: bool,
+    /// Pause before each playbook's submit step so the user can check the filled form
+    #[serde(default)]
+    pub review_before_submit: bool,
+    /// Ask before each field is filled or option chosen
+    #[serde(default)]
+    pub approve_each_fill: bool,
+    /// Browser for this run; the one chosen in settings when `None`
+    #[serde(default)]
+    pub browser_mode: Option<BrowserMode>,
+    /// Connect directly for this run, even if settings have a proxy
+    #[serde(default)]
+    pub direct_connection: bool,
+    /// Pick up at this step position, replaying only the earlier steps that bring the page
+    /// back (see `run_plan::replays_on_resume`). Set by `retry_broker_from_step`.
+    #[serde(default)]
+    pub resume_from_step: Option<u32>,
+}
+
+/// How likely a broker is to list the user
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Likelihood {
+    High,
+    Medium,
+    Low,
+}
+
+/// One broker's estimated chance of listing the user, from `likelihood::assess`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrokerLikelihood {
+    pub broker_id: String,
+    pub broker_name: String,
+    pub likelihood: Likelihood,
+    /// 0-100, higher is more likely
+    pub score: u8,
+    pub reasons: Vec<String>,
+    /// Part of the suggested first run
+    pub recommended: bool,
+}
+
+/// Which brokers most likely list the user, for picking a first run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LikelihoodAssessment {
+    /// e.g. "35-49", when the profile has a date of birth
+    pub age_bracket: Option<String>,
+    /// Most likely first
+    pub brokers: Vec<BrokerLikelihood>,
+    pub recommended_ids: Vec<String>,
+}
+
+/// A named run setup (e.g. "Monthly maintenance") saved so it can be started in one click
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunPreset {
+    pub name: String,
+    #[serde(default)]
+    pub broker_ids: Vec<String>,
+    /// Categories are expanded when the run starts, so brokers added to them later are included
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub playbook_selections: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    pub force: bool,
+    #[serde(default)]
+    pub options: RunOptions,
+    #[serde(default = "Utc::now")]
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Storage wrapper for saved run presets
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RunPresetStore {
+    pub presets: Vec<RunPreset>,
+}
+
 /// Event payload for frontend progress updates
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct OptOutProgress {
@@ -264,6 +931,167 @@ pub struct OptOutProgress {
     pub brokers_total: usize,
     pub action_required: Option<UserActionRequired>,
     pub error: Option<String>,
+    /// Which phase of the playbook the current step is in, for playbooks with more than one
+    #[serde(default)]
+    pub phase: Option<PhaseProgress>,
+}
+
+/// Where a run is in a playbook's phases ("Phase 2 of 3: filling the form")
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct PhaseProgress {
+    pub phase: StepPhase,
+    /// 1-based
+    pub number: u32,
+    pub total: u32,
+}
+
+/// What kind of problem stopped a broker in a run, for the run summary
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureClass {
+    /// An element the playbook expects wasn't on the page
+    Selector,
+    /// A CAPTCHA was still unsolved when a step failed
+    CaptchaTimeout,
+    /// An anti-bot block or challenge page
+    Blocked,
+    /// The page didn't load: DNS, connection or timeout errors
+    Network,
+    /// The form went through but the page showed an error
+    Rejected,
+    /// No playbook selected, available or passing checks
+    NoPlaybook,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrokerRunOutcome {
+    Succeeded,
+    Failed,
+    /// Submitted recently, so not run
+    Skipped,
+    /// Handled by a network parent's opt-out earlier in the run
+    Covered,
+    Cancelled,
+    /// The search found no listing, so nothing was submitted
+    NotFound,
+}
+
+/// How one broker went in a run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrokerRunSummary {
+    pub broker_id: String,
+    pub broker_name: String,
+    pub outcome: BrokerRunOutcome,
+    /// Time spent on the broker, including waiting for the user
+    pub duration_ms: u64,
+    pub playbook_id: Option<String>,
+    /// `None` for local playbooks
+    pub playbook_version: Option<u32>,
+    pub failure_step: Option<u32>,
+    /// The phase `failure_step` is in, when the playbook has phases
+    #[serde(default)]
+    pub failure_phase: Option<StepPhase>,
+    pub failure_class: Option<FailureClass>,
+    pub error: Option<String>,
+}
+
+/// Payload of the `opt-out-complete` and `opt-out-cancelled` events
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    /// Brokers whose search found no listing of the user
+    pub not_found: usize,
+    /// Brokers in flight when the run was cancelled
+    pub cancelled: usize,
+    pub duration_ms: u64,
+    /// In run order; brokers not reached before a cancel are left out
+    pub brokers: Vec<BrokerRunSummary>,
+}
+
+/// Something `preview_run_plan` found that may stop or slow down a broker's opt-out
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunPlanIssueKind {
+    MissingSelection,
+    MissingPlaybook,
+    /// Signature check or step validation failed; the engine would skip it
+    RejectedPlaybook,
+    StalePlaybook,
+    RecentlySubmitted,
+    /// The playbook fills or searches with a profile field that's empty
+    MissingProfileFields,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunPlanIssue {
+    pub kind: RunPlanIssueKind,
+    pub message: String,
+}
+
+/// One broker in a previewed run, in execution order
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunPlanEntry {
+    pub broker_id: String,
+    pub broker_name: String,
+    pub playbook_id: Option<String>,
+    pub playbook_title: Option<String>,
+    pub captcha_steps: u32,
+    pub manual_steps: u32,
+    pub estimated_duration_ms: u64,
+    pub issues: Vec<RunPlanIssue>,
+}
+
+/// Result of `preview_run_plan`: what a run would do, before any browser launches
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunPlan {
+    pub entries: Vec<RunPlanEntry>,
+    /// Run-wide warnings (e.g. unmet broker dependencies)
+    pub warnings: Vec<String>,
+    pub estimated_duration_ms: u64,
+}
+
+/// An item of the `preflight_check` checklist
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightCheckKind {
+    Chrome,
+    Profile,
+    Network,
+    Playbooks,
+    DiskSpace,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightStatus {
+    Passed,
+    /// The run can start, but some brokers may fail
+    Warning,
+    /// The run would fail; fix this first
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PreflightCheck {
+    pub kind: PreflightCheckKind,
+    pub status: PreflightStatus,
+    pub message: String,
+    /// Per-item problems (e.g. invalid profile fields, brokers without a usable playbook)
+    pub details: Vec<String>,
+}
+
+/// Result of `preflight_check`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+    /// No check failed
+    pub ready: bool,
 }
 
 // --- Community Playbook types ---
@@ -283,6 +1111,24 @@ pub struct PlaybookStep {
     pub wait_after_ms: u32,
     #[serde(default)]
     pub optional: bool,
+    /// Other locators for the same element, tried in order when `selector` matches nothing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_selectors: Vec<String>,
+    /// Starts a phase that runs until the next step with one. Outside the signature like
+    /// `fallback_selectors`, but it only ever names one of a few fixed labels.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phase: Option<StepPhase>,
+}
+
+/// A named stage of a playbook, so progress reads as more than a stream of steps
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepPhase {
+    /// Looking up the user's listing
+    Search,
+    FillForm,
+    /// After the submit: confirmation pages and checks
+    Verify,
 }
 
 fn default_wait_after() -> u32 {
@@ -307,6 +1153,18 @@ pub struct Playbook {
     pub success_count: u32,
     pub failure_count: u32,
     pub created_at: String,
+    /// Who submitted it. Not covered by the signature, which only covers the steps.
+    #[serde(default)]
+    pub contributor: Option<Contributor>,
+}
+
+/// The anonymous author of community playbooks: an ID the server assigns to a device key,
+/// and the handle its owner claimed, if any.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Contributor {
+    pub id: String,
+    #[serde(default)]
+    pub handle: Option<String>,
 }
 
 /// Playbook summary (from GET /playbooks list).
@@ -328,6 +1186,20 @@ pub struct PlaybookSummary {
     pub failure_count: u32,
     pub score: i32,
     pub created_at: String,
+    #[serde(default)]
+    pub contributor: Option<Contributor>,
+    /// Number of CAPTCHA steps. Computed locally from `steps`.
+    #[serde(default)]
+    pub captcha_steps: u32,
+    /// Number of steps that need the user (prompts, manual fills/selects). Computed locally.
+    #[serde(default)]
+    pub manual_steps: u32,
+    /// Estimated unattended run time in milliseconds, from step waits. Computed locally.
+    #[serde(default)]
+    pub estimated_duration_ms: u64,
+    /// This user's own results with the playbook. Filled in locally.
+    #[serde(default)]
+    pub personal: Option<PlaybookReliability>,
     /// Used for signature verification; not sent to the frontend.
     #[serde(default, skip_serializing)]
     pub signature: Option<String>,
@@ -371,6 +1243,8 @@ pub struct PlaybookReport {
     pub failure_step: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<FailureReason>,
     pub app_version: String,
 }
 
@@ -421,6 +1295,63 @@ pub struct SubmissionTrackerStore {
     pub submissions: Vec<TrackedSubmission>,
 }
 
+// --- Personal playbook reliability ---
+
+/// How a playbook has done in this user's own runs, kept apart from community stats
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaybookReliability {
+    pub playbook_id: String,
+    pub successes: u32,
+    pub failures: u32,
+    /// Failures since the last success
+    pub consecutive_failures: u32,
+    pub last_run_at: DateTime<Utc>,
+}
+
+/// Storage wrapper for personal playbook reliability
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PlaybookReliabilityStore {
+    pub playbooks: Vec<PlaybookReliability>,
+}
+
+// --- Broker difficulty ---
+
+/// How this user's opt-outs at a broker have gone, counting only attempts that
+/// succeeded or failed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrokerOutcomeStats {
+    pub broker_id: String,
+    pub attempts: u32,
+    pub failures: u32,
+    /// CAPTCHAs met across all attempts
+    pub captchas: u32,
+    /// Total time the successful attempts took
+    pub success_secs: u64,
+    pub last_attempt_at: DateTime<Utc>,
+}
+
+/// Storage wrapper for per-broker outcomes
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BrokerOutcomeStore {
+    pub brokers: Vec<BrokerOutcomeStats>,
+}
+
+/// A broker's difficulty recalibrated from local outcomes, to use over the registry's
+/// `difficulty` once there's enough to go on
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LocalDifficulty {
+    pub broker_id: String,
+    /// "easy", "medium" or "hard", like `Broker::difficulty`
+    pub difficulty: String,
+    /// 0 (no trouble) to 100 (always fails)
+    pub score: u32,
+    pub attempts: u32,
+    pub failure_rate: f32,
+    /// Average time a successful opt-out took; None when none has succeeded
+    pub average_secs: Option<u64>,
+    pub captchas_per_attempt: f32,
+}
+
 // --- Registry Sync types ---
 
 /// Response from GET /registry/version
@@ -447,19 +1378,324 @@ pub struct PlaybookReportEntry {
     pub outcome: String,
     pub failure_step: Option<u32>,
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub failure_reason: Option<FailureReason>,
     pub app_version: String,
     pub created_at: String,
 }
 
+// --- Playbook subscription types ---
+
+/// A broker the user wants to hear about when its playbooks change
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaybookSubscription {
+    pub broker_id: String,
+    pub subscribed_at: DateTime<Utc>,
+    /// None until the first check, which only records what already exists
+    #[serde(default)]
+    pub last_checked_at: Option<DateTime<Utc>>,
+    /// Playbook ids seen so far
+    #[serde(default)]
+    pub known_playbook_ids: Vec<String>,
+    /// The top-scored playbook at the last check
+    #[serde(default)]
+    pub best_playbook_id: Option<String>,
+}
+
+/// Storage wrapper for playbook subscriptions
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PlaybookSubscriptionStore {
+    pub subscriptions: Vec<PlaybookSubscription>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybookUpdateKind {
+    /// A playbook that wasn't there at the last check
+    New,
+    /// An existing playbook now has the top score
+    BetterScore,
+}
+
+/// Emitted as `playbook-updates` when a subscribed broker's playbooks change
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaybookUpdate {
+    pub broker_id: String,
+    pub broker_name: String,
+    pub playbook_id: String,
+    pub version: u32,
+    pub score: i32,
+    pub kind: PlaybookUpdateKind,
+}
+
+// --- Playbook vote types ---
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteDirection {
+    Up,
+    Down,
+}
+
+/// A vote this device cast on a community playbook
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaybookVote {
+    pub playbook_id: String,
+    pub vote: VoteDirection,
+    pub voted_at: DateTime<Utc>,
+}
+
+/// Storage wrapper for this device's playbook votes
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PlaybookVoteStore {
+    pub votes: Vec<PlaybookVote>,
+}
+
+// --- Playbook history types ---
+
+/// One revision of a broker's playbooks from GET /playbooks/history, any status
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaybookRevision {
+    pub id: String,
+    pub version: u32,
+    #[serde(default)]
+    pub title: Option<String>,
+    /// "pending", "approved", "rejected" or "superseded"
+    pub status: String,
+    pub notes: Option<String>,
+    pub steps_count: u32,
+    pub score: i32,
+    pub success_count: u32,
+    pub failure_count: u32,
+    pub created_at: String,
+    /// Score snapshots, oldest first
+    #[serde(default)]
+    pub score_history: Vec<PlaybookScorePoint>,
+}
+
+/// A playbook's score on a given day
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaybookScorePoint {
+    pub date: String,
+    pub score: i32,
+}
+
 /// A single recorded user action during recording mode
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RecordedAction {
     pub action: String,
     pub selector: Option<String>,
+    /// Every locator found for the element, most durable first; `selector` is the first of them
+    #[serde(default)]
+    pub selector_candidates: Vec<String>,
     pub profile_key: Option<String>,
+    /// How sure the match of the field to `profile_key` is, from 0 to 1
+    #[serde(default)]
+    pub match_confidence: Option<f32>,
     pub value: Option<String>,
     pub url: Option<String>,
     pub element_text: Option<String>,
     pub label: Option<String>,
     pub timestamp: u64,
 }
+
+/// The playbook taking shape during a recording (payload of the `recorder-draft` event)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlaybookDraft {
+    pub steps: Vec<PlaybookStep>,
+    /// Problems to fix before the playbook can be submitted
+    pub issues: Vec<String>,
+}
+
+/// The recording session in progress, so the UI can pick it back up after a reload
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecordingSession {
+    pub broker_id: String,
+    pub broker_name: String,
+    /// Page the recording browser is on (None when it doesn't answer)
+    pub current_url: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub elapsed_secs: u64,
+    pub action_count: usize,
+    /// The playbook drafted from everything recorded so far
+    pub draft: PlaybookDraft,
+}
+
+// --- App settings ---
+
+/// Where opt-out runs open broker pages
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserMode {
+    /// A separate Chrome window with a tab per broker
+    #[default]
+    External,
+    /// A single browser window docked beside the app that every broker page reuses
+    Docked,
+}
+
+/// How the automation browser talks to its proxy
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyKind {
+    #[default]
+    Http,
+    Socks5,
+}
+
+/// A proxy the automation browser sends its traffic through
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BrowserProxy {
+    pub kind: ProxyKind,
+    pub host: String,
+    pub port: u16,
+    /// Login the proxy asks for, if any (HTTP proxies only). The password is kept in
+    /// the OS keychain, not here.
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+/// The engine a browser is built on, which decides how runs drive it
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserEngine {
+    /// Chrome, Chromium and Edge, driven over the Chrome DevTools Protocol
+    Chromium,
+    /// Firefox, driven over WebDriver BiDi
+    Gecko,
+}
+
+/// A browser found on this machine
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DetectedBrowser {
+    /// e.g. "Google Chrome Beta" or "Chromium (Flatpak)"
+    pub name: String,
+    pub path: String,
+    /// Runs in a Flatpak or Snap sandbox
+    pub sandboxed: bool,
+    pub engine: BrowserEngine,
+}
+
+/// The browsers the automation could use, and which one it will
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChromeStatus {
+    pub installed: bool,
+    pub browsers: Vec<DetectedBrowser>,
+    /// Path of the browser runs will use (None when nothing is installed)
+    pub selected: Option<String>,
+}
+
+/// The Chromium build the app can download for machines without a browser it can drive
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BundledChromiumStatus {
+    pub version: String,
+    /// This build of the app has a checked download for this system
+    pub available: bool,
+    pub installed: bool,
+    pub downloading: bool,
+}
+
+/// Progress of the Chromium download, sent as `chromium-download` events
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChromiumDownloadProgress {
+    pub downloaded: u64,
+    /// Size of the download, when the server says
+    pub total: Option<u64>,
+}
+
+/// A weekly stretch of time, in the computer's local time, when background work may run
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RunWindow {
+    /// Days it opens on, 0 = Monday to 6 = Sunday
+    pub days: Vec<u32>,
+    /// Hour it opens (0-23)
+    pub start_hour: u32,
+    /// Hour it closes (1-24). Earlier than `start_hour` runs past midnight into the next day.
+    pub end_hour: u32,
+}
+
+/// Where background rechecks stand: whether they may run now and how many are waiting
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RecheckSchedule {
+    pub window_open: bool,
+    /// When the next window opens (None while one is open, or if none ever does)
+    pub next_window: Option<DateTime<Utc>>,
+    /// Confirmed removals due for a recheck that haven't had one
+    pub queued: usize,
+}
+
+/// User preferences persisted by the backend (settings.json).
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AppSettings {
+    /// Proxy for opt-outta.com API traffic only (http, https, socks5 or socks5h URL).
+    /// The automation browser uses `browser_proxy` instead.
+    #[serde(default)]
+    pub api_proxy: Option<String>,
+    /// Proxy for the automation browser (None = connect directly). Runs can override it.
+    #[serde(default)]
+    pub browser_proxy: Option<BrowserProxy>,
+    #[serde(default)]
+    pub browser_mode: BrowserMode,
+    /// Browser the automation uses, from `check_chrome_installed` (None = the first found)
+    #[serde(default)]
+    pub browser_path: Option<String>,
+    /// Play a sound when a run starts waiting for the user
+    #[serde(default)]
+    pub alert_sound: bool,
+    /// Re-alert every this many minutes while a run keeps waiting (0 = alert once)
+    #[serde(default)]
+    pub alert_repeat_minutes: u32,
+    /// Global shortcut that answers "continue" while a run waits (None = off)
+    #[serde(default)]
+    pub continue_hotkey: Option<String>,
+    /// Send the Global Privacy Control signal from the automation browser
+    #[serde(default)]
+    pub send_gpc: bool,
+    /// The user's own SMS-forwarding relay, polled for codes brokers text (None = ask the user)
+    #[serde(default)]
+    pub sms_relay_url: Option<String>,
+    /// Bearer token the relay expects, if any
+    #[serde(default)]
+    pub sms_relay_token: Option<String>,
+    /// The user's disposable-inbox provider. Brokers that take an email are each given a
+    /// new address from it, and runs follow the confirmation links mailed there (None = off).
+    #[serde(default)]
+    pub disposable_inbox_url: Option<String>,
+    /// Bearer token the inbox provider expects, if any
+    #[serde(default)]
+    pub disposable_inbox_token: Option<String>,
+    /// RFC 3161 time-stamp authority that stamps each submission's receipt (None = the
+    /// receipt is only signed on this device). Only a hash is sent.
+    #[serde(default)]
+    pub timestamp_authority_url: Option<String>,
+    /// Ask a language model for a replacement when a step's selector stops matching.
+    /// Needs the user's own API key, which is kept in the OS keychain, not here.
+    #[serde(default)]
+    pub ai_selector_repair: bool,
+    /// OpenAI-compatible API the repair requests go to (None = OpenAI)
+    #[serde(default)]
+    pub ai_api_base: Option<String>,
+    /// Model to ask (None = `selector_repair::DEFAULT_MODEL`)
+    #[serde(default)]
+    pub ai_model: Option<String>,
+    /// Pause a random number of seconds in this range between brokers (0 and 0 = no pause)
+    #[serde(default)]
+    pub pace_min_secs: u32,
+    #[serde(default)]
+    pub pace_max_secs: u32,
+    /// Wait at least this long before going back to a site visited earlier in the run
+    #[serde(default)]
+    pub domain_cooldown_secs: u32,
+    /// Record each broker's network activity and keep it (as HAR) with the attempt when it fails
+    #[serde(default)]
+    pub capture_har: bool,
+    /// Fill email fields with a plus address naming the broker (jane+optoutta-spokeo@…)
+    #[serde(default)]
+    pub email_aliases: bool,
+    /// Check confirmed removals in the background once they're due
+    #[serde(default)]
+    pub auto_recheck: bool,
+    /// When background work may run (empty = any time). Due work waits for the next window.
+    #[serde(default)]
+    pub run_windows: Vec<RunWindow>,
+}
@@ -0,0 +1,108 @@
+use crate::broker_responses;
+use crate::models::AppSettings;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Longest pause allowed between brokers, and the longest per-site cooldown.
+pub const MAX_PAUSE_SECS: u32 = 300;
+pub const MAX_COOLDOWN_SECS: u32 = 3600;
+
+/// The site a broker's pages are on, so related brokers sharing one ("www.x.com",
+/// "optout.x.com") count as the same visit: the host's registrable domain, as
+/// `broker_responses::url_site` works it out ("x.co.uk" isn't lumped in with all of "co.uk").
+fn site(url: &str) -> Option<String> {
+    broker_responses::url_site(url)
+}
+
+/// Spaces broker visits out over a run: a random pause between brokers, and a cooldown
+/// before going back to a site visited earlier in the run.
+pub struct Pacer {
+    pause: (Duration, Duration),
+    cooldown: Duration,
+    /// Site of the broker being visited, if its URL has one
+    current: Option<String>,
+    /// Whether any broker has been visited yet this run
+    started: bool,
+    left_at: HashMap<String, Instant>,
+}
+
+impl Pacer {
+    pub fn new(settings: &AppSettings) -> Self {
+        let min = settings.pace_min_secs.min(MAX_PAUSE_SECS);
+        let max = settings.pace_max_secs.clamp(min, MAX_PAUSE_SECS);
+        Self {
+            pause: (Duration::from_secs(min as u64), Duration::from_secs(max as u64)),
+            cooldown: Duration::from_secs(settings.domain_cooldown_secs.min(MAX_COOLDOWN_SECS) as u64),
+            current: None,
+            started: false,
+            left_at: HashMap::new(),
+        }
+    }
+
+    /// Note that the broker visited last is done with as of `now`.
+    pub fn finish_visit(&mut self, now: Instant) {
+        if let Some(site) = self.current.take() {
+            self.left_at.insert(site, now);
+        }
+    }
+
+    /// How long to wait before visiting `url`: a pause anywhere in the configured range,
+    /// unless this is the first broker, or whatever is left of the site's cooldown if
+    /// that's longer. `jitter` is from 0 to 1 and picks where in the range the pause falls.
+    pub fn wait_before(&self, url: &str, now: Instant, jitter: f64) -> Duration {
+        let (min, max) = self.pause;
+        let pause = if !self.started { Duration::ZERO } else { min + (max - min).mul_f64(jitter.clamp(0.0, 1.0)) };
+        let cooldown = site(url)
+            .and_then(|s| self.left_at.get(&s))
+            .map_or(Duration::ZERO, |left| (*left + self.cooldown).saturating_duration_since(now));
+        pause.max(cooldown)
+    }
+
+    pub fn start_visit(&mut self, url: &str) {
+        self.current = site(url);
+        self.started = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pacer(min: u32, max: u32, cooldown: u32) -> Pacer {
+        Pacer::new(&AppSettings {
+            pace_min_secs: min,
+            pace_max_secs: max,
+            domain_cooldown_secs: cooldown,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn pauses_between_brokers_within_the_range() {
+        // A range set the wrong way round is just the minimum
+        assert_eq!(pacer(20, 5, 0).pause, (Duration::from_secs(20), Duration::from_secs(20)));
+        let mut pacer = pacer(10, 30, 0);
+        let now = Instant::now();
+        assert_eq!(pacer.wait_before("https://a.com/optout", now, 0.5), Duration::ZERO);
+        pacer.start_visit("https://a.com/optout");
+        pacer.finish_visit(now);
+        assert_eq!(pacer.wait_before("https://b.com/optout", now, 0.0), Duration::from_secs(10));
+        assert_eq!(pacer.wait_before("https://b.com/optout", now, 0.5), Duration::from_secs(20));
+        assert_eq!(pacer.wait_before("https://b.com/optout", now, 1.0), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn waits_out_the_cooldown_of_a_site_visited_earlier() {
+        let mut pacer = pacer(5, 5, 120);
+        let start = Instant::now();
+        pacer.start_visit("https://www.peoplefinder.com/optout");
+        pacer.finish_visit(start);
+        pacer.start_visit("https://other.com/remove");
+        pacer.finish_visit(start + Duration::from_secs(60));
+
+        let later = start + Duration::from_secs(90);
+        assert_eq!(pacer.wait_before("https://optout.peoplefinder.com/form", later, 0.0), Duration::from_secs(30));
+        assert_eq!(pacer.wait_before("https://third.com", later, 0.0), Duration::from_secs(5));
+        assert_eq!(pacer.wait_before("https://www.peoplefinder.com", start + Duration::from_secs(500), 0.0), Duration::from_secs(5));
+    }
+}
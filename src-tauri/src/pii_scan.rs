@@ -0,0 +1,301 @@
+use crate::models::{PlaybookSubmission, Profile, RecordedAction};
+use std::ops::Range;
+
+/// Kind of personal information found in text bound for the community API.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PiiKind {
+    ProfileValue,
+    Email,
+    Phone,
+    Ssn,
+}
+
+impl PiiKind {
+    fn label(self) -> &'static str {
+        match self {
+            PiiKind::ProfileValue => "a value from your profile",
+            PiiKind::Email => "an email address",
+            PiiKind::Phone => "a phone number",
+            PiiKind::Ssn => "a Social Security number",
+        }
+    }
+
+    fn placeholder(self) -> &'static str {
+        match self {
+            PiiKind::ProfileValue => "[redacted]",
+            PiiKind::Email => "[email]",
+            PiiKind::Phone => "[phone]",
+            PiiKind::Ssn => "[ssn]",
+        }
+    }
+}
+
+/// Profile values shorter than this (state codes, initials) match too much by coincidence.
+const MIN_PROFILE_VALUE_LEN: usize = 3;
+
+/// Characters allowed between the digits of a phone number or SSN.
+const DIGIT_SEPARATORS: &str = " -.()+";
+
+fn profile_values(profile: &Profile) -> Vec<String> {
+    let mut values = vec![
+        profile.first_name.clone(),
+        profile.last_name.clone(),
+        profile.email.clone(),
+        profile.phone.clone(),
+        profile.address.clone(),
+        profile.city.clone(),
+        profile.zip.clone(),
+        profile.dob.clone(),
+    ];
+    values.extend(profile.alternate_emails.iter().cloned());
+    values.extend(profile.alternate_phones.iter().cloned());
+    for prev in &profile.previous_addresses {
+        values.extend([prev.address.clone(), prev.city.clone(), prev.zip.clone()]);
+    }
+    values
+        .into_iter()
+        .map(|v| v.trim().to_ascii_lowercase())
+        .filter(|v| v.len() >= MIN_PROFILE_VALUE_LEN)
+        .collect()
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric()
+}
+
+/// Whole-word, case-insensitive occurrences of each profile value.
+fn profile_spans(text: &str, profile: &Profile) -> Vec<Range<usize>> {
+    let haystack = text.to_ascii_lowercase();
+    let bytes = haystack.as_bytes();
+    let mut spans = Vec::new();
+    for value in profile_values(profile) {
+        let mut from = 0;
+        while let Some(i) = haystack[from..].find(&value) {
+            let start = from + i;
+            let end = start + value.len();
+            let bounded_start = start == 0 || !is_word_byte(bytes[start - 1]);
+            let bounded_end = end == bytes.len() || !is_word_byte(bytes[end]);
+            if bounded_start && bounded_end {
+                spans.push(start..end);
+            }
+            from = start + 1;
+            while !haystack.is_char_boundary(from) {
+                from += 1;
+            }
+        }
+    }
+    spans
+}
+
+/// Tokens shaped like `local@domain.tld`.
+fn email_spans(text: &str) -> Vec<Range<usize>> {
+    let is_email_char = |c: char| c.is_ascii_alphanumeric() || "._%+-@".contains(c);
+    let mut spans = Vec::new();
+    let mut start: Option<usize> = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        if is_email_char(c) {
+            start.get_or_insert(i);
+            continue;
+        }
+        if let Some(s) = start.take() {
+            let token = text[s..i].trim_end_matches('.');
+            if let Some((local, domain)) = token.split_once('@') {
+                let tld = domain.rsplit('.').next().unwrap_or_default();
+                if !local.is_empty()
+                    && domain.contains('.')
+                    && !domain.contains('@')
+                    && tld.len() >= 2
+                    && tld.chars().all(|c| c.is_ascii_alphabetic())
+                {
+                    spans.push(s..s + token.len());
+                }
+            }
+        }
+    }
+    spans
+}
+
+/// Runs of digits and separators that read as a phone number or SSN.
+fn number_spans(text: &str) -> Vec<(Range<usize>, PiiKind)> {
+    let bytes = text.as_bytes();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() || (i > 0 && is_word_byte(bytes[i - 1])) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let mut end = i;
+        let mut j = i;
+        while j < bytes.len() && (bytes[j].is_ascii_digit() || DIGIT_SEPARATORS.as_bytes().contains(&bytes[j])) {
+            if bytes[j].is_ascii_digit() {
+                end = j + 1;
+            }
+            j += 1;
+        }
+        // A number glued to letters (an ID like "abc123...") is not read as a phone number
+        if end < bytes.len() && is_word_byte(bytes[end]) {
+            i = j.max(start + 1);
+            continue;
+        }
+        // Include an opening "(" or "+" so the whole number is replaced
+        let start = if start > 0 && (bytes[start - 1] == b'(' || bytes[start - 1] == b'+') { start - 1 } else { start };
+        let run = &text[start..end];
+        let digits: String = run.chars().filter(|c| c.is_ascii_digit()).collect();
+        let groups: Vec<usize> = run
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|g| !g.is_empty())
+            .map(|g| g.len())
+            .collect();
+        if groups == [3, 2, 4] && !run.contains(['(', ')', '+', '.']) {
+            spans.push((start..end, PiiKind::Ssn));
+        } else if digits.len() == 10 || (digits.len() == 11 && digits.starts_with('1')) {
+            spans.push((start..end, PiiKind::Phone));
+        }
+        i = j.max(start + 1);
+    }
+    spans
+}
+
+/// Every stretch of `text` that looks like personal information, sorted by position.
+pub fn find_pii(text: &str, profile: Option<&Profile>) -> Vec<(Range<usize>, PiiKind)> {
+    let mut spans: Vec<(Range<usize>, PiiKind)> = Vec::new();
+    spans.extend(email_spans(text).into_iter().map(|r| (r, PiiKind::Email)));
+    spans.extend(number_spans(text));
+    if let Some(profile) = profile {
+        spans.extend(profile_spans(text, profile).into_iter().map(|r| (r, PiiKind::ProfileValue)));
+    }
+    spans.sort_by_key(|(r, _)| (r.start, std::cmp::Reverse(r.end)));
+    spans
+}
+
+/// `text` with every match from `find_pii` replaced by a placeholder for its kind.
+pub fn redact(text: &str, profile: Option<&Profile>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for (range, kind) in find_pii(text, profile) {
+        // Overlapping matches (an email that is also a profile value) are replaced once
+        if range.start < pos {
+            continue;
+        }
+        out.push_str(&text[pos..range.start]);
+        out.push_str(kind.placeholder());
+        pos = range.end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// Redact the free-text fields of a recorded action (the element text and label that step
+/// descriptions are generated from). Selectors and URLs are left intact so the step still
+/// works; `scan_submission` blocks them if they carry personal information.
+pub fn redact_action(action: &mut RecordedAction, profile: Option<&Profile>) {
+    for field in [&mut action.element_text, &mut action.label] {
+        if let Some(text) = field.as_mut() {
+            *text = redact(text, profile);
+        }
+    }
+}
+
+/// Where a playbook submission contains personal information, as "step 3 value (an email
+/// address)". The matched values themselves are never included.
+pub fn scan_submission(submission: &PlaybookSubmission, profile: Option<&Profile>) -> Vec<String> {
+    let mut findings = Vec::new();
+    let mut check = |place: String, text: Option<&str>| {
+        if let Some((_, kind)) = text.and_then(|t| find_pii(t, profile).into_iter().next()) {
+            findings.push(format!("{} ({})", place, kind.label()));
+        }
+    };
+    check("title".to_string(), submission.title.as_deref());
+    check("notes".to_string(), submission.notes.as_deref());
+    for step in &submission.steps {
+        let n = step.position;
+        check(format!("step {} selector", n), step.selector.as_deref());
+        for sel in &step.fallback_selectors {
+            check(format!("step {} fallback selector", n), Some(sel));
+        }
+        check(format!("step {} value", n), step.value.as_deref());
+        check(format!("step {} description", n), Some(&step.description));
+        check(format!("step {} instructions", n), step.instructions.as_deref());
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PlaybookStep, PreviousAddress};
+    use crate::test_fixtures;
+
+    fn profile() -> Profile {
+        Profile {
+            last_name: "Lee".to_string(),
+            previous_addresses: vec![PreviousAddress {
+                address: String::new(),
+                city: "Peoria".to_string(),
+                state: "IL".to_string(),
+                zip: String::new(),
+            }],
+            ..test_fixtures::profile()
+        }
+    }
+
+    #[test]
+    fn redacts_profile_values_as_whole_words() {
+        let p = profile();
+        assert_eq!(redact("Remove Jane LEE from Peoria", Some(&p)), "Remove [redacted] [redacted] from [redacted]");
+        // "Lee" inside another word is not the last name
+        assert_eq!(redact("Fleet sleeper", Some(&p)), "Fleet sleeper");
+    }
+
+    #[test]
+    fn redacts_generic_contact_patterns() {
+        assert_eq!(redact("Mail bob@mail.co now", None), "Mail [email] now");
+        assert_eq!(redact("Call (217) 555-0100.", None), "Call [phone].");
+        assert_eq!(redact("SSN 123-45-6789", None), "SSN [ssn]");
+        assert_eq!(
+            redact("div:nth-of-type(3) > a[href=\"/p/2024-01-15\"]", None),
+            "div:nth-of-type(3) > a[href=\"/p/2024-01-15\"]"
+        );
+        assert_eq!(redact("id=abc5551234567", None), "id=abc5551234567");
+    }
+
+    #[test]
+    fn redacts_step_errors() {
+        let p = profile();
+        assert_eq!(
+            redact("Click failed for a[title=\"Jane Lee, Springfield IL\"]: Element not found", Some(&p)),
+            "Click failed for a[title=\"[redacted] [redacted], [redacted] IL\"]: Element not found"
+        );
+    }
+
+    #[test]
+    fn reports_where_a_submission_leaks() {
+        let p = profile();
+        let step = PlaybookStep {
+            position: 2,
+            action: "click".to_string(),
+            selector: Some("a[title=\"Jane Lee\"]".to_string()),
+            profile_key: None,
+            value: None,
+            description: "Click the listing".to_string(),
+            instructions: None,
+            wait_after_ms: 500,
+            optional: false,
+            fallback_selectors: vec![],
+            phase: None,
+        };
+        let submission = PlaybookSubmission {
+            broker_id: "b".to_string(),
+            broker_name: "B".to_string(),
+            title: None,
+            notes: Some("Questions: help@broker.com".to_string()),
+            steps: vec![step],
+        };
+        assert_eq!(
+            scan_submission(&submission, Some(&p)),
+            vec!["notes (an email address)", "step 2 selector (a value from your profile)"]
+        );
+    }
+}
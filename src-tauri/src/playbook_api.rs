@@ -1,10 +1,24 @@
-use crate::models::{ApiEnvelope, BrokerRegistry, ChangelogEntry, Playbook, PlaybookReport, PlaybookReportEntry, PlaybookSubmission, PlaybookSubmitResponse, PlaybookSummary, RegistryVersionResponse};
-use ed25519_dalek::{SigningKey, Signer};
+use crate::models::{ApiEnvelope, BrokerRegistry, ChangelogEntry, Contributor, PageStructure, Playbook, PlaybookReport, PlaybookReportEntry, PlaybookRevision, PlaybookSubmission, PlaybookSubmitResponse, PlaybookSummary, RegistryVersionResponse};
+use ed25519_dalek::{Signer, SigningKey};
 use base64::{engine::general_purpose::STANDARD, Engine};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 const PRODUCTION_API_BASE: &str = "https://opt-outta.com/api/v1";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long a mirror that failed is tried last, after every other one.
+const MIRROR_DOWN_FOR: Duration = Duration::from_secs(5 * 60);
+
+/// Root CAs the API's certificate chain must end in. Only these roots are trusted for
+/// API traffic, so a hostile network can't substitute its own CA. Two independent CA
+/// families (Let's Encrypt, Google Trust Services) are pinned so the server can switch
+/// issuers without breaking installed apps. Rotations ship in app updates, which use the
+/// updater's own client and are never blocked by these pins.
+/// Release builds can replace the bundle via the `API_PINNED_ROOTS` env var at compile time.
+const PINNED_ROOTS_PEM: &str = match option_env!("API_PINNED_ROOTS") {
+    Some(pem) => pem,
+    None => include_str!("../certs/api_roots.pem"),
+};
 
 /// Sandbox configuration.
 /// Dev builds use sandbox by default. Release builds use production unless
@@ -17,30 +31,77 @@ const USE_SANDBOX: bool = if cfg!(debug_assertions) {
     option_env!("USE_SANDBOX").is_some()
 };
 
-fn api_base() -> &'static str {
-    if USE_SANDBOX { SANDBOX_API_URL } else { PRODUCTION_API_BASE }
-}
-
-/// Ed25519 signing key, embedded at compile time via `API_PRIVATE_KEY` env var.
-/// Falls back to a dummy key for local dev builds (API calls will be rejected by the server).
-/// Stores a Result to avoid panics on invalid keys (which would hang Tauri commands).
-static SIGNING_KEY: std::sync::LazyLock<Result<SigningKey, String>> =
-    std::sync::LazyLock::new(|| {
-        let key_b64 = match option_env!("API_PRIVATE_KEY") {
-            Some(k) if !k.is_empty() => k,
-            _ => "mDtbMauvCa/sJUI1HAQOLRPGqCg+D09JDI4g6AFnML6N7jL91TAk/LCAXW1ahl8AgYhf+7T6vr7XvlE5Df5Y0g==",
-        };
-        let key_bytes = STANDARD
-            .decode(key_b64)
-            .map_err(|e| format!("API_PRIVATE_KEY is not valid base64: {e}"))?;
-        // Sodium secret keys are 64 bytes (32-byte seed + 32-byte public key).
-        // ed25519-dalek expects just the 32-byte seed.
-        if key_bytes.len() < 32 {
-            return Err("API_PRIVATE_KEY must be at least 32 bytes".to_string());
-        }
-        let seed: [u8; 32] = key_bytes[..32].try_into().unwrap();
-        Ok(SigningKey::from_bytes(&seed))
-    });
+/// Fallback API hosts, tried in order when the primary can't be reached. Release builds
+/// set them via the comma-separated `API_MIRRORS` env var at compile time. Each must
+/// serve the same `/api/v1` paths (requests are signed over the path) with a certificate
+/// from the pinned roots.
+const API_MIRRORS: Option<&str> = option_env!("API_MIRRORS");
+
+/// The primary API base URL, then its mirrors.
+fn api_bases() -> Vec<&'static str> {
+    if USE_SANDBOX {
+        return vec![SANDBOX_API_URL];
+    }
+    let mirrors = API_MIRRORS
+        .unwrap_or_default()
+        .split(',')
+        .map(|m| m.trim().trim_end_matches('/'))
+        .filter(|m| !m.is_empty());
+    std::iter::once(PRODUCTION_API_BASE).chain(mirrors).collect()
+}
+
+/// When each API base that recently failed is worth trying first again.
+static MIRRORS_DOWN_UNTIL: std::sync::Mutex<Option<HashMap<&'static str, Instant>>> = std::sync::Mutex::new(None);
+
+/// `bases` in the order to try them: ones that are up in their usual order, then the
+/// ones that recently failed, soonest back first.
+fn try_order<'a>(bases: &[&'a str], down_until: &HashMap<&str, Instant>, now: Instant) -> Vec<&'a str> {
+    let (mut down, mut order): (Vec<&str>, Vec<&str>) =
+        bases.iter().partition(|b| down_until.get(*b).is_some_and(|until| *until > now));
+    down.sort_by_key(|b| down_until[b]);
+    order.extend(down);
+    order
+}
+
+fn mark_mirror(base: &'static str, up: bool) {
+    let mut guard = MIRRORS_DOWN_UNTIL.lock().unwrap();
+    let down_until = guard.get_or_insert_with(HashMap::new);
+    if up {
+        down_until.remove(base);
+    } else {
+        down_until.insert(base, Instant::now() + MIRROR_DOWN_FOR);
+    }
+}
+
+/// Per-device Ed25519 signing key. Generated on first run and kept in the OS keychain
+/// (see `SecretsCache`); installed here at startup via `init_device_key`.
+static DEVICE_SIGNING_KEY: std::sync::OnceLock<SigningKey> = std::sync::OnceLock::new();
+
+/// Install the device's signing key from its 32-byte seed. Later calls are ignored.
+pub fn init_device_key(seed: &[u8]) -> Result<(), String> {
+    let seed: [u8; 32] = seed
+        .try_into()
+        .map_err(|_| "Device key must be exactly 32 bytes".to_string())?;
+    let _ = DEVICE_SIGNING_KEY.set(SigningKey::from_bytes(&seed));
+    Ok(())
+}
+
+fn device_key() -> Result<&'static SigningKey, String> {
+    DEVICE_SIGNING_KEY
+        .get()
+        .ok_or_else(|| "Signing key error: device key not initialized".to_string())
+}
+
+/// Base64-encoded public half of the device key.
+pub fn device_public_key() -> Result<String, String> {
+    Ok(STANDARD.encode(device_key()?.verifying_key().to_bytes()))
+}
+
+/// Sign `message` with the device key, for records the user may need to prove came from
+/// this device. Returns the base64 signature.
+pub fn sign_attestation(message: &[u8]) -> Result<String, String> {
+    Ok(STANDARD.encode(device_key()?.sign(message).to_bytes()))
+}
 
 // ---------------------------------------------------------------------------
 // Ed25519 request signing
@@ -48,9 +109,7 @@ static SIGNING_KEY: std::sync::LazyLock<Result<SigningKey, String>> =
 
 /// Compute the X-Signature and X-Timestamp headers for a request.
 fn sign_request(method: &str, path: &str, body: &str) -> Result<(String, String), String> {
-    let key = SIGNING_KEY
-        .as_ref()
-        .map_err(|e| format!("Signing key error: {e}"))?;
+    let key = device_key()?;
 
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -66,70 +125,104 @@ fn sign_request(method: &str, path: &str, body: &str) -> Result<(String, String)
     Ok((sig_b64, timestamp))
 }
 
-/// Extract the path (+ query string) from a full URL.
-fn url_path(url: &str) -> String {
-    // URL is always api_base + suffix, so strip the origin
-    if let Some(pos) = url.find("/api/") {
-        url[pos..].to_string()
-    } else {
-        // Fallback: parse properly
-        match reqwest::Url::parse(url) {
-            Ok(parsed) => {
-                let mut path = parsed.path().to_string();
-                if let Some(q) = parsed.query() {
-                    path.push('?');
-                    path.push_str(q);
-                }
-                path
-            }
-            Err(_) => url.to_string(),
-        }
-    }
+/// The path a request to `base` + `suffix` is signed over, e.g. `/api/v1/registry`.
+fn signed_path(base: &str, suffix: &str) -> String {
+    let base_path = reqwest::Url::parse(base).map(|u| u.path().trim_end_matches('/').to_string()).unwrap_or_default();
+    format!("{}{}", base_path, suffix)
 }
 
-/// Send a signed GET request and return the response.
-/// In sandbox mode, uses bearer token auth instead of Ed25519.
-async fn signed_get(url: &str) -> Result<reqwest::Response, String> {
-    let client = reqwest::Client::builder()
-        .timeout(REQUEST_TIMEOUT)
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+/// User-configured proxy for API traffic (e.g. `socks5h://127.0.0.1:9050` for Tor).
+static API_PROXY: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
 
-    let mut req = client.get(url);
+/// Route all subsequent API requests through `proxy`, or connect directly when `None`.
+pub fn set_api_proxy(proxy: Option<String>) {
+    *API_PROXY.write().unwrap() = proxy;
+}
 
-    if USE_SANDBOX {
-        req = req.header("Authorization", format!("Bearer {}", SANDBOX_TOKEN));
-    } else {
-        let path = url_path(url);
-        let (signature, timestamp) = sign_request("GET", &path, "")?;
-        req = req.header("X-Signature", signature).header("X-Timestamp", timestamp);
+/// Build an HTTP client that only trusts the pinned API root CAs.
+fn http_client() -> Result<reqwest::Client, String> {
+    let roots = reqwest::Certificate::from_pem_bundle(PINNED_ROOTS_PEM.as_bytes())
+        .map_err(|e| format!("Invalid pinned API certificates: {}", e))?;
+    if roots.is_empty() {
+        return Err("No pinned API certificates configured".to_string());
     }
 
-    req.send().await.map_err(|e| format!("Request failed: {}", e))
-}
-
-/// Send a signed POST request with a JSON body and return the response.
-/// In sandbox mode, uses bearer token auth instead of Ed25519.
-async fn signed_post(url: &str, body: &str) -> Result<reqwest::Response, String> {
-    let client = reqwest::Client::builder()
+    let mut builder = reqwest::Client::builder()
         .timeout(REQUEST_TIMEOUT)
+        .tls_built_in_root_certs(false)
+        .https_only(true);
+    for root in roots {
+        builder = builder.add_root_certificate(root);
+    }
+
+    if let Some(proxy_url) = API_PROXY.read().unwrap().as_deref() {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| format!("Invalid API proxy: {}", e))?;
+        builder = builder.proxy(proxy);
+    }
+
+    builder
         .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
 
-    let mut req = client
-        .post(url)
-        .header("Content-Type", "application/json")
-        .body(body.to_string());
+/// Send a signed request for `suffix` (the part after the base URL, e.g. `/registry`) to
+/// the first API base that answers. In sandbox mode, uses bearer token auth instead of
+/// Ed25519.
+///
+/// A base that can't be connected to is marked down and the next one is tried. A GET also
+/// moves on after a timeout or a 5xx; a POST doesn't, since the server may have acted on it.
+async fn send_signed(method: reqwest::Method, suffix: &str, body: Option<&str>) -> Result<reqwest::Response, String> {
+    let client = http_client()?;
+    let bases = api_bases();
+    let order = {
+        let guard = MIRRORS_DOWN_UNTIL.lock().unwrap();
+        try_order(&bases, guard.as_ref().unwrap_or(&HashMap::new()), Instant::now())
+    };
+    let retry_on_server_error = method == reqwest::Method::GET;
+
+    // What to give back if no base answers properly: the last 5xx or connection error
+    let mut last = Err("Request failed: no API server configured".to_string());
+    for base in order {
+        let mut req = client.request(method.clone(), format!("{}{}", base, suffix));
+        if let Some(body) = body {
+            req = req.header("Content-Type", "application/json").body(body.to_string());
+        }
+        if USE_SANDBOX {
+            req = req.header("Authorization", format!("Bearer {}", SANDBOX_TOKEN));
+        } else {
+            let (signature, timestamp) = sign_request(method.as_str(), &signed_path(base, suffix), body.unwrap_or_default())?;
+            req = req
+                .header("X-Signature", signature)
+                .header("X-Timestamp", timestamp)
+                .header("X-Device-Key", device_public_key()?);
+        }
 
-    if USE_SANDBOX {
-        req = req.header("Authorization", format!("Bearer {}", SANDBOX_TOKEN));
-    } else {
-        let path = url_path(url);
-        let (signature, timestamp) = sign_request("POST", &path, body)?;
-        req = req.header("X-Signature", signature).header("X-Timestamp", timestamp);
+        match req.send().await {
+            Ok(response) if retry_on_server_error && response.status().is_server_error() => {
+                mark_mirror(base, false);
+                last = Ok(response);
+            }
+            Ok(response) => {
+                mark_mirror(base, true);
+                return Ok(response);
+            }
+            Err(e) if e.is_connect() || (retry_on_server_error && e.is_timeout()) => {
+                mark_mirror(base, false);
+                last = Err(format!("Request failed: {}", e));
+            }
+            Err(e) => return Err(format!("Request failed: {}", e)),
+        }
     }
+    last
+}
+
+async fn signed_get(suffix: &str) -> Result<reqwest::Response, String> {
+    send_signed(reqwest::Method::GET, suffix, None).await
+}
 
-    req.send().await.map_err(|e| format!("Request failed: {}", e))
+async fn signed_post(suffix: &str, body: &str) -> Result<reqwest::Response, String> {
+    send_signed(reqwest::Method::POST, suffix, Some(body)).await
 }
 
 // ---------------------------------------------------------------------------
@@ -148,15 +241,55 @@ pub fn get_device_id() -> String {
     format!("{:x}", hasher.finalize())
 }
 
+// ---------------------------------------------------------------------------
+// Device registration
+// ---------------------------------------------------------------------------
+
+/// Register this device's public key with the API. The request is signed with the
+/// device key itself, proving possession of the private half.
+pub async fn register_device() -> Result<(), String> {
+    let body_value = serde_json::json!({
+        "device_id": get_device_id(),
+        "public_key": device_public_key()?,
+        "app_version": env!("CARGO_PKG_VERSION"),
+    });
+    let body = serde_json::to_string(&body_value)
+        .map_err(|e| format!("Failed to serialize device registration: {}", e))?;
+
+    let response = signed_post("/devices", &body).await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let resp_body = response.text().await.unwrap_or_default();
+        return Err(format!("Device registration error ({}): {}", status, resp_body));
+    }
+
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // API functions
 // ---------------------------------------------------------------------------
 
+/// Whether the playbook API can be reached at all (through the proxy, if one is set) on
+/// any of its hosts. Any HTTP response counts; only connection and TLS failures are errors.
+pub async fn check_reachable() -> Result<(), String> {
+    let client = http_client()?;
+    let mut last_error = String::new();
+    for base in api_bases() {
+        match client.get(base).send().await {
+            Ok(_) => return Ok(()),
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+    Err(format!("Can't reach the playbook server: {}", last_error))
+}
+
 /// Fetch the best approved playbook for a broker, if one exists.
 pub async fn fetch_best_playbook(broker_id: &str) -> Result<Option<Playbook>, String> {
-    let url = format!("{}/playbooks?broker_id={}&sort=best&limit=1", api_base(), broker_id);
+    let path = format!("/playbooks?broker_id={}&sort=best&limit=1", broker_id);
 
-    let response = signed_get(&url).await?;
+    let response = signed_get(&path).await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -179,9 +312,9 @@ pub async fn fetch_best_playbook(broker_id: &str) -> Result<Option<Playbook>, St
 
 /// Fetch a single playbook with all steps.
 pub async fn fetch_playbook_detail(id: &str) -> Result<Playbook, String> {
-    let url = format!("{}/playbooks/{}", api_base(), id);
+    let path = format!("/playbooks/{}", id);
 
-    let response = signed_get(&url).await?;
+    let response = signed_get(&path).await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -199,9 +332,9 @@ pub async fn fetch_playbook_detail(id: &str) -> Result<Playbook, String> {
 
 /// Fetch list of playbook summaries for a broker.
 pub async fn fetch_playbooks(broker_id: &str) -> Result<Vec<PlaybookSummary>, String> {
-    let url = format!("{}/playbooks?broker_id={}&sort=best&limit=10", api_base(), broker_id);
+    let path = format!("/playbooks?broker_id={}&sort=best&limit=10", broker_id);
 
-    let response = signed_get(&url).await?;
+    let response = signed_get(&path).await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -219,11 +352,10 @@ pub async fn fetch_playbooks(broker_id: &str) -> Result<Vec<PlaybookSummary>, St
 
 /// Submit a new recorded playbook.
 pub async fn submit_playbook(submission: &PlaybookSubmission) -> Result<PlaybookSubmitResponse, String> {
-    let url = format!("{}/playbooks", api_base());
     let body = serde_json::to_string(submission)
         .map_err(|e| format!("Failed to serialize submission: {}", e))?;
 
-    let response = signed_post(&url, &body).await?;
+    let response = signed_post("/playbooks", &body).await?;
 
     let status = response.status();
     let resp_body = response.text().await.unwrap_or_default();
@@ -244,7 +376,7 @@ pub async fn submit_playbook(submission: &PlaybookSubmission) -> Result<Playbook
 
 /// Vote on a playbook (up or down).
 pub async fn vote_playbook(id: &str, vote: &str) -> Result<(), String> {
-    let url = format!("{}/playbooks/{}/vote", api_base(), id);
+    let path = format!("/playbooks/{}/vote", id);
     let device_id = get_device_id();
 
     let body_value = serde_json::json!({
@@ -254,7 +386,7 @@ pub async fn vote_playbook(id: &str, vote: &str) -> Result<(), String> {
     let body = serde_json::to_string(&body_value)
         .map_err(|e| format!("Failed to serialize vote: {}", e))?;
 
-    let response = signed_post(&url, &body).await?;
+    let response = signed_post(&path, &body).await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -267,9 +399,9 @@ pub async fn vote_playbook(id: &str, vote: &str) -> Result<(), String> {
 
 /// Check the current status of a submitted playbook.
 pub async fn check_playbook_status(id: &str) -> Result<String, String> {
-    let url = format!("{}/playbooks/{}", api_base(), id);
+    let path = format!("/playbooks/{}", id);
 
-    let response = signed_get(&url).await?;
+    let response = signed_get(&path).await?;
 
     if !response.status().is_success() {
         return Err(format!("Status check failed ({})", response.status()));
@@ -284,8 +416,13 @@ pub async fn check_playbook_status(id: &str) -> Result<String, String> {
 }
 
 /// Suggest a new broker to be added to the registry.
-pub async fn suggest_broker(name: &str, url: &str, notes: &str) -> Result<(), String> {
-    let api_url = format!("{}/broker-suggestions", api_base());
+/// `page_structure` is an optional PII-free snapshot of the broker's opt-out page.
+pub async fn suggest_broker(
+    name: &str,
+    url: &str,
+    notes: &str,
+    page_structure: Option<&PageStructure>,
+) -> Result<(), String> {
     let device_id = get_device_id();
 
     let body_value = serde_json::json!({
@@ -293,11 +430,12 @@ pub async fn suggest_broker(name: &str, url: &str, notes: &str) -> Result<(), St
         "name": name,
         "url": url,
         "notes": notes,
+        "page_structure": page_structure,
     });
     let body = serde_json::to_string(&body_value)
         .map_err(|e| format!("Failed to serialize suggestion: {}", e))?;
 
-    let response = signed_post(&api_url, &body).await?;
+    let response = signed_post("/broker-suggestions", &body).await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -310,8 +448,7 @@ pub async fn suggest_broker(name: &str, url: &str, notes: &str) -> Result<(), St
 
 /// Fetch the current registry version from the API.
 pub async fn fetch_registry_version() -> Result<String, String> {
-    let url = format!("{}/registry/version", api_base());
-    let response = signed_get(&url).await?;
+    let response = signed_get("/registry/version").await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -329,8 +466,7 @@ pub async fn fetch_registry_version() -> Result<String, String> {
 
 /// Fetch the full broker registry from the API.
 pub async fn fetch_registry() -> Result<BrokerRegistry, String> {
-    let url = format!("{}/registry", api_base());
-    let response = signed_get(&url).await?;
+    let response = signed_get("/registry").await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -348,8 +484,7 @@ pub async fn fetch_registry() -> Result<BrokerRegistry, String> {
 
 /// Fetch the app changelog.
 pub async fn fetch_changelog() -> Result<Vec<ChangelogEntry>, String> {
-    let url = format!("{}/changelog", api_base());
-    let response = signed_get(&url).await?;
+    let response = signed_get("/changelog").await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -365,10 +500,95 @@ pub async fn fetch_changelog() -> Result<Vec<ChangelogEntry>, String> {
     Ok(envelope.data)
 }
 
+/// Shortest and longest contributor handle.
+const HANDLE_LEN: (usize, usize) = (3, 24);
+
+/// Check a handle before claiming it: letters, digits, `_` and `-` only, so it can't
+/// pass for a URL or a real name with spaces. Returns it trimmed.
+pub fn validate_handle(handle: &str) -> Result<String, String> {
+    let handle = handle.trim();
+    let (min, max) = HANDLE_LEN;
+    if handle.chars().count() < min || handle.chars().count() > max {
+        return Err(format!("A handle must be {} to {} characters long.", min, max));
+    }
+    if !handle.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err("A handle can only use letters, numbers, _ and -.".to_string());
+    }
+    Ok(handle.to_string())
+}
+
+/// Claim `handle` for this device's contributor ID, replacing any handle it had. Playbooks
+/// this device submitted show it as their author.
+pub async fn claim_handle(handle: &str) -> Result<Contributor, String> {
+    let body_value = serde_json::json!({
+        "device_id": get_device_id(),
+        "handle": handle,
+    });
+    let body = serde_json::to_string(&body_value)
+        .map_err(|e| format!("Failed to serialize handle claim: {}", e))?;
+
+    let response = signed_post("/contributors", &body).await?;
+    let status = response.status();
+    if status == reqwest::StatusCode::CONFLICT {
+        return Err(format!("The handle \"{}\" is already taken.", handle));
+    }
+    if !status.is_success() {
+        let resp_body = response.text().await.unwrap_or_default();
+        return Err(format!("Handle claim error ({}): {}", status, resp_body));
+    }
+
+    let envelope: ApiEnvelope<Contributor> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse contributor: {}", e))?;
+
+    Ok(envelope.data)
+}
+
+/// This device's contributor ID and handle, or None before it has submitted or claimed one.
+pub async fn fetch_own_contributor() -> Result<Option<Contributor>, String> {
+    let response = signed_get("/contributors/me").await?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Contributor error ({}): {}", status, body));
+    }
+
+    let envelope: ApiEnvelope<Contributor> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse contributor: {}", e))?;
+
+    Ok(Some(envelope.data))
+}
+
+/// Fetch the approved playbooks a contributor wrote, best first.
+pub async fn fetch_contributor_playbooks(contributor_id: &str) -> Result<Vec<PlaybookSummary>, String> {
+    let path = format!("/playbooks?contributor_id={}&sort=best&limit=20", contributor_id);
+    let response = signed_get(&path).await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Contributor playbooks error ({}): {}", status, body));
+    }
+
+    let envelope: ApiEnvelope<Vec<PlaybookSummary>> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse playbook list: {}", e))?;
+
+    Ok(envelope.data)
+}
+
 /// Fetch community execution reports for a playbook.
 pub async fn fetch_playbook_reports(playbook_id: &str) -> Result<Vec<PlaybookReportEntry>, String> {
-    let url = format!("{}/playbooks/{}/reports", api_base(), playbook_id);
-    let response = signed_get(&url).await?;
+    let path = format!("/playbooks/{}/reports", playbook_id);
+    let response = signed_get(&path).await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -384,13 +604,35 @@ pub async fn fetch_playbook_reports(playbook_id: &str) -> Result<Vec<PlaybookRep
     Ok(envelope.data)
 }
 
+/// Fetch every playbook revision ever submitted for a broker, including rejected and
+/// superseded ones, oldest version first.
+pub async fn fetch_playbook_history(broker_id: &str) -> Result<Vec<PlaybookRevision>, String> {
+    let path = format!("/playbooks/history?broker_id={}", broker_id);
+    let response = signed_get(&path).await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Playbook history error ({}): {}", status, body));
+    }
+
+    let envelope: ApiEnvelope<Vec<PlaybookRevision>> = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse playbook history: {}", e))?;
+
+    let mut revisions = envelope.data;
+    revisions.sort_by(|a, b| a.version.cmp(&b.version).then_with(|| a.created_at.cmp(&b.created_at)));
+    Ok(revisions)
+}
+
 /// Report the outcome of running a playbook.
 pub async fn report_outcome(playbook_id: &str, report: &PlaybookReport) -> Result<(), String> {
-    let url = format!("{}/playbooks/{}/report", api_base(), playbook_id);
+    let path = format!("/playbooks/{}/report", playbook_id);
     let body = serde_json::to_string(report)
         .map_err(|e| format!("Failed to serialize report: {}", e))?;
 
-    let response = signed_post(&url, &body).await?;
+    let response = signed_post(&path, &body).await?;
 
     if !response.status().is_success() {
         let status = response.status();
@@ -400,3 +642,40 @@ pub async fn report_outcome(playbook_id: &str, report: &PlaybookReport) -> Resul
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tries_mirrors_that_are_down_last() {
+        let bases = ["https://a.example/api/v1", "https://b.example/api/v1", "https://c.example/api/v1"];
+        let now = Instant::now();
+        assert_eq!(try_order(&bases, &HashMap::new(), now), bases);
+
+        let mut down = HashMap::new();
+        down.insert(bases[0], now + Duration::from_secs(60));
+        down.insert(bases[1], now + Duration::from_secs(30));
+        assert_eq!(try_order(&bases, &down, now), [bases[2], bases[1], bases[0]]);
+        // Back in its place once the time is up
+        assert_eq!(try_order(&bases, &down, now + Duration::from_secs(45)), [bases[1], bases[2], bases[0]]);
+    }
+
+    #[test]
+    fn accepts_only_plain_handles() {
+        assert_eq!(validate_handle("  form_fixer-42 ").as_deref(), Ok("form_fixer-42"));
+        assert!(validate_handle("ab").is_err());
+        assert!(validate_handle("Jane Doe").is_err());
+        assert!(validate_handle("me@example.com").is_err());
+        assert!(validate_handle(&"x".repeat(25)).is_err());
+    }
+
+    #[test]
+    fn signs_the_same_path_on_every_mirror() {
+        assert_eq!(signed_path("https://opt-outta.com/api/v1", "/registry"), "/api/v1/registry");
+        assert_eq!(
+            signed_path("https://mirror.example.net/api/v1/", "/playbooks?broker_id=x"),
+            "/api/v1/playbooks?broker_id=x"
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::field_matching;
+use crate::models::{PlaybookDraft, PlaybookStep, RecordedAction};
+use crate::playbook_validation::{self, MAX_FALLBACK_SELECTORS};
+use crate::step_descriptions;
+
+/// Wait after each drafted step; users tune it per step during review.
+const DRAFT_WAIT_AFTER_MS: u32 = 500;
+
+/// Turn the actions recorded so far into provisional playbook steps, along with anything
+/// the user should fix before submitting. Safe to call after every new action.
+pub fn draft(actions: &[RecordedAction]) -> PlaybookDraft {
+    let kept = kept_actions(actions);
+    let steps = to_steps(&kept);
+    let mut issues = Vec::new();
+    if steps.is_empty() {
+        return PlaybookDraft { steps, issues };
+    }
+    if let Err(e) = playbook_validation::validate_steps(&steps) {
+        issues.push(e);
+    }
+    issues.extend(playbook_validation::honeypot_warnings(&steps));
+    // Fields whose profile value was a guess, for the user to confirm or change
+    for (step, action) in steps.iter().zip(&kept) {
+        if let (Some(key), Some(confidence)) = (&step.profile_key, action.match_confidence) {
+            if confidence < field_matching::CONFIDENT {
+                issues.push(format!(
+                    "Step {}: check that this field takes {}. Its label didn't make that clear.",
+                    step.position,
+                    step_descriptions::profile_field_phrase(key)
+                ));
+            }
+        }
+    }
+    if !steps.iter().any(|s| s.action == "done") {
+        issues.push("No confirmation detected yet. Submit the form, or add a Done step during review.".to_string());
+    }
+    PlaybookDraft { steps, issues }
+}
+
+/// Convert recorded actions into steps in the order they happened. Re-entering the same
+/// field keeps only the final value, and reloading the page just visited adds nothing.
+pub fn draft_steps(actions: &[RecordedAction]) -> Vec<PlaybookStep> {
+    to_steps(&kept_actions(actions))
+}
+
+/// The actions that make it into the draft, in order.
+fn kept_actions(actions: &[RecordedAction]) -> Vec<&RecordedAction> {
+    let mut sorted: Vec<&RecordedAction> = actions.iter().collect();
+    sorted.sort_by_key(|a| a.timestamp);
+
+    let mut kept: Vec<&RecordedAction> = Vec::new();
+    for action in sorted {
+        if let Some(last) = kept.last() {
+            if supersedes(last, action) {
+                kept.pop();
+            } else if is_repeat_navigate(last, action) {
+                continue;
+            }
+        }
+        kept.push(action);
+    }
+    kept
+}
+
+fn to_steps(kept: &[&RecordedAction]) -> Vec<PlaybookStep> {
+    kept.iter()
+        .enumerate()
+        .map(|(i, a)| PlaybookStep {
+            position: i as u32 + 1,
+            action: a.action.clone(),
+            selector: a.selector.clone(),
+            profile_key: a.profile_key.clone(),
+            value: a.value.clone().or_else(|| a.url.clone()),
+            description: step_descriptions::describe_action(a),
+            instructions: None,
+            wait_after_ms: DRAFT_WAIT_AFTER_MS,
+            optional: false,
+            fallback_selectors: a
+                .selector_candidates
+                .iter()
+                .filter(|s| Some(s.as_str()) != a.selector.as_deref())
+                .take(MAX_FALLBACK_SELECTORS)
+                .cloned()
+                .collect(),
+            phase: None,
+        })
+        .collect()
+}
+
+/// Whether `next` overwrites the field `prev` just filled in.
+fn supersedes(prev: &RecordedAction, next: &RecordedAction) -> bool {
+    matches!(next.action.as_str(), "fill" | "select" | "choose_radio")
+        && prev.action == next.action
+        && prev.selector.is_some()
+        && prev.selector == next.selector
+}
+
+fn is_repeat_navigate(prev: &RecordedAction, next: &RecordedAction) -> bool {
+    prev.action == "navigate" && next.action == "navigate" && prev.url.is_some() && prev.url == next.url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(kind: &str, selector: Option<&str>, timestamp: u64) -> RecordedAction {
+        RecordedAction {
+            action: kind.to_string(),
+            selector: selector.map(String::from),
+            selector_candidates: vec![],
+            profile_key: None,
+            match_confidence: None,
+            value: None,
+            url: None,
+            element_text: None,
+            label: None,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn keeps_only_the_last_entry_for_a_field() {
+        let mut navigate = action("navigate", None, 1);
+        navigate.url = Some("https://example.com/optout".to_string());
+        let mut first = action("fill", Some("#email"), 2);
+        first.profile_key = Some("email".to_string());
+        let mut second = first.clone();
+        second.timestamp = 3;
+        let reload = RecordedAction { timestamp: 0, ..navigate.clone() };
+
+        let steps = draft_steps(&[second, navigate, reload, first, action("click", Some("#go"), 4)]);
+        let kinds: Vec<_> = steps.iter().map(|s| s.action.as_str()).collect();
+        assert_eq!(kinds, ["navigate", "fill", "click"]);
+        assert_eq!(steps.iter().map(|s| s.position).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(steps[1].description, "Fill 'email' with your email");
+    }
+
+    #[test]
+    fn flags_missing_confirmations_and_guessed_fields() {
+        let mut navigate = action("navigate", None, 1);
+        navigate.url = Some("https://example.com/optout".to_string());
+        let draft_so_far = draft(&[navigate.clone()]);
+        assert_eq!(draft_so_far.issues.len(), 1);
+        assert!(draft_so_far.issues[0].contains("No confirmation"));
+
+        let mut done = action("done", None, 3);
+        done.value = Some("request received".to_string());
+        assert!(draft(&[navigate.clone(), done.clone()]).issues.is_empty());
+        assert!(draft(&[]).issues.is_empty());
+
+        let mut guessed = action("fill", Some("#f2"), 2);
+        guessed.profile_key = Some("firstName".to_string());
+        guessed.match_confidence = Some(0.5);
+        let issues = draft(&[navigate, guessed, done]).issues;
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].starts_with("Step 2: check that this field takes your first name"));
+    }
+}
@@ -0,0 +1,82 @@
+use crate::models::{PlaybookReliability, PlaybookReliabilityStore};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::PathBuf;
+
+const FILENAME: &str = "playbook_reliability.json";
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<PlaybookReliabilityStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(PlaybookReliabilityStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &PlaybookReliabilityStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// Count one finished run of a playbook. Cancelled runs shouldn't be recorded.
+pub fn record(app: &tauri::AppHandle, playbook_id: &str, succeeded: bool) -> Result<(), String> {
+    let mut store = load(app)?;
+    apply(&mut store, playbook_id, succeeded, Utc::now());
+    save(app, &store)
+}
+
+fn apply(store: &mut PlaybookReliabilityStore, playbook_id: &str, succeeded: bool, now: DateTime<Utc>) {
+    let index = match store.playbooks.iter().position(|p| p.playbook_id == playbook_id) {
+        Some(i) => i,
+        None => {
+            store.playbooks.push(PlaybookReliability {
+                playbook_id: playbook_id.to_string(),
+                successes: 0,
+                failures: 0,
+                consecutive_failures: 0,
+                last_run_at: now,
+            });
+            store.playbooks.len() - 1
+        }
+    };
+    let entry = &mut store.playbooks[index];
+    if succeeded {
+        entry.successes += 1;
+        entry.consecutive_failures = 0;
+    } else {
+        entry.failures += 1;
+        entry.consecutive_failures += 1;
+    }
+    entry.last_run_at = now;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_failure_streaks_per_playbook() {
+        let mut store = PlaybookReliabilityStore::default();
+        let now = Utc::now();
+        apply(&mut store, "pb-1", false, now);
+        apply(&mut store, "pb-1", false, now);
+        apply(&mut store, "pb-2", true, now);
+
+        let pb1 = &store.playbooks[0];
+        assert_eq!((pb1.successes, pb1.failures, pb1.consecutive_failures), (0, 2, 2));
+
+        apply(&mut store, "pb-1", true, now);
+        let pb1 = &store.playbooks[0];
+        assert_eq!((pb1.successes, pb1.failures, pb1.consecutive_failures), (1, 2, 0));
+        assert_eq!(store.playbooks.len(), 2);
+    }
+}
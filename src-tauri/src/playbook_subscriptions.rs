@@ -0,0 +1,198 @@
+use crate::models::{PlaybookSubscription, PlaybookSubscriptionStore, PlaybookSummary, PlaybookUpdate, PlaybookUpdateKind};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::PathBuf;
+
+const FILENAME: &str = "playbook_subscriptions.json";
+/// How often the background task looks for playbook changes.
+pub const CHECK_INTERVAL_SECS: u64 = 6 * 60 * 60;
+/// Delay before the first check, so startup isn't slowed by API calls.
+pub const FIRST_CHECK_DELAY_SECS: u64 = 60;
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<PlaybookSubscriptionStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(PlaybookSubscriptionStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &PlaybookSubscriptionStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+pub fn set(app: &tauri::AppHandle, broker_id: &str, subscribed: bool) -> Result<(), String> {
+    let mut store = load(app)?;
+    let existing = store.subscriptions.iter().any(|s| s.broker_id == broker_id);
+    if subscribed && !existing {
+        store.subscriptions.push(PlaybookSubscription {
+            broker_id: broker_id.to_string(),
+            subscribed_at: Utc::now(),
+            last_checked_at: None,
+            known_playbook_ids: Vec::new(),
+            best_playbook_id: None,
+        });
+    } else if !subscribed {
+        store.subscriptions.retain(|s| s.broker_id != broker_id);
+    }
+    save(app, &store)
+}
+
+/// Fetch the playbooks of every subscribed broker and return what changed since the
+/// last check. Brokers whose playbooks can't be fetched are retried next time.
+pub async fn check(app: &tauri::AppHandle) -> Result<Vec<PlaybookUpdate>, String> {
+    let broker_ids: Vec<String> = load(app)?.subscriptions.into_iter().map(|s| s.broker_id).collect();
+    let mut fetched = Vec::new();
+    for broker_id in broker_ids {
+        match crate::commands::playbooks::fetch_playbooks(app.clone(), broker_id.clone()).await {
+            Ok(playbooks) => fetched.push((broker_id, playbooks)),
+            Err(e) => eprintln!("[subscriptions] {}: Failed to fetch playbooks: {}", broker_id, e),
+        }
+    }
+
+    // Reload so a subscription changed while fetching isn't overwritten
+    let mut store = load(app)?;
+    let now = Utc::now();
+    let mut updates = Vec::new();
+    for (broker_id, playbooks) in fetched {
+        if let Some(sub) = store.subscriptions.iter_mut().find(|s| s.broker_id == broker_id) {
+            updates.extend(detect(sub, &playbooks));
+            observe(sub, &playbooks, now);
+        }
+    }
+    save(app, &store)?;
+    Ok(updates)
+}
+
+/// Check on a timer for as long as the app runs, emitting `playbook-updates` when
+/// something changed.
+pub async fn run_background_checks(app: tauri::AppHandle) {
+    use tauri::Emitter;
+    tokio::time::sleep(tokio::time::Duration::from_secs(FIRST_CHECK_DELAY_SECS)).await;
+    loop {
+        match check(&app).await {
+            Ok(updates) if !updates.is_empty() => {
+                let _ = app.emit("playbook-updates", &updates);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("[subscriptions] Update check failed: {}", e),
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+    }
+}
+
+fn top_scored(playbooks: &[PlaybookSummary]) -> Option<&PlaybookSummary> {
+    playbooks.iter().max_by_key(|p| p.score)
+}
+
+/// What changed for one subscription. The first check only sets the baseline.
+fn detect(sub: &PlaybookSubscription, playbooks: &[PlaybookSummary]) -> Vec<PlaybookUpdate> {
+    if sub.last_checked_at.is_none() {
+        return Vec::new();
+    }
+    let update = |p: &PlaybookSummary, kind| PlaybookUpdate {
+        broker_id: p.broker_id.clone(),
+        broker_name: p.broker_name.clone(),
+        playbook_id: p.id.clone(),
+        version: p.version,
+        score: p.score,
+        kind,
+    };
+
+    let mut updates: Vec<PlaybookUpdate> = playbooks
+        .iter()
+        .filter(|p| !sub.known_playbook_ids.contains(&p.id))
+        .map(|p| update(p, PlaybookUpdateKind::New))
+        .collect();
+    if let Some(best) = top_scored(playbooks) {
+        let already_reported = updates.iter().any(|u| u.playbook_id == best.id);
+        if !already_reported && sub.best_playbook_id.as_deref() != Some(best.id.as_str()) {
+            updates.push(update(best, PlaybookUpdateKind::BetterScore));
+        }
+    }
+    updates
+}
+
+fn observe(sub: &mut PlaybookSubscription, playbooks: &[PlaybookSummary], now: DateTime<Utc>) {
+    for p in playbooks {
+        if !sub.known_playbook_ids.contains(&p.id) {
+            sub.known_playbook_ids.push(p.id.clone());
+        }
+    }
+    sub.best_playbook_id = top_scored(playbooks).map(|p| p.id.clone());
+    sub.last_checked_at = Some(now);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(id: &str, score: i32) -> PlaybookSummary {
+        PlaybookSummary {
+            id: id.to_string(),
+            broker_id: "spokeo".to_string(),
+            broker_name: "Spokeo".to_string(),
+            title: None,
+            version: 1,
+            notes: None,
+            steps_count: 5,
+            upvotes: 0,
+            downvotes: 0,
+            success_count: 0,
+            failure_count: 0,
+            score,
+            created_at: String::new(),
+            contributor: None,
+            captcha_steps: 0,
+            manual_steps: 0,
+            estimated_duration_ms: 0,
+            personal: None,
+            signature: None,
+            steps: vec![],
+        }
+    }
+
+    fn subscription() -> PlaybookSubscription {
+        PlaybookSubscription {
+            broker_id: "spokeo".to_string(),
+            subscribed_at: Utc::now(),
+            last_checked_at: None,
+            known_playbook_ids: vec![],
+            best_playbook_id: None,
+        }
+    }
+
+    #[test]
+    fn first_check_only_sets_baseline() {
+        let mut sub = subscription();
+        let playbooks = [summary("a", 5), summary("b", 2)];
+        assert!(detect(&sub, &playbooks).is_empty());
+        observe(&mut sub, &playbooks, Utc::now());
+        assert!(detect(&sub, &playbooks).is_empty());
+        assert_eq!(sub.best_playbook_id.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn reports_new_and_newly_top_scored_playbooks() {
+        let mut sub = subscription();
+        observe(&mut sub, &[summary("a", 5), summary("b", 2)], Utc::now());
+
+        let updates = detect(&sub, &[summary("a", 5), summary("b", 2), summary("c", 1)]);
+        assert_eq!(updates.len(), 1);
+        assert_eq!((updates[0].playbook_id.as_str(), updates[0].kind), ("c", PlaybookUpdateKind::New));
+
+        let updates = detect(&sub, &[summary("a", 1), summary("b", 7)]);
+        assert_eq!(updates.len(), 1);
+        assert_eq!((updates[0].playbook_id.as_str(), updates[0].kind), ("b", PlaybookUpdateKind::BetterScore));
+    }
+}
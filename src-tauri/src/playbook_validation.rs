@@ -1,7 +1,9 @@
 use crate::models::PlaybookStep;
+use crate::step_cursor;
 
 const MAX_STEPS: usize = 100;
 const MAX_SELECTOR_LEN: usize = 500;
+pub const MAX_FALLBACK_SELECTORS: usize = 5;
 const MAX_VALUE_LEN: usize = 2000;
 const MAX_DESCRIPTION_LEN: usize = 500;
 const MAX_INSTRUCTIONS_LEN: usize = 2000;
@@ -12,14 +14,20 @@ const ALLOWED_ACTIONS: &[&str] = &[
     "fill",
     "select",
     "check",
+    "choose_radio",
     "click",
     "wait",
     "wait_for",
     "scroll_to",
     "find_and_click",
+    "fill_credential",
+    "fill_totp",
+    "fill_sms_code",
     "captcha",
     "user_prompt",
     "done",
+    "for_each_result",
+    "end_for_each",
 ];
 
 const ALLOWED_PROFILE_KEYS: &[&str] = &[
@@ -33,8 +41,24 @@ const ALLOWED_PROFILE_KEYS: &[&str] = &[
     "zip",
     "dob",
     "fullName",
+    "legalBasis",
 ];
 
+/// Fields of a previous address usable as `previousAddress[N].<field>`.
+const PREVIOUS_ADDRESS_FIELDS: &[&str] = &["address", "city", "state", "zip"];
+
+/// Whether a playbook may reference this profile key.
+fn is_allowed_profile_key(key: &str) -> bool {
+    if ALLOWED_PROFILE_KEYS.contains(&key) {
+        return true;
+    }
+    match crate::profile_format::parse_indexed_key(key) {
+        Some(("previousAddress", _, field)) => PREVIOUS_ADDRESS_FIELDS.contains(&field),
+        Some(("alternateEmail" | "alternatePhone", _, "")) => true,
+        _ => false,
+    }
+}
+
 /// Dangerous URL schemes that must never be navigated to.
 const BLOCKED_URL_SCHEMES: &[&str] = &[
     "javascript:",
@@ -73,6 +97,43 @@ const BLOCKED_SELECTOR_PATTERNS: &[&str] = &[
     "import(",
 ];
 
+/// Names sites give honeypot fields, which are hidden from people so only bots fill them.
+/// Matches the names `browser::is_honeypot` looks for on the page.
+const HONEYPOT_NAME_HINTS: &[&str] = &[
+    "honeypot",
+    "honey-pot",
+    "honey_pot",
+    "botfield",
+    "bot-field",
+    "bot_field",
+    "leave-blank",
+    "leave_blank",
+    "leave-this-blank",
+    "leave_this_blank",
+    "do-not-fill",
+    "do_not_fill",
+    "dont-fill",
+    "dont_fill",
+];
+
+/// Warnings for steps that fill a field named like a honeypot. A site that sees one filled
+/// drops the request without saying so. Warnings, not errors: the name alone can't prove it.
+pub fn honeypot_warnings(steps: &[PlaybookStep]) -> Vec<String> {
+    steps
+        .iter()
+        .filter(|s| s.action == "fill")
+        .filter_map(|s| {
+            let selector = s.selector.as_deref()?.to_lowercase();
+            HONEYPOT_NAME_HINTS.iter().any(|h| selector.contains(h)).then(|| {
+                format!(
+                    "Step {} fills a field that looks like a trap for bots. Sites quietly drop requests that fill one, so remove the step unless the field is really on the form.",
+                    s.position
+                )
+            })
+        })
+        .collect()
+}
+
 /// Validate a full list of playbook steps. Returns Ok(()) or an error describing the problem.
 pub fn validate_steps(steps: &[PlaybookStep]) -> Result<(), String> {
     if steps.is_empty() {
@@ -91,7 +152,35 @@ pub fn validate_steps(steps: &[PlaybookStep]) -> Result<(), String> {
         validate_step(step, &ctx)?;
     }
 
-    Ok(())
+    validate_loops(steps)
+}
+
+/// `for_each_result` loops must be closed by `end_for_each` and can't be nested.
+fn validate_loops(steps: &[PlaybookStep]) -> Result<(), String> {
+    let mut open: Option<usize> = None;
+    for (i, step) in steps.iter().enumerate() {
+        match step.action.as_str() {
+            step_cursor::FOR_EACH_ACTION => {
+                if let Some(start) = open {
+                    return Err(format!(
+                        "Step {}: for_each_result loops can't be nested (loop opened at step {}).",
+                        i + 1,
+                        start + 1
+                    ));
+                }
+                open = Some(i);
+            }
+            step_cursor::END_FOR_EACH_ACTION => {
+                open.take()
+                    .ok_or_else(|| format!("Step {}: end_for_each without a for_each_result.", i + 1))?;
+            }
+            _ => {}
+        }
+    }
+    match open {
+        Some(start) => Err(format!("Step {}: for_each_result is never closed with end_for_each.", start + 1)),
+        None => Ok(()),
+    }
 }
 
 fn validate_step(step: &PlaybookStep, ctx: &str) -> Result<(), String> {
@@ -109,6 +198,17 @@ fn validate_step(step: &PlaybookStep, ctx: &str) -> Result<(), String> {
     if let Some(ref sel) = step.selector {
         validate_selector(sel, ctx)?;
     }
+    if step.fallback_selectors.len() > MAX_FALLBACK_SELECTORS {
+        return Err(format!(
+            "{}: Too many fallback selectors ({}, max {}).",
+            ctx,
+            step.fallback_selectors.len(),
+            MAX_FALLBACK_SELECTORS
+        ));
+    }
+    for sel in &step.fallback_selectors {
+        validate_selector(sel, ctx)?;
+    }
 
     // Validate value if present
     if let Some(ref val) = step.value {
@@ -139,12 +239,13 @@ fn validate_step(step: &PlaybookStep, ctx: &str) -> Result<(), String> {
 
     // Validate profile_key if present
     if let Some(ref key) = step.profile_key {
-        if !ALLOWED_PROFILE_KEYS.contains(&key.as_str()) {
+        if !is_allowed_profile_key(key) {
             return Err(format!(
-                "{}: Unknown profile key '{}'. Allowed: {}",
+                "{}: Unknown profile key '{}'. Allowed: {}, previousAddress[N].{{{}}}, alternateEmail[N], alternatePhone[N]",
                 ctx,
                 key,
-                ALLOWED_PROFILE_KEYS.join(", ")
+                ALLOWED_PROFILE_KEYS.join(", "),
+                PREVIOUS_ADDRESS_FIELDS.join(",")
             ));
         }
     }
@@ -161,10 +262,17 @@ fn validate_step(step: &PlaybookStep, ctx: &str) -> Result<(), String> {
     match step.action.as_str() {
         "navigate" => validate_navigate_step(step, ctx)?,
         "fill" => validate_fill_step(step, ctx)?,
-        "select" => validate_select_step(step, ctx)?,
-        "click" | "check" | "scroll_to" | "find_and_click" | "wait_for" => {
+        "select" | "choose_radio" => validate_select_step(step, ctx)?,
+        "click" | "check" | "scroll_to" | "find_and_click" | "wait_for" | "fill_totp" | "fill_sms_code" => {
             validate_requires_selector(step, ctx)?
         }
+        "for_each_result" => {
+            validate_requires_selector(step, ctx)?;
+            if step.profile_key.is_none() {
+                return Err(format!("{}: for_each_result needs a profile_key to match results.", ctx));
+            }
+        }
+        "fill_credential" => validate_credential_step(step, ctx)?,
         "wait" => validate_wait_step(step, ctx)?,
         _ => {} // captcha, user_prompt, done — no extra validation needed
     }
@@ -231,6 +339,16 @@ fn validate_navigate_step(step: &PlaybookStep, ctx: &str) -> Result<(), String>
         .as_deref()
         .ok_or_else(|| format!("{}: Navigate step requires a URL value.", ctx))?;
 
+    validate_url(url, ctx)
+}
+
+/// Validate a URL the recorder is asked to start from. Same rules as navigate steps.
+pub fn validate_start_url(url: &str) -> Result<(), String> {
+    validate_url(url, "Start URL")
+}
+
+/// Validate any URL the app is about to open. `ctx` prefixes the error message.
+pub fn validate_url(url: &str, ctx: &str) -> Result<(), String> {
     if url.is_empty() {
         return Err(format!("{}: Navigate URL is empty.", ctx));
     }
@@ -288,6 +406,15 @@ fn validate_fill_step(step: &PlaybookStep, ctx: &str) -> Result<(), String> {
     Ok(())
 }
 
+fn validate_credential_step(step: &PlaybookStep, ctx: &str) -> Result<(), String> {
+    validate_requires_selector(step, ctx)?;
+    // The value names which part of the saved broker login to enter, never the login itself
+    if !matches!(step.value.as_deref(), Some("username") | Some("password")) {
+        return Err(format!("{}: fill_credential value must be \"username\" or \"password\".", ctx));
+    }
+    Ok(())
+}
+
 fn validate_select_step(step: &PlaybookStep, ctx: &str) -> Result<(), String> {
     validate_requires_selector(step, ctx)?;
     // Select steps can use profile_key (auto-fill from profile), value (fixed), or neither (manual selection by user)
@@ -336,6 +463,8 @@ mod tests {
             instructions: None,
             wait_after_ms: 500,
             optional: false,
+            fallback_selectors: vec![],
+            phase: None,
         }
     }
 
@@ -392,6 +521,15 @@ mod tests {
         assert!(validate_steps(&[step]).is_err());
     }
 
+    #[test]
+    fn validates_fallback_selectors() {
+        let mut step = make_step("click");
+        step.fallback_selectors = vec!["[data-testid=\"submit\"]".to_string()];
+        assert!(validate_steps(&[step.clone()]).is_ok());
+        step.fallback_selectors.push("[onclick=\"alert(1)\"]".to_string());
+        assert!(validate_steps(&[step]).is_err());
+    }
+
     #[test]
     fn rejects_too_many_steps() {
         let steps: Vec<PlaybookStep> = (0..101)
@@ -418,6 +556,59 @@ mod tests {
         assert!(validate_steps(&[step]).is_err());
     }
 
+    #[test]
+    fn allows_previous_address_keys() {
+        let mut step = make_step("fill");
+        step.profile_key = Some("previousAddress[0].city".to_string());
+        assert!(validate_steps(&[step.clone()]).is_ok());
+        step.profile_key = Some("previousAddress[0].ssn".to_string());
+        assert!(validate_steps(&[step]).is_err());
+    }
+
+    #[test]
+    fn allows_alternate_contact_keys() {
+        let mut step = make_step("fill");
+        step.profile_key = Some("alternateEmail[1]".to_string());
+        assert!(validate_steps(&[step.clone()]).is_ok());
+        step.profile_key = Some("alternatePhone[0].digits".to_string());
+        assert!(validate_steps(&[step]).is_err());
+    }
+
+    fn loop_steps(actions: &[&str]) -> Vec<PlaybookStep> {
+        actions
+            .iter()
+            .map(|a| {
+                let mut step = make_step(a);
+                step.selector = Some(".result".to_string());
+                step.profile_key = Some("lastName".to_string());
+                step
+            })
+            .collect()
+    }
+
+    #[test]
+    fn allows_closed_result_loop() {
+        let steps = loop_steps(&["for_each_result", "click", "end_for_each"]);
+        assert!(validate_steps(&steps).is_ok());
+    }
+
+    #[test]
+    fn rejects_unclosed_or_nested_loops() {
+        assert!(validate_steps(&loop_steps(&["for_each_result", "click"])).is_err());
+        assert!(validate_steps(&loop_steps(&["click", "end_for_each"])).is_err());
+        let nested = loop_steps(&["for_each_result", "for_each_result", "end_for_each", "end_for_each"]);
+        assert!(validate_steps(&nested).is_err());
+    }
+
+    #[test]
+    fn credential_fills_name_the_field() {
+        let mut step = make_step("fill_credential");
+        step.value = Some("password".to_string());
+        assert!(validate_steps(&[step.clone()]).is_ok());
+        step.value = Some("ssn".to_string());
+        assert!(validate_steps(&[step]).is_err());
+    }
+
     #[test]
     fn allows_valid_fill() {
         let mut step = make_step("fill");
@@ -431,4 +622,27 @@ mod tests {
         step.value = Some("<script>alert(1)</script>".to_string());
         assert!(validate_steps(&[step]).is_err());
     }
+
+    #[test]
+    fn warns_on_honeypot_fills() {
+        let mut trap = make_step("fill");
+        trap.selector = Some("input[name=\"bot_field\"]".to_string());
+        let mut email = make_step("fill");
+        email.position = 2;
+        email.selector = Some("#email".to_string());
+        let warnings = honeypot_warnings(&[trap, email]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Step 1 "));
+    }
+
+    #[test]
+    fn allows_https_start_url() {
+        assert!(validate_start_url("https://www.spokeo.com/").is_ok());
+    }
+
+    #[test]
+    fn rejects_internal_start_url() {
+        assert!(validate_start_url("http://192.168.1.1/").is_err());
+        assert!(validate_start_url("javascript:alert(1)").is_err());
+    }
 }
@@ -95,6 +95,8 @@ mod tests {
                 instructions: Some("Search for your name in the database using the search bar on the top of the page to get your profile URL.".to_string()),
                 wait_after_ms: 1000,
                 optional: false,
+                fallback_selectors: vec![],
+                phase: None,
             },
             PlaybookStep {
                 position: 2,
@@ -106,6 +108,8 @@ mod tests {
                 instructions: None,
                 wait_after_ms: 500,
                 optional: false,
+                fallback_selectors: vec![],
+                phase: None,
             },
             PlaybookStep {
                 position: 3,
@@ -117,6 +121,8 @@ mod tests {
                 instructions: None,
                 wait_after_ms: 500,
                 optional: false,
+                fallback_selectors: vec![],
+                phase: None,
             },
             PlaybookStep {
                 position: 4,
@@ -128,6 +134,8 @@ mod tests {
                 instructions: None,
                 wait_after_ms: 500,
                 optional: false,
+                fallback_selectors: vec![],
+                phase: None,
             },
             PlaybookStep {
                 position: 5,
@@ -139,6 +147,8 @@ mod tests {
                 instructions: None,
                 wait_after_ms: 500,
                 optional: false,
+                fallback_selectors: vec![],
+                phase: None,
             },
             PlaybookStep {
                 position: 6,
@@ -150,6 +160,8 @@ mod tests {
                 instructions: Some("Check email for link to click on. When you click on the link you should see a verification that it worked below the form.".to_string()),
                 wait_after_ms: 1000,
                 optional: false,
+                fallback_selectors: vec![],
+                phase: None,
             },
         ]
     }
@@ -201,6 +213,7 @@ mod tests {
             success_count: 0,
             failure_count: 0,
             created_at: "2025-01-01T00:00:00Z".to_string(),
+            contributor: None,
         };
 
         let result = verify_playbook_signature(&playbook);
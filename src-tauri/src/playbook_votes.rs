@@ -0,0 +1,113 @@
+use crate::models::{PlaybookVote, PlaybookVoteStore, VoteDirection};
+use crate::playbook_api;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+const FILENAME: &str = "playbook_votes.json";
+
+/// Held while a vote is checked, sent and recorded, so two clicks can't both get past
+/// the duplicate check before either is saved.
+static VOTING: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<PlaybookVoteStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(PlaybookVoteStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &PlaybookVoteStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// The value the API takes for a vote, where "none" takes a vote back.
+fn api_value(vote: Option<VoteDirection>) -> &'static str {
+    match vote {
+        Some(VoteDirection::Up) => "up",
+        Some(VoteDirection::Down) => "down",
+        None => "none",
+    }
+}
+
+/// Refuse a vote that wouldn't change anything: the same vote again, or taking back a
+/// vote that was never cast. Switching between up and down is allowed.
+fn check_change(existing: Option<VoteDirection>, requested: Option<VoteDirection>) -> Result<(), String> {
+    match (existing, requested) {
+        (Some(_), None) | (None, Some(_)) => Ok(()),
+        (Some(a), Some(b)) if a != b => Ok(()),
+        (Some(_), Some(_)) => Err("You've already voted on this playbook.".to_string()),
+        (None, None) => Err("You haven't voted on this playbook.".to_string()),
+    }
+}
+
+fn apply(store: &mut PlaybookVoteStore, playbook_id: &str, vote: Option<VoteDirection>) {
+    store.votes.retain(|v| v.playbook_id != playbook_id);
+    if let Some(vote) = vote {
+        store.votes.push(PlaybookVote { playbook_id: playbook_id.to_string(), vote, voted_at: Utc::now() });
+    }
+}
+
+/// Cast, change or (with `None`) take back this device's vote on a playbook. The vote
+/// is only remembered once the API has accepted it.
+pub async fn cast(app: &tauri::AppHandle, playbook_id: &str, vote: Option<VoteDirection>) -> Result<(), String> {
+    let _voting = VOTING.lock().await;
+    let existing = load(app)?.votes.into_iter().find(|v| v.playbook_id == playbook_id).map(|v| v.vote);
+    check_change(existing, vote)?;
+    playbook_api::vote_playbook(playbook_id, api_value(vote)).await?;
+    // Reload in case the votes were imported while the request was out
+    let mut store = load(app)?;
+    apply(&mut store, playbook_id, vote);
+    save(app, &store)
+}
+
+/// Remember votes cast before they were kept here, skipping playbooks that already have
+/// one. Nothing is sent: the API already has them.
+pub fn import(app: &tauri::AppHandle, votes: HashMap<String, VoteDirection>) -> Result<(), String> {
+    let mut store = load(app)?;
+    for (playbook_id, vote) in votes {
+        if !store.votes.iter().any(|v| v.playbook_id == playbook_id) {
+            apply(&mut store, &playbook_id, Some(vote));
+        }
+    }
+    save(app, &store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_only_votes_that_change_something() {
+        use VoteDirection::{Down, Up};
+        assert!(check_change(None, Some(Up)).is_ok());
+        assert!(check_change(Some(Up), Some(Down)).is_ok());
+        assert!(check_change(Some(Down), None).is_ok());
+        assert!(check_change(Some(Up), Some(Up)).is_err());
+        assert!(check_change(None, None).is_err());
+    }
+
+    #[test]
+    fn keeps_one_vote_per_playbook() {
+        let mut store = PlaybookVoteStore::default();
+        apply(&mut store, "a", Some(VoteDirection::Up));
+        apply(&mut store, "b", Some(VoteDirection::Up));
+        apply(&mut store, "a", Some(VoteDirection::Down));
+        let votes: Vec<_> = store.votes.iter().map(|v| (v.playbook_id.as_str(), v.vote)).collect();
+        assert_eq!(votes, [("b", VoteDirection::Up), ("a", VoteDirection::Down)]);
+        apply(&mut store, "b", None);
+        assert_eq!(store.votes.len(), 1);
+    }
+}
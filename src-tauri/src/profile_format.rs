@@ -0,0 +1,231 @@
+use crate::models::Profile;
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDate;
+
+/// USPS state, district and territory codes with their full names.
+pub const STATES: &[(&str, &str)] = &[
+    ("AL", "Alabama"), ("AK", "Alaska"), ("AZ", "Arizona"), ("AR", "Arkansas"),
+    ("CA", "California"), ("CO", "Colorado"), ("CT", "Connecticut"), ("DE", "Delaware"),
+    ("FL", "Florida"), ("GA", "Georgia"), ("HI", "Hawaii"), ("ID", "Idaho"),
+    ("IL", "Illinois"), ("IN", "Indiana"), ("IA", "Iowa"), ("KS", "Kansas"),
+    ("KY", "Kentucky"), ("LA", "Louisiana"), ("ME", "Maine"), ("MD", "Maryland"),
+    ("MA", "Massachusetts"), ("MI", "Michigan"), ("MN", "Minnesota"), ("MS", "Mississippi"),
+    ("MO", "Missouri"), ("MT", "Montana"), ("NE", "Nebraska"), ("NV", "Nevada"),
+    ("NH", "New Hampshire"), ("NJ", "New Jersey"), ("NM", "New Mexico"), ("NY", "New York"),
+    ("NC", "North Carolina"), ("ND", "North Dakota"), ("OH", "Ohio"), ("OK", "Oklahoma"),
+    ("OR", "Oregon"), ("PA", "Pennsylvania"), ("RI", "Rhode Island"), ("SC", "South Carolina"),
+    ("SD", "South Dakota"), ("TN", "Tennessee"), ("TX", "Texas"), ("UT", "Utah"),
+    ("VT", "Vermont"), ("VA", "Virginia"), ("WA", "Washington"), ("WV", "West Virginia"),
+    ("WI", "Wisconsin"), ("WY", "Wyoming"), ("DC", "District of Columbia"), ("PR", "Puerto Rico"),
+    ("VI", "U.S. Virgin Islands"), ("GU", "Guam"), ("AS", "American Samoa"),
+    ("MP", "Northern Mariana Islands"),
+];
+
+/// Two-letter code for a state given as a code or full name (case-insensitive).
+pub fn state_code(value: &str) -> Option<&'static str> {
+    let value = value.trim();
+    STATES
+        .iter()
+        .find(|(code, name)| code.eq_ignore_ascii_case(value) || name.eq_ignore_ascii_case(value))
+        .map(|(code, _)| *code)
+}
+
+/// Split an indexed profile key like `previousAddress[1].city` into its list name,
+/// index and field (`("previousAddress", 1, "city")`). The field is empty when the
+/// key has none (`alternateEmail[0]`).
+pub fn parse_indexed_key(key: &str) -> Option<(&str, usize, &str)> {
+    let (name, rest) = key.split_once('[')?;
+    let (index, field) = rest.split_once(']')?;
+    let index = index.parse().ok()?;
+    let field = match field {
+        "" => "",
+        f => f.strip_prefix('.')?,
+    };
+    Some((name, index, field))
+}
+
+/// The 10 national digits of a US phone number, dropping a leading country code.
+fn phone_digits(value: &str) -> Option<String> {
+    let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
+    match digits.len() {
+        10 => Some(digits),
+        11 if digits.starts_with('1') => Some(digits[1..].to_string()),
+        _ => None,
+    }
+}
+
+/// Capitalize each word of an all-lower or all-upper name. Mixed case
+/// ("McDonald", "DeShawn") is assumed deliberate and kept.
+fn title_case(value: &str) -> String {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    let has_lower = value.chars().any(|c| c.is_lowercase());
+    let has_upper = value.chars().any(|c| c.is_uppercase());
+    if has_lower && has_upper {
+        return value;
+    }
+    let mut out = String::with_capacity(value.len());
+    let mut word_start = true;
+    for c in value.chars() {
+        if word_start {
+            out.extend(c.to_uppercase());
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        word_start = matches!(c, ' ' | '-' | '\'');
+    }
+    out
+}
+
+/// Put a profile into canonical form before it is saved: trimmed fields,
+/// title-cased names, `555-123-4567` phones, two-letter states, lowercase emails.
+/// Values that can't be parsed are left for validation to report.
+pub fn normalize_profile(profile: &mut Profile) {
+    profile.first_name = title_case(&profile.first_name);
+    profile.last_name = title_case(&profile.last_name);
+    profile.email = profile.email.trim().to_lowercase();
+    profile.phone = normalize_phone(&profile.phone);
+    profile.address = profile.address.trim().to_string();
+    profile.city = title_case(&profile.city);
+    profile.state = normalize_state(&profile.state);
+    profile.zip = profile.zip.trim().to_string();
+    profile.dob = profile.dob.trim().to_string();
+    profile.country = profile.country.trim().to_ascii_uppercase();
+
+    for email in profile.alternate_emails.iter_mut() {
+        *email = email.trim().to_lowercase();
+    }
+    for phone in profile.alternate_phones.iter_mut() {
+        *phone = normalize_phone(phone);
+    }
+    for addr in profile.previous_addresses.iter_mut() {
+        addr.address = addr.address.trim().to_string();
+        addr.city = title_case(&addr.city);
+        addr.state = normalize_state(&addr.state);
+        addr.zip = addr.zip.trim().to_string();
+    }
+}
+
+fn normalize_phone(value: &str) -> String {
+    match phone_digits(value) {
+        Some(d) => format!("{}-{}-{}", &d[..3], &d[3..6], &d[6..]),
+        None => value.trim().to_string(),
+    }
+}
+
+fn normalize_state(value: &str) -> String {
+    match state_code(value) {
+        Some(code) => code.to_string(),
+        None => value.trim().to_string(),
+    }
+}
+
+/// Convert a canonical profile value into the format a broker's form expects.
+///
+/// Supported transforms:
+/// - `phone:digits` (5551234567), `phone:dashes`, `phone:dots`, `phone:parens`
+///   ((555) 123-4567), `phone:e164` (+15551234567)
+/// - `date:<strftime>` for the date of birth, e.g. `date:%m/%d/%Y`
+/// - `state:name` (Illinois), `zip:5`, `upper`, `lower`
+///
+/// Unknown transforms and values that don't parse are passed through unchanged.
+pub fn apply_transform(value: &str, transform: &str) -> String {
+    if let Some(kind) = transform.strip_prefix("phone:") {
+        let Some(d) = phone_digits(value) else {
+            return value.to_string();
+        };
+        let (area, prefix, line) = (&d[..3], &d[3..6], &d[6..]);
+        return match kind {
+            "digits" => d.clone(),
+            "dashes" => format!("{}-{}-{}", area, prefix, line),
+            "dots" => format!("{}.{}.{}", area, prefix, line),
+            "parens" => format!("({}) {}-{}", area, prefix, line),
+            "e164" => format!("+1{}", d),
+            _ => value.to_string(),
+        };
+    }
+    if let Some(fmt) = transform.strip_prefix("date:") {
+        // An invalid format string would panic when displayed, so check it first
+        let items: Vec<Item> = StrftimeItems::new(fmt).collect();
+        return match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            Ok(date) if !items.contains(&Item::Error) => date.format_with_items(items.into_iter()).to_string(),
+            _ => value.to_string(),
+        };
+    }
+    match transform {
+        "state:name" => state_code(value)
+            .and_then(|code| STATES.iter().find(|(c, _)| *c == code))
+            .map(|(_, name)| name.to_string())
+            .unwrap_or_else(|| value.to_string()),
+        "zip:5" => value.chars().take(5).collect(),
+        "upper" => value.to_uppercase(),
+        "lower" => value.to_lowercase(),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PreviousAddress;
+
+    #[test]
+    fn normalizes_profile_fields() {
+        let mut profile = Profile {
+            first_name: "  mary-jane ".to_string(),
+            last_name: "O'NEIL".to_string(),
+            email: " Mary@Example.com ".to_string(),
+            phone: "+1 (555) 123 4567".to_string(),
+            address: " 1 Main St ".to_string(),
+            city: "springfield".to_string(),
+            state: "illinois".to_string(),
+            zip: " 62701 ".to_string(),
+            dob: "1985-04-12".to_string(),
+            alternate_emails: vec![],
+            alternate_phones: vec!["555.987.6543".to_string()],
+            previous_addresses: vec![PreviousAddress {
+                address: "2 Oak Ave".to_string(),
+                city: "new york".to_string(),
+                state: "ny".to_string(),
+                zip: "10001".to_string(),
+            }],
+            country: " us".to_string(),
+        };
+        normalize_profile(&mut profile);
+        assert_eq!(profile.first_name, "Mary-Jane");
+        assert_eq!(profile.last_name, "O'Neil");
+        assert_eq!(profile.email, "mary@example.com");
+        assert_eq!(profile.phone, "555-123-4567");
+        assert_eq!(profile.country, "US");
+        assert_eq!(profile.city, "Springfield");
+        assert_eq!(profile.state, "IL");
+        assert_eq!(profile.zip, "62701");
+        assert_eq!(profile.alternate_phones[0], "555-987-6543");
+        assert_eq!(profile.previous_addresses[0].city, "New York");
+        assert_eq!(profile.previous_addresses[0].state, "NY");
+    }
+
+    #[test]
+    fn parses_indexed_keys() {
+        assert_eq!(parse_indexed_key("previousAddress[1].city"), Some(("previousAddress", 1, "city")));
+        assert_eq!(parse_indexed_key("alternateEmail[0]"), Some(("alternateEmail", 0, "")));
+        assert_eq!(parse_indexed_key("previousAddress[x].city"), None);
+        assert_eq!(parse_indexed_key("city"), None);
+    }
+
+    #[test]
+    fn keeps_mixed_case_names() {
+        assert_eq!(title_case("McDonald"), "McDonald");
+    }
+
+    #[test]
+    fn applies_broker_transforms() {
+        assert_eq!(apply_transform("555-123-4567", "phone:digits"), "5551234567");
+        assert_eq!(apply_transform("555-123-4567", "phone:parens"), "(555) 123-4567");
+        assert_eq!(apply_transform("555-123-4567", "phone:e164"), "+15551234567");
+        assert_eq!(apply_transform("1985-04-12", "date:%m/%d/%Y"), "04/12/1985");
+        assert_eq!(apply_transform("IL", "state:name"), "Illinois");
+        assert_eq!(apply_transform("62701-1234", "zip:5"), "62701");
+        assert_eq!(apply_transform("1985-04-12", "date:%Q"), "1985-04-12");
+        assert_eq!(apply_transform("abc", "unknown"), "abc");
+    }
+}
@@ -0,0 +1,464 @@
+use crate::models::{ImportedField, PreviousAddress, Profile, ProfileImport};
+use crate::profile_format;
+use chrono::NaiveDate;
+
+/// vCard properties that carry nothing worth mapping onto a profile.
+const IGNORED_VCARD: &[&str] = &["BEGIN", "END", "VERSION", "PRODID", "UID", "REV", "PHOTO", "LOGO", "SOUND", "KEY"];
+
+/// Parse a vCard (`.vcf`) or CSV export into a profile. Anything that couldn't be placed
+/// is returned in `unmatched` so the user can map it by hand. Only the first contact
+/// in the file is read.
+pub fn import(content: &str, file_name: &str) -> Result<ProfileImport, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let is_vcard = content.trim_start().to_uppercase().starts_with("BEGIN:VCARD")
+        || file_name.to_lowercase().ends_with(".vcf");
+    let mut import = if is_vcard { from_vcard(content)? } else { from_csv(content)? };
+    profile_format::normalize_profile(&mut import.profile);
+    Ok(import)
+}
+
+fn empty_profile() -> Profile {
+    Profile {
+        first_name: String::new(),
+        last_name: String::new(),
+        email: String::new(),
+        phone: String::new(),
+        address: String::new(),
+        city: String::new(),
+        state: String::new(),
+        zip: String::new(),
+        dob: String::new(),
+        alternate_emails: vec![],
+        alternate_phones: vec![],
+        previous_addresses: vec![],
+        country: String::new(),
+    }
+}
+
+/// Collects values into a profile, first one wins, later ones become alternates.
+struct Builder {
+    profile: Profile,
+    unmatched: Vec<ImportedField>,
+}
+
+impl Builder {
+    fn new() -> Self {
+        Builder {
+            profile: empty_profile(),
+            unmatched: Vec::new(),
+        }
+    }
+
+    fn unmatched(&mut self, label: &str, value: &str) {
+        if !value.trim().is_empty() {
+            self.unmatched.push(ImportedField {
+                label: label.to_string(),
+                value: value.trim().to_string(),
+            });
+        }
+    }
+
+    fn name(&mut self, first: &str, last: &str) {
+        if self.profile.first_name.is_empty() && self.profile.last_name.is_empty() {
+            self.profile.first_name = first.trim().to_string();
+            self.profile.last_name = last.trim().to_string();
+        }
+    }
+
+    fn full_name(&mut self, full: &str) {
+        let full = full.trim();
+        match full.rsplit_once(' ') {
+            Some((first, last)) => self.name(first.split_whitespace().next().unwrap_or(first), last),
+            None => self.name(full, ""),
+        }
+    }
+
+    fn email(&mut self, value: &str) {
+        let value = value.trim();
+        if value.is_empty() || self.profile.email == value || self.profile.alternate_emails.iter().any(|e| e == value) {
+            return;
+        }
+        if self.profile.email.is_empty() {
+            self.profile.email = value.to_string();
+        } else {
+            self.profile.alternate_emails.push(value.to_string());
+        }
+    }
+
+    fn phone(&mut self, value: &str) {
+        let value = value.trim();
+        if value.is_empty() || self.profile.phone == value || self.profile.alternate_phones.iter().any(|p| p == value) {
+            return;
+        }
+        if self.profile.phone.is_empty() {
+            self.profile.phone = value.to_string();
+        } else {
+            self.profile.alternate_phones.push(value.to_string());
+        }
+    }
+
+    fn address(&mut self, address: PreviousAddress) {
+        if address.address.trim().is_empty() && address.city.trim().is_empty() {
+            return;
+        }
+        if self.profile.address.is_empty() && self.profile.city.is_empty() {
+            self.profile.address = address.address;
+            self.profile.city = address.city;
+            self.profile.state = address.state;
+            self.profile.zip = address.zip;
+        } else {
+            self.profile.previous_addresses.push(address);
+        }
+    }
+
+    fn dob(&mut self, label: &str, value: &str) {
+        match parse_date(value) {
+            Some(date) if self.profile.dob.is_empty() => self.profile.dob = date,
+            _ => self.unmatched(label, value),
+        }
+    }
+
+    fn finish(self) -> ProfileImport {
+        ProfileImport {
+            profile: self.profile,
+            unmatched: self.unmatched,
+        }
+    }
+}
+
+/// Birthdays as exported by contacts apps: `1980-06-15`, `19800615`, `06/15/1980`.
+/// Year-less birthdays (`--0615`) aren't usable and return None.
+fn parse_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    let value = value.split('T').next().unwrap_or(value);
+    ["%Y-%m-%d", "%Y%m%d", "%m/%d/%Y"]
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(value, f).ok())
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+// --- vCard ---
+
+fn from_vcard(content: &str) -> Result<ProfileImport, String> {
+    // Continuation lines start with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut builder = Builder::new();
+    let mut full_name: Option<String> = None;
+    let mut in_card = false;
+    for line in &lines {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = key.split(';');
+        let name = parts.next().unwrap_or_default();
+        // Apple groups properties as `item1.EMAIL`
+        let name = name.rsplit('.').next().unwrap_or(name).to_uppercase();
+        let params = parts.collect::<Vec<_>>().join(";").to_uppercase();
+
+        match name.as_str() {
+            "BEGIN" if value.trim().eq_ignore_ascii_case("VCARD") => in_card = true,
+            "END" if value.trim().eq_ignore_ascii_case("VCARD") => break,
+            _ if !in_card => {}
+            "N" => {
+                let fields = split_escaped(value, ';');
+                let last = fields.first().cloned().unwrap_or_default();
+                let first = fields.get(1).cloned().unwrap_or_default();
+                builder.name(&first, &last);
+            }
+            "FN" => full_name = Some(unescape(value)),
+            "EMAIL" => builder.email(&unescape(value)),
+            "TEL" => builder.phone(&unescape(value)),
+            "ADR" => {
+                let fields = split_escaped(value, ';');
+                let field = |i: usize| fields.get(i).cloned().unwrap_or_default();
+                if params.contains("WORK") {
+                    let joined: Vec<String> = (2..6).map(field).filter(|f| !f.is_empty()).collect();
+                    builder.unmatched("Work address", &joined.join(", "));
+                } else {
+                    builder.address(PreviousAddress {
+                        address: field(2),
+                        city: field(3),
+                        state: field(4),
+                        zip: field(5),
+                    });
+                }
+            }
+            "BDAY" => builder.dob("Birthday", value),
+            other if IGNORED_VCARD.contains(&other) || other.starts_with("X-") => {}
+            other => builder.unmatched(&vcard_label(other), &unescape(value)),
+        }
+    }
+    if !in_card {
+        return Err("This doesn't look like a vCard file.".to_string());
+    }
+    if let Some(full) = full_name {
+        builder.full_name(&full);
+    }
+    Ok(builder.finish())
+}
+
+fn vcard_label(name: &str) -> String {
+    match name {
+        "ORG" => "Organization".to_string(),
+        "TITLE" => "Job title".to_string(),
+        "NICKNAME" => "Nickname".to_string(),
+        "NOTE" => "Note".to_string(),
+        "URL" => "Website".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n' | 'N')) => {
+                chars.next();
+                out.push(' ');
+            }
+            ('\\', Some(next)) => {
+                chars.next();
+                out.push(next);
+            }
+            _ => out.push(c),
+        }
+    }
+    out.trim().to_string()
+}
+
+/// Split a structured vCard value on an unescaped separator.
+fn split_escaped(value: &str, sep: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == sep {
+            fields.push(unescape(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    fields.push(unescape(&current));
+    fields
+}
+
+// --- CSV ---
+
+/// Which profile field a CSV column holds, judged from its header.
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    FirstName,
+    LastName,
+    FullName,
+    Email,
+    Phone,
+    Street,
+    City,
+    State,
+    Zip,
+    Dob,
+    /// Type and label columns describing another column ("Phone 1 - Type")
+    Ignore,
+}
+
+fn classify_header(header: &str) -> Option<Column> {
+    let h: String = header.to_lowercase().chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    // Google exports label columns like "E-mail 1 - Value"; strip the numbering
+    let h: String = h.replace("value", "").chars().filter(|c| !c.is_ascii_digit()).collect();
+    let column = match h.as_str() {
+        _ if h.ends_with("type") || h.ends_with("label") => Column::Ignore,
+        "firstname" | "givenname" | "first" => Column::FirstName,
+        "lastname" | "familyname" | "surname" | "last" => Column::LastName,
+        "name" | "fullname" | "displayname" => Column::FullName,
+        "birthday" | "dob" | "dateofbirth" | "birthdate" => Column::Dob,
+        "address" | "addressline" | "streetaddress" => Column::Street,
+        _ if h.contains("email") => Column::Email,
+        _ if h.contains("phone") || h.contains("mobile") => Column::Phone,
+        _ if h.contains("street") => Column::Street,
+        _ if h.contains("city") => Column::City,
+        _ if h.contains("state") || h.contains("province") || h.contains("region") => Column::State,
+        _ if h.contains("zip") || h.contains("postal") || h.contains("postcode") => Column::Zip,
+        _ => return None,
+    };
+    Some(column)
+}
+
+fn from_csv(content: &str) -> Result<ProfileImport, String> {
+    let rows = parse_csv(content);
+    let mut rows = rows.into_iter().filter(|r| r.iter().any(|f| !f.trim().is_empty()));
+    let headers = rows.next().ok_or("The file is empty.")?;
+    let values = rows.next().ok_or("The file has a header row but no contact.")?;
+
+    let mut builder = Builder::new();
+    let (mut first, mut last, mut full) = (String::new(), String::new(), String::new());
+    let mut address = PreviousAddress {
+        address: String::new(),
+        city: String::new(),
+        state: String::new(),
+        zip: String::new(),
+    };
+    for (header, value) in headers.iter().zip(values.iter()) {
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        // A second street/city/etc. column means a second address; leave those to the user
+        let slot = |field: &mut String| {
+            if field.is_empty() {
+                *field = value.to_string();
+                true
+            } else {
+                false
+            }
+        };
+        let placed = match classify_header(header) {
+            Some(Column::FirstName) => slot(&mut first),
+            Some(Column::LastName) => slot(&mut last),
+            Some(Column::FullName) => slot(&mut full),
+            Some(Column::Email) => {
+                builder.email(value);
+                true
+            }
+            Some(Column::Phone) => {
+                builder.phone(value);
+                true
+            }
+            Some(Column::Street) => slot(&mut address.address),
+            Some(Column::City) => slot(&mut address.city),
+            Some(Column::State) => slot(&mut address.state),
+            Some(Column::Zip) => slot(&mut address.zip),
+            Some(Column::Dob) => {
+                builder.dob(header, value);
+                true
+            }
+            Some(Column::Ignore) => true,
+            None => false,
+        };
+        if !placed {
+            builder.unmatched(header, value);
+        }
+    }
+    if !first.is_empty() || !last.is_empty() {
+        builder.name(&first, &last);
+    } else if !full.is_empty() {
+        builder.full_name(&full);
+    }
+    builder.address(address);
+    Ok(builder.finish())
+}
+
+/// Rows of a CSV file. Handles quoted fields with embedded commas, quotes and newlines.
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn imports_vcard_with_alternates_and_unmatched_fields() {
+        let vcard = "BEGIN:VCARD\r\nVERSION:3.0\r\nN:Smith;Jane;;;\r\nFN:Jane Smith\r\n\
+            item1.EMAIL;TYPE=INTERNET:jane@example.com\r\nEMAIL:jsmith@work.example\r\n\
+            TEL;TYPE=CELL:(555) 123-4567\r\n\
+            ADR;TYPE=HOME:;;123 Main St;Springfield;Illinois;62701;USA\r\n\
+            ADR;TYPE=WORK:;;1 Office Pl;Chicago;IL;60601;\r\n\
+            BDAY:19800615\r\nORG:Acme\\, Inc.\r\nNOTE:Long note that\r\n  continues\r\nEND:VCARD\r\n";
+        let import = import(vcard, "jane.vcf").unwrap();
+        let p = &import.profile;
+        assert_eq!((p.first_name.as_str(), p.last_name.as_str()), ("Jane", "Smith"));
+        assert_eq!(p.email, "jane@example.com");
+        assert_eq!(p.alternate_emails, vec!["jsmith@work.example"]);
+        assert_eq!(p.phone, "555-123-4567");
+        assert_eq!((p.address.as_str(), p.city.as_str(), p.state.as_str(), p.zip.as_str()), ("123 Main St", "Springfield", "IL", "62701"));
+        assert_eq!(p.dob, "1980-06-15");
+
+        let labels: Vec<(&str, &str)> = import.unmatched.iter().map(|f| (f.label.as_str(), f.value.as_str())).collect();
+        assert_eq!(
+            labels,
+            vec![
+                ("Work address", "1 Office Pl, Chicago, IL, 60601"),
+                ("Organization", "Acme, Inc."),
+                ("Note", "Long note that continues"),
+            ]
+        );
+    }
+
+    #[test]
+    fn imports_csv_with_quoted_fields() {
+        let csv = "Name,E-mail 1 - Value,Phone 1 - Type,Phone 1 - Value,Address 1 - Street,Address 1 - City,Address 1 - Region,Address 1 - Postal Code,Birthday,Company\n\
+            \"Jane Q Smith\",jane@example.com,Mobile,555.123.4567,\"123 Main St, Apt 4\",Springfield,IL,62701,06/15/1980,\"Acme \"\"East\"\"\"\n";
+        let import = import(csv, "contacts.csv").unwrap();
+        let p = &import.profile;
+        assert_eq!((p.first_name.as_str(), p.last_name.as_str()), ("Jane", "Smith"));
+        assert_eq!(p.email, "jane@example.com");
+        assert_eq!(p.phone, "555-123-4567");
+        assert_eq!(p.address, "123 Main St, Apt 4");
+        assert_eq!(p.dob, "1980-06-15");
+        assert_eq!(import.unmatched.len(), 1);
+        assert_eq!(import.unmatched[0].value, "Acme \"East\"");
+    }
+
+    #[test]
+    fn leaves_yearless_birthdays_and_second_addresses_unmatched() {
+        let csv = "First Name,Last Name,Street,City,Street,City,Birthday\nJane,Smith,1 A St,Austin,2 B St,Dallas,--06-15\n";
+        let import = import(csv, "export.csv").unwrap();
+        assert_eq!(import.profile.city, "Austin");
+        assert!(import.profile.dob.is_empty());
+        let labels: Vec<&str> = import.unmatched.iter().map(|f| f.label.as_str()).collect();
+        assert_eq!(labels, vec!["Street", "City", "Birthday"]);
+    }
+
+    #[test]
+    fn rejects_empty_files() {
+        assert!(import("", "empty.csv").is_err());
+        assert!(import("Name,Email\n", "header.csv").is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use crate::models::{Profile, ProfileFieldError};
+use crate::profile_format;
+use crate::region;
+use chrono::NaiveDate;
+
+/// Validate a profile, returning one error per bad field. Field names match the
+/// frontend keys (`firstName`, `previousAddresses[0].zip`, ...). Optional fields
+/// are only checked when filled in. Phone, state and ZIP formats are only checked for
+/// US residents.
+pub fn validate_profile(profile: &Profile) -> Vec<ProfileFieldError> {
+    let mut errors = Vec::new();
+
+    require(&mut errors, "firstName", &profile.first_name, "First name is required");
+    require(&mut errors, "lastName", &profile.last_name, "Last name is required");
+    if profile.email.trim().is_empty() {
+        push(&mut errors, "email", "Email is required");
+    } else {
+        check_email(&mut errors, "email", &profile.email);
+    }
+
+    let us = region::is_us(profile);
+    if us {
+        check_phone(&mut errors, "phone", &profile.phone);
+        check_state(&mut errors, "state", &profile.state);
+        check_zip(&mut errors, "zip", &profile.zip);
+    }
+    check_dob(&mut errors, "dob", &profile.dob);
+    check_country(&mut errors, "country", &profile.country);
+
+    for (i, email) in profile.alternate_emails.iter().enumerate() {
+        check_email(&mut errors, &format!("alternateEmails[{}]", i), email);
+    }
+    if us {
+        for (i, phone) in profile.alternate_phones.iter().enumerate() {
+            check_phone(&mut errors, &format!("alternatePhones[{}]", i), phone);
+        }
+        for (i, addr) in profile.previous_addresses.iter().enumerate() {
+            check_state(&mut errors, &format!("previousAddresses[{}].state", i), &addr.state);
+            check_zip(&mut errors, &format!("previousAddresses[{}].zip", i), &addr.zip);
+        }
+    }
+
+    errors
+}
+
+fn push(errors: &mut Vec<ProfileFieldError>, field: &str, message: &str) {
+    errors.push(ProfileFieldError {
+        field: field.to_string(),
+        message: message.to_string(),
+    });
+}
+
+fn require(errors: &mut Vec<ProfileFieldError>, field: &str, value: &str, message: &str) {
+    if value.trim().is_empty() {
+        push(errors, field, message);
+    }
+}
+
+fn check_email(errors: &mut Vec<ProfileFieldError>, field: &str, value: &str) {
+    let value = value.trim();
+    if value.is_empty() {
+        return;
+    }
+    let valid = match value.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !value.contains(char::is_whitespace)
+        }
+        None => false,
+    };
+    if !valid {
+        push(errors, field, "Enter a valid email address");
+    }
+}
+
+fn check_phone(errors: &mut Vec<ProfileFieldError>, field: &str, value: &str) {
+    let value = value.trim();
+    if value.is_empty() {
+        return;
+    }
+    if value.chars().any(|c| !(c.is_ascii_digit() || " ()-.+".contains(c))) {
+        push(errors, field, "Phone numbers may only contain digits, spaces, and ()-.+");
+        return;
+    }
+    let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
+    let national = match digits.len() {
+        11 if digits.starts_with('1') => &digits[1..],
+        _ => digits.as_str(),
+    };
+    if national.len() != 10 {
+        push(errors, field, "Phone number must have 10 digits");
+    }
+}
+
+fn check_state(errors: &mut Vec<ProfileFieldError>, field: &str, value: &str) {
+    let value = value.trim();
+    if value.is_empty() {
+        return;
+    }
+    if profile_format::state_code(value).is_none() {
+        push(errors, field, "Enter a US state name or two-letter code (e.g. CA)");
+    }
+}
+
+fn check_country(errors: &mut Vec<ProfileFieldError>, field: &str, value: &str) {
+    let value = value.trim();
+    if value.is_empty() {
+        return;
+    }
+    if value.len() != 2 || !value.chars().all(|c| c.is_ascii_alphabetic()) {
+        push(errors, field, "Enter a two-letter country code (e.g. US, GB, DE)");
+    }
+}
+
+fn check_zip(errors: &mut Vec<ProfileFieldError>, field: &str, value: &str) {
+    let value = value.trim();
+    if value.is_empty() {
+        return;
+    }
+    let (base, plus4) = match value.split_once('-') {
+        Some((base, ext)) => (base, Some(ext)),
+        None => (value, None),
+    };
+    let all_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    let valid = base.len() == 5 && all_digits(base) && plus4.is_none_or(|ext| ext.len() == 4 && all_digits(ext));
+    if !valid {
+        push(errors, field, "ZIP must be 5 digits or ZIP+4 (12345-6789)");
+    }
+}
+
+fn check_dob(errors: &mut Vec<ProfileFieldError>, field: &str, value: &str) {
+    let value = value.trim();
+    if value.is_empty() {
+        return;
+    }
+    match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => {
+            let today = chrono::Utc::now().date_naive();
+            if date >= today {
+                push(errors, field, "Date of birth must be in the past");
+            }
+        }
+        Err(_) => push(errors, field, "Date of birth must be a valid date (YYYY-MM-DD)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PreviousAddress;
+    use crate::test_fixtures::profile;
+
+    fn fields(errors: &[ProfileFieldError]) -> Vec<&str> {
+        errors.iter().map(|e| e.field.as_str()).collect()
+    }
+
+    #[test]
+    fn accepts_valid_profile() {
+        assert!(validate_profile(&profile()).is_empty());
+    }
+
+    #[test]
+    fn allows_empty_optional_fields() {
+        let mut p = profile();
+        p.phone = String::new();
+        p.state = String::new();
+        p.zip = String::new();
+        p.dob = String::new();
+        assert!(validate_profile(&p).is_empty());
+    }
+
+    #[test]
+    fn requires_name_and_email() {
+        let mut p = profile();
+        p.first_name = " ".to_string();
+        p.email = String::new();
+        assert_eq!(fields(&validate_profile(&p)), vec!["firstName", "email"]);
+    }
+
+    #[test]
+    fn rejects_bad_formats() {
+        let mut p = profile();
+        p.email = "jane@example".to_string();
+        p.phone = "555-1234".to_string();
+        p.state = "Ilinois".to_string();
+        p.zip = "6270".to_string();
+        p.dob = "04/12/1985".to_string();
+        assert_eq!(fields(&validate_profile(&p)), vec!["email", "phone", "state", "zip", "dob"]);
+    }
+
+    #[test]
+    fn accepts_country_code_and_zip_plus_four() {
+        let mut p = profile();
+        p.phone = "+1 555 123 4567".to_string();
+        p.zip = "62701-1234".to_string();
+        assert!(validate_profile(&p).is_empty());
+    }
+
+    #[test]
+    fn skips_us_formats_outside_the_us() {
+        let mut p = profile();
+        p.country = "GB".to_string();
+        p.phone = "+44 20 7946 0958".to_string();
+        p.state = "Greater London".to_string();
+        p.zip = "SW1A 1AA".to_string();
+        assert!(validate_profile(&p).is_empty());
+        p.country = "Britain".to_string();
+        assert_eq!(fields(&validate_profile(&p)), vec!["country"]);
+    }
+
+    #[test]
+    fn indexes_nested_field_errors() {
+        let mut p = profile();
+        p.alternate_emails = vec!["ok@example.com".to_string(), "nope".to_string()];
+        p.previous_addresses = vec![PreviousAddress {
+            address: "2 Oak Ave".to_string(),
+            city: "Peoria".to_string(),
+            state: "IL".to_string(),
+            zip: "abcde".to_string(),
+        }];
+        assert_eq!(
+            fields(&validate_profile(&p)),
+            vec!["alternateEmails[1]", "previousAddresses[0].zip"]
+        );
+    }
+}
@@ -1,29 +1,55 @@
 use crate::browser;
-use crate::models::RecordedAction;
+use crate::consent_banners;
+use crate::done_detection;
+use crate::field_matching::{self, FieldHints};
+use crate::models::{KnownField, Profile, RecordedAction, RecordingSession};
+use crate::pii_scan;
+use crate::playbook_draft;
+use crate::settings;
 use chromiumoxide::browser::Browser;
+use chromiumoxide::cdp::browser_protocol::page::{EventFrameNavigated, EventNavigatedWithinDocument, FrameId};
+use chromiumoxide::cdp::js_protocol::runtime::{AddBindingParams, EventBindingCalled};
 use chromiumoxide::page::Page;
+use futures::StreamExt;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
-use tokio::time::{sleep, Duration};
+use tokio::time::Duration;
 
-/// JavaScript injected into pages during recording mode.
+/// CDP binding the recorder JS calls with each action, delivered to Rust immediately.
+const RECORDER_BINDING: &str = "__optOuttaRecord";
+
+/// JavaScript injected into every document during recording mode.
 /// Records user actions (clicks, form interactions, navigations) but NEVER captures field values or PII.
 const RECORDER_JS: &str = r#"
 (() => {
-    if (window.__optOuttaRecorder) return;
+    // Steps are replayed against the top document; ignore clicks inside iframes (CAPTCHAs, ads)
+    if (window.__optOuttaRecorder || window !== window.top) return;
 
     window.__optOuttaRecorder = {
-        actions: [],
         lastClickTime: 0,
         lastClickSelector: ''
     };
 
-    function cssSelector(el) {
-        if (el.id) return '#' + CSS.escape(el.id);
-        if (el.name && (el.tagName === 'INPUT' || el.tagName === 'SELECT' || el.tagName === 'TEXTAREA')) {
-            return el.tagName.toLowerCase() + '[name="' + CSS.escape(el.name) + '"]';
+    // The binding sends the action over CDP synchronously, so actions right before a
+    // navigation (e.g. submitting a search form with Enter) aren't lost with the page.
+    function record(action) {
+        if (typeof window.__optOuttaRecord === 'function') {
+            window.__optOuttaRecord(JSON.stringify(action));
         }
+    }
+
+    function isUnique(sel, el) {
+        try {
+            const found = document.querySelectorAll(sel);
+            return found.length === 1 && found[0] === el;
+        } catch (e) {
+            return false;
+        }
+    }
+
+    function structuralPath(el) {
         let path = [];
         while (el && el.nodeType === 1) {
             let selector = el.tagName.toLowerCase();
@@ -37,6 +63,27 @@ const RECORDER_JS: &str = r#"
         return path.join(' > ');
     }
 
+    // Every locator that picks out just this element, most durable first. The structural
+    // path always comes last so there is at least one, even if it breaks on a redesign.
+    function selectorCandidates(el) {
+        const tag = el.tagName.toLowerCase();
+        const candidates = [];
+        const add = (sel) => { if (!candidates.includes(sel) && isUnique(sel, el)) candidates.push(sel); };
+        if (el.id) add('#' + CSS.escape(el.id));
+        if (el.name && ['INPUT', 'SELECT', 'TEXTAREA', 'BUTTON'].includes(el.tagName)) {
+            add(tag + '[name="' + CSS.escape(el.name) + '"]');
+        }
+        const ariaLabel = el.getAttribute('aria-label');
+        if (ariaLabel) add(tag + '[aria-label="' + CSS.escape(ariaLabel) + '"]');
+        for (const attr of ['data-testid', 'data-test-id', 'data-test', 'data-qa']) {
+            const value = el.getAttribute(attr);
+            if (value) add('[' + attr + '="' + CSS.escape(value) + '"]');
+        }
+        const path = structuralPath(el);
+        if (!candidates.includes(path)) candidates.push(path);
+        return candidates;
+    }
+
     function getLabel(field) {
         if (field.id) {
             const label = document.querySelector('label[for="' + CSS.escape(field.id) + '"]');
@@ -49,52 +96,69 @@ const RECORDER_JS: &str = r#"
         return field.getAttribute('aria-label') || null;
     }
 
-    function inferProfileKey(field) {
-        const hints = [
-            field.name || '',
-            field.id || '',
-            field.placeholder || '',
-            field.getAttribute('autocomplete') || '',
-            (getLabel(field) || '').toLowerCase()
-        ].join(' ').toLowerCase();
-
-        if (/first.?name|given.?name|fname/i.test(hints)) return 'firstName';
-        if (/last.?name|family.?name|lname|surname/i.test(hints)) return 'lastName';
-        if (/email/i.test(hints)) return 'email';
-        if (/phone|tel/i.test(hints)) return 'phone';
-        if (/street|address(?!.*city|.*state|.*zip)/i.test(hints)) return 'address';
-        if (/city/i.test(hints)) return 'city';
-        if (/state|province/i.test(hints)) return 'state';
-        if (/zip|postal/i.test(hints)) return 'zip';
-        if (/dob|birth|birthday/i.test(hints)) return 'dob';
-        return null;
+    // What the field says about itself; the app works out which profile value it takes
+    function fieldHints(field) {
+        return {
+            label: getLabel(field),
+            // Rich-text boxes have no placeholder property, only the attribute their editor sets
+            placeholder: field.placeholder || field.getAttribute('aria-placeholder') || field.getAttribute('data-placeholder') || null,
+            name: field.name || null,
+            id: field.id || null,
+            autocomplete: field.getAttribute('autocomplete') || null,
+            type: field.type || null
+        };
+    }
+
+    // The outermost contenteditable element, which is what takes focus in a rich-text box
+    function isEditingHost(el) {
+        return el.isContentEditable && !(el.parentElement && el.parentElement.isContentEditable);
     }
 
     // Track form field interactions (blur = user finished typing)
     document.addEventListener('blur', (e) => {
         const el = e.target;
-        if (!el || !['INPUT', 'SELECT', 'TEXTAREA'].includes(el.tagName)) return;
+        if (!el || !(['INPUT', 'SELECT', 'TEXTAREA'].includes(el.tagName) || isEditingHost(el))) return;
         if (el.type === 'hidden') return;
 
-        const selector = cssSelector(el);
-        const profileKey = inferProfileKey(el);
+        const candidates = selectorCandidates(el);
+        const selector = candidates[0];
         const label = getLabel(el);
 
         if (el.tagName === 'SELECT') {
-            window.__optOuttaRecorder.actions.push({
+            // Both the option's value and its label, so playback can match either
+            const option = el.selectedOptions && el.selectedOptions[0];
+            record({
                 action: 'select',
                 selector: selector,
-                profile_key: profileKey,
-                value: null,
+                selector_candidates: candidates,
+                profile_key: null,
+                field: fieldHints(el),
+                value: el.value || null,
                 url: null,
-                element_text: null,
+                element_text: option ? option.text.trim().substring(0, 100) : null,
                 label: label,
                 timestamp: Date.now()
             });
+        } else if (el.type === 'radio' && el.name) {
+            // The whole group, so playback picks the option by its label rather than its place
+            if (!el.checked) return;
+            const group = 'input[type="radio"][name="' + CSS.escape(el.name) + '"]';
+            record({
+                action: 'choose_radio',
+                selector: group,
+                selector_candidates: [group],
+                profile_key: null,
+                value: label || el.value || null,
+                url: null,
+                element_text: null,
+                label: null,
+                timestamp: Date.now()
+            });
         } else if (el.type === 'checkbox' || el.type === 'radio') {
-            window.__optOuttaRecorder.actions.push({
+            record({
                 action: 'check',
                 selector: selector,
+                selector_candidates: candidates,
                 profile_key: null,
                 value: el.checked ? 'true' : 'false',
                 url: null,
@@ -102,11 +166,26 @@ const RECORDER_JS: &str = r#"
                 label: label,
                 timestamp: Date.now()
             });
+        } else if (el.type === 'password' || /username|^login$/i.test(el.autocomplete || el.name || '')) {
+            // Account logins come from the saved broker credentials, never from what was typed
+            record({
+                action: 'fill_credential',
+                selector: selector,
+                selector_candidates: candidates,
+                profile_key: null,
+                value: el.type === 'password' ? 'password' : 'username',
+                url: null,
+                element_text: null,
+                label: label,
+                timestamp: Date.now()
+            });
         } else {
-            window.__optOuttaRecorder.actions.push({
+            record({
                 action: 'fill',
                 selector: selector,
-                profile_key: profileKey,
+                selector_candidates: candidates,
+                profile_key: null,
+                field: fieldHints(el),
                 value: null,
                 url: null,
                 element_text: null,
@@ -123,7 +202,8 @@ const RECORDER_JS: &str = r#"
         // Skip form fields — those are handled by blur
         if (['INPUT', 'SELECT', 'TEXTAREA'].includes(el.tagName) && el.type !== 'submit') return;
 
-        const selector = cssSelector(el);
+        const candidates = selectorCandidates(el);
+        const selector = candidates[0];
         const now = Date.now();
 
         // Deduplicate rapid clicks on same selector
@@ -133,9 +213,10 @@ const RECORDER_JS: &str = r#"
         window.__optOuttaRecorder.lastClickSelector = selector;
         window.__optOuttaRecorder.lastClickTime = now;
 
-        window.__optOuttaRecorder.actions.push({
+        record({
             action: 'click',
             selector: selector,
+            selector_candidates: candidates,
             profile_key: null,
             value: null,
             url: null,
@@ -144,19 +225,128 @@ const RECORDER_JS: &str = r#"
             timestamp: now
         });
     }, true);
+
+    // Track form submissions so the poller knows to look for a confirmation message.
+    // This marker is consumed by the poller and never becomes a playbook step.
+    document.addEventListener('submit', (e) => {
+        const form = e.target;
+        const now = Date.now();
+
+        // Forms submitted with Enter (typical for search boxes) have no recorded click.
+        // Record a click on the submit button so playback reproduces the submission.
+        const button = e.submitter || (form && form.querySelector && form.querySelector('[type="submit"], button:not([type])'));
+        if (button && now - window.__optOuttaRecorder.lastClickTime > 500) {
+            const candidates = selectorCandidates(button);
+            const selector = candidates[0];
+            window.__optOuttaRecorder.lastClickSelector = selector;
+            window.__optOuttaRecorder.lastClickTime = now;
+            record({
+                action: 'click',
+                selector: selector,
+                selector_candidates: candidates,
+                profile_key: null,
+                value: null,
+                url: null,
+                element_text: (button.textContent || button.value || '').trim().substring(0, 100),
+                label: null,
+                timestamp: now
+            });
+        }
+
+        record({
+            action: 'form_submit',
+            selector: form && form.nodeType === 1 ? selectorCandidates(form)[0] : null,
+            selector_candidates: [],
+            profile_key: null,
+            value: null,
+            url: null,
+            element_text: null,
+            label: null,
+            timestamp: now
+        });
+    }, true);
 })()
 "#;
 
+/// Internal marker action pushed by the recorder JS when a form is submitted.
+const FORM_SUBMIT_MARKER: &str = "form_submit";
+
+/// How the text of a button that submits the opt-out form starts, for forms without a
+/// `<form>` element. "Send code" or "Request a link" buttons don't submit anything.
+const SUBMIT_BUTTON_HINTS: &[&str] = &[
+    "submit",
+    "opt out",
+    "opt-out",
+    "remove my",
+    "delete my",
+    "send request",
+    "send my request",
+];
+
+/// Whether a recorded action represents a form submission.
+fn is_submission(action: &RecordedAction) -> bool {
+    if action.action == FORM_SUBMIT_MARKER {
+        return true;
+    }
+    if action.action != "click" {
+        return false;
+    }
+    let text = action.element_text.as_deref().unwrap_or("").trim().to_lowercase();
+    SUBMIT_BUTTON_HINTS.iter().any(|hint| text.starts_with(hint))
+}
+
+/// Look for a confirmation phrase in the page text. Returns the matched phrase
+/// (one of `done_detection::CONFIRMATION_PHRASES`, never raw page content).
+async fn detect_confirmation(page: &Page) -> Option<String> {
+    let text = page
+        .evaluate("(document.body && document.body.innerText) || ''")
+        .await
+        .ok()
+        .and_then(|val| val.into_value::<String>().ok())?;
+    done_detection::confirmation_phrase(&text).map(str::to_string)
+}
+
+/// Build the `done` action appended when a confirmation phrase is detected.
+fn confirmation_action(phrase: &str) -> RecordedAction {
+    RecordedAction {
+        action: "done".to_string(),
+        selector: None,
+        selector_candidates: vec![],
+        profile_key: None,
+        match_confidence: None,
+        value: Some(phrase.to_string()),
+        url: None,
+        element_text: None,
+        label: Some(format!("Confirmation detected: \"{}\"", phrase)),
+        timestamp: chrono::Utc::now().timestamp_millis() as u64,
+    }
+}
+
+/// Event carrying the playbook drafted from everything recorded so far, so the builder UI
+/// shows it taking shape without polling.
+const RECORDER_DRAFT_EVENT: &str = "recorder-draft";
+
+/// Append actions to the session and push the redrafted playbook to the frontend.
+async fn push_actions(app: &tauri::AppHandle, actions: &Mutex<Vec<RecordedAction>>, new_actions: Vec<RecordedAction>) {
+    use tauri::Emitter;
+    if new_actions.is_empty() {
+        return;
+    }
+    let mut actions = actions.lock().await;
+    actions.extend(new_actions);
+    let _ = app.emit(RECORDER_DRAFT_EVENT, playbook_draft::draft(&actions));
+}
+
 /// State for an active recording session.
 pub struct ActiveRecording {
-    #[allow(dead_code)]
     pub broker_id: String,
-    #[allow(dead_code)]
     pub broker_name: String,
+    started_at: chrono::DateTime<chrono::Utc>,
+    app: tauri::AppHandle,
     browser: Browser,
-    _handler_task: JoinHandle<()>,
-    page: Arc<Page>,
-    poll_task: JoinHandle<()>,
+    page: Page,
+    handler_task: JoinHandle<()>,
+    event_task: JoinHandle<()>,
     actions: Arc<Mutex<Vec<RecordedAction>>>,
 }
 
@@ -169,119 +359,332 @@ impl RecorderState {
     }
 }
 
-/// Start a recording session: launch Chrome, navigate to opt-out URL, inject recorder JS.
+/// How often to look for a confirmation message after a submission when nothing else happens.
+/// Also how often the session checks that its browser is still there.
+const CONFIRMATION_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// How long to wait for the recording page to report its URL.
+const URL_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long to wait for Chrome to close before killing it.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Event carrying the final draft of a session that ended because its browser or page
+/// went away rather than through `stop_recording`.
+const RECORDER_LOST_EVENT: &str = "recorder-lost";
+
+/// An action as the recorder JS sends it. Filled fields come with their hints, which are
+/// matched to a profile key here and then dropped.
+#[derive(serde::Deserialize)]
+struct RecordedPayload {
+    #[serde(flatten)]
+    action: RecordedAction,
+    #[serde(default)]
+    field: Option<Box<FieldHints>>,
+}
+
+/// What the recording page reports over CDP.
+enum RecorderEvent {
+    /// An action sent by the recorder JS through the binding
+    Action(RecordedAction, Option<Box<FieldHints>>),
+    /// A frame loaded a new document
+    Navigated {
+        frame_id: FrameId,
+        parent_id: Option<FrameId>,
+        url: String,
+    },
+    /// History API or fragment navigation within a document
+    NavigatedWithinDocument { frame_id: FrameId, url: String },
+}
+
+/// Navigations that happen this soon after a recorded click or submit are treated as caused
+/// by that interaction and are not recorded as separate `navigate` steps (search result URLs
+/// often embed the searched name).
+const INTERACTION_NAV_WINDOW_MS: u64 = 5_000;
+
+/// Start a recording session: launch Chrome, bind the recorder JS into every document, and
+/// navigate to the start URL. Actions and navigations arrive as CDP events.
+///
+/// `start_url` lets the recording begin somewhere other than the broker's opt-out page
+/// (e.g. its home or search page). When given, the recording starts with a `navigate`
+/// step to it so the resulting playbook replays the full search flow.
+#[allow(clippy::too_many_arguments)]
 pub async fn start_recording(
+    app: tauri::AppHandle,
     state: &RecorderState,
     broker_id: String,
     broker_name: String,
     opt_out_url: String,
+    start_url: Option<String>,
+    profile: Option<Profile>,
+    known_fields: Vec<KnownField>,
 ) -> Result<(), String> {
     let mut guard = state.0.lock().await;
     if guard.is_some() {
         return Err("A recording session is already active.".to_string());
     }
 
-    let (browser, mut handler) = browser::launch().await?;
+    let start_url = start_url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    if let Some(ref url) = start_url {
+        crate::playbook_validation::validate_start_url(url)?;
+    }
+    let initial_url = start_url.clone().unwrap_or(opt_out_url);
+
+    let proxy = settings::load(&app).ok().and_then(|s| s.browser_proxy);
+    let (browser, mut handler) = browser::launch_chromium(&app, proxy.as_ref()).await?;
+    // The handler ends when the connection to Chrome does, so a crash or the user quitting
+    // Chrome shows up here even if the page's event streams stay open
+    let browser_gone = Arc::new(AtomicBool::new(false));
+    let handler_gone = Arc::clone(&browser_gone);
     let handler_task = tokio::spawn(async move {
-        use futures::StreamExt;
         while let Some(_) = handler.next().await {}
+        handler_gone.store(true, Ordering::SeqCst);
     });
 
+    // Start on a blank page so the binding, injected script and listeners are all in
+    // place before the broker's first document loads
     let page = browser
-        .new_page(&opt_out_url)
+        .new_page("about:blank")
         .await
         .map_err(|e| format!("Failed to open page: {}", e))?;
-
-    sleep(Duration::from_secs(2)).await;
-
-    // Inject recorder JS
-    page.evaluate(RECORDER_JS)
+    if let Some(login) = browser::proxy_login(&app, proxy.as_ref()) {
+        browser::answer_proxy_login(&browser::Page::Chromium(page.clone()), login).await?;
+    }
+    page.execute(AddBindingParams::new(RECORDER_BINDING))
+        .await
+        .map_err(|e| format!("Failed to add recorder binding: {}", e))?;
+    page.evaluate_on_new_document(RECORDER_JS)
         .await
         .map_err(|e| format!("Failed to inject recorder: {}", e))?;
-
-    let page = Arc::new(page);
+    let bindings = page
+        .event_listener::<EventBindingCalled>()
+        .await
+        .map_err(|e| format!("Failed to listen for recorded actions: {}", e))?;
+    let navigations = page
+        .event_listener::<EventFrameNavigated>()
+        .await
+        .map_err(|e| format!("Failed to listen for navigations: {}", e))?;
+    let in_page_navigations = page
+        .event_listener::<EventNavigatedWithinDocument>()
+        .await
+        .map_err(|e| format!("Failed to listen for navigations: {}", e))?;
+
+    let mut initial_actions = Vec::new();
+    if let Some(url) = start_url {
+        initial_actions.push(RecordedAction {
+            action: "navigate".to_string(),
+            selector: None,
+            selector_candidates: vec![],
+            profile_key: None,
+            match_confidence: None,
+            value: Some(url.clone()),
+            url: Some(url),
+            element_text: None,
+            label: None,
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+        });
+    }
     let actions: Arc<Mutex<Vec<RecordedAction>>> = Arc::new(Mutex::new(Vec::new()));
+    push_actions(&app, &actions, initial_actions).await;
 
-    // Start URL polling loop to detect navigation and re-inject JS
-    let poll_page = Arc::clone(&page);
-    let poll_actions = Arc::clone(&actions);
-    let poll_task = tokio::spawn(async move {
+    page.goto(initial_url.as_str())
+        .await
+        .map_err(|e| format!("Failed to open page: {}", e))?;
+
+    let events = futures::stream::select(
+        bindings.filter_map(|e| async move {
+            if e.name != RECORDER_BINDING {
+                return None;
+            }
+            serde_json::from_str(&e.payload)
+                .ok()
+                .map(|p: RecordedPayload| RecorderEvent::Action(p.action, p.field))
+        }),
+        futures::stream::select(
+            navigations.map(|e| RecorderEvent::Navigated {
+                frame_id: e.frame.id.clone(),
+                parent_id: e.frame.parent_id.clone(),
+                url: e.frame.url.clone(),
+            }),
+            in_page_navigations.map(|e| RecorderEvent::NavigatedWithinDocument {
+                frame_id: e.frame_id.clone(),
+                url: e.url.clone(),
+            }),
+        ),
+    );
+
+    let event_app = app.clone();
+    let event_actions = Arc::clone(&actions);
+    let session_page = page.clone();
+    let event_task = tokio::spawn(async move {
+        futures::pin_mut!(events);
+        let mut main_frame: Option<FrameId> = None;
         let mut last_url = String::new();
+        // A confirmation only counts on the page a submission led to
+        let mut submitted = false;
+        let mut awaiting_confirmation = false;
+        let mut last_interaction_ms: u64 = 0;
         loop {
-            sleep(Duration::from_secs(2)).await;
-
-            let url_result = poll_page
-                .evaluate("window.location.href")
-                .await;
+            if browser_gone.load(Ordering::SeqCst) {
+                break;
+            }
+            // Wake up now and then so a confirmation message that renders a moment after
+            // the navigation is still noticed
+            let event = match tokio::time::timeout(CONFIRMATION_CHECK_INTERVAL, events.next()).await {
+                Ok(Some(event)) => Some(event),
+                Ok(None) => break, // Page/browser closed
+                Err(_) => None,
+            };
 
-            let current_url = match url_result {
-                Ok(val) => val.into_value::<String>().unwrap_or_default(),
-                Err(_) => break, // Page/browser closed
+            let navigated_to = match event {
+                Some(RecorderEvent::Action(mut action, field)) => {
+                    if let Some(matched) = field.and_then(|f| field_matching::match_field(&f, &known_fields)) {
+                        action.profile_key = Some(matched.profile_key);
+                        action.match_confidence = Some(matched.confidence);
+                        // Playback picks the option from the profile, not the one picked now
+                        if action.action == "select" {
+                            action.value = None;
+                            action.element_text = None;
+                        }
+                    }
+                    pii_scan::redact_action(&mut action, profile.as_ref());
+                    if is_submission(&action) {
+                        submitted = true;
+                        awaiting_confirmation = false;
+                    }
+                    if action.action == "click" || action.action == FORM_SUBMIT_MARKER {
+                        last_interaction_ms = last_interaction_ms.max(action.timestamp);
+                    }
+                    // Runs close cookie banners themselves, so clicking one away isn't a step
+                    let closes_banner = action.action == "click"
+                        && action.selector.as_deref().is_some_and(consent_banners::is_banner_button);
+                    if action.action != FORM_SUBMIT_MARKER && !closes_banner {
+                        push_actions(&event_app, &event_actions, vec![action]).await;
+                    }
+                    None
+                }
+                // Iframes (ads, CAPTCHAs) navigate constantly; only the top frame counts
+                Some(RecorderEvent::Navigated { frame_id, parent_id: None, url }) => {
+                    main_frame = Some(frame_id);
+                    Some(url)
+                }
+                Some(RecorderEvent::NavigatedWithinDocument { frame_id, url })
+                    if main_frame.as_ref() == Some(&frame_id) =>
+                {
+                    Some(url)
+                }
+                _ => None,
             };
 
-            if !current_url.is_empty() && current_url != last_url {
-                if !last_url.is_empty() {
-                    // Record navigation
-                    poll_actions.lock().await.push(RecordedAction {
+            if submitted && navigated_to.as_deref().is_some_and(|u| u != "about:blank") {
+                submitted = false;
+                awaiting_confirmation = true;
+            }
+
+            if let Some(url) = navigated_to.filter(|u| *u != last_url && u.as_str() != "about:blank") {
+                let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+                let caused_by_interaction =
+                    now_ms.saturating_sub(last_interaction_ms) < INTERACTION_NAV_WINDOW_MS;
+                if !last_url.is_empty() && !caused_by_interaction {
+                    let navigate = RecordedAction {
                         action: "navigate".to_string(),
                         selector: None,
+                        selector_candidates: vec![],
                         profile_key: None,
-                        value: Some(current_url.clone()),
-                        url: Some(current_url.clone()),
+                        match_confidence: None,
+                        value: Some(url.clone()),
+                        url: Some(url.clone()),
                         element_text: None,
                         label: None,
-                        timestamp: chrono::Utc::now().timestamp_millis() as u64,
-                    });
+                        timestamp: now_ms,
+                    };
+                    push_actions(&event_app, &event_actions, vec![navigate]).await;
                 }
-                last_url = current_url;
-
-                // Re-inject recorder JS on new page
-                let _ = poll_page.evaluate(RECORDER_JS).await;
+                last_url = url;
             }
 
-            // Collect any actions the JS has recorded
-            let extract_result = poll_page
-                .evaluate("(() => { const a = (window.__optOuttaRecorder || {}).actions || []; window.__optOuttaRecorder.actions = []; return a; })()")
-                .await;
-
-            if let Ok(val) = extract_result {
-                if let Ok(new_actions) = val.into_value::<Vec<RecordedAction>>() {
-                    if !new_actions.is_empty() {
-                        poll_actions.lock().await.extend(new_actions);
-                    }
+            // After a submission, watch for confirmation text and record a success assertion
+            if awaiting_confirmation {
+                if let Some(phrase) = detect_confirmation(&page).await {
+                    let phrase = pii_scan::redact(&phrase, profile.as_ref());
+                    push_actions(&event_app, &event_actions, vec![confirmation_action(&phrase)]).await;
+                    awaiting_confirmation = false;
                 }
             }
         }
+        // Only reached when the browser or page went away; `stop_recording` aborts this task
+        release_lost_session(&event_app, &event_actions).await;
     });
 
     *guard = Some(ActiveRecording {
         broker_id,
         broker_name,
+        started_at: chrono::Utc::now(),
+        app,
         browser,
-        _handler_task: handler_task,
-        page,
-        poll_task,
+        page: session_page,
+        handler_task,
+        event_task,
         actions,
     });
 
     Ok(())
 }
 
+/// Close a session's browser, killing it if it doesn't answer, and stop its handler.
+async fn shut_down(mut recording: ActiveRecording) {
+    if !matches!(tokio::time::timeout(CLOSE_TIMEOUT, recording.browser.close()).await, Ok(Ok(_))) {
+        let _ = recording.browser.kill().await;
+    }
+    recording.handler_task.abort();
+}
+
+/// Clear the session that recorded into `actions` once its browser or page is gone, so a
+/// new recording can start, and hand the frontend what it recorded to review. A session
+/// stopped or replaced in the meantime is left alone.
+async fn release_lost_session(app: &tauri::AppHandle, actions: &Arc<Mutex<Vec<RecordedAction>>>) {
+    use tauri::{Emitter, Manager};
+    let recording = {
+        let state = app.state::<RecorderState>();
+        let mut guard = state.0.lock().await;
+        if !guard.as_ref().is_some_and(|r| Arc::ptr_eq(&r.actions, actions)) {
+            return;
+        }
+        guard.take()
+    };
+    if let Some(recording) = recording {
+        shut_down(recording).await;
+    }
+    let mut recorded = actions.lock().await.clone();
+    recorded.sort_by_key(|a| a.timestamp);
+    let _ = app.emit(RECORDER_LOST_EVENT, playbook_draft::draft(&recorded));
+}
+
+/// Drop the current session whatever state it's in, discarding what it recorded. For a
+/// session that's stuck, such as one whose Chrome hung instead of closing.
+pub async fn force_reset(state: &RecorderState) {
+    let recording = state.0.lock().await.take();
+    if let Some(recording) = recording {
+        recording.event_task.abort();
+        shut_down(recording).await;
+    }
+}
+
 /// Mark a CAPTCHA step at the current point in the recording.
 pub async fn mark_captcha(state: &RecorderState) -> Result<(), String> {
     let guard = state.0.lock().await;
     let recording = guard.as_ref().ok_or("No active recording session.")?;
 
-    recording.actions.lock().await.push(RecordedAction {
+    let captcha = RecordedAction {
         action: "captcha".to_string(),
         selector: None,
+        selector_candidates: vec![],
         profile_key: None,
+        match_confidence: None,
         value: None,
         url: None,
         element_text: None,
         label: Some("User solved CAPTCHA".to_string()),
         timestamp: chrono::Utc::now().timestamp_millis() as u64,
-    });
+    };
+    push_actions(&recording.app, &recording.actions, vec![captcha]).await;
 
     Ok(())
 }
@@ -291,20 +694,46 @@ pub async fn mark_user_prompt(state: &RecorderState) -> Result<(), String> {
     let guard = state.0.lock().await;
     let recording = guard.as_ref().ok_or("No active recording session.")?;
 
-    recording.actions.lock().await.push(RecordedAction {
+    let prompt = RecordedAction {
         action: "user_prompt".to_string(),
         selector: None,
+        selector_candidates: vec![],
         profile_key: None,
+        match_confidence: None,
         value: None,
         url: None,
         element_text: None,
         label: Some("Manual step".to_string()),
         timestamp: chrono::Utc::now().timestamp_millis() as u64,
-    });
+    };
+    push_actions(&recording.app, &recording.actions, vec![prompt]).await;
 
     Ok(())
 }
 
+/// The session in progress, if any: which broker, where its browser is, and what it has
+/// recorded so far.
+pub async fn status(state: &RecorderState) -> Option<RecordingSession> {
+    let guard = state.0.lock().await;
+    let recording = guard.as_ref()?;
+    let current_url = match tokio::time::timeout(URL_TIMEOUT, recording.page.url()).await {
+        Ok(Ok(url)) => url,
+        _ => None,
+    };
+    let mut actions = recording.actions.lock().await.clone();
+    actions.sort_by_key(|a| a.timestamp);
+    let now = chrono::Utc::now();
+    Some(RecordingSession {
+        broker_id: recording.broker_id.clone(),
+        broker_name: recording.broker_name.clone(),
+        current_url,
+        started_at: recording.started_at,
+        elapsed_secs: (now - recording.started_at).num_seconds().max(0) as u64,
+        action_count: actions.len(),
+        draft: playbook_draft::draft(&actions),
+    })
+}
+
 /// Get a snapshot of the current recorded actions without stopping.
 pub async fn get_current_actions(state: &RecorderState) -> Result<Vec<RecordedAction>, String> {
     let guard = state.0.lock().await;
@@ -316,30 +745,47 @@ pub async fn get_current_actions(state: &RecorderState) -> Result<Vec<RecordedAc
 /// Stop the recording session and return all recorded actions.
 pub async fn stop_recording(state: &RecorderState) -> Result<Vec<RecordedAction>, String> {
     let mut guard = state.0.lock().await;
-    let mut recording = guard.take().ok_or("No active recording session.")?;
-
-    // Stop the polling loop
-    recording.poll_task.abort();
-
-    // Extract any final actions from the page
-    let final_result = recording
-        .page
-        .evaluate("(() => { const a = (window.__optOuttaRecorder || {}).actions || []; window.__optOuttaRecorder.actions = []; return a; })()")
-        .await;
+    let recording = guard.take().ok_or("No active recording session.")?;
 
+    // Stop listening; actions arrive as they happen, so there's nothing left to collect
+    recording.event_task.abort();
     let mut all_actions = recording.actions.lock().await.clone();
 
-    if let Ok(val) = final_result {
-        if let Ok(final_actions) = val.into_value::<Vec<RecordedAction>>() {
-            all_actions.extend(final_actions);
-        }
-    }
-
     // Close browser
-    let _ = recording.browser.close().await;
+    shut_down(recording).await;
 
     // Sort by timestamp
     all_actions.sort_by_key(|a| a.timestamp);
 
     Ok(all_actions)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click(text: &str) -> RecordedAction {
+        RecordedAction {
+            action: "click".to_string(),
+            selector: Some("button".to_string()),
+            selector_candidates: vec![],
+            profile_key: None,
+            match_confidence: None,
+            value: None,
+            url: None,
+            element_text: Some(text.to_string()),
+            label: None,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn only_submit_buttons_count_as_submissions() {
+        for text in ["Submit", " Submit request ", "Opt Out Now", "Remove my listing", "Delete my data"] {
+            assert!(is_submission(&click(text)), "{text}");
+        }
+        for text in ["Send code", "Request a verification link", "Remove", "How to opt out", "Resend email"] {
+            assert!(!is_submission(&click(text)), "{text}");
+        }
+    }
+}
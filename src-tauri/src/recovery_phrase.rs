@@ -0,0 +1,127 @@
+use sha2::{Digest, Sha256};
+
+/// One word per byte. No two share their first four letters, so a phrase can be typed with
+/// just those.
+const WORDS: [&str; 256] = [
+    "acid", "acorn", "actor", "adapt", "admit", "adult", "agent", "album", "alert", "alley",
+    "alpha", "amber", "angle", "ankle", "apple", "april", "arena", "armor", "arrow", "atlas",
+    "attic", "audio", "autumn", "avocado", "badge", "bagel", "baker", "balcony", "bamboo", "banjo",
+    "barrel", "basket", "beach", "beaver", "begin", "below", "bench", "berry", "bicycle", "bishop",
+    "blanket", "blossom", "board", "bonus", "border", "bottle", "bracket", "brave", "bread",
+    "brick", "bridge", "broom", "bubble", "bucket", "budget", "buffalo", "bundle", "burger",
+    "butter", "cabin", "cactus", "camel", "candle", "canoe", "canvas", "carbon", "cargo", "carpet",
+    "castle", "cattle", "cedar", "cement", "chalk", "chapter", "charcoal", "cherry", "chimney",
+    "choice", "cider", "circle", "citrus", "clay", "cliff", "clock", "cloud", "clover", "cobalt",
+    "coconut", "coffee", "comet", "copper", "coral", "cotton", "cousin", "coyote", "crater",
+    "crayon", "cricket", "crystal", "cupboard", "curtain", "cushion", "dagger", "daisy", "dancer",
+    "dawn", "decade", "delta", "denim", "desert", "diamond", "dinner", "dizzy", "doctor",
+    "dolphin", "domino", "donkey", "dragon", "drawer", "dream", "drum", "duck", "dune", "eagle",
+    "earth", "easel", "echo", "eclipse", "elbow", "elder", "ember", "empire", "engine", "envelope",
+    "equal", "escape", "evening", "exhibit", "fabric", "falcon", "fancy", "farmer", "feather",
+    "fence", "ferry", "fiber", "fiddle", "finger", "flame", "flavor", "flute", "focus", "forest",
+    "fossil", "fountain", "frame", "frost", "fruit", "funnel", "galaxy", "garden", "garlic",
+    "gentle", "giant", "ginger", "giraffe", "glacier", "glove", "goblet", "golden", "gorilla",
+    "grape", "gravel", "guitar", "habit", "hammer", "harbor", "harvest", "hazel", "helmet", "hero",
+    "hobby", "honey", "horizon", "hotel", "hunter", "husky", "iceberg", "idea", "igloo", "image",
+    "index", "indigo", "insect", "island", "ivory", "jacket", "jaguar", "jelly", "jersey", "jewel",
+    "journey", "judge", "juice", "jungle", "kayak", "kettle", "kidney", "kingdom", "kitten",
+    "koala", "label", "ladder", "lagoon", "lantern", "laptop", "lemon", "leopard", "letter",
+    "lilac", "limb", "linen", "lion", "lizard", "lobster", "locket", "lotus", "lumber", "lunar",
+    "magnet", "mango", "maple", "marble", "meadow", "medal", "melon", "mercury", "metal", "mirror",
+    "mitten", "monkey", "mosaic", "motor", "muffin", "museum", "mustard", "napkin", "nectar",
+    "needle", "nickel", "noodle", "north", "notebook", "novel", "nutmeg", "oasis",
+];
+
+/// Bytes in the key a phrase encodes (AES-256).
+const KEY_LEN: usize = 32;
+
+/// Words in a phrase: one per key byte, then a checksum word.
+pub const PHRASE_WORDS: usize = KEY_LEN + 1;
+
+/// Letters of a word that are enough to tell it apart.
+const PREFIX_LEN: usize = 4;
+
+fn checksum(key: &[u8]) -> u8 {
+    Sha256::digest(key)[0]
+}
+
+/// Spell out the encryption key as a recovery phrase the user can write down.
+pub fn encode(key: &[u8]) -> Result<String, String> {
+    if key.len() != KEY_LEN {
+        return Err("The encryption key has an unexpected length.".to_string());
+    }
+    let words: Vec<&str> = key
+        .iter()
+        .chain(std::iter::once(&checksum(key)))
+        .map(|b| WORDS[*b as usize])
+        .collect();
+    Ok(words.join(" "))
+}
+
+/// A word's byte, from the whole word or its first four letters.
+fn word_index(word: &str) -> Option<u8> {
+    let position = if word.len() == PREFIX_LEN {
+        WORDS.iter().position(|w| w.starts_with(word))
+    } else {
+        WORDS.iter().position(|w| *w == word)
+    };
+    position.map(|i| i as u8)
+}
+
+/// The key a recovery phrase spells. Case, extra spaces and line breaks don't matter, and
+/// each word can be shortened to its first four letters.
+pub fn decode(phrase: &str) -> Result<Vec<u8>, String> {
+    let words: Vec<String> = phrase.split_whitespace().map(|w| w.to_lowercase()).collect();
+    if words.len() != PHRASE_WORDS {
+        return Err(format!(
+            "A recovery phrase has {} words, but this one has {}.",
+            PHRASE_WORDS,
+            words.len()
+        ));
+    }
+    let mut bytes = Vec::with_capacity(PHRASE_WORDS);
+    for (i, word) in words.iter().enumerate() {
+        let byte = word_index(word)
+            .ok_or_else(|| format!("Word {} (\"{}\") isn't in the recovery word list.", i + 1, word))?;
+        bytes.push(byte);
+    }
+    let check = bytes.pop().unwrap_or_default();
+    if checksum(&bytes) != check {
+        return Err("This recovery phrase doesn't add up. Check for a mistyped or swapped word.".to_string());
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_a_key_with_shortened_words() {
+        let key: Vec<u8> = (0..32).map(|i| (i * 7 + 3) as u8).collect();
+        let phrase = encode(&key).unwrap();
+        assert_eq!(phrase.split(' ').count(), PHRASE_WORDS);
+        assert_eq!(decode(&phrase).unwrap(), key);
+
+        let shortened: Vec<String> = phrase.split(' ').map(|w| w[..4].to_uppercase()).collect();
+        assert_eq!(decode(&shortened.join("\n  ")).unwrap(), key);
+        assert!(encode(&key[..16]).is_err());
+    }
+
+    #[test]
+    fn rejects_typos_and_swapped_words() {
+        let key: Vec<u8> = (0..32).collect();
+        let mut words: Vec<String> = encode(&key).unwrap().split(' ').map(String::from).collect();
+        words.swap(4, 5);
+        assert!(decode(&words.join(" ")).unwrap_err().contains("doesn't add up"));
+
+        words[3] = "zebra".to_string();
+        assert!(decode(&words.join(" ")).unwrap_err().contains("Word 4"));
+        assert!(decode("acid acorn").unwrap_err().contains("33 words"));
+        // Every word is told apart by its first four letters
+        for (i, word) in WORDS.iter().enumerate() {
+            assert_eq!(word_index(word), Some(i as u8));
+            assert_eq!(word_index(&word[..PREFIX_LEN]), Some(i as u8));
+        }
+    }
+}
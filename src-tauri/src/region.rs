@@ -0,0 +1,71 @@
+use crate::models::{Broker, Profile};
+
+/// Country assumed for profiles saved before the country field existed, and for
+/// registry entries that don't list the countries they serve.
+pub const DEFAULT_COUNTRY: &str = "US";
+
+/// The user's country as an uppercase ISO code.
+pub fn country(profile: &Profile) -> String {
+    let country = profile.country.trim();
+    if country.is_empty() {
+        DEFAULT_COUNTRY.to_string()
+    } else {
+        country.to_ascii_uppercase()
+    }
+}
+
+pub fn is_us(profile: &Profile) -> bool {
+    country(profile) == DEFAULT_COUNTRY
+}
+
+/// Whether the broker takes requests from residents of `country`. Brokers that don't
+/// list their countries only serve US residents; "*" means anywhere.
+pub fn serves(broker: &Broker, country: &str) -> bool {
+    if broker.countries.is_empty() {
+        return country.eq_ignore_ascii_case(DEFAULT_COUNTRY);
+    }
+    broker
+        .countries
+        .iter()
+        .any(|c| c == "*" || c.eq_ignore_ascii_case(country))
+}
+
+/// IDs of brokers that won't accept a request from the user, in registry order.
+pub fn out_of_region(brokers: &[Broker], profile: &Profile) -> Vec<String> {
+    let country = country(profile);
+    brokers
+        .iter()
+        .filter(|b| !serves(b, &country))
+        .map(|b| b.id.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn broker(id: &str, countries: &[&str]) -> Broker {
+        Broker { countries: countries.iter().map(|c| c.to_string()).collect(), ..test_fixtures::broker(id) }
+    }
+
+    fn profile(country: &str) -> Profile {
+        Profile { country: country.to_string(), ..test_fixtures::profile() }
+    }
+
+    #[test]
+    fn unlisted_countries_mean_us_only() {
+        let brokers = vec![broker("us", &[]), broker("global", &["*"]), broker("uk", &["GB", "IE"])];
+        assert_eq!(out_of_region(&brokers, &profile("")), vec!["uk"]);
+        assert_eq!(out_of_region(&brokers, &profile("us")), vec!["uk"]);
+        assert!(is_us(&profile(" ")));
+    }
+
+    #[test]
+    fn non_us_users_only_see_brokers_serving_their_country() {
+        let brokers = vec![broker("us", &[]), broker("global", &["*"]), broker("uk", &["GB", "IE"])];
+        assert_eq!(out_of_region(&brokers, &profile("ie")), vec!["us"]);
+        assert_eq!(out_of_region(&brokers, &profile("DE")), vec!["us", "uk"]);
+        assert!(!is_us(&profile("de")));
+    }
+}
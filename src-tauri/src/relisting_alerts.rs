@@ -0,0 +1,189 @@
+use crate::models::{RelistingAlert, RelistingAlertStatus, RelistingAlertStore, RemovalCheck, SubmissionRecord};
+use chrono::{DateTime, Duration, Utc};
+use std::fs;
+use std::path::PathBuf;
+
+const FILENAME: &str = "relisting_alerts.json";
+/// Longest an alert can be snoozed for.
+pub const MAX_SNOOZE_DAYS: u32 = 90;
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<RelistingAlertStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(RelistingAlertStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &RelistingAlertStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// Whether an alert still wants the user's attention: open, or snoozed and due back.
+pub fn is_active(alert: &RelistingAlert, now: DateTime<Utc>) -> bool {
+    match alert.status {
+        RelistingAlertStatus::Open => true,
+        RelistingAlertStatus::Snoozed => alert.snoozed_until.is_none_or(|until| until <= now),
+        RelistingAlertStatus::Dismissed | RelistingAlertStatus::Resolved => false,
+    }
+}
+
+/// What to do about a relisting. A broker that keeps relisting after opt-outs is worth a
+/// complaint, not just another request.
+fn suggested_action(broker_name: &str, earlier_alerts: usize) -> String {
+    if earlier_alerts == 0 {
+        format!("Run the opt-out for {} again. Brokers often relist people when they refresh their data.", broker_name)
+    } else {
+        format!(
+            "{} has relisted you {} times after removal. Run the opt-out again and file a complaint with your state regulator.",
+            broker_name,
+            earlier_alerts + 1
+        )
+    }
+}
+
+/// Record a relisting found by `check`. A broker with an open or snoozed alert has that
+/// alert brought up to date instead of gaining a second one.
+fn raise_in(store: &mut RelistingAlertStore, record: &SubmissionRecord, broker_name: &str, check: &RemovalCheck) -> RelistingAlert {
+    if let Some(alert) = store
+        .alerts
+        .iter_mut()
+        .find(|a| a.broker_id == record.broker_id && matches!(a.status, RelistingAlertStatus::Open | RelistingAlertStatus::Snoozed))
+    {
+        alert.detected_at = check.checked_at;
+        alert.matches = check.matches;
+        alert.evidence_id = check.evidence_id.clone().or(alert.evidence_id.take());
+        alert.record_id = record.id.clone();
+        return alert.clone();
+    }
+    let earlier = store
+        .alerts
+        .iter()
+        .filter(|a| a.broker_id == record.broker_id && a.status == RelistingAlertStatus::Resolved)
+        .count();
+    let alert = RelistingAlert {
+        id: uuid::Uuid::new_v4().to_string(),
+        broker_id: record.broker_id.clone(),
+        broker_name: broker_name.to_string(),
+        record_id: record.id.clone(),
+        detected_at: check.checked_at,
+        matches: check.matches,
+        evidence_id: check.evidence_id.clone(),
+        suggested_action: suggested_action(broker_name, earlier),
+        status: RelistingAlertStatus::Open,
+        snoozed_until: None,
+        rerun_id: None,
+    };
+    store.alerts.push(alert.clone());
+    alert
+}
+
+pub fn raise(app: &tauri::AppHandle, record: &SubmissionRecord, broker_name: &str, check: &RemovalCheck) -> Result<RelistingAlert, String> {
+    let mut store = load(app)?;
+    let alert = raise_in(&mut store, record, broker_name, check);
+    save(app, &store)?;
+    Ok(alert)
+}
+
+/// Active alerts, newest first.
+pub fn active(app: &tauri::AppHandle) -> Result<Vec<RelistingAlert>, String> {
+    let now = Utc::now();
+    let mut alerts: Vec<RelistingAlert> = load(app)?.alerts.into_iter().filter(|a| is_active(a, now)).collect();
+    alerts.sort_by_key(|a| std::cmp::Reverse(a.detected_at));
+    Ok(alerts)
+}
+
+pub fn get(app: &tauri::AppHandle, id: &str) -> Result<RelistingAlert, String> {
+    load(app)?
+        .alerts
+        .into_iter()
+        .find(|a| a.id == id)
+        .ok_or_else(|| "Relisting alert not found".to_string())
+}
+
+fn update(app: &tauri::AppHandle, id: &str, f: impl FnOnce(&mut RelistingAlert)) -> Result<(), String> {
+    let mut store = load(app)?;
+    let alert = store.alerts.iter_mut().find(|a| a.id == id).ok_or("Relisting alert not found")?;
+    f(alert);
+    save(app, &store)
+}
+
+pub fn snooze(app: &tauri::AppHandle, id: &str, days: u32) -> Result<(), String> {
+    if days == 0 || days > MAX_SNOOZE_DAYS {
+        return Err(format!("Snooze for 1 to {} days.", MAX_SNOOZE_DAYS));
+    }
+    update(app, id, |alert| {
+        alert.status = RelistingAlertStatus::Snoozed;
+        alert.snoozed_until = Some(Utc::now() + Duration::days(days as i64));
+    })
+}
+
+pub fn dismiss(app: &tauri::AppHandle, id: &str) -> Result<(), String> {
+    update(app, id, |alert| alert.status = RelistingAlertStatus::Dismissed)
+}
+
+/// Close an alert once a run to opt out again has started.
+pub fn resolve(app: &tauri::AppHandle, id: &str, run_id: &str) -> Result<(), String> {
+    update(app, id, |alert| {
+        alert.status = RelistingAlertStatus::Resolved;
+        alert.snoozed_until = None;
+        alert.rerun_id = Some(run_id.to_string());
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BrokerSubmissionStatus;
+    use crate::test_fixtures;
+
+    fn record(id: &str) -> SubmissionRecord {
+        SubmissionRecord { id: id.to_string(), ..test_fixtures::record("spokeo", BrokerSubmissionStatus::ReListed) }
+    }
+
+    fn check(at: DateTime<Utc>, evidence_id: Option<&str>) -> RemovalCheck {
+        RemovalCheck { checked_at: at, still_listed: true, matches: 2, evidence_id: evidence_id.map(String::from) }
+    }
+
+    #[test]
+    fn keeps_one_active_alert_per_broker() {
+        let mut store = RelistingAlertStore::default();
+        let first = Utc::now();
+        let alert = raise_in(&mut store, &record("r1"), "Spokeo", &check(first, Some("e1")));
+        let again = raise_in(&mut store, &record("r2"), "Spokeo", &check(first + Duration::days(3), None));
+        assert_eq!(store.alerts.len(), 1);
+        assert_eq!(again.id, alert.id);
+        assert_eq!(again.record_id, "r2");
+        assert_eq!(again.evidence_id.as_deref(), Some("e1"));
+
+        // Once dealt with, the next relisting is a new alert that suggests escalating
+        store.alerts[0].status = RelistingAlertStatus::Resolved;
+        let next = raise_in(&mut store, &record("r3"), "Spokeo", &check(first + Duration::days(60), None));
+        assert_ne!(next.id, alert.id);
+        assert!(next.suggested_action.contains("2 times"));
+    }
+
+    #[test]
+    fn snoozed_alerts_come_back_when_due() {
+        let mut store = RelistingAlertStore::default();
+        let now = Utc::now();
+        let mut alert = raise_in(&mut store, &record("r1"), "Spokeo", &check(now, None));
+        assert!(is_active(&alert, now));
+        alert.status = RelistingAlertStatus::Snoozed;
+        alert.snoozed_until = Some(now + Duration::days(7));
+        assert!(!is_active(&alert, now + Duration::days(6)));
+        assert!(is_active(&alert, now + Duration::days(7)));
+        alert.status = RelistingAlertStatus::Dismissed;
+        assert!(!is_active(&alert, now + Duration::days(30)));
+    }
+}
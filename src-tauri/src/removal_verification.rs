@@ -0,0 +1,147 @@
+use crate::browser;
+use crate::engine;
+use crate::evidence;
+use crate::history;
+use crate::models::*;
+use crate::relisting_alerts;
+use crate::run_plan;
+use crate::settings;
+use crate::step_cursor;
+use chrono::Utc;
+
+/// Split a playbook into its search portion: every step before the `find_and_click`
+/// (or `for_each_result`) step that picks the user's listing, plus that step itself.
+fn search_portion(steps: &[PlaybookStep]) -> Result<(Vec<&PlaybookStep>, &PlaybookStep), String> {
+    let mut sorted: Vec<&PlaybookStep> = steps.iter().collect();
+    sorted.sort_by_key(|s| s.position);
+    let pick = sorted
+        .iter()
+        .position(|s| (s.action == "find_and_click" || s.action == step_cursor::FOR_EACH_ACTION) && s.profile_key.is_some())
+        .ok_or("This broker's playbook has no search step, so removal can't be checked automatically.")?;
+    let listing_step = sorted[pick];
+    sorted.truncate(pick);
+    Ok((sorted, listing_step))
+}
+
+/// Re-run the search portion of a broker's playbook for a confirmed submission and check
+/// whether the user still appears. A screenshot of the results is kept as evidence, and when
+/// the listing is found the record is flipped to `ReListed` and a relisting alert raised.
+pub async fn verify_removal(
+    app: &tauri::AppHandle,
+    record_id: &str,
+    profile: &Profile,
+    selection: &str,
+) -> Result<RemovalCheck, String> {
+    let h = history::load(app)?;
+    let record = h
+        .records
+        .iter()
+        .find(|r| r.id == record_id)
+        .ok_or("Submission record not found")?;
+    if record.status != BrokerSubmissionStatus::Confirmed {
+        return Err("Only confirmed submissions can be checked for removal.".to_string());
+    }
+
+    let registry = crate::commands::brokers::get_brokers(app.clone())?;
+    let broker = registry
+        .brokers
+        .into_iter()
+        .find(|b| b.id == record.broker_id)
+        .ok_or_else(|| format!("Unknown broker: {}", record.broker_id))?;
+
+    let pb = engine::load_playbook(app, &broker, selection)
+        .await
+        .ok_or("No playbook available for this broker")?;
+    engine::check_playbook(&pb)?;
+    let (search_steps, listing_step) = search_portion(&pb.steps)?;
+
+    // Search with the same address the original submission used
+    let options = RunOptions {
+        search_previous_addresses: true,
+        repeat_alternate_contacts: true,
+        ..Default::default()
+    };
+    let pass_profile = run_plan::search_passes(profile, &pb.steps, &options)
+        .into_iter()
+        .find(|p| p.searched_with == record.searched_with)
+        .map(|p| p.profile)
+        .unwrap_or_else(|| profile.clone());
+    let profile = &pass_profile;
+
+    let app_settings = settings::load(app).unwrap_or_default();
+    let browser_mode = app_settings.browser_mode;
+    let send_gpc = app_settings.send_gpc;
+    let browser_instance = browser::launch_with(app, &browser_mode, app_settings.browser_proxy.as_ref()).await?;
+
+    let result = async {
+        let page = browser::open_page(&browser_instance, &browser_mode, &broker.opt_out_url, send_gpc, &broker.automation).await?;
+
+        for step in &search_steps {
+            let action = match engine::playbook_step_to_form_action(step) {
+                Some(a) => {
+                    let a = engine::apply_legal_basis(engine::apply_field_format(a, &broker), &broker, profile);
+                    engine::apply_credentials(app, a, &broker, profile)
+                }
+                None => continue,
+            };
+            match action {
+                FormAction::Captcha { .. }
+                | FormAction::UserPrompt { .. }
+                | FormAction::ManualFill { .. }
+                | FormAction::ManualSelect { .. } => {
+                    return Err(format!(
+                        "Step \"{}\" needs your input, so this broker's removal has to be checked manually.",
+                        step.description
+                    ));
+                }
+                other => {
+                    if let Err(e) = browser::execute_action(&page, &other, profile, &broker.automation).await {
+                        if !step.optional {
+                            return Err(format!("Search step \"{}\" failed: {}", step.description, e));
+                        }
+                    }
+                }
+            }
+            if step.wait_after_ms > 0 {
+                tokio::time::sleep(tokio::time::Duration::from_millis(step.wait_after_ms as u64)).await;
+            }
+        }
+
+        let selector = listing_step.selector.as_deref().unwrap_or_default();
+        let profile_key = listing_step.profile_key.as_deref().unwrap_or_default();
+        let matches = browser::count_profile_matches(&page, selector, profile, profile_key).await?;
+        let screenshot = browser::capture_screenshot(&page).await.ok();
+        browser::close_page(page, &browser_mode).await;
+        Ok((matches, screenshot))
+    }
+    .await;
+
+    browser::close_browser(app, browser_instance, &browser_mode).await;
+    let (matches, screenshot) = result?;
+
+    let evidence_id = match screenshot {
+        Some(bytes) => evidence::store(app, record_id, EvidenceKind::Screenshot, &bytes)
+            .map(|item| item.id)
+            .ok(),
+        None => None,
+    };
+
+    let check = RemovalCheck {
+        checked_at: Utc::now(),
+        still_listed: matches > 0,
+        matches,
+        evidence_id,
+    };
+
+    // Reload so changes made while the check ran aren't overwritten
+    if let Some(mut record) = history::load(app)?.records.into_iter().find(|r| r.id == record_id) {
+        record.last_removal_check = Some(check.clone());
+        if check.still_listed {
+            record.status = BrokerSubmissionStatus::ReListed;
+            relisting_alerts::raise(app, &record, &broker.name, &check)?;
+        }
+        history::upsert_record(app, record)?;
+    }
+
+    Ok(check)
+}
@@ -0,0 +1,121 @@
+use crate::deadlines::Statute;
+use crate::models::{Broker, Profile, RequestEmail, RequestType};
+
+/// Lines identifying the user so the broker can find their records. Only fields the
+/// user filled in are included.
+fn identity_lines(profile: &Profile) -> Vec<String> {
+    let mut lines = vec![format!("Name: {} {}", profile.first_name, profile.last_name)];
+    let street = profile.address.trim();
+    let place = [profile.city.trim(), profile.state.trim(), profile.zip.trim()]
+        .into_iter()
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    match (street.is_empty(), place.is_empty()) {
+        (false, false) => lines.push(format!("Address: {}, {}", street, place)),
+        (false, true) => lines.push(format!("Address: {}", street)),
+        (true, false) => lines.push(format!("Address: {}", place)),
+        (true, true) => {}
+    }
+    if !profile.email.trim().is_empty() {
+        lines.push(format!("Email: {}", profile.email.trim()));
+    }
+    if !profile.phone.trim().is_empty() {
+        lines.push(format!("Phone: {}", profile.phone.trim()));
+    }
+    lines
+}
+
+/// A privacy request email to `broker`, citing `statute` when one applies to the user.
+pub fn request_email(
+    profile: &Profile,
+    broker: &Broker,
+    request_type: RequestType,
+    statute: Option<&Statute>,
+) -> RequestEmail {
+    let law = statute
+        .map(|s| format!(" under the {} ({})", s.short_name, s.citation))
+        .unwrap_or_default();
+    let (subject, ask) = match request_type {
+        RequestType::Deletion => (
+            "Request to delete my personal information",
+            format!(
+                "I am writing to request that {} delete all personal information it holds about me{}, \
+                 and that it stop selling or sharing that information.",
+                broker.name, law
+            ),
+        ),
+        RequestType::Access => (
+            "Request to access my personal information",
+            format!(
+                "I am writing to request access to the personal information {} has collected about me{}. \
+                 Please tell me the specific pieces of information you hold, the categories of sources \
+                 they came from, the purposes you use them for, and the categories of third parties \
+                 you have sold or disclosed them to.",
+                broker.name, law
+            ),
+        ),
+    };
+
+    let mut body = format!("To {},\n\n{}\n\n", broker.name, ask);
+    body.push_str("Please use the following details to locate my records:\n\n");
+    for line in identity_lines(profile) {
+        body.push_str(&line);
+        body.push('\n');
+    }
+    if let Some(s) = statute {
+        body.push_str(&format!(
+            "\nThe law requires a response within {} days of receiving this request. \
+             Please confirm receipt and let me know if you need anything else to verify my identity.\n",
+            s.response_days
+        ));
+    } else {
+        body.push_str("\nPlease confirm receipt and let me know if you need anything else to verify my identity.\n");
+    }
+    body.push_str(&format!("\nThank you,\n{} {}\n", profile.first_name, profile.last_name));
+
+    RequestEmail {
+        subject: subject.to_string(),
+        body,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deadlines::statute_for;
+    use crate::test_fixtures;
+
+    fn profile() -> Profile {
+        Profile {
+            last_name: "Lee".to_string(),
+            phone: String::new(),
+            address: String::new(),
+            city: "Fresno".to_string(),
+            state: "CA".to_string(),
+            zip: "93701".to_string(),
+            ..test_fixtures::profile()
+        }
+    }
+
+    fn broker() -> Broker {
+        Broker { name: "PeopleFinder".to_string(), method: "email".to_string(), ..test_fixtures::broker("b") }
+    }
+
+    #[test]
+    fn access_request_cites_statute_and_identifies_user() {
+        let email = request_email(&profile(), &broker(), RequestType::Access, statute_for("CA"));
+        assert_eq!(email.subject, "Request to access my personal information");
+        assert!(email.body.contains("collected about me under the CCPA (Cal. Civ. Code"));
+        assert!(email.body.contains("Address: Fresno CA 93701\nEmail: jane@example.com\n"));
+        assert!(email.body.contains("within 45 days"));
+        assert!(!email.body.contains("Phone:"));
+    }
+
+    #[test]
+    fn deletion_request_without_statute() {
+        let email = request_email(&profile(), &broker(), RequestType::Deletion, None);
+        assert!(email.body.contains("PeopleFinder delete all personal information it holds about me, and"));
+        assert!(!email.body.contains("days"));
+    }
+}
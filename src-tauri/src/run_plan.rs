@@ -0,0 +1,544 @@
+use crate::models::{
+    Broker, BrokerSubmissionStatus, FormAction, Playbook, PlaybookStep, Profile, RequestType, RunOptions, SubmissionRecord,
+};
+use crate::step_cursor;
+use crate::step_descriptions;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+
+/// Average delay `browser::execute_action` adds before each automated action.
+const AVG_ACTION_DELAY_MS: u64 = 1000;
+/// Typical time `browser::navigate` waits for a page to settle (capped at `browser::SETTLE_CEILING`).
+const NAVIGATE_SETTLE_MS: u64 = 1500;
+/// Playbooks older than this are flagged as possibly out of date.
+const STALE_PLAYBOOK_DAYS: i64 = 180;
+/// Minimum outcome reports before a playbook's failure rate is trusted.
+const MIN_OUTCOME_REPORTS: u32 = 3;
+
+/// CAPTCHA/manual step counts and estimated run time for a list of playbook steps.
+pub struct StepStats {
+    pub captcha_steps: u32,
+    pub manual_steps: u32,
+    pub estimated_duration_ms: u64,
+}
+
+pub fn step_stats(steps: &[PlaybookStep]) -> StepStats {
+    let mut stats = StepStats {
+        captcha_steps: 0,
+        manual_steps: 0,
+        estimated_duration_ms: 0,
+    };
+
+    for step in steps {
+        match step.action.as_str() {
+            "captcha" => stats.captcha_steps += 1,
+            "user_prompt" => stats.manual_steps += 1,
+            "fill" if step.profile_key.is_none() => stats.manual_steps += 1,
+            "select" | "choose_radio" if step.profile_key.is_none() && step.value.is_none() => stats.manual_steps += 1,
+            "navigate" => stats.estimated_duration_ms += AVG_ACTION_DELAY_MS + NAVIGATE_SETTLE_MS,
+            "done" => {}
+            _ => stats.estimated_duration_ms += AVG_ACTION_DELAY_MS,
+        }
+        stats.estimated_duration_ms += step.wait_after_ms as u64;
+    }
+
+    stats
+}
+
+/// Why a playbook may no longer match the broker's site, if it looks stale.
+pub fn stale_reason(pb: &Playbook, now: DateTime<Utc>) -> Option<String> {
+    let reports = pb.success_count + pb.failure_count;
+    if reports >= MIN_OUTCOME_REPORTS && pb.failure_count > pb.success_count {
+        return Some(format!(
+            "Playbook fails more often than it succeeds ({} of {} runs failed)",
+            pb.failure_count, reports
+        ));
+    }
+    let created = DateTime::parse_from_rfc3339(&pb.created_at).ok()?;
+    let age = now.signed_duration_since(created.with_timezone(&Utc));
+    if age > Duration::days(STALE_PLAYBOOK_DAYS) {
+        return Some(format!("Playbook was recorded {} days ago", age.num_days()));
+    }
+    None
+}
+
+/// Cooldown for brokers without `relist_days`, so a run doesn't re-submit them right away.
+const DEFAULT_COOLDOWN_DAYS: i64 = 30;
+
+/// When a broker can be submitted again after its latest record, or `None` if it can be
+/// submitted now (no record, the last attempt failed or the listing reappeared, or the
+/// last request only asked what the broker holds).
+pub fn cooldown_until(broker: &Broker, latest: Option<&SubmissionRecord>) -> Option<DateTime<Utc>> {
+    let record = latest?;
+    if record.request_type == RequestType::Access {
+        return None;
+    }
+    match record.status {
+        BrokerSubmissionStatus::Failed | BrokerSubmissionStatus::ReListed => None,
+        _ => {
+            let days = broker.relist_days.map(|d| d as i64).unwrap_or(DEFAULT_COOLDOWN_DAYS);
+            Some(record.submitted_at + Duration::days(days))
+        }
+    }
+}
+
+/// Whether the broker was submitted recently enough that a run should skip it.
+pub fn recently_submitted(broker: &Broker, latest: Option<&SubmissionRecord>, now: DateTime<Utc>) -> bool {
+    cooldown_until(broker, latest).is_some_and(|until| now < until)
+}
+
+/// What the latest removal check says about whether the broker lists the user.
+/// Ordered so the most valuable brokers to run sort first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Exposure {
+    Listed,
+    Unknown,
+    NotFound,
+}
+
+pub fn exposure(latest: Option<&SubmissionRecord>) -> Exposure {
+    let Some(record) = latest else {
+        return Exposure::Unknown;
+    };
+    match record.status {
+        BrokerSubmissionStatus::ReListed => return Exposure::Listed,
+        BrokerSubmissionStatus::NotFound => return Exposure::NotFound,
+        _ => {}
+    }
+    match &record.last_removal_check {
+        Some(check) if check.still_listed => Exposure::Listed,
+        Some(_) => Exposure::NotFound,
+        None => Exposure::Unknown,
+    }
+}
+
+/// Stable-sort brokers so ones with a confirmed listing come first and ones with no hit come last.
+pub fn prioritize_by_exposure(brokers: &mut [Broker], latest: &[SubmissionRecord]) {
+    brokers.sort_by_key(|b| exposure(latest.iter().find(|r| r.broker_id == b.id)));
+}
+
+/// Whether a playbook stops for the user: a CAPTCHA, a prompt or a value it can't fill itself.
+/// "Opt out everywhere" runs the playbooks that don't first, so the hands-free part finishes
+/// before the run starts asking for help.
+pub fn needs_user(steps: &[PlaybookStep]) -> bool {
+    let stats = step_stats(steps);
+    stats.captcha_steps > 0 || stats.manual_steps > 0
+}
+
+/// One execution of a broker's playbook, with the profile it should use.
+pub struct SearchPass {
+    pub profile: Profile,
+    /// Recorded on the submission so each pass stays a distinct record
+    pub searched_with: Option<String>,
+}
+
+/// Position of the step treated as a playbook's final submit: its last `click`. Review mode
+/// pauses before it.
+pub fn submit_step_position(steps: &[PlaybookStep]) -> Option<u32> {
+    steps.iter().rev().find(|s| s.action == "click").map(|s| s.position)
+}
+
+/// Where a retry of a failed broker picks up: the failed step, or the `for_each_result` step
+/// of the loop it failed in, since a loop body can't run without its loop.
+pub fn resume_position(steps: &[PlaybookStep], failed_step: u32) -> u32 {
+    let mut open_loop = None;
+    for step in steps.iter().take_while(|s| s.position < failed_step) {
+        match step.action.as_str() {
+            step_cursor::FOR_EACH_ACTION => open_loop = Some(step.position),
+            step_cursor::END_FOR_EACH_ACTION => open_loop = None,
+            _ => {}
+        }
+    }
+    open_loop.unwrap_or(failed_step)
+}
+
+/// Whether a step before the resume point is replayed on a retry. Navigating, waiting,
+/// automatic fills and opening the user's listing just bring the reopened page back to where
+/// it was; other clicks, CAPTCHAs and anything that asks the user are what the retry spares.
+pub fn replays_on_resume(action: &FormAction) -> bool {
+    matches!(
+        action,
+        FormAction::Navigate { .. }
+            | FormAction::Wait { .. }
+            | FormAction::WaitFor { .. }
+            | FormAction::ScrollTo { .. }
+            | FormAction::Fill { .. }
+            | FormAction::Select { .. }
+            | FormAction::Check { .. }
+            | FormAction::ChooseRadio { .. }
+            | FormAction::FillCredential { .. }
+            | FormAction::FindAndClick { .. }
+    )
+}
+
+/// Whether a playbook searches for the listing (a `find_and_click` or `for_each_result` matched
+/// on a profile value)
+/// rather than submitting a plain form.
+pub fn is_search_driven(steps: &[PlaybookStep]) -> bool {
+    steps
+        .iter()
+        .any(|s| (s.action == "find_and_click" || s.action == step_cursor::FOR_EACH_ACTION) && s.profile_key.is_some())
+}
+
+/// Whether any step fills or matches on the given profile key.
+fn uses_profile_key(steps: &[PlaybookStep], key: &str) -> bool {
+    steps.iter().any(|s| s.profile_key.as_deref() == Some(key))
+}
+
+/// The passes to run for one broker: the profile as-is, then, when enabled, once more per
+/// previous address (search-driven playbooks only) and once per alternate email or phone
+/// (playbooks that use that key), with the value swapped in.
+pub fn search_passes(profile: &Profile, steps: &[PlaybookStep], options: &RunOptions) -> Vec<SearchPass> {
+    let mut passes = vec![SearchPass {
+        profile: profile.clone(),
+        searched_with: None,
+    }];
+
+    if options.search_previous_addresses && is_search_driven(steps) {
+        for prev in &profile.previous_addresses {
+            if prev.city.trim().is_empty() && prev.zip.trim().is_empty() {
+                continue;
+            }
+            let mut p = profile.clone();
+            p.address = prev.address.clone();
+            p.city = prev.city.clone();
+            p.state = prev.state.clone();
+            p.zip = prev.zip.clone();
+            let place = [prev.city.as_str(), prev.state.as_str()]
+                .iter()
+                .filter(|s| !s.is_empty())
+                .copied()
+                .collect::<Vec<_>>()
+                .join(", ");
+            passes.push(SearchPass {
+                profile: p,
+                searched_with: Some(format!("Previous address: {}", if place.is_empty() { &prev.zip } else { &place })),
+            });
+        }
+    }
+
+    // Labels use the position rather than the value so contact details stay out of history
+    if options.repeat_alternate_contacts {
+        if uses_profile_key(steps, "email") {
+            for (i, email) in profile.alternate_emails.iter().enumerate() {
+                if email.trim().is_empty() {
+                    continue;
+                }
+                let mut p = profile.clone();
+                p.email = email.clone();
+                passes.push(SearchPass {
+                    profile: p,
+                    searched_with: Some(format!("Alternate email #{}", i + 1)),
+                });
+            }
+        }
+        if uses_profile_key(steps, "phone") {
+            for (i, phone) in profile.alternate_phones.iter().enumerate() {
+                if phone.trim().is_empty() {
+                    continue;
+                }
+                let mut p = profile.clone();
+                p.phone = phone.clone();
+                passes.push(SearchPass {
+                    profile: p,
+                    searched_with: Some(format!("Alternate phone #{}", i + 1)),
+                });
+            }
+        }
+    }
+
+    passes
+}
+
+/// Profile fields a playbook can fill or search with, as profile keys. Other keys are
+/// resolved per broker or per pass (the privacy law, previous addresses, other contacts).
+const PROFILE_FIELD_KEYS: [&str; 10] =
+    ["firstName", "lastName", "fullName", "email", "phone", "address", "city", "state", "zip", "dob"];
+
+fn has_value(profile: &Profile, key: &str) -> bool {
+    let value = match key {
+        "firstName" => &profile.first_name,
+        "lastName" => &profile.last_name,
+        "fullName" => return !(profile.first_name.trim().is_empty() && profile.last_name.trim().is_empty()),
+        "email" => &profile.email,
+        "phone" => &profile.phone,
+        "address" => &profile.address,
+        "city" => &profile.city,
+        "state" => &profile.state,
+        "zip" => &profile.zip,
+        "dob" => &profile.dob,
+        _ => return true,
+    };
+    !value.trim().is_empty()
+}
+
+/// Profile fields every run of the playbook fills or searches with, in step order.
+/// Optional steps may be skipped, so their fields aren't required.
+pub fn required_profile_keys(steps: &[PlaybookStep]) -> Vec<&str> {
+    let mut keys: Vec<&str> = Vec::new();
+    for key in steps.iter().filter(|s| !s.optional).filter_map(|s| s.profile_key.as_deref()) {
+        if PROFILE_FIELD_KEYS.contains(&key) && !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Why the profile can't run `broker`'s playbook ("Spokeo playbook needs your email and
+/// ZIP code"), or None when it has every field the playbook requires.
+pub fn missing_fields_message(broker: &Broker, profile: &Profile, steps: &[PlaybookStep]) -> Option<String> {
+    let missing: Vec<&str> = required_profile_keys(steps)
+        .into_iter()
+        .filter(|k| !has_value(profile, k))
+        .map(step_descriptions::field_name)
+        .collect();
+    let (last, rest) = missing.split_last()?;
+    let fields = if rest.is_empty() { last.to_string() } else { format!("{} and {}", rest.join(", "), last) };
+    Some(format!("{} playbook needs your {}", broker.name, fields))
+}
+
+/// Order brokers for a run: network parents first, then every broker after the
+/// brokers it `depends_on`. Otherwise the caller's order is kept.
+///
+/// `satisfied` holds broker IDs that already have a successful submission, so a
+/// dependency outside this run is not an issue. Returns the ordered brokers and
+/// a warning for each dependency that can't be honoured.
+pub fn order_brokers(mut brokers: Vec<Broker>, satisfied: &HashSet<String>) -> (Vec<Broker>, Vec<String>) {
+    let mut warnings = Vec::new();
+    brokers.sort_by_key(|b| !b.network_parent);
+
+    let selected: HashSet<String> = brokers.iter().map(|b| b.id.clone()).collect();
+    for broker in &brokers {
+        for dep in &broker.depends_on {
+            if !selected.contains(dep) && !satisfied.contains(dep) {
+                warnings.push(format!(
+                    "{} depends on {}, which is not in this run and has not been submitted yet",
+                    broker.name, dep
+                ));
+            }
+        }
+    }
+
+    let mut ordered: Vec<Broker> = Vec::with_capacity(brokers.len());
+    let mut placed: HashSet<String> = HashSet::new();
+    let mut remaining = brokers;
+    while !remaining.is_empty() {
+        let ready = remaining.iter().position(|b| {
+            b.depends_on
+                .iter()
+                .all(|dep| !selected.contains(dep) || placed.contains(dep))
+        });
+        match ready {
+            Some(i) => {
+                let broker = remaining.remove(i);
+                placed.insert(broker.id.clone());
+                ordered.push(broker);
+            }
+            None => {
+                let names: Vec<&str> = remaining.iter().map(|b| b.name.as_str()).collect();
+                warnings.push(format!(
+                    "Circular dependency between {}; running them in registry order",
+                    names.join(", ")
+                ));
+                ordered.append(&mut remaining);
+            }
+        }
+    }
+
+    (ordered, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn broker(id: &str, depends_on: &[&str]) -> Broker {
+        Broker { depends_on: depends_on.iter().map(|s| s.to_string()).collect(), ..test_fixtures::broker(id) }
+    }
+
+    fn record(status: BrokerSubmissionStatus, days_ago: i64) -> SubmissionRecord {
+        SubmissionRecord { submitted_at: Utc::now() - Duration::days(days_ago), ..test_fixtures::record("a", status) }
+    }
+
+    fn ids(brokers: &[Broker]) -> Vec<&str> {
+        brokers.iter().map(|b| b.id.as_str()).collect()
+    }
+
+    #[test]
+    fn runs_dependencies_first() {
+        let brokers = vec![broker("a", &["c"]), broker("b", &[]), broker("c", &[])];
+        let (ordered, warnings) = order_brokers(brokers, &HashSet::new());
+        assert_eq!(ids(&ordered), vec!["b", "c", "a"]);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn warns_on_unselected_dependency() {
+        let brokers = vec![broker("a", &["x"]), broker("b", &["y"])];
+        let satisfied: HashSet<String> = ["y".to_string()].into_iter().collect();
+        let (ordered, warnings) = order_brokers(brokers, &satisfied);
+        assert_eq!(ids(&ordered), vec!["a", "b"]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("x"));
+    }
+
+    #[test]
+    fn breaks_cycles_with_warning() {
+        let brokers = vec![broker("a", &["b"]), broker("b", &["a"])];
+        let (ordered, warnings) = order_brokers(brokers, &HashSet::new());
+        assert_eq!(ordered.len(), 2);
+        assert!(warnings[0].starts_with("Circular dependency"));
+    }
+
+    fn profile() -> Profile {
+        Profile { dob: String::new(), ..test_fixtures::profile() }
+    }
+
+    fn step(action: &str, key: Option<&str>) -> PlaybookStep {
+        PlaybookStep {
+            position: 0,
+            action: action.to_string(),
+            selector: Some(".result".to_string()),
+            profile_key: key.map(|k| k.to_string()),
+            value: None,
+            description: String::new(),
+            instructions: None,
+            wait_after_ms: 0,
+            optional: false,
+            fallback_selectors: vec![],
+            phase: None,
+        }
+    }
+
+    #[test]
+    fn treats_last_click_as_submit() {
+        let steps: Vec<PlaybookStep> = ["navigate", "click", "fill", "captcha", "click", "done"]
+            .iter()
+            .enumerate()
+            .map(|(i, action)| PlaybookStep { position: i as u32 + 1, ..step(action, None) })
+            .collect();
+        assert_eq!(submit_step_position(&steps), Some(5));
+        assert_eq!(submit_step_position(&steps[..4]), Some(2));
+        assert_eq!(submit_step_position(&steps[..1]), None);
+    }
+
+    #[test]
+    fn resumes_loops_from_their_header() {
+        let steps: Vec<PlaybookStep> = ["navigate", "fill", "for_each_result", "click", "click", "end_for_each", "click"]
+            .iter()
+            .enumerate()
+            .map(|(i, action)| PlaybookStep { position: i as u32 + 1, ..step(action, None) })
+            .collect();
+        assert_eq!(resume_position(&steps, 2), 2);
+        assert_eq!(resume_position(&steps, 5), 3);
+        assert_eq!(resume_position(&steps, 7), 7);
+    }
+
+    #[test]
+    fn repeats_search_playbooks_per_previous_address() {
+        let mut profile = profile();
+        profile.previous_addresses = vec![crate::models::PreviousAddress {
+            address: "2 Oak Ave".to_string(),
+            city: "Peoria".to_string(),
+            state: "IL".to_string(),
+            zip: "61602".to_string(),
+        }];
+        let search = vec![step("fill", Some("city")), step("find_and_click", Some("lastName"))];
+        let form = vec![step("fill", Some("email")), step("click", None)];
+        let enabled = RunOptions {
+            search_previous_addresses: true,
+            ..Default::default()
+        };
+
+        let passes = search_passes(&profile, &search, &enabled);
+        assert_eq!(passes.len(), 2);
+        assert_eq!(passes[1].profile.city, "Peoria");
+        assert_eq!(passes[1].searched_with.as_deref(), Some("Previous address: Peoria, IL"));
+
+        assert_eq!(search_passes(&profile, &form, &enabled).len(), 1);
+        assert_eq!(search_passes(&profile, &search, &RunOptions::default()).len(), 1);
+    }
+
+    #[test]
+    fn repeats_contact_playbooks_per_alternate_email() {
+        let mut profile = profile();
+        profile.alternate_emails = vec!["old@example.com".to_string(), String::new()];
+        profile.alternate_phones = vec!["555-987-6543".to_string()];
+        let steps = vec![step("fill", Some("email")), step("click", None)];
+        let enabled = RunOptions {
+            repeat_alternate_contacts: true,
+            ..Default::default()
+        };
+
+        let passes = search_passes(&profile, &steps, &enabled);
+        assert_eq!(passes.len(), 2);
+        assert_eq!(passes[1].profile.email, "old@example.com");
+        assert_eq!(passes[1].searched_with.as_deref(), Some("Alternate email #1"));
+    }
+
+    #[test]
+    fn skips_within_relist_window() {
+        let mut b = broker("a", &[]);
+        b.relist_days = Some(90);
+        let now = Utc::now();
+        assert!(recently_submitted(&b, Some(&record(BrokerSubmissionStatus::Submitted, 10)), now));
+        assert!(!recently_submitted(&b, Some(&record(BrokerSubmissionStatus::Confirmed, 100)), now));
+        assert!(!recently_submitted(&b, Some(&record(BrokerSubmissionStatus::Failed, 1)), now));
+        assert!(!recently_submitted(&b, None, now));
+        let access = SubmissionRecord {
+            request_type: RequestType::Access,
+            ..record(BrokerSubmissionStatus::Submitted, 10)
+        };
+        assert!(!recently_submitted(&b, Some(&access), now));
+    }
+
+    #[test]
+    fn flags_playbooks_that_need_the_user() {
+        assert!(!needs_user(&[step("fill", Some("email")), step("click", None)]));
+        assert!(needs_user(&[step("fill", Some("email")), step("captcha", None)]));
+        // A fill with no profile key is typed in by the user
+        assert!(needs_user(&[step("fill", None)]));
+    }
+
+    #[test]
+    fn queues_listed_brokers_first() {
+        let mut brokers = vec![broker("e", &[]), broker("a", &[]), broker("b", &[]), broker("c", &[]), broker("d", &[])];
+        let check = |still_listed| crate::models::RemovalCheck {
+            checked_at: Utc::now(),
+            still_listed,
+            matches: if still_listed { 1 } else { 0 },
+            evidence_id: None,
+        };
+        let mut gone = record(BrokerSubmissionStatus::Confirmed, 40);
+        gone.last_removal_check = Some(check(false));
+        let mut listed = record(BrokerSubmissionStatus::Confirmed, 40);
+        listed.broker_id = "c".to_string();
+        listed.last_removal_check = Some(check(true));
+        let mut relisted = record(BrokerSubmissionStatus::ReListed, 40);
+        relisted.broker_id = "d".to_string();
+        let mut unlisted = record(BrokerSubmissionStatus::NotFound, 40);
+        unlisted.broker_id = "e".to_string();
+
+        prioritize_by_exposure(&mut brokers, &[gone, listed, relisted, unlisted]);
+        assert_eq!(ids(&brokers), vec!["c", "d", "b", "e", "a"]);
+    }
+
+    #[test]
+    fn names_missing_profile_fields() {
+        let mut steps = vec![step("fill", Some("email")), step("fill", Some("zip")), step("fill", Some("legalBasis"))];
+        steps.push(PlaybookStep { optional: true, ..step("fill", Some("phone")) });
+        assert_eq!(required_profile_keys(&steps), vec!["email", "zip"]);
+
+        let spokeo = Broker { name: "Spokeo".to_string(), ..broker("spokeo", &[]) };
+        let mut p = profile();
+        p.phone.clear();
+        assert_eq!(missing_fields_message(&spokeo, &p, &steps), None);
+        p.email.clear();
+        p.zip = "  ".to_string();
+        assert_eq!(
+            missing_fields_message(&spokeo, &p, &steps).as_deref(),
+            Some("Spokeo playbook needs your email and ZIP code")
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use crate::models::{RunPreset, RunPresetStore};
+use chrono::Utc;
+use std::fs;
+use std::path::PathBuf;
+
+const FILENAME: &str = "run_presets.json";
+/// Longest preset name, so names fit on a button.
+pub const MAX_NAME_LEN: usize = 60;
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<RunPresetStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(RunPresetStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &RunPresetStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// Save a preset, replacing any with the same name (ignoring case).
+pub fn upsert(app: &tauri::AppHandle, mut preset: RunPreset) -> Result<RunPreset, String> {
+    preset.name = preset.name.trim().to_string();
+    if preset.name.is_empty() {
+        return Err("Give the preset a name".to_string());
+    }
+    if preset.name.chars().count() > MAX_NAME_LEN {
+        return Err(format!("Preset names can be at most {} characters", MAX_NAME_LEN));
+    }
+    if preset.broker_ids.is_empty() && preset.categories.is_empty() {
+        return Err("A preset needs at least one broker or category".to_string());
+    }
+    preset.updated_at = Utc::now();
+
+    let mut store = load(app)?;
+    match store.presets.iter_mut().find(|p| p.name.eq_ignore_ascii_case(&preset.name)) {
+        Some(existing) => *existing = preset.clone(),
+        None => store.presets.push(preset.clone()),
+    }
+    save(app, &store)?;
+    Ok(preset)
+}
+
+pub fn delete(app: &tauri::AppHandle, name: &str) -> Result<(), String> {
+    let mut store = load(app)?;
+    store.presets.retain(|p| !p.name.eq_ignore_ascii_case(name.trim()));
+    save(app, &store)
+}
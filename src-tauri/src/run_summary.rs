@@ -0,0 +1,68 @@
+use crate::models::{Broker, BrokerRunOutcome, BrokerRunSummary, FailureClass};
+use std::time::Instant;
+
+/// Error text (raw or from `format_step_error`) that points at the connection rather than the page.
+const NETWORK_HINTS: &[&str] = &[
+    "net::err_",
+    "navigation failed",
+    "failed to open page",
+    "timed out",
+    "lost connection",
+    "not responding",
+];
+
+/// Classify a failed step. `blocked` and `captcha_unsolved` describe the page at the
+/// time of failure and take precedence over the error text.
+pub fn classify_step_failure(error: &str, blocked: bool, captcha_unsolved: bool) -> FailureClass {
+    if blocked {
+        return FailureClass::Blocked;
+    }
+    if captcha_unsolved {
+        return FailureClass::CaptchaTimeout;
+    }
+    let lower = error.to_lowercase();
+    if NETWORK_HINTS.iter().any(|hint| lower.contains(hint)) {
+        FailureClass::Network
+    } else if lower.contains("could not find the element") || lower.contains("element not found") {
+        FailureClass::Selector
+    } else {
+        FailureClass::Other
+    }
+}
+
+/// A summary with no playbook or failure details, timed from `started`.
+pub fn broker_summary(broker: &Broker, outcome: BrokerRunOutcome, started: Instant) -> BrokerRunSummary {
+    BrokerRunSummary {
+        broker_id: broker.id.clone(),
+        broker_name: broker.name.clone(),
+        outcome,
+        duration_ms: started.elapsed().as_millis() as u64,
+        playbook_id: None,
+        playbook_version: None,
+        failure_step: None,
+        failure_phase: None,
+        failure_class: None,
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_state_wins_over_error_text() {
+        let error = "Could not find the element for \"Submit\". The page layout may have changed.";
+        assert_eq!(classify_step_failure(error, false, false), FailureClass::Selector);
+        assert_eq!(classify_step_failure(error, false, true), FailureClass::CaptchaTimeout);
+        assert_eq!(classify_step_failure(error, true, true), FailureClass::Blocked);
+    }
+
+    #[test]
+    fn recognizes_network_errors() {
+        let error = "Page navigation failed for \"Open form\". The URL may be invalid or the site may be down.";
+        assert_eq!(classify_step_failure(error, false, false), FailureClass::Network);
+        assert_eq!(classify_step_failure("Navigation failed: net::ERR_NAME_NOT_RESOLVED", false, false), FailureClass::Network);
+        assert_eq!(classify_step_failure("Step \"Pick state\" failed: bad option", false, false), FailureClass::Other);
+    }
+}
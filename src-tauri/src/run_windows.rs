@@ -0,0 +1,190 @@
+use crate::engine::EngineState;
+use crate::history;
+use crate::models::{BrokerSubmissionStatus, RecheckSchedule, RunWindow, SubmissionRecord};
+use crate::recorder::RecorderState;
+use crate::removal_verification;
+use crate::settings;
+use chrono::{Datelike, Duration, Local, NaiveDateTime, TimeZone, Timelike, Utc};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// How often the background task looks for due rechecks.
+pub const CHECK_INTERVAL_SECS: u64 = 15 * 60;
+/// Delay before the first look, so startup isn't slowed by a browser launch.
+pub const FIRST_CHECK_DELAY_SECS: u64 = 120;
+
+/// Set while a background recheck has the automation browser, so runs and recordings
+/// wait for it instead of launching a second one on the same profile.
+static RECHECKING: AtomicBool = AtomicBool::new(false);
+
+pub fn is_rechecking() -> bool {
+    RECHECKING.load(Ordering::SeqCst)
+}
+
+/// Check that windows name real days and hours. Saved windows are trusted by `is_open`.
+pub fn validate(windows: &[RunWindow]) -> Result<(), String> {
+    for window in windows {
+        if window.days.is_empty() {
+            return Err("Pick at least one day for each run window.".to_string());
+        }
+        if window.days.iter().any(|d| *d > 6) {
+            return Err("Run window days go from 0 (Monday) to 6 (Sunday).".to_string());
+        }
+        if window.start_hour > 23 || window.end_hour == 0 || window.end_hour > 24 {
+            return Err("Run windows open on the hour from 0 to 23 and close from 1 to 24.".to_string());
+        }
+        if window.start_hour == window.end_hour {
+            return Err("A run window has to close at a different hour than it opens.".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn window_open(window: &RunWindow, at: NaiveDateTime) -> bool {
+    let day = at.weekday().num_days_from_monday();
+    let hour = at.hour();
+    let on = |d: u32| window.days.contains(&d);
+    if window.start_hour < window.end_hour {
+        on(day) && (window.start_hour..window.end_hour).contains(&hour)
+    } else {
+        // Past midnight: the evening of a listed day and the early hours after it
+        (on(day) && hour >= window.start_hour) || (on((day + 6) % 7) && hour < window.end_hour)
+    }
+}
+
+/// Whether background work may run at local time `at`. No windows means any time.
+pub fn is_open(windows: &[RunWindow], at: NaiveDateTime) -> bool {
+    windows.is_empty() || windows.iter().any(|w| window_open(w, at))
+}
+
+/// The next local time after `at` that a window opens. Windows open on the hour, so
+/// checking each hour of the coming week finds it. None when nothing ever opens.
+pub fn next_open(windows: &[RunWindow], at: NaiveDateTime) -> Option<NaiveDateTime> {
+    let hour = at.date().and_hms_opt(at.hour(), 0, 0)?;
+    (1..=7 * 24 + 1)
+        .map(|h| hour + Duration::hours(h))
+        .find(|t| is_open(windows, *t))
+}
+
+/// Due for a removal check and not yet checked since it came due.
+fn awaiting_recheck(record: &SubmissionRecord) -> bool {
+    record.status == BrokerSubmissionStatus::Confirmed
+        && record.next_check_date.is_some_and(|due| {
+            record.last_removal_check.as_ref().is_none_or(|c| c.checked_at < due)
+        })
+}
+
+fn queued(app: &tauri::AppHandle) -> Result<Vec<SubmissionRecord>, String> {
+    Ok(history::get_due_for_recheck(app)?.into_iter().filter(awaiting_recheck).collect())
+}
+
+pub fn schedule(app: &tauri::AppHandle) -> Result<RecheckSchedule, String> {
+    let windows = settings::load(app)?.run_windows;
+    let now = Local::now().naive_local();
+    let window_open = is_open(&windows, now);
+    let next_window = if window_open {
+        None
+    } else {
+        next_open(&windows, now)
+            .and_then(|t| Local.from_local_datetime(&t).earliest())
+            .map(|t| t.with_timezone(&Utc))
+    };
+    Ok(RecheckSchedule { window_open, next_window, queued: queued(app)?.len() })
+}
+
+/// Whether a run or recording has the automation browser.
+async fn browser_busy(app: &tauri::AppHandle) -> bool {
+    use tauri::Manager;
+    let running = app.state::<EngineState>().0.lock().await.as_ref().is_some_and(|e| e.is_busy());
+    running || app.state::<RecorderState>().0.lock().await.is_some()
+}
+
+/// Check every queued removal while a window stays open, one at a time. Records that
+/// can't be checked are skipped for the rest of the session; they usually need the
+/// user, and retrying every interval would keep opening the browser for nothing.
+async fn recheck_queued(app: &tauri::AppHandle, skipped: &mut HashSet<String>) -> Result<Vec<String>, String> {
+    if !settings::load(app)?.auto_recheck {
+        return Ok(Vec::new());
+    }
+    let Some(profile) = crate::commands::profile::get_profile(app.clone())? else {
+        return Ok(Vec::new());
+    };
+    let mut checked = Vec::new();
+    for record in queued(app)?.into_iter().filter(|r| !skipped.contains(&r.id)) {
+        // The window can close or the user start a run part way through the queue
+        if !is_open(&settings::load(app)?.run_windows, Local::now().naive_local()) {
+            break;
+        }
+        RECHECKING.store(true, Ordering::SeqCst);
+        if browser_busy(app).await {
+            RECHECKING.store(false, Ordering::SeqCst);
+            break;
+        }
+        let result = removal_verification::verify_removal(app, &record.id, &profile, "best").await;
+        RECHECKING.store(false, Ordering::SeqCst);
+        match result {
+            Ok(_) => checked.push(record.id),
+            Err(e) => {
+                eprintln!("[run-windows] {}: Removal check failed: {}", record.broker_id, e);
+                skipped.insert(record.id);
+            }
+        }
+    }
+    Ok(checked)
+}
+
+/// Recheck due removals on a timer for as long as the app runs, emitting
+/// `background-rechecks` with the checked record IDs. Outside the user's run windows
+/// they stay queued until one opens.
+pub async fn run_background_rechecks(app: tauri::AppHandle) {
+    use tauri::Emitter;
+    let mut skipped = HashSet::new();
+    tokio::time::sleep(tokio::time::Duration::from_secs(FIRST_CHECK_DELAY_SECS)).await;
+    loop {
+        match recheck_queued(&app, &mut skipped).await {
+            Ok(checked) if !checked.is_empty() => {
+                let _ = app.emit("background-rechecks", &checked);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("[run-windows] Background recheck failed: {}", e),
+        }
+        tokio::time::sleep(tokio::time::Duration::from_secs(CHECK_INTERVAL_SECS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        // 2024-01-01 was a Monday
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(hour, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn opens_only_inside_windows() {
+        let weekdays = RunWindow { days: vec![0, 1, 2, 3, 4], start_hour: 9, end_hour: 17 };
+        let friday_nights = RunWindow { days: vec![4], start_hour: 22, end_hour: 2 };
+        let windows = [weekdays, friday_nights];
+        assert!(is_open(&windows, at(1, 9)));
+        assert!(!is_open(&windows, at(1, 17)));
+        assert!(!is_open(&windows, at(6, 12)));
+        assert!(is_open(&windows, at(5, 23)));
+        assert!(is_open(&windows, at(6, 1)));
+        assert!(!is_open(&windows, at(7, 1)));
+        assert!(is_open(&[], at(7, 3)));
+        assert!(validate(&windows).is_ok());
+        assert!(validate(&[RunWindow { days: vec![7], start_hour: 9, end_hour: 17 }]).is_err());
+        assert!(validate(&[RunWindow { days: vec![0], start_hour: 9, end_hour: 9 }]).is_err());
+    }
+
+    #[test]
+    fn finds_when_the_next_window_opens() {
+        let windows = [RunWindow { days: vec![0, 1, 2, 3, 4], start_hour: 9, end_hour: 17 }];
+        let expected = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        assert_eq!(next_open(&windows, at(5, 18)), Some(expected));
+        assert_eq!(next_open(&windows, at(6, 12)), Some(expected));
+        assert_eq!(next_open(&[RunWindow { days: vec![], start_hour: 9, end_hour: 17 }], at(1, 8)), None);
+    }
+}
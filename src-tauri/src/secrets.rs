@@ -9,13 +9,50 @@ const SECRETS_ENTRY: &str = "secrets";
 #[derive(Serialize, Deserialize)]
 struct StoredSecrets {
     encryption_key: String, // base64-encoded AES-256 key
+    #[serde(default)]
+    device_key: Option<String>, // base64-encoded Ed25519 seed used to sign API requests
+    #[serde(default)]
+    device_registered: bool,
+    #[serde(default)]
+    ai_api_key: Option<String>, // the user's own key for selector repair
+    #[serde(default)]
+    browser_proxy_password: Option<String>, // login for the automation browser's proxy
 }
 
 struct Inner {
     encryption_key: Vec<u8>,
+    device_key: Vec<u8>,
+    device_registered: bool,
+    ai_api_key: Option<String>,
+    browser_proxy_password: Option<String>,
     loaded: bool,
 }
 
+impl Inner {
+    fn stored(&self) -> StoredSecrets {
+        StoredSecrets {
+            encryption_key: BASE64.encode(&self.encryption_key),
+            device_key: Some(BASE64.encode(&self.device_key)),
+            device_registered: self.device_registered,
+            ai_api_key: self.ai_api_key.clone(),
+            browser_proxy_password: self.browser_proxy_password.clone(),
+        }
+    }
+}
+
+fn random_key() -> Vec<u8> {
+    let mut key = vec![0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    key
+}
+
+fn write_entry(stored: &StoredSecrets) -> Result<(), String> {
+    let entry = keyring::Entry::new(SERVICE_NAME, SECRETS_ENTRY)
+        .map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(stored).map_err(|e| e.to_string())?;
+    entry.set_password(&json).map_err(|e| e.to_string())
+}
+
 /// In-memory cache for secrets backed by a single OS keychain entry.
 /// Call `load()` once at startup — all subsequent reads come from memory.
 pub struct SecretsCache(Mutex<Inner>);
@@ -24,6 +61,10 @@ impl SecretsCache {
     pub fn new() -> Self {
         Self(Mutex::new(Inner {
             encryption_key: Vec::new(),
+            device_key: Vec::new(),
+            device_registered: false,
+            ai_api_key: None,
+            browser_proxy_password: None,
             loaded: false,
         }))
     }
@@ -38,25 +79,39 @@ impl SecretsCache {
         let entry = keyring::Entry::new(SERVICE_NAME, SECRETS_ENTRY)
             .map_err(|e| e.to_string())?;
 
-        let stored = match entry.get_password() {
+        let mut stored = match entry.get_password() {
             Ok(json) => serde_json::from_str::<StoredSecrets>(&json)
                 .map_err(|e| format!("Failed to parse secrets: {}", e))?,
             Err(keyring::Error::NoEntry) => {
-                let mut key = vec![0u8; 32];
-                rand::thread_rng().fill_bytes(&mut key);
                 let stored = StoredSecrets {
-                    encryption_key: BASE64.encode(&key),
+                    encryption_key: BASE64.encode(random_key()),
+                    device_key: Some(BASE64.encode(random_key())),
+                    device_registered: false,
+                    ai_api_key: None,
+                    browser_proxy_password: None,
                 };
-                let json = serde_json::to_string(&stored).map_err(|e| e.to_string())?;
-                entry.set_password(&json).map_err(|e| e.to_string())?;
+                write_entry(&stored)?;
                 stored
             }
             Err(e) => return Err(e.to_string()),
         };
 
+        // Installs from before device registration have no device key yet
+        if stored.device_key.is_none() {
+            stored.device_key = Some(BASE64.encode(random_key()));
+            stored.device_registered = false;
+            write_entry(&stored)?;
+        }
+
         inner.encryption_key = BASE64
             .decode(&stored.encryption_key)
             .map_err(|e| format!("Failed to decode encryption key: {}", e))?;
+        inner.device_key = BASE64
+            .decode(stored.device_key.as_deref().unwrap_or_default())
+            .map_err(|e| format!("Failed to decode device key: {}", e))?;
+        inner.device_registered = stored.device_registered;
+        inner.ai_api_key = stored.ai_api_key;
+        inner.browser_proxy_password = stored.browser_proxy_password;
         inner.loaded = true;
         Ok(())
     }
@@ -68,4 +123,71 @@ impl SecretsCache {
         }
         Ok(inner.encryption_key.clone())
     }
+
+    /// Swap in a restored encryption key. Anything encrypted with the old one stops opening.
+    pub fn set_encryption_key(&self, key: Vec<u8>) -> Result<(), String> {
+        let mut inner = self.0.lock().unwrap();
+        if !inner.loaded {
+            return Err("Secrets not loaded".to_string());
+        }
+        write_entry(&StoredSecrets { encryption_key: BASE64.encode(&key), ..inner.stored() })?;
+        inner.encryption_key = key;
+        Ok(())
+    }
+
+    /// The 32-byte Ed25519 seed this device signs API requests with.
+    pub fn get_device_key(&self) -> Result<Vec<u8>, String> {
+        let inner = self.0.lock().unwrap();
+        if !inner.loaded {
+            return Err("Secrets not loaded".to_string());
+        }
+        Ok(inner.device_key.clone())
+    }
+
+    pub fn is_device_registered(&self) -> bool {
+        self.0.lock().unwrap().device_registered
+    }
+
+    /// Remember that the API has accepted this device's public key.
+    pub fn mark_device_registered(&self) -> Result<(), String> {
+        let mut inner = self.0.lock().unwrap();
+        if !inner.loaded {
+            return Err("Secrets not loaded".to_string());
+        }
+        write_entry(&StoredSecrets { device_registered: true, ..inner.stored() })?;
+        inner.device_registered = true;
+        Ok(())
+    }
+
+    /// The user's API key for the selector-repair model, if they've added one.
+    pub fn get_ai_api_key(&self) -> Option<String> {
+        self.0.lock().unwrap().ai_api_key.clone()
+    }
+
+    /// Store or (with `None`) forget the selector-repair API key.
+    pub fn set_ai_api_key(&self, key: Option<String>) -> Result<(), String> {
+        let mut inner = self.0.lock().unwrap();
+        if !inner.loaded {
+            return Err("Secrets not loaded".to_string());
+        }
+        write_entry(&StoredSecrets { ai_api_key: key.clone(), ..inner.stored() })?;
+        inner.ai_api_key = key;
+        Ok(())
+    }
+
+    /// Password for the automation browser's proxy, if it needs one.
+    pub fn get_browser_proxy_password(&self) -> Option<String> {
+        self.0.lock().unwrap().browser_proxy_password.clone()
+    }
+
+    /// Store or (with `None`) forget the browser proxy's password.
+    pub fn set_browser_proxy_password(&self, password: Option<String>) -> Result<(), String> {
+        let mut inner = self.0.lock().unwrap();
+        if !inner.loaded {
+            return Err("Secrets not loaded".to_string());
+        }
+        write_entry(&StoredSecrets { browser_proxy_password: password.clone(), ..inner.stored() })?;
+        inner.browser_proxy_password = password;
+        Ok(())
+    }
 }
@@ -0,0 +1,250 @@
+use crate::local_playbooks;
+use crate::models::{AppSettings, FormAction, LocalPlaybook, PageStructure, Playbook, PlaybookStep, Profile};
+use crate::pii_scan;
+use crate::playbook_validation::MAX_FALLBACK_SELECTORS;
+use crate::step_descriptions;
+use serde_json::json;
+use std::time::Duration;
+
+pub const DEFAULT_API_BASE: &str = "https://api.openai.com/v1";
+pub const DEFAULT_MODEL: &str = "gpt-4o-mini";
+
+/// Fields and buttons described to the model; long pages are cut off here.
+const MAX_ELEMENTS: usize = 150;
+
+const SYSTEM_PROMPT: &str = "You repair CSS selectors for a browser automation tool. You get a \
+step whose selector no longer matches and a list of the elements on the page. Reply with JSON \
+only: {\"selector\": \"...\"} with one selector copied exactly from the list, or \
+{\"selector\": null} if none of them is the element the step means.";
+
+/// Where and how to ask for repairs. Only exists once the user has opted in and added a key.
+pub struct RepairConfig {
+    pub api_base: String,
+    pub model: String,
+    pub api_key: String,
+}
+
+pub fn config(settings: &AppSettings, api_key: Option<String>) -> Option<RepairConfig> {
+    if !settings.ai_selector_repair {
+        return None;
+    }
+    Some(RepairConfig {
+        api_base: settings.ai_api_base.clone().unwrap_or_else(|| DEFAULT_API_BASE.to_string()),
+        model: settings.ai_model.clone().unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+        api_key: api_key.filter(|k| !k.is_empty())?,
+    })
+}
+
+/// Check that the API base URL is one the app will send a key to.
+pub fn validate_api_base(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid API URL: {}", e))?;
+    match parsed.scheme() {
+        "https" => Ok(()),
+        "http" if matches!(parsed.host_str(), Some("localhost") | Some("127.0.0.1")) => Ok(()),
+        _ => Err("The API URL must use https:// (http:// is only allowed for localhost).".to_string()),
+    }
+}
+
+/// The page's fields and buttons as sent to the model: labels, names and selectors only,
+/// with anything matching the profile redacted. Elements whose selector itself contains
+/// personal details are left out, since the model could only answer with them.
+pub fn describe_page(structure: &PageStructure, profile: Option<&Profile>) -> serde_json::Value {
+    let clean = |text: &Option<String>| text.as_deref().map(|t| pii_scan::redact(t, profile));
+    let usable = |selector: &str| pii_scan::redact(selector, profile) == selector;
+    let fields: Vec<_> = structure
+        .forms
+        .iter()
+        .flat_map(|form| &form.fields)
+        .filter(|f| f.visible && usable(&f.selector))
+        .map(|f| {
+            json!({
+                "selector": f.selector,
+                "tag": f.tag,
+                "type": f.field_type,
+                "label": clean(&f.label),
+                "placeholder": clean(&f.placeholder),
+                "name": f.name,
+                "id": f.id,
+            })
+        })
+        .take(MAX_ELEMENTS)
+        .collect();
+    let buttons: Vec<_> = structure
+        .buttons
+        .iter()
+        .filter(|b| b.visible && usable(&b.selector))
+        .map(|b| json!({ "selector": b.selector, "text": pii_scan::redact(&b.text, profile) }))
+        .take(MAX_ELEMENTS.saturating_sub(fields.len()))
+        .collect();
+    json!({
+        "title": pii_scan::redact(&structure.title, profile),
+        "fields": fields,
+        "buttons": buttons,
+    })
+}
+
+/// What the model is asked about the failed step.
+pub fn build_prompt(step: &PlaybookStep, page: &serde_json::Value) -> String {
+    let intent = match step.profile_key.as_deref() {
+        Some(key) => format!(" It fills in {}.", step_descriptions::profile_field_phrase(key)),
+        None => String::new(),
+    };
+    format!(
+        "The step \"{}\" ({}) used the selector `{}`, which no longer matches anything on the page.{}\n\
+         Which element on this page is it meant for?\n\n{}",
+        step.description,
+        step.action,
+        step.selector.as_deref().unwrap_or_default(),
+        intent,
+        page,
+    )
+}
+
+/// The selector in the model's reply, if it's one of the page's own. Anything else (an
+/// invented selector, prose, `null`) is no suggestion at all.
+pub fn parse_suggestion(reply: &str, page: &serde_json::Value) -> Option<String> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    let parsed: serde_json::Value = serde_json::from_str(reply.get(start..=end)?).ok()?;
+    let selector = parsed.get("selector")?.as_str()?.trim();
+    let known = ["fields", "buttons"]
+        .iter()
+        .filter_map(|list| page.get(list)?.as_array())
+        .flatten()
+        .any(|el| el.get("selector").and_then(|s| s.as_str()) == Some(selector));
+    known.then(|| selector.to_string())
+}
+
+/// Ask the model for a selector to replace `step`'s. `Ok(None)` when it found nothing fitting.
+pub async fn suggest(
+    config: &RepairConfig,
+    step: &PlaybookStep,
+    structure: &PageStructure,
+    profile: Option<&Profile>,
+) -> Result<Option<String>, String> {
+    let page = describe_page(structure, profile);
+    let body = json!({
+        "model": config.model,
+        "temperature": 0,
+        "messages": [
+            { "role": "system", "content": SYSTEM_PROMPT },
+            { "role": "user", "content": build_prompt(step, &page) },
+        ],
+    });
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .post(format!("{}/chat/completions", config.api_base.trim_end_matches('/')))
+        .bearer_auth(&config.api_key)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("Selector repair request failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Selector repair API returned {}", response.status()));
+    }
+    let reply: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Selector repair API sent an unexpected response: {}", e))?;
+    let content = reply["choices"][0]["message"]["content"].as_str().unwrap_or_default();
+    Ok(parse_suggestion(content, &page))
+}
+
+/// `action` aimed at `selector` instead, for the actions that target a single element.
+pub fn with_selector(action: &FormAction, selector: &str) -> Option<FormAction> {
+    let mut repaired = action.clone();
+    match &mut repaired {
+        FormAction::Fill { selector: s, .. }
+        | FormAction::Select { selector: s, .. }
+        | FormAction::Check { selector: s, .. }
+        | FormAction::ChooseRadio { selector: s, .. }
+        | FormAction::Click { selector: s }
+        | FormAction::WaitFor { selector: s, .. }
+        | FormAction::ScrollTo { selector: s }
+        | FormAction::FindAndClick { selector: s, .. } => *s = selector.to_string(),
+        _ => return None,
+    }
+    Some(repaired)
+}
+
+/// Point the step at `position` to the approved selector, keeping the old one as its first
+/// fallback in case the page changes back.
+pub fn patch_step(steps: &mut [PlaybookStep], position: u32, selector: &str) {
+    let Some(step) = steps.iter_mut().find(|s| s.position == position) else { return };
+    if let Some(old) = step.selector.replace(selector.to_string()) {
+        step.fallback_selectors.retain(|s| s != &old && s != selector);
+        step.fallback_selectors.insert(0, old);
+        step.fallback_selectors.truncate(MAX_FALLBACK_SELECTORS);
+    }
+}
+
+/// The local playbook approved repairs are saved to: a local playbook is patched in place,
+/// a community one gets a local copy the user can run until the community version is fixed.
+pub fn repair_target(app: &tauri::AppHandle, pb: &Playbook) -> LocalPlaybook {
+    if pb.status == "local" {
+        let existing = local_playbooks::get_all(app).ok().and_then(|all| all.into_iter().find(|p| p.id == pb.id));
+        if let Some(existing) = existing {
+            return existing;
+        }
+    }
+    let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    LocalPlaybook {
+        id: uuid::Uuid::new_v4().to_string(),
+        broker_id: pb.broker_id.clone(),
+        broker_name: pb.broker_name.clone(),
+        title: Some(format!("{} (repaired)", pb.title.as_deref().unwrap_or(&pb.broker_name))),
+        notes: Some(format!("Community playbook v{} with selectors repaired during a run.", pb.version)),
+        steps: pb.steps.clone(),
+        created_at: now.clone(),
+        updated_at: now,
+        submitted_at: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(selector: &str, fallbacks: &[&str]) -> PlaybookStep {
+        PlaybookStep {
+            position: 2,
+            action: "fill".to_string(),
+            selector: Some(selector.to_string()),
+            profile_key: Some("email".to_string()),
+            value: None,
+            description: "Fill in your email".to_string(),
+            instructions: None,
+            wait_after_ms: 500,
+            optional: false,
+            fallback_selectors: fallbacks.iter().map(|s| s.to_string()).collect(),
+            phase: None,
+        }
+    }
+
+    #[test]
+    fn accepts_only_selectors_from_the_page() {
+        let page = json!({
+            "fields": [{ "selector": "#contact-email" }],
+            "buttons": [{ "selector": "button.submit" }],
+        });
+        let fenced = "```json\n{\"selector\": \"#contact-email\"}\n```";
+        assert_eq!(parse_suggestion(fenced, &page).as_deref(), Some("#contact-email"));
+        assert_eq!(parse_suggestion("{\"selector\": \"button.submit\"}", &page).as_deref(), Some("button.submit"));
+        assert_eq!(parse_suggestion("{\"selector\": \"#made-up\"}", &page), None);
+        assert_eq!(parse_suggestion("{\"selector\": null}", &page), None);
+        assert_eq!(parse_suggestion("Try #contact-email", &page), None);
+    }
+
+    #[test]
+    fn keeps_the_old_selector_as_a_fallback() {
+        let mut steps = vec![step("#email", &["input[name=email]", "#contact-email"])];
+        patch_step(&mut steps, 2, "#contact-email");
+        assert_eq!(steps[0].selector.as_deref(), Some("#contact-email"));
+        assert_eq!(steps[0].fallback_selectors, ["#email", "input[name=email]"]);
+        patch_step(&mut steps, 9, "#other");
+        assert_eq!(steps[0].selector.as_deref(), Some("#contact-email"));
+    }
+}
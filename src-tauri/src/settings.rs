@@ -0,0 +1,42 @@
+use crate::models::AppSettings;
+use std::fs;
+use std::path::PathBuf;
+
+const FILENAME: &str = "settings.json";
+/// Longest allowed gap between repeated waiting-for-user alerts.
+pub const MAX_ALERT_REPEAT_MINUTES: u32 = 120;
+
+fn settings_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<AppSettings, String> {
+    let path = settings_path(app)?;
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+pub fn save(app: &tauri::AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    let data = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// Check that a proxy URL uses a scheme reqwest can route API traffic through.
+pub fn validate_proxy_url(url: &str) -> Result<(), String> {
+    let lower = url.trim().to_lowercase();
+    let allowed = ["http://", "https://", "socks5://", "socks5h://"];
+    if !allowed.iter().any(|scheme| lower.starts_with(scheme)) {
+        return Err(
+            "Proxy URL must start with http://, https://, socks5:// or socks5h:// (use socks5h://127.0.0.1:9050 for Tor).".to_string(),
+        );
+    }
+    reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid proxy URL: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// How long to wait for a broker's text before asking the user for the code.
+pub const WAIT_SECS: u64 = 120;
+/// Gap between checks of the relay.
+pub const POLL_SECS: u64 = 4;
+
+/// A forwarded text message. Twilio's webhook field name is accepted too, so a relay can
+/// hand back what it received unchanged.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SmsMessage {
+    #[serde(alias = "Body")]
+    pub body: String,
+    #[serde(default)]
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+/// Check that the relay URL is one the app can poll.
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid SMS relay URL: {}", e))?;
+    match parsed.scheme() {
+        "https" => Ok(()),
+        "http" if matches!(parsed.host_str(), Some("localhost") | Some("127.0.0.1")) => Ok(()),
+        _ => Err("The SMS relay URL must use https:// (http:// is only allowed for localhost).".to_string()),
+    }
+}
+
+/// The verification code in a text: the first 4-8 digit number after a word like "code",
+/// otherwise the only such number in the message.
+pub fn extract_code(body: &str) -> Option<String> {
+    let lower = body.to_lowercase();
+    let mut candidates: Vec<(usize, String)> = Vec::new();
+    let mut start = None;
+    for (i, c) in body.char_indices().chain(std::iter::once((body.len(), ' '))) {
+        match (c.is_ascii_digit(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                if (4..=8).contains(&(i - s)) {
+                    candidates.push((s, body[s..i].to_string()));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    // Whole words only, so "shopping" doesn't count as "pin"
+    let keyword = ["code", "pin", "passcode", "otp"]
+        .iter()
+        .flat_map(|k| lower.match_indices(k).map(|(i, _)| i))
+        .filter(|&i| !lower[..i].ends_with(|c: char| c.is_alphabetic()))
+        .min();
+    if let Some(at) = keyword {
+        if let Some((_, code)) = candidates.iter().find(|(pos, _)| *pos > at) {
+            return Some(code.clone());
+        }
+    }
+    match candidates.as_slice() {
+        [(_, only)] => Some(only.clone()),
+        _ => None,
+    }
+}
+
+/// The code from the newest message received since `since` that names the broker. A text
+/// that doesn't name it is only used when it's the only one, since with several it could
+/// be any service's code.
+pub fn pick_code(messages: &[SmsMessage], broker_name: &str, since: DateTime<Utc>) -> Option<String> {
+    let mut recent: Vec<&SmsMessage> = messages
+        .iter()
+        .filter(|m| m.received_at.is_none_or(|t| t >= since))
+        .collect();
+    recent.sort_by_key(|m| std::cmp::Reverse(m.received_at));
+    let name = broker_name.to_lowercase();
+    let named = recent
+        .iter()
+        .filter(|m| m.body.to_lowercase().contains(&name))
+        .find_map(|m| extract_code(&m.body));
+    match recent.as_slice() {
+        [only] if named.is_none() => extract_code(&only.body),
+        _ => named,
+    }
+}
+
+/// Messages the relay has received since `since`. The relay answers
+/// `GET {url}?since={RFC 3339 time}` with a JSON array of messages.
+pub async fn fetch_messages(url: &str, token: Option<&str>, since: DateTime<Utc>) -> Result<Vec<SmsMessage>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut request = client.get(url.trim()).query(&[("since", since.to_rfc3339())]);
+    if let Some(token) = token.filter(|t| !t.is_empty()) {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.map_err(|e| format!("SMS relay unreachable: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("SMS relay returned {}", response.status()));
+    }
+    response
+        .json::<Vec<SmsMessage>>()
+        .await
+        .map_err(|e| format!("SMS relay sent an unexpected response: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(body: &str, minutes_ago: i64) -> SmsMessage {
+        SmsMessage {
+            body: body.to_string(),
+            received_at: Some(Utc::now() - chrono::Duration::minutes(minutes_ago)),
+        }
+    }
+
+    #[test]
+    fn finds_the_code_in_common_texts() {
+        assert_eq!(extract_code("Your PeopleFinder code is 482913.").as_deref(), Some("482913"));
+        assert_eq!(extract_code("2024 alert: use code 7781 to verify").as_deref(), Some("7781"));
+        assert_eq!(extract_code("G-552019 is your verification number").as_deref(), Some("552019"));
+        assert_eq!(extract_code("Call 5551234567 about shopping order 1234 or 5678"), None);
+    }
+
+    #[test]
+    fn prefers_recent_texts_naming_the_broker() {
+        let since = Utc::now() - chrono::Duration::minutes(5);
+        let messages = vec![
+            message("Your PeopleFinder code is 111111", 10),
+            message("Your Spokeo code is 222222", 2),
+            message("Your bank code is 333333", 1),
+        ];
+        assert_eq!(pick_code(&messages, "Spokeo", since).as_deref(), Some("222222"));
+        // Either recent text could be Radaris's, and PeopleFinder's is too old
+        assert_eq!(pick_code(&messages, "Radaris", since), None);
+        assert_eq!(pick_code(&messages, "PeopleFinder", since), None);
+        // A lone text is taken even when it doesn't name the broker
+        assert_eq!(pick_code(&messages[2..], "Radaris", since).as_deref(), Some("333333"));
+    }
+}
@@ -0,0 +1,167 @@
+use crate::models::PlaybookStep;
+
+/// Opens a loop that runs the steps up to the matching `END_FOR_EACH_ACTION` once per
+/// search result matching the step's `selector` and `profile_key`.
+pub const FOR_EACH_ACTION: &str = "for_each_result";
+pub const END_FOR_EACH_ACTION: &str = "end_for_each";
+
+/// What the engine should run next.
+pub enum CursorItem<'a> {
+    Step(&'a PlaybookStep),
+    /// Start iteration `index` of a `for_each_result` loop: return to the results and
+    /// open the `index`-th matching listing.
+    LoopIteration { header: &'a PlaybookStep, index: u32 },
+}
+
+struct ActiveLoop {
+    header: usize,
+    index: u32,
+    count: u32,
+}
+
+/// Walks a playbook's steps, repeating `for_each_result` bodies. When a loop header is
+/// returned the engine counts the matches and calls `enter_loop`; without that call
+/// the body is skipped.
+pub struct StepCursor<'a> {
+    steps: &'a [PlaybookStep],
+    pc: usize,
+    /// Loop header most recently returned by `next`, until the following call
+    last_header: Option<usize>,
+    active: Option<ActiveLoop>,
+    pending_iteration: bool,
+}
+
+impl<'a> StepCursor<'a> {
+    pub fn new(steps: &'a [PlaybookStep]) -> Self {
+        Self {
+            steps,
+            pc: 0,
+            last_header: None,
+            active: None,
+            pending_iteration: false,
+        }
+    }
+
+    /// Run the body of the loop header just returned by `next` `count` times.
+    pub fn enter_loop(&mut self, count: u32) {
+        let Some(header) = self.last_header.take() else {
+            return;
+        };
+        if count == 0 {
+            return;
+        }
+        self.pc = header + 1;
+        self.active = Some(ActiveLoop { header, index: 0, count });
+        self.pending_iteration = true;
+    }
+
+    pub fn next(&mut self) -> Option<CursorItem<'a>> {
+        self.last_header = None;
+        if self.pending_iteration {
+            self.pending_iteration = false;
+            if let Some(active) = &self.active {
+                return Some(CursorItem::LoopIteration {
+                    header: &self.steps[active.header],
+                    index: active.index,
+                });
+            }
+        }
+
+        loop {
+            let step = self.steps.get(self.pc)?;
+            match step.action.as_str() {
+                FOR_EACH_ACTION => {
+                    // Jump past the body; `enter_loop` rewinds into it
+                    let header = self.pc;
+                    let end = self.steps[header + 1..]
+                        .iter()
+                        .position(|s| s.action == END_FOR_EACH_ACTION)
+                        .map(|i| header + 1 + i)
+                        .unwrap_or(self.steps.len());
+                    self.pc = end + 1;
+                    self.last_header = Some(header);
+                    return Some(CursorItem::Step(step));
+                }
+                END_FOR_EACH_ACTION => match &mut self.active {
+                    Some(active) if active.index + 1 < active.count => {
+                        active.index += 1;
+                        self.pc = active.header + 1;
+                        return Some(CursorItem::LoopIteration {
+                            header: &self.steps[active.header],
+                            index: active.index,
+                        });
+                    }
+                    _ => {
+                        self.active = None;
+                        self.pc += 1;
+                    }
+                },
+                _ => {
+                    self.pc += 1;
+                    return Some(CursorItem::Step(step));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(action: &str, position: u32) -> PlaybookStep {
+        PlaybookStep {
+            position,
+            action: action.to_string(),
+            selector: Some(".result".to_string()),
+            profile_key: Some("lastName".to_string()),
+            value: None,
+            description: format!("step {}", position),
+            instructions: None,
+            wait_after_ms: 0,
+            optional: false,
+            fallback_selectors: vec![],
+            phase: None,
+        }
+    }
+
+    /// Run the cursor, entering every loop with `count`, and describe what it yields.
+    fn trace(steps: &[PlaybookStep], count: u32) -> Vec<String> {
+        let mut cursor = StepCursor::new(steps);
+        let mut out = Vec::new();
+        while let Some(item) = cursor.next() {
+            match item {
+                CursorItem::Step(s) if s.action == FOR_EACH_ACTION => {
+                    out.push(format!("loop {}", s.position));
+                    cursor.enter_loop(count);
+                }
+                CursorItem::Step(s) => out.push(format!("{} {}", s.action, s.position)),
+                CursorItem::LoopIteration { index, .. } => out.push(format!("result {}", index)),
+            }
+        }
+        out
+    }
+
+    fn steps() -> Vec<PlaybookStep> {
+        vec![
+            step("fill", 1),
+            step(FOR_EACH_ACTION, 2),
+            step("click", 3),
+            step(END_FOR_EACH_ACTION, 4),
+            step("done", 5),
+        ]
+    }
+
+    #[test]
+    fn repeats_body_per_result() {
+        assert_eq!(
+            trace(&steps(), 2),
+            vec!["fill 1", "loop 2", "result 0", "click 3", "result 1", "click 3", "done 5"]
+        );
+    }
+
+    #[test]
+    fn skips_body_without_results() {
+        assert_eq!(trace(&steps(), 0), vec!["fill 1", "loop 2", "done 5"]);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::models::{PlaybookStep, RecordedAction};
+use crate::profile_format;
+
+/// Longest label or button text quoted in a description before it's cut short.
+const MAX_QUOTED_LEN: usize = 60;
+
+/// What a description can be built from. Recordings know the field's label and the
+/// clicked element's text; playbook steps only have what the selector gives away.
+struct Parts<'a> {
+    action: &'a str,
+    label: Option<String>,
+    profile_key: Option<&'a str>,
+    value: Option<&'a str>,
+    selector: Option<&'a str>,
+}
+
+/// A readable description of a recorded action, e.g. "Fill 'Email Address' with your email".
+pub fn describe_action(action: &RecordedAction) -> String {
+    // Marked manual steps and detected confirmations are labelled by the recorder itself
+    if let ("user_prompt" | "done", Some(label)) = (action.action.as_str(), action.label.as_deref()) {
+        return label.to_string();
+    }
+    let label = match action.action.as_str() {
+        "click" => action.element_text.as_deref().or(action.label.as_deref()),
+        _ => action.label.as_deref(),
+    };
+    describe(Parts {
+        action: &action.action,
+        label: label.map(quoted).or_else(|| action.selector.as_deref().and_then(selector_hint)),
+        profile_key: action.profile_key.as_deref(),
+        value: match action.action.as_str() {
+            "navigate" => action.url.as_deref().or(action.value.as_deref()),
+            // The option's label reads better than its value
+            "select" => action.element_text.as_deref().or(action.value.as_deref()),
+            _ => action.value.as_deref(),
+        },
+        selector: action.selector.as_deref(),
+    })
+}
+
+/// A readable description of a playbook step, worked out from its selector and profile field.
+pub fn describe_step(step: &PlaybookStep) -> String {
+    describe(Parts {
+        action: &step.action,
+        label: step.selector.as_deref().and_then(selector_hint),
+        profile_key: step.profile_key.as_deref(),
+        value: step.value.as_deref(),
+        selector: step.selector.as_deref(),
+    })
+}
+
+/// Whether a step's description says too little to follow, like "click", "#btn" or nothing.
+pub fn is_terse(step: &PlaybookStep) -> bool {
+    let description = step.description.trim();
+    description.split_whitespace().count() < 2
+        || description.eq_ignore_ascii_case(&step.action)
+        || description.eq_ignore_ascii_case(&step.action.replace('_', " "))
+        || step.selector.as_deref() == Some(description)
+}
+
+/// Replace terse descriptions with generated ones, leaving written ones alone. Only for
+/// display: a community playbook's signature covers the descriptions it shipped with.
+pub fn fill_terse_descriptions(steps: &mut [PlaybookStep]) {
+    for step in steps.iter_mut().filter(|s| is_terse(s) && s.action != "user_prompt") {
+        step.description = describe_step(step);
+    }
+}
+
+fn describe(parts: Parts) -> String {
+    let Parts { action, label, profile_key, value, selector } = parts;
+    let field = profile_key.map(profile_field_phrase);
+    match (action, label) {
+        ("navigate", _) => format!("Go to {}", value.unwrap_or("the opt-out page")),
+        ("fill", Some(label)) => match field {
+            Some(field) => format!("Fill {} with {}", label, field),
+            None => format!("Fill in {}", label),
+        },
+        ("fill", None) => format!("Fill in {}", field.as_deref().unwrap_or("the field")),
+        ("fill_credential", label) => format!(
+            "Fill {} with your account {}",
+            label.as_deref().unwrap_or("the login field"),
+            value.unwrap_or("login")
+        ),
+        ("fill_totp", label) => format!("Fill {} with your authenticator code", label.as_deref().unwrap_or("the code field")),
+        ("fill_sms_code", label) => format!("Fill {} with the texted code", label.as_deref().unwrap_or("the code field")),
+        ("select", label) => format!(
+            "Choose {} in {}",
+            field.or_else(|| value.map(quoted)).as_deref().unwrap_or("an option"),
+            label.as_deref().unwrap_or("the dropdown")
+        ),
+        ("check", label) => format!("Check {}", label.as_deref().unwrap_or("the checkbox")),
+        ("choose_radio", label) => format!(
+            "Choose {}{}",
+            field.or_else(|| value.map(quoted)).as_deref().unwrap_or("an option"),
+            label.map(|l| format!(" for {}", l)).unwrap_or_default()
+        ),
+        ("click", label) => format!("Click {}", label.as_deref().or(selector).unwrap_or("the button")),
+        ("find_and_click", _) => format!("Find and click {}", value.map(quoted).as_deref().unwrap_or("the matching link")),
+        ("scroll_to", label) => format!("Scroll to {}", label.as_deref().or(selector).unwrap_or("the element")),
+        ("captcha", _) => "Solve the CAPTCHA".to_string(),
+        ("user_prompt", _) => "Manual step".to_string(),
+        ("wait", _) => "Wait for the page to load".to_string(),
+        ("wait_for", label) => format!("Wait for {} to appear", label.as_deref().or(selector).unwrap_or("the element")),
+        ("done", _) => "Opt-out confirmed".to_string(),
+        ("for_each_result", _) => "Repeat for each matching listing".to_string(),
+        ("end_for_each", _) => "Move on to the next listing".to_string(),
+        (other, _) => other.replace('_', " "),
+    }
+}
+
+/// "your email", "your previous city" and so on for a profile key.
+pub fn profile_field_phrase(key: &str) -> String {
+    match profile_format::parse_indexed_key(key) {
+        Some(("previousAddress", _, name)) => format!("your previous {}", field_name(name)),
+        Some(("alternateEmail", _, _)) => "your other email".to_string(),
+        Some(("alternatePhone", _, _)) => "your other phone".to_string(),
+        _ => format!("your {}", field_name(key)),
+    }
+}
+
+pub fn field_name(key: &str) -> &str {
+    match key {
+        "firstName" => "first name",
+        "lastName" => "last name",
+        "fullName" => "full name",
+        "zip" => "ZIP code",
+        "dob" => "date of birth",
+        "legalBasis" => "privacy law",
+        "email" | "phone" | "address" | "city" | "state" => key,
+        _ => "details",
+    }
+}
+
+/// Quote text from the page, tidied and cut short if it runs long.
+fn quoted(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() > MAX_QUOTED_LEN {
+        let cut: String = text.chars().take(MAX_QUOTED_LEN).collect();
+        format!("'{}…'", cut.trim_end())
+    } else {
+        format!("'{}'", text)
+    }
+}
+
+/// A human name for the element a selector targets, from its label-like attributes or ID:
+/// `input[aria-label="Email Address"]` gives 'Email Address', `#first_name` 'first name'.
+fn selector_hint(selector: &str) -> Option<String> {
+    for attr in ["aria-label", "placeholder", "title", "name"] {
+        let Some(start) = selector.find(&format!("[{}=", attr)) else { continue };
+        let rest = &selector[start + attr.len() + 2..];
+        let value = rest.trim_start_matches(['"', '\'']);
+        let end = value.find(['"', '\'', ']']).unwrap_or(value.len());
+        if end > 0 {
+            return Some(quoted(&humanize(&value[..end])));
+        }
+    }
+    let id = selector.rsplit('#').next().filter(|_| selector.contains('#'))?;
+    let id: String = id.chars().take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '-').collect();
+    // Generated IDs ("input-3f9a2c") don't say anything about the field
+    if id.is_empty() || id.chars().filter(|c| c.is_ascii_digit()).count() > 2 {
+        return None;
+    }
+    Some(quoted(&humanize(&id)))
+}
+
+fn humanize(name: &str) -> String {
+    name.replace(['_', '-'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded(action: &str, selector: Option<&str>, label: Option<&str>) -> RecordedAction {
+        RecordedAction {
+            action: action.to_string(),
+            selector: selector.map(String::from),
+            selector_candidates: vec![],
+            profile_key: None,
+            match_confidence: None,
+            value: None,
+            url: None,
+            element_text: None,
+            label: label.map(String::from),
+            timestamp: 0,
+        }
+    }
+
+    fn step(action: &str, selector: Option<&str>, description: &str) -> PlaybookStep {
+        PlaybookStep {
+            position: 1,
+            action: action.to_string(),
+            selector: selector.map(String::from),
+            profile_key: None,
+            value: None,
+            description: description.to_string(),
+            instructions: None,
+            wait_after_ms: 500,
+            optional: false,
+            fallback_selectors: vec![],
+            phase: None,
+        }
+    }
+
+    #[test]
+    fn describes_recordings_from_labels_and_text() {
+        let mut fill = recorded("fill", Some("#f1"), Some("Email  Address"));
+        fill.profile_key = Some("email".to_string());
+        assert_eq!(describe_action(&fill), "Fill 'Email Address' with your email");
+
+        let mut click = recorded("click", Some("button.primary"), None);
+        click.element_text = Some("Submit Request".to_string());
+        assert_eq!(describe_action(&click), "Click 'Submit Request'");
+
+        let mut select = recorded("select", Some("select[name=\"state\"]"), None);
+        select.profile_key = Some("previousAddress[0].state".to_string());
+        assert_eq!(describe_action(&select), "Choose your previous state in 'state'");
+
+        let mut reason = recorded("select", Some("#reason"), Some("Reason for removal"));
+        reason.value = Some("opt_other".to_string());
+        reason.element_text = Some("Other".to_string());
+        assert_eq!(describe_action(&reason), "Choose 'Other' in 'Reason for removal'");
+
+        let mut radio = recorded("choose_radio", Some("input[type=\"radio\"][name=\"resident_state\"]"), None);
+        radio.value = Some("I am a California resident".to_string());
+        assert_eq!(describe_action(&radio), "Choose 'I am a California resident' for 'resident state'");
+    }
+
+    #[test]
+    fn fills_in_only_terse_descriptions() {
+        let mut fill = step("fill", Some("input[aria-label=\"First Name\"]"), "fill");
+        fill.profile_key = Some("firstName".to_string());
+        let mut steps = vec![
+            fill,
+            step("click", Some("#submit-request"), "#submit-request"),
+            step("click", Some("#btn-84213"), ""),
+            step("click", Some("#go"), "Click the blue Remove button"),
+        ];
+        fill_terse_descriptions(&mut steps);
+        let descriptions: Vec<_> = steps.iter().map(|s| s.description.as_str()).collect();
+        assert_eq!(
+            descriptions,
+            [
+                "Fill 'First Name' with your first name",
+                "Click 'submit request'",
+                "Click #btn-84213",
+                "Click the blue Remove button",
+            ]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use crate::models::{PhaseProgress, PlaybookStep, StepPhase};
+use crate::run_plan;
+use crate::step_cursor;
+
+/// How progress names a phase ("Phase 2 of 3: filling the form").
+pub fn label(phase: StepPhase) -> &'static str {
+    match phase {
+        StepPhase::Search => "searching for your listing",
+        StepPhase::FillForm => "filling the form",
+        StepPhase::Verify => "confirming the request",
+    }
+}
+
+/// Where each phase of a playbook starts, as (step position, phase), in order. Phases set
+/// on the steps win. Otherwise they're worked out from the steps: the search up to the step
+/// that picks the user's listing, the form up to the final submit, and whatever confirms it
+/// after. Empty when there's only one phase, which says nothing a step description doesn't.
+pub fn phase_starts(steps: &[PlaybookStep]) -> Vec<(u32, StepPhase)> {
+    let mut sorted: Vec<&PlaybookStep> = steps.iter().collect();
+    sorted.sort_by_key(|s| s.position);
+
+    let mut starts: Vec<(u32, StepPhase)> = Vec::new();
+    let mut push = |position: u32, phase: StepPhase| {
+        if starts.last().is_none_or(|(_, last)| *last != phase) {
+            starts.push((position, phase));
+        }
+    };
+    if sorted.iter().any(|s| s.phase.is_some()) {
+        for step in &sorted {
+            if let Some(phase) = step.phase {
+                push(step.position, phase);
+            }
+        }
+    } else if let Some(first) = sorted.first() {
+        let pick = sorted
+            .iter()
+            .find(|s| (s.action == "find_and_click" || s.action == step_cursor::FOR_EACH_ACTION) && s.profile_key.is_some())
+            .map(|s| s.position);
+        let submit = run_plan::submit_step_position(steps);
+        match pick {
+            Some(_) => push(first.position, StepPhase::Search),
+            None => push(first.position, StepPhase::FillForm),
+        }
+        if let Some(after_pick) = pick.and_then(|p| sorted.iter().find(|s| s.position > p)) {
+            push(after_pick.position, StepPhase::FillForm);
+        }
+        if let Some(after_submit) = submit.and_then(|p| sorted.iter().find(|s| s.position > p)) {
+            push(after_submit.position, StepPhase::Verify);
+        }
+    }
+    if starts.len() < 2 {
+        return Vec::new();
+    }
+    starts
+}
+
+/// The phase the step at `position` belongs to, counted among `starts`.
+pub fn progress_at(starts: &[(u32, StepPhase)], position: u32) -> Option<PhaseProgress> {
+    let index = starts.iter().rposition(|(start, _)| *start <= position)?;
+    Some(PhaseProgress {
+        phase: starts[index].1,
+        number: index as u32 + 1,
+        total: starts.len() as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(position: u32, action: &str, profile_key: Option<&str>) -> PlaybookStep {
+        PlaybookStep {
+            position,
+            action: action.to_string(),
+            selector: Some("#x".to_string()),
+            profile_key: profile_key.map(String::from),
+            value: None,
+            description: action.to_string(),
+            instructions: None,
+            wait_after_ms: 0,
+            optional: false,
+            fallback_selectors: vec![],
+            phase: None,
+        }
+    }
+
+    #[test]
+    fn works_out_phases_from_the_steps() {
+        let steps = vec![
+            step(1, "navigate", None),
+            step(2, "fill", Some("first_name")),
+            step(3, "find_and_click", Some("full_name")),
+            step(4, "fill", Some("email")),
+            step(5, "click", None),
+            step(6, "done", None),
+        ];
+        let starts = phase_starts(&steps);
+        assert_eq!(starts, [(1, StepPhase::Search), (4, StepPhase::FillForm), (6, StepPhase::Verify)]);
+        let progress = progress_at(&starts, 5).unwrap();
+        assert_eq!((progress.phase, progress.number, progress.total), (StepPhase::FillForm, 2, 3));
+
+        // A plain form with nothing after its submit is a single phase
+        let form = vec![step(1, "fill", Some("email")), step(2, "click", None)];
+        assert!(phase_starts(&form).is_empty());
+    }
+
+    #[test]
+    fn labelled_phases_win() {
+        let mut steps = vec![step(1, "navigate", None), step(2, "fill", Some("email")), step(3, "click", None)];
+        steps[0].phase = Some(StepPhase::FillForm);
+        steps[2].phase = Some(StepPhase::Verify);
+        let starts = phase_starts(&steps);
+        assert_eq!(starts, [(1, StepPhase::FillForm), (3, StepPhase::Verify)]);
+        assert_eq!(progress_at(&starts, 2).map(|p| p.number), Some(1));
+    }
+}
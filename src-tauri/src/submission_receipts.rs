@@ -0,0 +1,240 @@
+use crate::evidence;
+use crate::history;
+use crate::models::{EvidenceKind, ReceiptEvidence, ReceiptStore, SubmissionReceipt, SubmissionRecord};
+use crate::playbook_api;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const FILENAME: &str = "receipts.json";
+/// DER of the SHA-256 AlgorithmIdentifier (OID 2.16.840.1.101.3.4.2.1, NULL parameters).
+const SHA256_ALGORITHM: &[u8] = &[0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05, 0x00];
+
+fn store_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    use tauri::Manager;
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir.join(FILENAME))
+}
+
+pub fn load(app: &tauri::AppHandle) -> Result<ReceiptStore, String> {
+    let path = store_path(app)?;
+    if !path.exists() {
+        return Ok(ReceiptStore::default());
+    }
+    let data = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn save(app: &tauri::AppHandle, store: &ReceiptStore) -> Result<(), String> {
+    let path = store_path(app)?;
+    let data = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, data).map_err(|e| e.to_string())
+}
+
+/// Check that the time-stamp authority's URL is one the app can use.
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url.trim()).map_err(|e| format!("Invalid time-stamp authority URL: {}", e))?;
+    match parsed.scheme() {
+        "https" | "http" => Ok(()),
+        _ => Err("The time-stamp authority URL must start with https:// or http://.".to_string()),
+    }
+}
+
+fn kind_name(kind: &EvidenceKind) -> &'static str {
+    match kind {
+        EvidenceKind::Screenshot => "screenshot",
+        EvidenceKind::Mhtml => "mhtml",
+        EvidenceKind::Har => "har",
+        EvidenceKind::Email => "email",
+    }
+}
+
+/// The text a receipt signs and timestamps: what was submitted, when, and the hash of
+/// each piece of evidence. Anyone holding the evidence can rebuild it and check.
+pub fn statement(record: &SubmissionRecord, evidence: &[ReceiptEvidence]) -> String {
+    let mut text = format!(
+        "Opt-Outta submission receipt\nRecord: {}\nBroker: {}\nSubmitted: {}\n",
+        record.id,
+        record.broker_id,
+        record.submitted_at.to_rfc3339(),
+    );
+    for item in evidence {
+        text.push_str(&format!("Evidence: {} sha256:{}\n", kind_name(&item.kind), item.sha256));
+    }
+    text
+}
+
+/// An RFC 3161 TimeStampReq for a SHA-256 `digest`, asking for the authority's
+/// certificate in the reply.
+pub fn timestamp_request(digest: &[u8; 32], nonce: [u8; 8]) -> Vec<u8> {
+    let mut nonce = nonce;
+    // A positive INTEGER with no leading zero byte, as DER wants
+    nonce[0] = nonce[0] % 0x7f + 1;
+    let mut imprint = SHA256_ALGORITHM.to_vec();
+    imprint.extend([0x04, 0x20]);
+    imprint.extend(digest);
+
+    let mut body = vec![0x02, 0x01, 0x01];
+    body.extend([0x30, imprint.len() as u8]);
+    body.extend(imprint);
+    body.extend([0x02, 0x08]);
+    body.extend(nonce);
+    body.extend([0x01, 0x01, 0xff]);
+
+    let mut request = vec![0x30, body.len() as u8];
+    request.extend(body);
+    request
+}
+
+/// Tag, header length and content length of the DER value at the start of `bytes`.
+fn der_header(bytes: &[u8]) -> Option<(u8, usize, usize)> {
+    let (&tag, rest) = bytes.split_first()?;
+    let (&first, rest) = rest.split_first()?;
+    if first < 0x80 {
+        return Some((tag, 2, first as usize));
+    }
+    let n = (first & 0x7f) as usize;
+    if n == 0 || n > 4 || rest.len() < n {
+        return None;
+    }
+    let len = rest[..n].iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+    Some((tag, 2 + n, len))
+}
+
+/// Whether a TimeStampResp granted the request and carries a token.
+pub fn timestamp_granted(reply: &[u8]) -> bool {
+    let check = || -> Option<bool> {
+        let (tag, header, len) = der_header(reply)?;
+        let body = reply.get(header..header + len).filter(|_| tag == 0x30)?;
+        let (tag, info_header, info_len) = der_header(body)?;
+        let info = body.get(info_header..info_header + info_len).filter(|_| tag == 0x30)?;
+        // PKIStatus: 0 granted, 1 granted with modifications
+        let status = match info {
+            [0x02, 0x01, status, ..] => *status,
+            _ => return None,
+        };
+        Some(status <= 1 && body.len() > info_header + info_len)
+    };
+    check().unwrap_or(false)
+}
+
+/// Ask the time-stamp authority at `url` to stamp `digest`, returning its DER reply.
+async fn request_timestamp(url: &str, digest: &[u8; 32]) -> Result<Vec<u8>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .post(url.trim())
+        .header("Content-Type", "application/timestamp-query")
+        .body(timestamp_request(digest, rand::random()))
+        .send()
+        .await
+        .map_err(|e| format!("Time-stamp authority unreachable: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Time-stamp authority returned {}", response.status()));
+    }
+    let reply = response.bytes().await.map_err(|e| e.to_string())?.to_vec();
+    if !timestamp_granted(&reply) {
+        return Err("The time-stamp authority turned the request down".to_string());
+    }
+    Ok(reply)
+}
+
+/// Make a receipt for a submission from the evidence it has so far: hash each file,
+/// sign the statement with this device's key and, when `authority` is set, get it
+/// timestamped there. A receipt is still kept when signing or stamping fails, as the
+/// evidence hashes are worth having on their own.
+pub async fn create(app: &tauri::AppHandle, record_id: &str, authority: Option<&str>) -> Result<SubmissionReceipt, String> {
+    let record = history::load(app)?
+        .records
+        .into_iter()
+        .find(|r| r.id == record_id)
+        .ok_or("Submission not found")?;
+    let mut items = evidence::list_for_record(app, record_id)?;
+    // Network logs are for debugging, not proof
+    items.retain(|i| i.kind != EvidenceKind::Har);
+    items.sort_by_key(|i| i.captured_at);
+    let mut hashed = Vec::with_capacity(items.len());
+    for item in items {
+        let (_, bytes) = evidence::read(app, &item.id)?;
+        hashed.push(ReceiptEvidence {
+            evidence_id: item.id,
+            kind: item.kind,
+            sha256: format!("{:x}", Sha256::digest(&bytes)),
+        });
+    }
+
+    let statement = statement(&record, &hashed);
+    let digest: [u8; 32] = Sha256::digest(statement.as_bytes()).into();
+    let (signature, public_key) = match (playbook_api::sign_attestation(statement.as_bytes()), playbook_api::device_public_key()) {
+        (Ok(signature), Ok(key)) => (Some(signature), Some(key)),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("[receipts] Couldn't sign the receipt: {}", e);
+            (None, None)
+        }
+    };
+    let mut timestamp_token = None;
+    if let Some(url) = authority {
+        match request_timestamp(url, &digest).await {
+            Ok(reply) => timestamp_token = Some(STANDARD.encode(reply)),
+            Err(e) => eprintln!("[receipts] {}", e),
+        }
+    }
+
+    let receipt = SubmissionReceipt {
+        record_id: record.id.clone(),
+        broker_id: record.broker_id.clone(),
+        submitted_at: record.submitted_at,
+        evidence: hashed,
+        statement,
+        digest: digest.iter().map(|b| format!("{:02x}", b)).collect(),
+        signature,
+        public_key,
+        timestamp_authority: timestamp_token.as_ref().and(authority.map(String::from)),
+        timestamp_token,
+        created_at: Utc::now(),
+    };
+    let mut store = load(app)?;
+    store.receipts.retain(|r| r.record_id != receipt.record_id);
+    store.receipts.push(receipt.clone());
+    save(app, &store)?;
+    Ok(receipt)
+}
+
+pub fn get(app: &tauri::AppHandle, record_id: &str) -> Result<Option<SubmissionReceipt>, String> {
+    Ok(load(app)?.receipts.into_iter().find(|r| r.record_id == record_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_a_timestamp_request_for_the_digest() {
+        let digest = [7u8; 32];
+        let request = timestamp_request(&digest, [0xff; 8]);
+        assert_eq!(request.len(), 69);
+        assert_eq!(&request[..5], &[0x30, 0x43, 0x02, 0x01, 0x01]);
+        assert_eq!(&request[7..22], SHA256_ALGORITHM);
+        assert_eq!(&request[24..56], &digest);
+        // The nonce stays a positive INTEGER
+        assert_eq!(&request[56..59], &[0x02, 0x08, 0x02]);
+        assert_eq!(&request[66..], &[0x01, 0x01, 0xff]);
+    }
+
+    #[test]
+    fn reads_the_status_of_a_timestamp_reply() {
+        let granted = [0x30, 0x07, 0x30, 0x03, 0x02, 0x01, 0x00, 0x30, 0x00];
+        let rejected = [0x30, 0x05, 0x30, 0x03, 0x02, 0x01, 0x02];
+        let no_token = [0x30, 0x05, 0x30, 0x03, 0x02, 0x01, 0x00];
+        assert!(timestamp_granted(&granted));
+        assert!(!timestamp_granted(&rejected));
+        assert!(!timestamp_granted(&no_token));
+        assert!(!timestamp_granted(&[0x30]));
+    }
+}
@@ -0,0 +1,71 @@
+use crate::models::{Broker, BrokerSubmissionStatus, Profile, RequestType, SubmissionMethod, SubmissionRecord};
+use chrono::Utc;
+
+/// A people-search broker with a web form and nothing else set. Tests change what they
+/// need with struct update syntax.
+pub fn broker(id: &str) -> Broker {
+    Broker {
+        id: id.to_string(),
+        name: id.to_string(),
+        url: String::new(),
+        category: "people-search".to_string(),
+        method: "web-form".to_string(),
+        opt_out_url: String::new(),
+        known_fields: vec![],
+        notes: String::new(),
+        requires_verification: None,
+        relist_days: None,
+        difficulty: "easy".to_string(),
+        last_verified: String::new(),
+        network: None,
+        network_parent: false,
+        depends_on: vec![],
+        field_formats: Default::default(),
+        jurisdiction: None,
+        coverage_states: vec![],
+        countries: vec![],
+        automation: Default::default(),
+    }
+}
+
+/// An automated deletion request to `broker_id`, submitted just now.
+pub fn record(broker_id: &str, status: BrokerSubmissionStatus) -> SubmissionRecord {
+    SubmissionRecord {
+        id: "r".to_string(),
+        broker_id: broker_id.to_string(),
+        status,
+        submitted_at: Utc::now(),
+        confirmed_at: None,
+        next_check_date: None,
+        error_message: None,
+        run_id: "run".to_string(),
+        method: SubmissionMethod::Automated,
+        last_removal_check: None,
+        covered_by: None,
+        searched_with: None,
+        failure_reason: None,
+        outcome_check: None,
+        gpc_sent: false,
+        request_type: RequestType::Deletion,
+        failed_step: None,
+    }
+}
+
+/// A complete, valid US profile without alternate contacts or previous addresses.
+pub fn profile() -> Profile {
+    Profile {
+        first_name: "Jane".to_string(),
+        last_name: "Doe".to_string(),
+        email: "jane@example.com".to_string(),
+        phone: "(555) 123-4567".to_string(),
+        address: "1 Main St".to_string(),
+        city: "Springfield".to_string(),
+        state: "IL".to_string(),
+        zip: "62701".to_string(),
+        dob: "1985-04-12".to_string(),
+        alternate_emails: vec![],
+        alternate_phones: vec![],
+        previous_addresses: vec![],
+        country: "US".to_string(),
+    }
+}
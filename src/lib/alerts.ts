@@ -0,0 +1,29 @@
+import { getCurrentWindow, UserAttentionType } from "@tauri-apps/api/window";
+
+let audioContext: AudioContext | null = null;
+
+/** Play a short two-tone chime. Generated in code so no sound file is bundled. */
+export function playAlertSound() {
+  audioContext ??= new AudioContext();
+  const ctx = audioContext;
+  [880, 660].forEach((frequency, i) => {
+    const start = ctx.currentTime + i * 0.25;
+    const osc = ctx.createOscillator();
+    const gain = ctx.createGain();
+    osc.frequency.value = frequency;
+    gain.gain.setValueAtTime(0.2, start);
+    gain.gain.exponentialRampToValueAtTime(0.001, start + 0.22);
+    osc.connect(gain).connect(ctx.destination);
+    osc.start(start);
+    osc.stop(start + 0.22);
+  });
+}
+
+/** Flash the taskbar/dock icon until the app window is focused. */
+export async function requestAttention() {
+  try {
+    await getCurrentWindow().requestUserAttention(UserAttentionType.Critical);
+  } catch {
+    // Not supported on every platform
+  }
+}
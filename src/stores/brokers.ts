@@ -1,17 +1,26 @@
 import { defineStore } from "pinia";
 import { ref, computed } from "vue";
 import { invoke } from "@tauri-apps/api/core";
-import type { Broker, BrokerRegistry } from "../types";
+import type { Broker, BrokerCategory, BrokerCredential, BrokerRegistry, BrokerDifficulty, LikelihoodAssessment, LocalDifficulty, RequestEmail, RequestType } from "../types";
 
 export const useBrokersStore = defineStore("brokers", () => {
   const brokers = ref<Broker[]>([]);
+  const categoryGroups = ref<BrokerCategory[]>([]);
   const version = ref("");
   const loading = ref(false);
   const searchQuery = ref("");
   const categoryFilter = ref<string | null>(null);
+  // Brokers that don't take requests from the user's country, hidden unless asked for
+  const outOfRegion = ref<string[]>([]);
+  const showOutOfRegion = ref(false);
+  // Difficulty recalibrated from this device's runs, for brokers run often enough
+  const localDifficulty = ref<LocalDifficulty[]>([]);
 
   const filteredBrokers = computed(() => {
     let result = brokers.value;
+    if (!showOutOfRegion.value) {
+      result = result.filter((b) => !outOfRegion.value.includes(b.id));
+    }
     if (categoryFilter.value) {
       result = result.filter((b) => b.category === categoryFilter.value);
     }
@@ -22,16 +31,44 @@ export const useBrokersStore = defineStore("brokers", () => {
     return result;
   });
 
-  const categories = computed(() => {
-    const cats = new Set(brokers.value.map((b) => b.category));
-    return Array.from(cats).sort();
-  });
+  const categories = computed(() => categoryGroups.value.map((c) => c.id));
+
+  function brokersInCategory(category: string): Broker[] {
+    const ids = categoryGroups.value.find((c) => c.id === category)?.broker_ids ?? [];
+    return brokers.value.filter((b) => ids.includes(b.id) && !outOfRegion.value.includes(b.id));
+  }
+
+  function isOutOfRegion(brokerId: string): boolean {
+    return outOfRegion.value.includes(brokerId);
+  }
+
+  function localDifficultyFor(brokerId: string): LocalDifficulty | null {
+    return localDifficulty.value.find((d) => d.broker_id === brokerId) ?? null;
+  }
+
+  /** The broker's difficulty from local runs when there are enough, else the registry's. */
+  function difficultyFor(broker: Broker): BrokerDifficulty {
+    return localDifficultyFor(broker.id)?.difficulty ?? broker.difficulty;
+  }
+
+  /** Why the shown difficulty differs from the registry's, for a tooltip. */
+  function difficultyNote(broker: Broker): string {
+    const local = localDifficultyFor(broker.id);
+    if (!local) return `Registry difficulty: ${broker.difficulty}`;
+    const parts = [`${Math.round(local.failure_rate * 100)}% failed`];
+    if (local.average_secs !== null) parts.push(`about ${Math.max(1, Math.round(local.average_secs / 60))} min each`);
+    parts.push(`${local.captchas_per_attempt.toFixed(1)} CAPTCHAs per run`);
+    return `From your last ${local.attempts} runs: ${parts.join(", ")} (registry says ${broker.difficulty})`;
+  }
 
   async function loadBrokers() {
     loading.value = true;
     try {
       const registry = await invoke<BrokerRegistry>("get_brokers");
       brokers.value = registry.brokers;
+      categoryGroups.value = registry.categories;
+      outOfRegion.value = registry.out_of_region;
+      localDifficulty.value = registry.local_difficulty ?? [];
       version.value = registry.version;
     } catch (e) {
       console.error("Failed to load brokers:", e);
@@ -48,6 +85,9 @@ export const useBrokersStore = defineStore("brokers", () => {
       if (updated) {
         const registry = await invoke<BrokerRegistry>("get_brokers");
         brokers.value = registry.brokers;
+        categoryGroups.value = registry.categories;
+        outOfRegion.value = registry.out_of_region;
+        localDifficulty.value = registry.local_difficulty ?? [];
         version.value = registry.version;
       }
     } catch (e) {
@@ -55,15 +95,54 @@ export const useBrokersStore = defineStore("brokers", () => {
     }
   }
 
+  async function getRequestEmail(brokerId: string, requestType: RequestType) {
+    return invoke<RequestEmail>("get_request_email", { brokerId, requestType });
+  }
+
+  async function assessLikelihood() {
+    return invoke<LikelihoodAssessment>("assess_broker_likelihood");
+  }
+
+  async function getCredential(brokerId: string) {
+    return invoke<BrokerCredential | null>("get_broker_credential", { brokerId });
+  }
+
+  /** Save a broker login. Leave `password` empty to generate a strong one. */
+  async function saveCredential(brokerId: string, username: string, password: string | null) {
+    return invoke<BrokerCredential>("save_broker_credential", { brokerId, username, password });
+  }
+
+  /** Save an authenticator setup key or otpauth:// link for a broker login, or clear it with null. */
+  async function setTotp(brokerId: string, secret: string | null) {
+    return invoke<BrokerCredential>("set_broker_totp", { brokerId, secret });
+  }
+
+  async function deleteCredential(brokerId: string) {
+    await invoke("delete_broker_credential", { brokerId });
+  }
+
   return {
     brokers,
     version,
     loading,
     searchQuery,
     categoryFilter,
+    outOfRegion,
+    showOutOfRegion,
     filteredBrokers,
     categories,
+    brokersInCategory,
+    isOutOfRegion,
+    localDifficultyFor,
+    difficultyFor,
+    difficultyNote,
     loadBrokers,
     syncRegistry,
+    getRequestEmail,
+    assessLikelihood,
+    getCredential,
+    saveCredential,
+    setTotp,
+    deleteCredential,
   };
 });
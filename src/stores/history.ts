@@ -1,12 +1,18 @@
 import { defineStore } from "pinia";
 import { ref, computed } from "vue";
 import { invoke } from "@tauri-apps/api/core";
-import type { SubmissionRecord, BrokerStatus } from "../types";
+import type { SubmissionRecord, SubmissionMethod, BrokerStatus, RequestDeadline, RequestType, ComplaintPackage, RelistingAlert, BrokerResponse } from "../types";
 
 export const useHistoryStore = defineStore("history", () => {
   const submissions = ref<SubmissionRecord[]>([]);
   const latestPerBroker = ref<SubmissionRecord[]>([]);
-  const relistAlerts = ref<SubmissionRecord[]>([]);
+  const relistAlerts = ref<RelistingAlert[]>([]);
+  // Latest submissions whose relist check date has come
+  const dueRechecks = ref<SubmissionRecord[]>([]);
+  const deadlines = ref<RequestDeadline[]>([]);
+  // Failed attempts that kept a network log
+  const networkLogRecords = ref<string[]>([]);
+  const receiptRecords = ref<string[]>([]);
   const loading = ref(false);
 
   const confirmedCount = computed(
@@ -24,19 +30,40 @@ export const useHistoryStore = defineStore("history", () => {
     () => latestPerBroker.value.filter((r) => r.status === "failed").length
   );
 
+  // Brokers whose last search found no listing
+  const notFoundCount = computed(
+    () => latestPerBroker.value.filter((r) => r.status === "not_found").length
+  );
+
   const relistCount = computed(() => relistAlerts.value.length);
 
+  const overdueRequests = computed(() =>
+    deadlines.value.filter((d) => d.status !== "on_track")
+  );
+
+  function deadlineFor(recordId: string): RequestDeadline | undefined {
+    return deadlines.value.find((d) => d.record_id === recordId);
+  }
+
   async function loadHistory() {
     loading.value = true;
     try {
-      const [allSubs, latest, alerts] = await Promise.all([
+      const [allSubs, latest, alerts, rechecks, due, logs, receipts] = await Promise.all([
         invoke<SubmissionRecord[]>("get_submissions"),
         invoke<SubmissionRecord[]>("get_latest_submissions"),
-        invoke<SubmissionRecord[]>("get_relisting_alerts"),
+        invoke<RelistingAlert[]>("get_relisting_alerts"),
+        invoke<SubmissionRecord[]>("get_due_rechecks"),
+        invoke<RequestDeadline[]>("get_request_deadlines"),
+        invoke<string[]>("get_network_log_records"),
+        invoke<string[]>("get_receipt_records"),
       ]);
       submissions.value = allSubs;
       latestPerBroker.value = latest;
       relistAlerts.value = alerts;
+      dueRechecks.value = rechecks;
+      deadlines.value = due;
+      networkLogRecords.value = logs;
+      receiptRecords.value = receipts;
     } catch (e) {
       console.error("Failed to load history:", e);
     } finally {
@@ -62,27 +89,124 @@ export const useHistoryStore = defineStore("history", () => {
         return "failed" as BrokerStatus;
       case "re_listed":
         return "re_listed" as BrokerStatus;
+      case "not_found":
+        return "not_found" as BrokerStatus;
       default:
         return "not_started" as BrokerStatus;
     }
   }
 
+  async function recordManualSubmission(
+    brokerId: string,
+    method: SubmissionMethod,
+    requestType: RequestType = "deletion"
+  ) {
+    const record = await invoke<SubmissionRecord>("record_manual_submission", {
+      brokerId,
+      method,
+      requestType,
+    });
+    await loadHistory();
+    return record;
+  }
+
+  async function buildComplaintPackage(recordId: string, toFtc = false) {
+    return invoke<ComplaintPackage>("build_complaint_package", { recordId, toFtc });
+  }
+
   async function confirmSubmission(id: string) {
     await invoke("update_submission_status", { id, status: "confirmed" });
     await loadHistory();
   }
 
+  /** Returns how many submissions were updated. */
+  async function bulkUpdateStatus(ids: string[], status: string) {
+    const count = await invoke<number>("bulk_update_submission_status", { ids, status });
+    await loadHistory();
+    return count;
+  }
+
+  /** `date` is YYYY-MM-DD, or null to clear the check date. */
+  async function bulkSetNextCheckDate(ids: string[], date: string | null) {
+    const nextCheckDate = date ? new Date(`${date}T00:00:00`).toISOString() : null;
+    const count = await invoke<number>("bulk_set_next_check_date", { ids, nextCheckDate });
+    await loadHistory();
+    return count;
+  }
+
+  async function snoozeRelistAlert(id: string, days: number) {
+    await invoke("snooze_relisting_alert", { id, days });
+    relistAlerts.value = relistAlerts.value.filter((a) => a.id !== id);
+  }
+
+  async function dismissRelistAlert(id: string) {
+    await invoke("dismiss_relisting_alert", { id });
+    relistAlerts.value = relistAlerts.value.filter((a) => a.id !== id);
+  }
+
+  /** Imports each .eml file in turn, so one unreadable file doesn't stop the rest. */
+  async function importBrokerEmails(files: File[]) {
+    const results: { fileName: string; response?: BrokerResponse; error?: string }[] = [];
+    for (const file of files) {
+      try {
+        const response = await invoke<BrokerResponse>("import_broker_email", {
+          fileName: file.name,
+          content: await file.text(),
+        });
+        results.push({ fileName: file.name, response });
+      } catch (e) {
+        results.push({ fileName: file.name, error: String(e) });
+      }
+    }
+    await loadHistory();
+    return results;
+  }
+
+  function hasNetworkLog(recordId: string): boolean {
+    return networkLogRecords.value.includes(recordId);
+  }
+
+  /** Writes the attempt's network log to Downloads and returns the file's path. */
+  async function saveNetworkLog(recordId: string) {
+    return invoke<string>("save_network_log", { recordId });
+  }
+
+  function hasReceipt(recordId: string): boolean {
+    return receiptRecords.value.includes(recordId);
+  }
+
+  /** Writes the submission's receipt to a folder in Downloads and returns the folder's path. */
+  async function saveReceipt(recordId: string) {
+    return invoke<string>("save_submission_receipt", { recordId });
+  }
+
   return {
     submissions,
     latestPerBroker,
     relistAlerts,
+    dueRechecks,
+    deadlines,
+    overdueRequests,
     loading,
     confirmedCount,
     pendingCount,
     failedCount,
+    notFoundCount,
     relistCount,
     loadHistory,
     getStatusForBroker,
+    deadlineFor,
     confirmSubmission,
+    bulkUpdateStatus,
+    bulkSetNextCheckDate,
+    recordManualSubmission,
+    buildComplaintPackage,
+    snoozeRelistAlert,
+    dismissRelistAlert,
+    importBrokerEmails,
+    hasNetworkLog,
+    saveNetworkLog,
+    hasReceipt,
+    saveReceipt,
   };
 });
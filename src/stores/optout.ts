@@ -1,12 +1,23 @@
 import { defineStore } from "pinia";
-import { ref, computed } from "vue";
+import { ref, computed, watch } from "vue";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { toast } from "vue-sonner";
+import { playAlertSound, requestAttention } from "../lib/alerts";
 import type {
+  AppSettings,
+  BrokerRunSummary,
+  ChromeStatus,
+  FailureClass,
   RunStatus,
   OptOutProgress,
   OptOutComplete,
+  PhaseProgress,
+  RunOptions,
+  RunPreset,
+  StepPhase,
+  PreflightReport,
+  RunPlan,
   UserActionRequired,
 } from "../types";
 
@@ -14,8 +25,39 @@ export interface BrokerOutcome {
   brokerId: string;
   brokerName: string;
   succeeded: boolean;
+  skipped: boolean;
+  // The search found no listing, so there was nothing to submit
+  notFound: boolean;
+  cancelled: boolean;
   lastStep: string;
   error: string | null;
+  // From the run summary; null until the run completes
+  durationMs: number | null;
+  failureClass: FailureClass | null;
+  failureStep: number | null;
+  failurePhase: StepPhase | null;
+  playbookVersion: number | null;
+}
+
+export const STEP_PHASE_LABELS: Record<StepPhase, string> = {
+  search: "searching for your listing",
+  fill_form: "filling the form",
+  verify: "confirming the request",
+};
+
+export const FAILURE_CLASS_LABELS: Record<FailureClass, string> = {
+  selector: "Page changed",
+  captcha_timeout: "CAPTCHA not solved",
+  blocked: "Blocked by broker",
+  network: "Network error",
+  rejected: "Form rejected",
+  no_playbook: "No playbook",
+  other: "Error",
+};
+
+export function formatDuration(ms: number): string {
+  const seconds = Math.round(ms / 1000);
+  return seconds < 60 ? `${seconds}s` : `${Math.floor(seconds / 60)}m ${seconds % 60}s`;
 }
 
 export const useOptOutStore = defineStore("optout", () => {
@@ -24,16 +66,19 @@ export const useOptOutStore = defineStore("optout", () => {
   const currentBrokerId = ref<string | null>(null);
   const currentBrokerName = ref<string | null>(null);
   const currentStep = ref("");
+  const currentPhase = ref<PhaseProgress | null>(null);
   const brokersCompleted = ref(0);
   const brokersTotal = ref(0);
   const actionRequired = ref<UserActionRequired | null>(null);
   const error = ref<string | null>(null);
   const chromeInstalled = ref<boolean | null>(null);
+  const chromeStatus = ref<ChromeStatus | null>(null);
   const lastResult = ref<OptOutComplete | null>(null);
 
   // Per-broker tracking during a run
   const brokerProgress = ref<Map<string, { name: string; step: string; error: string | null }>>(new Map());
   const brokerOutcomes = ref<BrokerOutcome[]>([]);
+  const presets = ref<RunPreset[]>([]);
 
   const isActive = computed(
     () => status.value === "running" || status.value === "waiting_for_user"
@@ -47,19 +92,83 @@ export const useOptOutStore = defineStore("optout", () => {
   let listenersSetup = false;
   let prevCompleted = 0;
 
+  // Alert policy for prompts that sit unanswered, read from settings at run start
+  let alertSettings: Pick<AppSettings, "alert_sound" | "alert_repeat_minutes"> = {
+    alert_sound: false,
+    alert_repeat_minutes: 0,
+  };
+  let alertTimer: ReturnType<typeof setInterval> | null = null;
+
+  function alertUser() {
+    if (alertSettings.alert_sound) playAlertSound();
+    requestAttention();
+  }
+
+  function stopAlerts() {
+    if (alertTimer) clearInterval(alertTimer);
+    alertTimer = null;
+  }
+
+  watch(actionRequired, (action, previous) => {
+    if (!action) {
+      stopAlerts();
+      return;
+    }
+    if (previous) return;
+    alertUser();
+    const minutes = alertSettings.alert_repeat_minutes;
+    if (minutes > 0) {
+      alertTimer = setInterval(() => {
+        alertUser();
+        toast.warning("Still waiting for you", {
+          description: `${currentBrokerName.value ?? "A broker"} needs your input to continue the run.`,
+        });
+      }, minutes * 60_000);
+    }
+  });
+
   function finalizeBroker(brokerId: string) {
     const entry = brokerProgress.value.get(brokerId);
     if (!entry) return;
-    const succeeded = entry.step === "Opt-out submitted" && !entry.error;
+    const succeeded =
+      (entry.step === "Opt-out submitted" || entry.step === "Covered by network opt-out") && !entry.error;
+    const skipped =
+      entry.step === "Recently submitted — skipped" || entry.step === "Not submitted — held back at review";
     brokerOutcomes.value.push({
       brokerId,
       brokerName: entry.name,
       succeeded,
+      skipped,
+      notFound: entry.step === "No listing found" && !entry.error,
+      cancelled: entry.step === "Cancelled",
       lastStep: entry.step,
       error: entry.error,
+      durationMs: null,
+      failureClass: null,
+      failureStep: null,
+      failurePhase: null,
+      playbookVersion: null,
     });
   }
 
+  function outcomeFromSummary(summary: BrokerRunSummary): BrokerOutcome {
+    return {
+      brokerId: summary.broker_id,
+      brokerName: summary.broker_name,
+      succeeded: summary.outcome === "succeeded" || summary.outcome === "covered",
+      skipped: summary.outcome === "skipped",
+      notFound: summary.outcome === "not_found",
+      cancelled: summary.outcome === "cancelled",
+      lastStep: brokerProgress.value.get(summary.broker_id)?.step ?? "",
+      error: summary.error,
+      durationMs: summary.duration_ms,
+      failureClass: summary.failure_class,
+      failureStep: summary.failure_step,
+      failurePhase: summary.failure_phase,
+      playbookVersion: summary.playbook_version,
+    };
+  }
+
   async function setupListeners() {
     if (listenersSetup) return;
     listenersSetup = true;
@@ -71,6 +180,7 @@ export const useOptOutStore = defineStore("optout", () => {
       currentBrokerId.value = p.broker_id;
       currentBrokerName.value = p.broker_name;
       currentStep.value = p.current_step;
+      currentPhase.value = p.phase;
       brokersTotal.value = p.brokers_total;
       actionRequired.value = p.action_required;
       error.value = p.error;
@@ -95,19 +205,14 @@ export const useOptOutStore = defineStore("optout", () => {
       brokersCompleted.value = p.brokers_completed;
     });
 
-    await listen<OptOutComplete>("opt-out-complete", (event) => {
-      lastResult.value = event.payload;
-      status.value = "completed";
+    // The global continue shortcut answered the prompt outside the app
+    await listen("user-action-resolved", () => {
       actionRequired.value = null;
+      status.value = "running";
+    });
 
-      // Finalize any remaining broker that didn't get a completed event
-      if (brokerOutcomes.value.length < brokerProgress.value.size) {
-        for (const [id] of brokerProgress.value) {
-          if (!brokerOutcomes.value.find((o) => o.brokerId === id)) {
-            finalizeBroker(id);
-          }
-        }
-      }
+    await listen<OptOutComplete>("opt-out-complete", (event) => {
+      applySummary(event.payload, "completed");
 
       // Show summary toast
       const r = event.payload;
@@ -115,37 +220,78 @@ export const useOptOutStore = defineStore("optout", () => {
         toast.error("Run failed", { description: `${r.failed} of ${r.total} brokers failed` });
       } else if (r.failed > 0) {
         toast.warning("Run completed with errors", { description: `${r.succeeded} succeeded, ${r.failed} failed` });
+      } else if (r.skipped > 0 || r.not_found > 0) {
+        const parts = [`${r.succeeded} succeeded`];
+        if (r.not_found > 0) parts.push(`${r.not_found} not listed`);
+        if (r.skipped > 0) parts.push(`${r.skipped} skipped`);
+        toast.success("Run complete", { description: parts.join(", ") });
       } else {
         toast.success("Run complete", { description: `${r.succeeded} of ${r.total} brokers succeeded` });
       }
 
-      // Reload history so dashboard/brokers views reflect new data
-      import("./history").then(({ useHistoryStore }) => {
-        useHistoryStore().loadHistory();
+      reloadHistory();
+    });
+
+    await listen<OptOutComplete>("opt-out-cancelled", (event) => {
+      applySummary(event.payload, "cancelled");
+      const r = event.payload;
+      toast.info("Run cancelled", {
+        description: `${r.succeeded} submitted before stopping, ${r.total - r.brokers.length} not started`,
       });
+      reloadHistory();
+    });
+  }
+
+  function applySummary(summary: OptOutComplete, finalStatus: RunStatus) {
+    lastResult.value = summary;
+    status.value = finalStatus;
+    actionRequired.value = null;
+
+    // The summary is authoritative; progress events are the fallback
+    if (summary.brokers.length > 0) {
+      brokerOutcomes.value = summary.brokers.map(outcomeFromSummary);
+    } else if (brokerOutcomes.value.length < brokerProgress.value.size) {
+      // Finalize any remaining broker that didn't get a completed event
+      for (const [id] of brokerProgress.value) {
+        if (!brokerOutcomes.value.find((o) => o.brokerId === id)) {
+          finalizeBroker(id);
+        }
+      }
+    }
+  }
+
+  // Reload history so dashboard/brokers views reflect new data
+  function reloadHistory() {
+    import("./history").then(({ useHistoryStore }) => {
+      useHistoryStore().loadHistory();
     });
   }
 
   async function checkChromeInstalled(): Promise<boolean> {
-    const installed = await invoke<boolean>("check_chrome_installed");
-    chromeInstalled.value = installed;
-    return installed;
+    const status = await invoke<ChromeStatus>("check_chrome_installed");
+    chromeStatus.value = status;
+    chromeInstalled.value = status.installed;
+    return status.installed;
   }
 
+  // `categories` adds every broker in them; ones without a selection use "best"
   async function startRun(
     brokerIds: string[],
-    playbookSelections?: Record<string, string>
+    playbookSelections?: Record<string, string>,
+    force = false,
+    options?: RunOptions,
+    categories?: string[],
+    everywhere = false
   ) {
-    await setupListeners();
-    error.value = null;
-    lastResult.value = null;
-    brokerProgress.value = new Map();
-    brokerOutcomes.value = [];
-    prevCompleted = 0;
+    await prepareRun();
     try {
       const id = await invoke<string>("start_opt_out_run", {
         brokerIds,
         playbookSelections: playbookSelections ?? null,
+        force,
+        options: options ?? null,
+        categories: categories ?? null,
+        everywhere,
       });
       runId.value = id;
       status.value = "running";
@@ -158,6 +304,74 @@ export const useOptOutStore = defineStore("optout", () => {
     }
   }
 
+  async function prepareRun() {
+    await setupListeners();
+    error.value = null;
+    lastResult.value = null;
+    brokerProgress.value = new Map();
+    brokerOutcomes.value = [];
+    prevCompleted = 0;
+    try {
+      alertSettings = await invoke<AppSettings>("get_settings");
+    } catch {
+      // Keep the previous alert settings
+    }
+  }
+
+  // Re-run one failed broker from the step it failed at
+  async function retryFromStep(recordId: string) {
+    await prepareRun();
+    try {
+      runId.value = await invoke<string>("retry_broker_from_step", { recordId });
+      status.value = "running";
+      brokersCompleted.value = 0;
+      brokersTotal.value = 1;
+    } catch (e) {
+      const msg = e instanceof Error ? e.message : String(e);
+      toast.error("Failed to retry", { description: msg });
+      throw e;
+    }
+  }
+
+  // Opt out again from a broker that relisted the user; the alert closes once it starts
+  async function rerunRelisted(alertId: string) {
+    await prepareRun();
+    try {
+      runId.value = await invoke<string>("rerun_relisted_broker", { alertId });
+      status.value = "running";
+      brokersCompleted.value = 0;
+      brokersTotal.value = 1;
+    } catch (e) {
+      const msg = e instanceof Error ? e.message : String(e);
+      toast.error("Failed to start opt-out run", { description: msg });
+      throw e;
+    }
+  }
+
+  async function previewRunPlan(
+    brokerIds: string[],
+    playbookSelections?: Record<string, string>,
+    categories?: string[]
+  ): Promise<RunPlan> {
+    return invoke<RunPlan>("preview_run_plan", {
+      brokerIds,
+      playbookSelections: playbookSelections ?? null,
+      categories: categories ?? null,
+    });
+  }
+
+  async function preflightCheck(
+    brokerIds: string[],
+    playbookSelections?: Record<string, string>,
+    categories?: string[]
+  ): Promise<PreflightReport> {
+    return invoke<PreflightReport>("preflight_check", {
+      brokerIds,
+      playbookSelections: playbookSelections ?? null,
+      categories: categories ?? null,
+    });
+  }
+
   async function continueAfterUserAction(response?: string) {
     await invoke("continue_opt_out", { response: response ?? null });
     actionRequired.value = null;
@@ -176,9 +390,87 @@ export const useOptOutStore = defineStore("optout", () => {
     await continueAfterUserAction("abort");
   }
 
+  async function chooseListing(index: number) {
+    await continueAfterUserAction(`listing:${index}`);
+  }
+
+  async function skipListing() {
+    await continueAfterUserAction("skip");
+  }
+
+  async function holdBackSubmission() {
+    await continueAfterUserAction("skip");
+  }
+
+  async function approveSelectorRepair() {
+    await continueAfterUserAction("approve");
+  }
+
+  async function rejectSelectorRepair() {
+    await continueAfterUserAction("reject");
+  }
+
+  async function confirmFieldMatch() {
+    await continueAfterUserAction("continue");
+  }
+
+  async function rejectFieldMatch() {
+    await continueAfterUserAction("reject");
+  }
+
+  async function approveFill() {
+    await continueAfterUserAction("continue");
+  }
+
+  async function skipFill() {
+    await continueAfterUserAction("skip");
+  }
+
+  async function fillManualValue(value: string) {
+    await continueAfterUserAction(`value:${value}`);
+  }
+
+  // The app clears it from the clipboard again after 30 seconds
+  async function copyProfileValue(profileKey: string) {
+    await invoke("copy_profile_value", { profileKey });
+  }
+
+  async function loadPresets() {
+    try {
+      presets.value = await invoke<RunPreset[]>("get_run_presets");
+    } catch (e) {
+      console.error("Failed to load run presets:", e);
+    }
+  }
+
+  async function savePreset(preset: RunPreset) {
+    await invoke<RunPreset>("save_run_preset", { preset });
+    await loadPresets();
+  }
+
+  async function deletePreset(name: string) {
+    await invoke("delete_run_preset", { name });
+    await loadPresets();
+  }
+
+  /** Run every broker with an approved playbook, hands-free ones first. */
+  async function startEverywhere(options?: RunOptions) {
+    await startRun([], undefined, false, options, undefined, true);
+  }
+
+  async function startPreset(preset: RunPreset) {
+    await startRun(
+      preset.broker_ids,
+      preset.playbook_selections,
+      preset.force,
+      preset.options,
+      preset.categories.length > 0 ? preset.categories : undefined
+    );
+  }
+
+  // The run stops and closes the browser by itself, then sends "opt-out-cancelled"
   async function cancelRun() {
     await invoke("cancel_opt_out");
-    status.value = "failed";
     actionRequired.value = null;
   }
 
@@ -190,17 +482,32 @@ export const useOptOutStore = defineStore("optout", () => {
       `Date: ${date}`,
       `Run ID: ${runId.value}`,
       "",
-      `Results: ${result?.succeeded ?? 0} succeeded, ${result?.failed ?? 0} failed out of ${result?.total ?? 0} brokers`,
+      `Results: ${result?.succeeded ?? 0} succeeded, ${result?.failed ?? 0} failed, ${result?.skipped ?? 0} skipped${result?.not_found ? `, ${result.not_found} not listed` : ""}${result?.cancelled ? `, ${result.cancelled} cancelled` : ""} out of ${result?.total ?? 0} brokers`,
       "",
       "Details:",
       "─".repeat(50),
     ];
 
     for (const outcome of brokerOutcomes.value) {
-      const icon = outcome.succeeded ? "[OK]" : "[FAIL]";
-      lines.push(`${icon}  ${outcome.brokerName}`);
+      const icon = outcome.succeeded
+        ? "[OK]"
+        : outcome.skipped
+          ? "[SKIP]"
+          : outcome.notFound
+            ? "[NOT LISTED]"
+            : outcome.cancelled
+            ? "[CANCELLED]"
+            : "[FAIL]";
+      const duration = outcome.durationMs !== null ? ` (${formatDuration(outcome.durationMs)})` : "";
+      lines.push(`${icon}  ${outcome.brokerName}${duration}`);
+      if (outcome.playbookVersion !== null) {
+        lines.push(`       Playbook: v${outcome.playbookVersion}`);
+      }
       if (outcome.error) {
-        lines.push(`       Error: ${outcome.error}`);
+        const phase = outcome.failurePhase ? `, ${STEP_PHASE_LABELS[outcome.failurePhase]}` : "";
+        const where = outcome.failureStep !== null ? ` at step ${outcome.failureStep}${phase}` : "";
+        const kind = outcome.failureClass ? `${FAILURE_CLASS_LABELS[outcome.failureClass]}${where}: ` : "";
+        lines.push(`       Error: ${kind}${outcome.error}`);
       }
     }
 
@@ -225,6 +532,7 @@ export const useOptOutStore = defineStore("optout", () => {
     currentBrokerId.value = null;
     currentBrokerName.value = null;
     currentStep.value = "";
+    currentPhase.value = null;
     brokersCompleted.value = 0;
     brokersTotal.value = 0;
     actionRequired.value = null;
@@ -240,22 +548,45 @@ export const useOptOutStore = defineStore("optout", () => {
     currentBrokerId,
     currentBrokerName,
     currentStep,
+    currentPhase,
     brokersCompleted,
     brokersTotal,
     actionRequired,
     error,
     chromeInstalled,
+    chromeStatus,
     lastResult,
     brokerOutcomes,
+    presets,
     isActive,
     progressPercent,
     setupListeners,
     checkChromeInstalled,
+    previewRunPlan,
+    preflightCheck,
     startRun,
+    retryFromStep,
+    rerunRelisted,
     continueAfterUserAction,
     retryFailedStep,
     skipFailedStep,
     abortBroker,
+    chooseListing,
+    skipListing,
+    holdBackSubmission,
+    approveSelectorRepair,
+    rejectSelectorRepair,
+    confirmFieldMatch,
+    rejectFieldMatch,
+    approveFill,
+    skipFill,
+    fillManualValue,
+    copyProfileValue,
+    loadPresets,
+    savePreset,
+    deletePreset,
+    startPreset,
+    startEverywhere,
     cancelRun,
     generateReport,
     copyReport,
@@ -1,12 +1,19 @@
 import { defineStore } from "pinia";
 import { ref, shallowRef, computed } from "vue";
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { toast } from "vue-sonner";
 import type {
-  RecordedAction,
+  PlaybookDraft,
+  RecordingSession,
   PlaybookStep,
   PlaybookSummary,
+  PlaybookRevision,
+  PlaybookSubscription,
   Playbook,
+  Contributor,
+  PlaybookVote,
+  VoteDirection,
   LocalPlaybook,
   RecordingStatus,
   PlaybookSubmission,
@@ -18,27 +25,24 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
   const recordingStatus = ref<RecordingStatus>("idle");
   const recordingBrokerId = ref<string | null>(null);
   const recordingBrokerName = ref<string | null>(null);
-  const recordedActions = ref<RecordedAction[]>([]);
   const editableSteps = ref<PlaybookStep[]>([]);
   const playbookTitle = ref<string | null>(null);
 
-  // Live polling state
-  const seenActionCount = ref(0);
-  const pollingInterval = ref<ReturnType<typeof setInterval> | null>(null);
+  // Live recording state, fed by `recorder-draft` events
+  const draftIssues = ref<string[]>([]);
+  let unlistenRecorder: UnlistenFn | null = null;
+  let unlistenLost: UnlistenFn | null = null;
 
   // Playbook browsing state
   const playbookCache = shallowRef<Record<string, PlaybookSummary[]>>({});
+  const historyCache = shallowRef<Record<string, PlaybookRevision[]>>({});
+  const subscriptions = ref<PlaybookSubscription[]>([]);
   const selectedPlaybook = ref<Playbook | null>(null);
   const loadingPlaybooks = ref(false);
   const expandedBrokerId = ref<string | null>(null);
 
-  // Vote tracking (persisted to localStorage)
-  const userVotes = ref<Record<string, "up" | "down">>(
-    JSON.parse(localStorage.getItem("opt-outta-votes") || "{}")
-  );
-  function persistVotes() {
-    localStorage.setItem("opt-outta-votes", JSON.stringify(userVotes.value));
-  }
+  // This device's votes, kept by the backend so the same vote isn't sent twice
+  const userVotes = ref<Record<string, VoteDirection>>({});
 
   // Local playbook state
   const localPlaybooks = ref<LocalPlaybook[]>([]);
@@ -57,41 +61,88 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
 
   // --- Recording ---
 
-  async function startRecording(brokerId: string, brokerName: string, optOutUrl: string) {
-    await invoke("start_recording", {
-      brokerId,
-      brokerName,
-      optOutUrl,
-    });
+  async function startRecording(
+    brokerId: string,
+    brokerName: string,
+    optOutUrl: string,
+    startUrl: string | null = null
+  ) {
+    editableSteps.value = [];
+    draftIssues.value = [];
+    // Listen before starting so the opening navigate step isn't missed
+    await listenToRecorder();
+    try {
+      await invoke("start_recording", {
+        brokerId,
+        brokerName,
+        optOutUrl,
+        startUrl,
+      });
+    } catch (e) {
+      stopListening();
+      throw e;
+    }
     recordingBrokerId.value = brokerId;
     recordingBrokerName.value = brokerName;
-    recordedActions.value = [];
-    editableSteps.value = [];
-    seenActionCount.value = 0;
     recordingStatus.value = "recording";
-    pollingInterval.value = setInterval(pollActions, 1500);
   }
 
-  async function stopRecording() {
-    if (pollingInterval.value) {
-      clearInterval(pollingInterval.value);
-      pollingInterval.value = null;
-    }
-    const actions = await invoke<RecordedAction[]>("stop_recording");
-    recordedActions.value = actions;
-    // Reconcile: append any actions we haven't seen yet
-    if (actions.length > seenActionCount.value) {
-      const unseen = actions.slice(seenActionCount.value);
-      const newSteps = convertActionsToSteps(unseen);
-      // Re-number appended steps to continue from existing
-      const basePos = editableSteps.value.length;
-      newSteps.forEach((s, i) => (s.position = basePos + i + 1));
-      editableSteps.value = [...editableSteps.value, ...newSteps];
-      seenActionCount.value = actions.length;
+  /**
+   * Pick up a session still recording in the backend, such as after the app window
+   * reloaded. Returns whether there was one.
+   */
+  async function restoreRecording(): Promise<boolean> {
+    if (recordingStatus.value !== "idle") return false;
+    // Listen first so nothing recorded between the two is missed
+    await listenToRecorder();
+    const session = await invoke<RecordingSession | null>("get_recording_status").catch(() => null);
+    if (!session) {
+      stopListening();
+      return false;
     }
+    recordingBrokerId.value = session.broker_id;
+    recordingBrokerName.value = session.broker_name;
+    applyDraft(session.draft);
+    recordingStatus.value = "recording";
+    return true;
+  }
+
+  async function listenToRecorder() {
+    stopListening();
+    unlistenRecorder = await listen<PlaybookDraft>("recorder-draft", (event) => {
+      applyDraft(event.payload);
+    });
+    // Chrome closed or crashed under the recording; keep what it got for review
+    unlistenLost = await listen<PlaybookDraft>("recorder-lost", (event) => {
+      stopListening();
+      applyDraft(event.payload);
+      recordingStatus.value = "reviewing";
+      toast.warning("The recording browser closed", {
+        description: "Recording stopped. Review the steps recorded before it closed.",
+      });
+    });
+  }
+
+  function stopListening() {
+    unlistenRecorder?.();
+    unlistenRecorder = null;
+    unlistenLost?.();
+    unlistenLost = null;
+  }
+
+  async function stopRecording() {
+    stopListening();
+    // The final draft covers every action, including any whose event we missed
+    applyDraft(await invoke<PlaybookDraft>("stop_recording"));
     recordingStatus.value = "reviewing";
   }
 
+  /** Drop a stuck recording session, discarding what it recorded. */
+  async function resetRecorder() {
+    await invoke("force_reset_recorder");
+    resetRecording();
+  }
+
   async function markCaptcha() {
     await invoke("mark_captcha_step");
   }
@@ -100,59 +151,10 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
     await invoke("mark_user_prompt_step");
   }
 
-  async function pollActions() {
-    try {
-      const actions = await invoke<RecordedAction[]>("get_recorded_actions");
-      if (actions.length > seenActionCount.value) {
-        const unseen = actions.slice(seenActionCount.value);
-        const newSteps = convertActionsToSteps(unseen);
-        const basePos = editableSteps.value.length;
-        newSteps.forEach((s, i) => (s.position = basePos + i + 1));
-        editableSteps.value = [...editableSteps.value, ...newSteps];
-        seenActionCount.value = actions.length;
-      }
-    } catch {
-      // Recording may have ended between polls — ignore
-    }
-  }
-
-  function convertActionsToSteps(actions: RecordedAction[]): PlaybookStep[] {
-    return actions.map((a, i) => ({
-      position: i + 1,
-      action: a.action,
-      selector: a.selector,
-      profile_key: a.profile_key,
-      value: a.value ?? a.url,
-      description: generateDescription(a),
-      instructions: null,
-      wait_after_ms: 500,
-      optional: false,
-    }));
-  }
-
-  function generateDescription(action: RecordedAction): string {
-    switch (action.action) {
-      case "navigate":
-        return `Go to ${action.url || action.value || "page"}`;
-      case "fill":
-        return `Enter ${action.profile_key || action.label || "value"} in ${action.label || action.selector || "field"}`;
-      case "select":
-        return `Select ${action.profile_key || "option"} in ${action.label || action.selector || "dropdown"}`;
-      case "check":
-        return `Toggle checkbox ${action.label || action.selector || ""}`;
-      case "click":
-        return `Click "${action.element_text || action.selector || "element"}"`;
-      case "captcha":
-        return "Solve CAPTCHA";
-      case "user_prompt":
-        return action.label || action.element_text || "Manual step";
-      case "wait":
-        return "Wait for page";
-      case "wait_for":
-        return `Wait for ${action.selector || "element"}`;
-      default:
-        return action.action;
-    }
+  /** Replace the steps with the backend's latest draft of the recording. */
+  function applyDraft(draft: PlaybookDraft) {
+    editableSteps.value = draft.steps;
+    draftIssues.value = draft.issues;
   }
 
   // --- Step editing ---
@@ -197,15 +199,20 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
   // --- Validation ---
 
   const ALLOWED_ACTIONS = new Set([
-    "navigate", "fill", "select", "check", "click", "wait",
-    "wait_for", "scroll_to", "find_and_click", "captcha", "user_prompt", "done",
+    "navigate", "fill", "select", "check", "choose_radio", "click", "wait",
+    "wait_for", "scroll_to", "find_and_click", "fill_credential", "fill_totp", "fill_sms_code", "captcha", "user_prompt", "done",
+    "for_each_result", "end_for_each",
   ]);
 
   const ALLOWED_PROFILE_KEYS = new Set([
     "firstName", "lastName", "email", "phone", "address",
-    "city", "state", "zip", "dob", "fullName",
+    "city", "state", "zip", "dob", "fullName", "legalBasis",
   ]);
 
+  // previousAddress[N].<field>, alternateEmail[N], alternatePhone[N]
+  const INDEXED_PROFILE_KEY =
+    /^(previousAddress\[\d+\]\.(address|city|state|zip)|alternate(Email|Phone)\[\d+\])$/;
+
   const BLOCKED_URL_SCHEMES = [
     "javascript:", "data:", "file:", "blob:", "vbscript:",
     "about:", "chrome:", "chrome-extension:",
@@ -252,7 +259,11 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
         }
       }
 
-      if (step.profile_key && !ALLOWED_PROFILE_KEYS.has(step.profile_key)) {
+      if (
+        step.profile_key &&
+        !ALLOWED_PROFILE_KEYS.has(step.profile_key) &&
+        !INDEXED_PROFILE_KEY.test(step.profile_key)
+      ) {
         return `${ctx}: Unknown profile key '${step.profile_key}'.`;
       }
 
@@ -353,19 +364,15 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
   }
 
   function resetRecording() {
-    if (pollingInterval.value) {
-      clearInterval(pollingInterval.value);
-      pollingInterval.value = null;
-    }
+    stopListening();
     recordingStatus.value = "idle";
     recordingBrokerId.value = null;
     recordingBrokerName.value = null;
-    recordedActions.value = [];
     editableSteps.value = [];
+    draftIssues.value = [];
     playbookTitle.value = null;
     editingLocalId.value = null;
     submittingFromLocalId.value = null;
-    seenActionCount.value = 0;
   }
 
   async function markLocalAsSubmitted(id: string): Promise<void> {
@@ -412,70 +419,139 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
     playbookCache.value = newCache;
   }
 
+  /** Drop a broker's cached list so the next fetch picks up new playbooks. */
+  function invalidatePlaybooks(brokerId: string) {
+    const cache = { ...playbookCache.value };
+    delete cache[brokerId];
+    playbookCache.value = cache;
+  }
+
+  async function fetchPlaybookHistory(brokerId: string) {
+    try {
+      const list = await invoke<PlaybookRevision[]>("fetch_playbook_history", { brokerId });
+      historyCache.value = { ...historyCache.value, [brokerId]: list };
+    } catch (e) {
+      toast.error("Failed to load playbook history", { description: String(e) });
+    }
+  }
+
+  function getHistoryForBroker(brokerId: string): PlaybookRevision[] | null {
+    return historyCache.value[brokerId] ?? null;
+  }
+
+  // --- Update subscriptions ---
+
+  async function loadSubscriptions() {
+    try {
+      subscriptions.value = await invoke<PlaybookSubscription[]>("get_playbook_subscriptions");
+    } catch {
+      subscriptions.value = [];
+    }
+  }
+
+  function isSubscribed(brokerId: string): boolean {
+    return subscriptions.value.some((s) => s.broker_id === brokerId);
+  }
+
+  async function toggleSubscription(brokerId: string) {
+    const subscribed = !isSubscribed(brokerId);
+    try {
+      await invoke("set_playbook_subscription", { brokerId, subscribed });
+      await loadSubscriptions();
+    } catch (e) {
+      toast.error("Failed to update subscription", { description: String(e) });
+    }
+  }
+
   async function fetchPlaybookDetail(id: string) {
     const detail = await invoke<Playbook>("fetch_playbook_detail", { id });
     selectedPlaybook.value = detail;
   }
 
+  // --- Contributors ---
+
+  async function fetchContributorPlaybooks(contributorId: string): Promise<PlaybookSummary[]> {
+    return invoke<PlaybookSummary[]>("fetch_contributor_playbooks", { contributorId });
+  }
+
+  async function getOwnContributor(): Promise<Contributor | null> {
+    return invoke<Contributor | null>("get_own_contributor");
+  }
+
+  async function claimHandle(handle: string): Promise<Contributor> {
+    return invoke<Contributor>("claim_contributor_handle", { handle });
+  }
+
   const votingInProgress = new Set<string>();
 
-  function getUserVote(id: string): "up" | "down" | null {
-    return userVotes.value[id] ?? null;
+  async function loadVotes() {
+    // Votes used to be kept in localStorage; hand any left there to the backend once
+    const legacy = localStorage.getItem("opt-outta-votes");
+    if (legacy) {
+      try {
+        await invoke("import_playbook_votes", { votes: JSON.parse(legacy) });
+        localStorage.removeItem("opt-outta-votes");
+      } catch {
+        // Try again next launch
+      }
+    }
+    try {
+      const votes = await invoke<PlaybookVote[]>("get_playbook_votes");
+      userVotes.value = Object.fromEntries(votes.map((v) => [v.playbook_id, v.vote]));
+    } catch {
+      userVotes.value = {};
+    }
   }
 
-  async function voteOnPlaybook(id: string, vote: "up" | "down") {
-    if (votingInProgress.has(id)) return;
-    votingInProgress.add(id);
+  function getUserVote(id: string): VoteDirection | null {
+    return userVotes.value[id] ?? null;
+  }
 
-    const existing = userVotes.value[id];
-
-    function applyCacheDelta(field: "upvotes" | "downvotes", delta: 1 | -1) {
-      const cache = { ...playbookCache.value };
-      for (const brokerId of Object.keys(cache)) {
-        const list = cache[brokerId];
-        if (list.some((p) => p.id === id)) {
-          cache[brokerId] = list.map((p) =>
-            p.id === id ? { ...p, [field]: p[field] + delta } : p
-          );
-          break;
-        }
+  function applyVoteDelta(id: string, field: "upvotes" | "downvotes", delta: 1 | -1) {
+    const cache = { ...playbookCache.value };
+    for (const brokerId of Object.keys(cache)) {
+      const list = cache[brokerId];
+      if (list.some((p) => p.id === id)) {
+        cache[brokerId] = list.map((p) =>
+          p.id === id ? { ...p, [field]: p[field] + delta } : p
+        );
+        break;
       }
-      playbookCache.value = cache;
     }
+    playbookCache.value = cache;
+    if (selectedPlaybook.value?.id === id) {
+      selectedPlaybook.value = { ...selectedPlaybook.value, [field]: selectedPlaybook.value[field] + delta };
+    }
+  }
 
-    // Three cases:
-    // 1. Same arrow as existing vote → undo (remove vote)
-    // 2. Opposite arrow from existing vote → just undo existing (return to neutral)
-    // 3. No existing vote → apply the vote
+  /**
+   * The same arrow again takes the vote back; the other arrow changes it. Counts update
+   * straight away and are put back if the vote doesn't go through.
+   */
+  async function voteOnPlaybook(id: string, vote: VoteDirection) {
+    if (votingInProgress.has(id)) return;
+    votingInProgress.add(id);
 
-    // Same arrow as existing vote → do nothing
-    if (existing === vote) {
-      votingInProgress.delete(id);
-      return;
+    const existing = getUserVote(id);
+    const next = existing === vote ? null : vote;
+    const field = (v: VoteDirection) => (v === "up" ? "upvotes" as const : "downvotes" as const);
+
+    function setLocal(v: VoteDirection | null) {
+      const { [id]: _, ...rest } = userVotes.value;
+      userVotes.value = v ? { ...rest, [id]: v } : rest;
     }
 
-    const undoVote = existing ?? null;
-    const applyVote = !existing ? vote : null;
+    if (existing) applyVoteDelta(id, field(existing), -1);
+    if (next) applyVoteDelta(id, field(next), 1);
+    setLocal(next);
 
     try {
-      // Undo existing vote if any
-      if (undoVote) {
-        const undoField = undoVote === "up" ? "upvotes" as const : "downvotes" as const;
-        const { [id]: _, ...rest } = userVotes.value;
-        userVotes.value = rest;
-        persistVotes();
-        applyCacheDelta(undoField, -1);
-        invoke("vote_on_playbook", { id, vote: "none" }).catch(() => {});
-      }
-
-      // Apply new vote only if there was no prior vote
-      if (applyVote) {
-        const applyField = applyVote === "up" ? "upvotes" as const : "downvotes" as const;
-        userVotes.value = { ...userVotes.value, [id]: applyVote };
-        persistVotes();
-        applyCacheDelta(applyField, 1);
-        invoke("vote_on_playbook", { id, vote: applyVote }).catch(() => {});
-      }
+      await invoke("vote_on_playbook", { id, vote: next });
+    } catch (e) {
+      if (next) applyVoteDelta(id, field(next), -1);
+      if (existing) applyVoteDelta(id, field(existing), 1);
+      setLocal(existing);
+      toast.error("Vote not counted", { description: String(e) });
     } finally {
       votingInProgress.delete(id);
     }
@@ -578,7 +654,7 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
     recordingStatus,
     recordingBrokerId,
     recordingBrokerName,
-    recordedActions,
+    draftIssues,
     editableSteps,
     playbookTitle,
     isRecording,
@@ -589,6 +665,7 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
     loadingPlaybooks,
     // Recording actions
     startRecording,
+    restoreRecording,
     stopRecording,
     markCaptcha,
     markUserPrompt,
@@ -598,11 +675,23 @@ export const usePlaybooksStore = defineStore("playbooks", () => {
     addUserPromptStep,
     submitPlaybook,
     resetRecording,
+    resetRecorder,
     // Browsing state + actions
     expandedBrokerId,
     fetchPlaybooks,
     fetchAllPlaybooks,
     fetchPlaybookDetail,
+    fetchPlaybookHistory,
+    fetchContributorPlaybooks,
+    getOwnContributor,
+    claimHandle,
+    invalidatePlaybooks,
+    getHistoryForBroker,
+    subscriptions,
+    loadSubscriptions,
+    isSubscribed,
+    toggleSubscription,
+    loadVotes,
     getUserVote,
     voteOnPlaybook,
     getPlaybooksForBroker,
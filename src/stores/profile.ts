@@ -1,7 +1,7 @@
 import { defineStore } from "pinia";
 import { ref, computed } from "vue";
 import { invoke } from "@tauri-apps/api/core";
-import type { Profile } from "../types";
+import type { ImportedField, Profile, ProfileFieldError, ProfileImport } from "../types";
 
 function emptyProfile(): Profile {
   return {
@@ -17,6 +17,7 @@ function emptyProfile(): Profile {
     alternateEmails: [],
     alternatePhones: [],
     previousAddresses: [],
+    country: "US",
   };
 }
 
@@ -24,6 +25,9 @@ export const useProfileStore = defineStore("profile", () => {
   const profile = ref<Profile>(emptyProfile());
   const loaded = ref(false);
   const loading = ref(false);
+  const fieldErrors = ref<ProfileFieldError[]>([]);
+  // Values from the last imported file that still need a home
+  const importUnmatched = ref<ImportedField[]>([]);
 
   const completeness = computed(() => {
     const fields = [
@@ -56,6 +60,55 @@ export const useProfileStore = defineStore("profile", () => {
     }
   }
 
+  async function validateProfile(): Promise<boolean> {
+    fieldErrors.value = await invoke<ProfileFieldError[]>("validate_profile", { profile: profile.value });
+    return fieldErrors.value.length === 0;
+  }
+
+  function errorFor(field: string): string | null {
+    return fieldErrors.value.find((e) => e.field === field)?.message ?? null;
+  }
+
+  /**
+   * Fill the form from a vCard or CSV export. Imported values only fill empty fields;
+   * extra emails, phones and addresses are appended. Nothing is saved until the user saves.
+   */
+  async function importFromFile(file: File) {
+    const result = await invoke<ProfileImport>("import_profile", {
+      fileName: file.name,
+      content: await file.text(),
+    });
+    const current = profile.value;
+    const imported = result.profile;
+    const scalars = ["firstName", "lastName", "email", "phone", "address", "city", "state", "zip", "dob"] as const;
+    for (const key of scalars) {
+      if (!current[key].trim() && imported[key]) current[key] = imported[key];
+    }
+    for (const email of imported.alternateEmails) {
+      if (email !== current.email && !current.alternateEmails.includes(email)) current.alternateEmails.push(email);
+    }
+    for (const phone of imported.alternatePhones) {
+      if (phone !== current.phone && !current.alternatePhones.includes(phone)) current.alternatePhones.push(phone);
+    }
+    current.previousAddresses.push(...imported.previousAddresses);
+    importUnmatched.value = result.unmatched;
+  }
+
+  /** Put an unmatched imported value into a profile field, or drop it when `target` is empty. */
+  function assignImported(index: number, target: string) {
+    const field = importUnmatched.value[index];
+    if (!field) return;
+    const p = profile.value;
+    if (target === "alternateEmails" || target === "alternatePhones") {
+      p[target].push(field.value);
+    } else if (target === "previousAddresses") {
+      p.previousAddresses.push({ address: field.value, city: "", state: "", zip: "" });
+    } else if (target) {
+      (p as unknown as Record<string, string>)[target] = field.value;
+    }
+    importUnmatched.value.splice(index, 1);
+  }
+
   async function saveProfile() {
     await invoke("save_profile", { profile: profile.value });
   }
@@ -65,5 +118,19 @@ export const useProfileStore = defineStore("profile", () => {
     profile.value = emptyProfile();
   }
 
-  return { profile, loaded, loading, completeness, loadProfile, saveProfile, deleteProfile };
+  return {
+    profile,
+    loaded,
+    loading,
+    fieldErrors,
+    importUnmatched,
+    completeness,
+    loadProfile,
+    validateProfile,
+    errorFor,
+    importFromFile,
+    assignImported,
+    saveProfile,
+    deleteProfile,
+  };
 });
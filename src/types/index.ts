@@ -11,6 +11,17 @@ export interface Profile {
   alternateEmails: string[];
   alternatePhones: string[];
   previousAddresses: PreviousAddress[];
+  country: string;
+}
+
+export interface ImportedField {
+  label: string;
+  value: string;
+}
+
+export interface ProfileImport {
+  profile: Profile;
+  unmatched: ImportedField[];
 }
 
 export interface PreviousAddress {
@@ -20,6 +31,11 @@ export interface PreviousAddress {
   zip: string;
 }
 
+export interface ProfileFieldError {
+  field: string;
+  message: string;
+}
+
 export interface KnownField {
   label: string;
   type: string;
@@ -39,6 +55,22 @@ export interface Broker {
   relist_days: number | null;
   difficulty: BrokerDifficulty;
   last_verified: string;
+  network: string | null;
+  network_parent: boolean;
+  depends_on: string[];
+  field_formats: Record<string, string>;
+  jurisdiction: string | null;
+  coverage_states: string[];
+  countries: string[];
+  automation: BrokerAutomation;
+}
+
+/** Site quirks the browser works around for a broker. */
+export interface BrokerAutomation {
+  slow_typing: boolean;
+  mobile_viewport: boolean;
+  blocks_headless: boolean;
+  slow_load: boolean;
 }
 
 export type BrokerCategory =
@@ -52,9 +84,36 @@ export type BrokerMethod = "web-form" | "email" | "api";
 
 export type BrokerDifficulty = "easy" | "medium" | "hard";
 
+export interface BrokerNetwork {
+  id: string;
+  parent_id: string | null;
+  broker_ids: string[];
+}
+
+export interface BrokerCategory {
+  id: string;
+  broker_ids: string[];
+}
+
+/** A broker's difficulty recalibrated from this device's own runs of it. */
+export interface LocalDifficulty {
+  broker_id: string;
+  difficulty: BrokerDifficulty;
+  /** 0 (no trouble) to 100 (always fails) */
+  score: number;
+  attempts: number;
+  failure_rate: number;
+  average_secs: number | null;
+  captchas_per_attempt: number;
+}
+
 export interface BrokerRegistry {
   version: string;
   brokers: Broker[];
+  networks: BrokerNetwork[];
+  categories: BrokerCategory[];
+  out_of_region: string[];
+  local_difficulty: LocalDifficulty[];
 }
 
 export enum BrokerStatus {
@@ -64,6 +123,7 @@ export enum BrokerStatus {
   Confirmed = "confirmed",
   ReListed = "re_listed",
   Failed = "failed",
+  NotFound = "not_found",
 }
 
 export interface SubmissionRecord {
@@ -75,6 +135,153 @@ export interface SubmissionRecord {
   next_check_date: string | null;
   error_message: string | null;
   run_id: string;
+  method: SubmissionMethod;
+  last_removal_check: RemovalCheck | null;
+  covered_by: string | null;
+  searched_with: string | null;
+  failure_reason: FailureReason | null;
+  outcome_check: OutcomeCheck | null;
+  gpc_sent: boolean;
+  request_type: RequestType;
+  failed_step: FailedStep | null;
+}
+
+export interface FailedStep {
+  playbook: string;
+  position: number;
+}
+
+export type RequestType = "deletion" | "access";
+
+export interface RequestEmail {
+  subject: string;
+  body: string;
+}
+
+export type DeadlineStatus = "on_track" | "overdue" | "extension_lapsed";
+
+export interface RequestDeadline {
+  record_id: string;
+  broker_id: string;
+  broker_name: string;
+  jurisdiction: string;
+  statute: string;
+  citation: string;
+  regulator: string;
+  complaint_url: string;
+  submitted_at: string;
+  due_at: string;
+  extended_due_at: string;
+  status: DeadlineStatus;
+  days_overdue: number;
+  suggestions: string[];
+}
+
+export interface ComplaintPackage {
+  folder: string;
+  recipient: string;
+  filing_url: string;
+  statute: string | null;
+  letter: string;
+  evidence_files: string[];
+}
+
+export type FailureReason = "blocked_by_broker" | "step_failed" | "refused_by_email";
+
+export type PageOutcome = "confirmed" | "error_shown" | "unknown";
+
+export interface OutcomeCheck {
+  outcome: PageOutcome;
+  matched: string | null;
+}
+
+export interface RemovalCheck {
+  checked_at: string;
+  still_listed: boolean;
+  matches: number;
+  evidence_id: string | null;
+}
+
+export type RelistingAlertStatus = "open" | "snoozed" | "dismissed" | "resolved";
+
+/** A broker found listing the user again after a confirmed removal */
+export interface RelistingAlert {
+  id: string;
+  broker_id: string;
+  broker_name: string;
+  record_id: string;
+  detected_at: string;
+  matches: number;
+  evidence_id: string | null;
+  suggested_action: string;
+  status: RelistingAlertStatus;
+  snoozed_until: string | null;
+  rerun_id: string | null;
+}
+
+export type BrokerResponseKind = "confirmation" | "verification_request" | "refusal" | "unrecognized";
+
+/** A broker's email imported from a .eml file. */
+export interface BrokerResponse {
+  id: string;
+  file_name: string;
+  from: string;
+  subject: string;
+  received_at: string | null;
+  kind: BrokerResponseKind;
+  broker_id: string | null;
+  broker_name: string | null;
+  record_id: string | null;
+  new_status: BrokerStatus | null;
+  evidence_id: string | null;
+  imported_at: string;
+}
+
+/** An email address given to one broker in place of the user's own. */
+export interface EmailAlias {
+  address: string;
+  broker_id: string;
+  broker_name: string;
+  first_used: string;
+  run_ids: string[];
+}
+
+export type EvidenceKind = "screenshot" | "mhtml" | "har" | "email";
+
+export interface EvidenceItem {
+  id: string;
+  record_id: string;
+  kind: EvidenceKind;
+  captured_at: string;
+}
+
+export type SubmissionMethod =
+  | "automated"
+  | "manual_web"
+  | "manual_email"
+  | "manual_phone"
+  | "manual_mail";
+
+export interface HistoryRepairReport {
+  duplicates_removed: number;
+  next_check_dates_repaired: number;
+  unknown_broker_records: number;
+  details: string[];
+}
+
+export interface BrokerNote {
+  broker_id: string;
+  note: string;
+  updated_at: string;
+}
+
+export interface BrokerCredential {
+  broker_id: string;
+  username: string;
+  password: string;
+  totp_secret: string | null;
+  created_at: string;
+  updated_at: string;
 }
 
 export type RunStatus =
@@ -83,16 +290,41 @@ export type RunStatus =
   | "waiting_for_user"
   | "paused"
   | "completed"
-  | "failed";
+  | "failed"
+  | "cancelling"
+  | "cancelled";
+
+export interface ListingCandidate {
+  index: number;
+  text: string;
+}
 
 export interface UserActionRequired {
-  type: "solve_captcha" | "verify_email" | "verify_phone" | "manual_step" | "user_prompt" | "step_failed";
+  type:
+    | "solve_captcha"
+    | "verify_email"
+    | "verify_phone"
+    | "manual_step"
+    | "user_prompt"
+    | "step_failed"
+    | "choose_listing"
+    | "manual_fill"
+    | "review_before_submit"
+    | "approve_selector_repair"
+    | "confirm_field_match"
+    | "approve_fill";
   captcha_type?: string;
   message: string;
   description?: string;
   step_description?: string;
   step_position?: number;
   broker_name?: string;
+  candidates?: ListingCandidate[];
+  error?: string;
+  failed_selector?: string;
+  proposed_selector?: string;
+  profile_key?: string;
+  fills_with?: string;
 }
 
 export interface OptOutProgress {
@@ -105,6 +337,123 @@ export interface OptOutProgress {
   brokers_total: number;
   action_required: UserActionRequired | null;
   error: string | null;
+  phase: PhaseProgress | null;
+}
+
+// Where a run is in a playbook's phases ("Phase 2 of 3: filling the form")
+export interface PhaseProgress {
+  phase: StepPhase;
+  number: number;
+  total: number;
+}
+
+export interface RunOptions {
+  search_previous_addresses: boolean;
+  repeat_alternate_contacts: boolean;
+  skip_unlisted: boolean;
+  review_before_submit: boolean;
+  approve_each_fill?: boolean;
+  browser_mode?: BrowserMode | null;
+  // Skip the proxy in settings for this run
+  direct_connection?: boolean;
+  resume_from_step?: number | null;
+}
+
+export type Likelihood = "high" | "medium" | "low";
+
+export interface BrokerLikelihood {
+  broker_id: string;
+  broker_name: string;
+  likelihood: Likelihood;
+  score: number;
+  reasons: string[];
+  recommended: boolean;
+}
+
+export interface LikelihoodAssessment {
+  age_bracket: string | null;
+  brokers: BrokerLikelihood[];
+  recommended_ids: string[];
+}
+
+export interface RunPreset {
+  name: string;
+  broker_ids: string[];
+  categories: string[];
+  playbook_selections: Record<string, string>;
+  force: boolean;
+  options: RunOptions;
+  updated_at?: string;
+}
+
+export type RunPlanIssueKind =
+  | "missing_selection"
+  | "missing_playbook"
+  | "rejected_playbook"
+  | "stale_playbook"
+  | "recently_submitted"
+  | "missing_profile_fields";
+
+export interface RunPlanIssue {
+  kind: RunPlanIssueKind;
+  message: string;
+}
+
+export interface RunPlanEntry {
+  broker_id: string;
+  broker_name: string;
+  playbook_id: string | null;
+  playbook_title: string | null;
+  captcha_steps: number;
+  manual_steps: number;
+  estimated_duration_ms: number;
+  issues: RunPlanIssue[];
+}
+
+export interface RunPlan {
+  entries: RunPlanEntry[];
+  warnings: string[];
+  estimated_duration_ms: number;
+}
+
+export type PreflightCheckKind = "chrome" | "profile" | "network" | "playbooks" | "disk_space";
+
+export type PreflightStatus = "passed" | "warning" | "failed";
+
+export interface PreflightCheck {
+  kind: PreflightCheckKind;
+  status: PreflightStatus;
+  message: string;
+  details: string[];
+}
+
+export interface PreflightReport {
+  checks: PreflightCheck[];
+  ready: boolean;
+}
+
+export type FailureClass =
+  | "selector"
+  | "captcha_timeout"
+  | "blocked"
+  | "network"
+  | "rejected"
+  | "no_playbook"
+  | "other";
+
+export type BrokerRunOutcome = "succeeded" | "failed" | "skipped" | "covered" | "cancelled" | "not_found";
+
+export interface BrokerRunSummary {
+  broker_id: string;
+  broker_name: string;
+  outcome: BrokerRunOutcome;
+  duration_ms: number;
+  playbook_id: string | null;
+  playbook_version: number | null;
+  failure_step: number | null;
+  failure_phase: StepPhase | null;
+  failure_class: FailureClass | null;
+  error: string | null;
 }
 
 export interface OptOutComplete {
@@ -112,6 +461,11 @@ export interface OptOutComplete {
   total: number;
   succeeded: number;
   failed: number;
+  skipped: number;
+  not_found: number;
+  cancelled: number;
+  duration_ms: number;
+  brokers: BrokerRunSummary[];
 }
 
 // --- Community Playbook types ---
@@ -126,8 +480,13 @@ export interface PlaybookStep {
   instructions: string | null;
   wait_after_ms: number;
   optional: boolean;
+  fallback_selectors?: string[];
+  // Starts a phase that runs until the next step with one
+  phase?: StepPhase | null;
 }
 
+export type StepPhase = "search" | "fill_form" | "verify";
+
 export interface PlaybookSummary {
   id: string;
   broker_id: string;
@@ -142,6 +501,19 @@ export interface PlaybookSummary {
   failure_count: number;
   score: number;
   created_at: string;
+  captcha_steps: number;
+  manual_steps: number;
+  estimated_duration_ms: number;
+  personal: PlaybookReliability | null;
+  contributor: Contributor | null;
+}
+
+export interface PlaybookReliability {
+  playbook_id: string;
+  successes: number;
+  failures: number;
+  consecutive_failures: number;
+  last_run_at: string;
 }
 
 export interface Playbook {
@@ -159,6 +531,22 @@ export interface Playbook {
   success_count: number;
   failure_count: number;
   created_at: string;
+  contributor: Contributor | null;
+}
+
+/** Whoever shared a community playbook. `handle` is null until they claim one. */
+export interface Contributor {
+  id: string;
+  handle: string | null;
+}
+
+export type VoteDirection = "up" | "down";
+
+/** A vote this device cast on a community playbook */
+export interface PlaybookVote {
+  playbook_id: string;
+  vote: VoteDirection;
+  voted_at: string;
 }
 
 export interface LocalPlaybook {
@@ -176,7 +564,9 @@ export interface LocalPlaybook {
 export interface RecordedAction {
   action: string;
   selector: string | null;
+  selector_candidates: string[];
   profile_key: string | null;
+  match_confidence: number | null;
   value: string | null;
   url: string | null;
   element_text: string | null;
@@ -184,6 +574,24 @@ export interface RecordedAction {
   timestamp: number;
 }
 
+/** The playbook taking shape during a recording (`recorder-draft` event) */
+export interface PlaybookDraft {
+  steps: PlaybookStep[];
+  /** Problems to fix before the playbook can be submitted */
+  issues: string[];
+}
+
+/** The recording session in progress (`get_recording_status`) */
+export interface RecordingSession {
+  broker_id: string;
+  broker_name: string;
+  current_url: string | null;
+  started_at: string;
+  elapsed_secs: number;
+  action_count: number;
+  draft: PlaybookDraft;
+}
+
 // --- Submission Tracker types ---
 
 export interface TrackedSubmission {
@@ -209,10 +617,49 @@ export interface PlaybookReportEntry {
   outcome: string;
   failure_step: number | null;
   error_message: string | null;
+  failure_reason: FailureReason | null;
   app_version: string;
   created_at: string;
 }
 
+export interface PlaybookSubscription {
+  broker_id: string;
+  subscribed_at: string;
+  last_checked_at: string | null;
+  known_playbook_ids: string[];
+  best_playbook_id: string | null;
+}
+
+export type PlaybookUpdateKind = "new" | "better_score";
+
+export interface PlaybookUpdate {
+  broker_id: string;
+  broker_name: string;
+  playbook_id: string;
+  version: number;
+  score: number;
+  kind: PlaybookUpdateKind;
+}
+
+export interface PlaybookRevision {
+  id: string;
+  version: number;
+  title: string | null;
+  status: string;
+  notes: string | null;
+  steps_count: number;
+  score: number;
+  success_count: number;
+  failure_count: number;
+  created_at: string;
+  score_history: PlaybookScorePoint[];
+}
+
+export interface PlaybookScorePoint {
+  date: string;
+  score: number;
+}
+
 export type RecordingStatus = "idle" | "recording" | "reviewing" | "submitting";
 
 export interface PlaybookSubmission {
@@ -222,3 +669,85 @@ export interface PlaybookSubmission {
   notes: string | null;
   steps: PlaybookStep[];
 }
+
+// --- App settings ---
+
+export type BrowserMode = "external" | "docked";
+
+export type ProxyKind = "http" | "socks5";
+
+export interface BrowserProxy {
+  kind: ProxyKind;
+  host: string;
+  port: number;
+  // HTTP proxies only; the password is kept in the OS keychain
+  username: string | null;
+}
+
+// Chromium-based browsers are driven over CDP; Firefox (gecko) over WebDriver BiDi
+export type BrowserEngine = "chromium" | "gecko";
+
+export interface DetectedBrowser {
+  name: string;
+  path: string;
+  sandboxed: boolean;
+  engine: BrowserEngine;
+}
+
+export interface ChromeStatus {
+  installed: boolean;
+  browsers: DetectedBrowser[];
+  selected: string | null;
+}
+
+export interface BundledChromiumStatus {
+  version: string;
+  available: boolean;
+  installed: boolean;
+  downloading: boolean;
+}
+
+export interface ChromiumDownloadProgress {
+  downloaded: number;
+  total: number | null;
+}
+
+export interface RunWindow {
+  // 0 = Monday to 6 = Sunday
+  days: number[];
+  start_hour: number;
+  // Earlier than start_hour runs past midnight
+  end_hour: number;
+}
+
+export interface RecheckSchedule {
+  window_open: boolean;
+  next_window: string | null;
+  queued: number;
+}
+
+export interface AppSettings {
+  api_proxy: string | null;
+  browser_proxy: BrowserProxy | null;
+  browser_mode: BrowserMode;
+  browser_path: string | null;
+  alert_sound: boolean;
+  alert_repeat_minutes: number;
+  continue_hotkey: string | null;
+  send_gpc: boolean;
+  sms_relay_url: string | null;
+  sms_relay_token: string | null;
+  disposable_inbox_url: string | null;
+  disposable_inbox_token: string | null;
+  timestamp_authority_url: string | null;
+  ai_selector_repair: boolean;
+  ai_api_base: string | null;
+  ai_model: string | null;
+  pace_min_secs: number;
+  pace_max_secs: number;
+  domain_cooldown_secs: number;
+  capture_har: boolean;
+  email_aliases: boolean;
+  auto_recheck: boolean;
+  run_windows: RunWindow[];
+}